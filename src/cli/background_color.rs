@@ -0,0 +1,104 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Color that transparent pixels are flattened onto for formats without alpha
+/// (`--background`).
+///
+/// Parsed from a hex code (`#fff`, `#ffffff`, with or without the `#`) or one of a few
+/// color names. Defaults to white, which is what a transparent image usually sits on in a
+/// web page.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_optimizer::cli::BackgroundColor;
+///
+/// let color: BackgroundColor = "#ff8000".parse().unwrap();
+/// assert_eq!(color.rgb, [255, 128, 0]);
+/// assert_eq!("black".parse::<BackgroundColor>().unwrap().rgb, [0, 0, 0]);
+/// assert_eq!(BackgroundColor::default().to_string(), "#ffffff");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BackgroundColor {
+    /// Red, green and blue components
+    pub rgb: [u8; 3],
+}
+
+impl Default for BackgroundColor {
+    fn default() -> Self {
+        Self {
+            rgb: [255, 255, 255],
+        }
+    }
+}
+
+impl FromStr for BackgroundColor {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let trimmed = value.trim().to_ascii_lowercase();
+        let rgb = match trimmed.as_str() {
+            "white" => [255, 255, 255],
+            "black" => [0, 0, 0],
+            "gray" | "grey" => [128, 128, 128],
+            "red" => [255, 0, 0],
+            "green" => [0, 128, 0],
+            "blue" => [0, 0, 255],
+            hex => parse_hex(hex.strip_prefix('#').unwrap_or(hex)).ok_or_else(|| {
+                format!("expected a color name or a hex code such as #ffffff, got '{value}'")
+            })?,
+        };
+        Ok(Self { rgb })
+    }
+}
+
+impl fmt::Display for BackgroundColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [red, green, blue] = self.rgb;
+        write!(f, "#{red:02x}{green:02x}{blue:02x}")
+    }
+}
+
+/// Parses `rgb` or `rrggbb` hex digits.
+fn parse_hex(digits: &str) -> Option<[u8; 3]> {
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let component = |range: std::ops::Range<usize>| u8::from_str_radix(&digits[range], 16).ok();
+    match digits.len() {
+        3 => {
+            let mut rgb = [0; 3];
+            for (i, value) in rgb.iter_mut().enumerate() {
+                *value = component(i..i + 1)? * 0x11;
+            }
+            Some(rgb)
+        }
+        6 => Some([component(0..2)?, component(2..4)?, component(4..6)?]),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hex_codes() {
+        assert_eq!("#FFF".parse::<BackgroundColor>().unwrap().rgb, [255; 3]);
+        assert_eq!(
+            "0a0b0c".parse::<BackgroundColor>().unwrap().rgb,
+            [10, 11, 12]
+        );
+        assert_eq!(
+            "#f80".parse::<BackgroundColor>().unwrap().rgb,
+            [255, 136, 0]
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_colors() {
+        for value in ["", "#", "#ffff", "#gggggg", "transparent", "#+1+2+3"] {
+            assert!(value.parse::<BackgroundColor>().is_err(), "{value}");
+        }
+    }
+}
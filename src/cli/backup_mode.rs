@@ -0,0 +1,18 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// Strategy for naming backups when a backup from a previous run already exists.
+///
+/// Running the optimizer repeatedly with `--backup` would otherwise overwrite the first
+/// backup (the true original) with an already-optimized file. The mode decides how an
+/// existing backup is protected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupMode {
+    /// Keep the existing backup and do not create a new one
+    Skip,
+    /// Create numbered backups (`image.jpg.bak`, `image.jpg.bak.1`, ...)
+    Number,
+    /// Create timestamped backups (`image.jpg.20240101T120000.bak`)
+    Timestamp,
+}
@@ -1,5 +1,18 @@
 use clap::Parser;
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::Arc;
+
+use super::{
+    BackgroundColor, BackupMode, Command, DedupMode, ExtensionNormalization, FlattenCollisions,
+    InputLimit, LargerPolicy, PngLevel, Preset, ProgressInterval, SortOrder, Toggle,
+    parse_date_pattern, parse_format_concurrency, parse_format_quality, parse_input_limit,
+    parse_modified_since, parse_name_suffix, parse_png_level, parse_progress_interval,
+};
+use crate::file_ops::{NameTemplate, parse_byte_size};
+use crate::optimization::ImageFormat;
+use crate::settings::SidecarConfigs;
+use crate::updater::Repository;
 
 /// Command-line interface configuration for the image optimizer tool.
 ///
@@ -15,7 +28,7 @@ use std::path::PathBuf;
 /// // Parse CLI arguments
 /// let cli = Cli::parse();
 /// ```
-#[derive(Parser)]
+#[derive(Clone, Parser)]
 #[command(name = "image-optimizer")]
 #[command(about = "CLI tool for optimizing images (JPEG, PNG, WebP, SVG)")]
 #[command(long_about = None)]
@@ -26,47 +39,542 @@ pub struct Cli {
     #[arg(short, long)]
     pub input: Option<PathBuf>,
 
+    /// Read the files to process from a newline-delimited list ("-" for stdin) instead of
+    /// scanning; --input, if given, is the base for mirroring into --output
+    #[arg(long, value_name = "PATH")]
+    pub files_from: Option<PathBuf>,
+
+    /// Entries in the --files-from list are separated by NUL bytes (e.g. `find -print0`)
+    #[arg(short = '0', long, requires = "files_from")]
+    pub null: bool,
+
+    /// Read a single image from stdin and write the optimized image to stdout
+    #[arg(long, conflicts_with_all = ["input", "output", "files_from"])]
+    pub stdin: bool,
+
+    /// Load settings from this TOML file instead of searching for .image-optimizer.toml
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Ignore .image-optimizer.toml files found next to or above the input
+    #[arg(long, conflicts_with = "config")]
+    pub no_config: bool,
+
+    /// Print the effective settings after merging the preset and config file as TOML,
+    /// then exit without processing any image
+    #[arg(long)]
+    pub print_config: bool,
+
+    /// Start from a bundle of settings; explicit flags still override it
+    #[arg(long, value_enum)]
+    pub preset: Option<Preset>,
+
+    /// File name pattern for outputs, e.g. `{stem}.min.{ext}` (placeholders: {stem}, {ext},
+    /// {parent}, {width}, {height}; must contain {stem})
+    #[arg(long, value_name = "PATTERN", requires = "output")]
+    pub name_template: Option<NameTemplate>,
+
+    /// Place outputs in a subdirectory named after the capture date, e.g. `%Y/%m` (the
+    /// EXIF date, or the modification time without one)
+    #[arg(long, value_name = "PATTERN", value_parser = parse_date_pattern, requires = "output")]
+    pub organize_by_date: Option<String>,
+
+    /// Output format for --stdin and --sizes (defaults to the detected input format)
+    #[arg(long, value_enum)]
+    pub format: Option<ImageFormat>,
+
     /// Output directory (if not specified, optimizes in place)
     #[arg(short, long)]
     pub output: Option<PathBuf>,
 
+    /// Write the optimized result of a single input file to exactly this path, creating
+    /// its parent directories (the extension must match the input format)
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["output", "files_from", "stdin", "sizes", "watch"]
+    )]
+    pub output_file: Option<PathBuf>,
+
+    /// Write every output directly into --output by file name instead of mirroring the
+    /// input subdirectories
+    #[arg(long, requires = "output")]
+    pub flatten: bool,
+
+    /// How --flatten handles inputs that share a file name
+    #[arg(long, value_enum, default_value_t = FlattenCollisions::Suffix, requires = "flatten")]
+    pub flatten_collisions: FlattenCollisions,
+
+    /// Rewrite output file extensions: lowercase them, or also use the canonical spelling
+    /// (`.jpeg` becomes `.jpg`); only the file name changes
+    #[arg(long, value_enum, value_name = "MODE", requires = "output")]
+    pub output_ext_normalize: Option<ExtensionNormalization>,
+
+    /// Output path of each input under --flatten, assigned once the files are known (not
+    /// a command-line option; see `plan_flat_outputs`)
+    #[arg(skip)]
+    pub flat_outputs: HashMap<PathBuf, PathBuf>,
+
+    /// `.image-optimizer.toml` sidecars in subdirectories of the input, looked up per file
+    /// (not a command-line option; see `resolve_args`)
+    #[arg(skip)]
+    pub sidecar_configs: Option<Arc<SidecarConfigs>>,
+
+    /// Record optimized files in this JSON manifest and skip them on later runs while unchanged
+    #[arg(long, value_name = "PATH")]
+    pub cache: Option<PathBuf>,
+
+    /// Optimize byte-identical inputs once: hard-link or copy the result to the other
+    /// copies, or only report the groups of duplicates
+    #[arg(long, value_enum, value_name = "MODE", conflicts_with = "sizes")]
+    pub dedup: Option<DedupMode>,
+
+    /// Lock file that keeps concurrent runs apart (default: .image-optimizer.lock in the
+    /// input directory)
+    #[arg(long, value_name = "PATH")]
+    pub lock_file: Option<PathBuf>,
+
+    /// Wait for another run holding the lock to finish instead of failing
+    #[arg(long)]
+    pub wait: bool,
+
+    /// Embed a small marker in optimized PNG, JPEG and SVG files, and skip marked files
+    /// on later in-place runs with the same settings (unless --force)
+    #[arg(long)]
+    pub mark: bool,
+
+    /// Leave files that already exist in the output directory untouched
+    #[arg(long, conflicts_with = "force")]
+    pub skip_existing: bool,
+
+    /// Overwrite existing output files, even if they are newer than the input
+    #[arg(long)]
+    pub force: bool,
+
     /// Create backup files (.bak)
     #[arg(long)]
     pub backup: bool,
 
+    /// Directory to collect backups in, mirroring the input structure (implies --backup)
+    #[arg(long)]
+    pub backup_dir: Option<PathBuf>,
+
+    /// How to name backups when one already exists (skip keeps the first, true original)
+    #[arg(long, value_enum, default_value_t = BackupMode::Skip)]
+    pub backup_mode: BackupMode,
+
+    /// What to do when the optimized file is not smaller than the original: keep the
+    /// original, keep the re-encoded file anyway, or skip it and report a failure
+    #[arg(long, value_enum, default_value_t = LargerPolicy::KeepOriginal)]
+    pub on_larger: LargerPolicy,
+
+    /// Keep a result that --on-larger discards next to the kept file, with this suffix
+    /// inserted before the extension (e.g. `.larger` writes `photo.larger.jpg`)
+    #[arg(long, value_name = "SUFFIX", value_parser = parse_name_suffix)]
+    pub keep_larger_as: Option<String>,
+
+    /// Only keep an optimized result that is smaller than the original by at least this
+    /// percentage (0-99); otherwise keep the original and count the file as skipped
+    #[arg(long, value_name = "PERCENT", default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=99))]
+    pub prefer_smaller_than_original_by: u8,
+
     /// Use lossless compression
     #[arg(long)]
     pub webp_lossless: bool,
 
+    /// WebP near-lossless level (0-100): lower values adjust more pixels to compress
+    /// flat regions better while keeping edges; implies lossless encoding
+    #[arg(long, value_name = "LEVEL", value_parser = clap::value_parser!(u8).range(0..=100))]
+    pub webp_near_lossless: Option<u8>,
+
+    /// WebP encoder effort (0-6): higher is slower but produces smaller files
+    #[arg(long, value_name = "METHOD", default_value_t = 4, value_parser = clap::value_parser!(u8).range(0..=6))]
+    pub webp_method: u8,
+
+    /// Only remove metadata (EXIF, XMP, comments) without re-encoding pixels
+    #[arg(long, conflicts_with_all = ["max_size", "format", "jpeg_quality", "quality"])]
+    pub strip_only: bool,
+
+    /// Optimize JPEGs losslessly on their DCT coefficients instead of re-encoding them
+    /// (falls back to a re-encode at --jpeg-quality when resizing)
+    #[arg(long)]
+    pub jpeg_lossless: bool,
+
     /// JPEG quality (1-100), ignored if lossless is set (applies to raster formats only)
     #[arg(long, default_value = "85")]
     pub jpeg_quality: u8,
 
+    /// Quality for one format, e.g. `webp=75` (repeatable, overrides --jpeg-quality for that format)
+    #[arg(long, value_name = "FORMAT=VALUE", value_parser = parse_format_quality)]
+    pub quality: Vec<(ImageFormat, u8)>,
+
+    /// Never re-encode a JPEG above the quality estimated from its quantization tables,
+    /// so low-quality sources are not bloated
+    #[arg(long)]
+    pub no_upscale_quality: bool,
+
+    /// Trellis quantization for re-encoded JPEGs; `off` encodes much faster but
+    /// produces larger baseline files
+    #[arg(long, value_enum, default_value_t = Toggle::On)]
+    pub jpeg_trellis: Toggle,
+
+    /// Optimized Huffman tables for re-encoded JPEGs; `off` skips a pass over the image
+    /// but produces larger files (only takes effect with --jpeg-trellis off)
+    #[arg(long, value_enum, default_value_t = Toggle::On)]
+    pub jpeg_optimize_coding: Toggle,
+
+    /// Embed the source ICC color profile in re-encoded JPEG output
+    #[arg(long)]
+    pub keep_icc: bool,
+
+    /// Convert raster images tagged with a non-sRGB ICC profile (Display P3, Adobe RGB)
+    /// to sRGB before encoding, dropping the profile
+    #[arg(long, conflicts_with = "strip_only")]
+    pub to_srgb: bool,
+
+    /// Color to flatten transparency onto when converting to a format without alpha
+    /// (JPEG): a hex code such as #ffffff or a name (white, black, gray, red, green, blue)
+    #[arg(long, value_name = "COLOR", default_value_t = BackgroundColor::default())]
+    pub background: BackgroundColor,
+
     /// Recursively scan subdirectories
     #[arg(short, long)]
     pub recursive: bool,
 
+    /// Descend at most N directory levels below the input (1 = only its own files);
+    /// implies --recursive
+    #[arg(long, value_name = "N")]
+    pub max_depth: Option<std::num::NonZeroUsize>,
+
+    /// Follow symbolic links while scanning (cyclic links are reported and skipped)
+    #[arg(long)]
+    pub follow_symlinks: bool,
+
+    /// Only process files whose path relative to the input matches this glob (repeatable)
+    #[arg(long, value_name = "GLOB")]
+    pub include: Vec<String>,
+
+    /// Skip files and directories whose relative path matches this glob (repeatable, wins over --include)
+    #[arg(long, value_name = "GLOB")]
+    pub exclude: Vec<String>,
+
+    /// Never descend into directories with this exact name, at any depth, e.g.
+    /// `node_modules` or `.git` (repeatable)
+    #[arg(long, value_name = "NAME")]
+    pub exclude_dir: Vec<String>,
+
+    /// Skip files ignored by .gitignore, .ignore and global git excludes
+    #[arg(long)]
+    pub respect_gitignore: bool,
+
+    /// Treat unreadable files and directories during scanning as fatal errors
+    #[arg(long)]
+    pub strict_scan: bool,
+
+    /// Order in which images are processed
+    #[arg(long, value_enum, default_value_t = SortOrder::Path)]
+    pub sort: SortOrder,
+
+    /// Only process N randomly chosen files from the scan or --files-from list; the other
+    /// files are left untouched
+    #[arg(long, value_name = "N", conflicts_with = "watch")]
+    pub sample: Option<std::num::NonZeroUsize>,
+
+    /// Seed for --sample, to pick the same files again (default: random, printed)
+    #[arg(long, requires = "sample")]
+    pub seed: Option<u64>,
+
+    /// Abort before touching anything if more than N image files are found, guarding
+    /// against a mistyped input path (override with --yes)
+    #[arg(long, value_name = "N")]
+    pub max_files: Option<std::num::NonZeroUsize>,
+
+    /// Answer yes to safety checks such as --max-files and the confirmation of large
+    /// in-place runs without backups, for scripts
+    #[arg(short, long)]
+    pub yes: bool,
+
+    /// Skip files smaller than this size, e.g. 10KB (directory scans only)
+    #[arg(long, value_name = "SIZE", value_parser = parse_byte_size)]
+    pub min_size: Option<u64>,
+
+    /// Only process files modified after this point: a duration back from now such as
+    /// 24h or 7d, or a date such as 2024-05-01 (directory scans only)
+    #[arg(
+        long,
+        visible_alias = "only-modified-since",
+        value_name = "WHEN",
+        value_parser = parse_modified_since
+    )]
+    pub modified_since: Option<std::time::SystemTime>,
+
+    /// Skip images above this limit before decoding them: a file size such as 50MB or a
+    /// pixel count such as 100MP or 4000000px
+    #[arg(long, value_name = "LIMIT", value_parser = parse_input_limit)]
+    pub max_input_size: Option<InputLimit>,
+
+    /// Write one resized copy of each raster image per width, e.g. 320,640,1280, named
+    /// like photo-320.webp (widths above the source are skipped)
+    #[arg(
+        long,
+        value_name = "WIDTHS",
+        value_delimiter = ',',
+        value_parser = clap::value_parser!(u32).range(1..),
+        requires = "output",
+        conflicts_with_all = ["max_size", "strip_only", "flatten"]
+    )]
+    pub sizes: Vec<u32>,
+
     /// Maximum size for the longer edge (resizes if larger, applies to raster formats only)
     #[arg(long)]
     pub max_size: Option<u32>,
 
     /// Oxipng optimization level (0-6 or max)
-    #[arg(long, default_value = "2")]
-    pub png_optimization_level: String,
+    #[arg(long, value_name = "LEVEL", default_value = "2", value_parser = parse_png_level)]
+    pub png_optimization_level: PngLevel,
 
     /// Zopfli iterations for optimization (1-255)
-    #[arg(long, default_value = "15")]
+    #[arg(long, default_value = "15", conflicts_with = "no_zopfli")]
     pub zopfli_iterations: std::num::NonZeroU8,
 
     #[arg(long)]
     pub no_zopfli: bool,
 
+    /// Libdeflater compression level used with --no-zopfli (1-12): lower is faster but
+    /// larger; has no effect while zopfli is enabled
+    #[arg(long, value_name = "LEVEL", default_value_t = 12, value_parser = clap::value_parser!(u8).range(1..=12))]
+    pub deflate_level: u8,
+
+    /// Decimal places kept in SVG path data (0-8)
+    #[arg(long, value_name = "DIGITS", default_value_t = 2, value_parser = clap::value_parser!(u8).range(0..=8))]
+    pub svg_precision: u8,
+
+    /// Write .svg inputs as gzip-compressed .svgz files (.svgz inputs are always recompressed)
+    #[arg(long, requires = "output")]
+    pub svgz: bool,
+
+    /// Gzip compression level for .svgz output (0-9)
+    #[arg(long, value_name = "LEVEL", default_value_t = 9, value_parser = clap::value_parser!(u32).range(0..=9))]
+    pub svgz_level: u32,
+
     #[arg(long)]
     pub no_parallel: bool,
 
+    /// Files of one format optimized at the same time, e.g. `png=2` (repeatable; that
+    /// format then runs in its own pool, other formats keep the shared one)
+    #[arg(
+        long,
+        value_name = "FORMAT=N",
+        value_parser = parse_format_concurrency,
+        conflicts_with = "no_parallel"
+    )]
+    pub concurrency_per_format: Vec<(ImageFormat, std::num::NonZeroUsize)>,
+
+    /// Bound the memory of images optimized at the same time to roughly this many MB,
+    /// estimated from their dimensions (huge images then run with less parallelism)
+    #[arg(long, value_name = "MB")]
+    pub memory_limit: Option<std::num::NonZeroU64>,
+
+    /// Print the original size, optimized size and change of each processed file
+    #[arg(short, long)]
+    pub verbose: bool,
+
+    /// Only print errors: no progress bar, file count, per-file lines or summary
+    #[arg(short, long)]
+    pub quiet: bool,
+
+    /// Print a stable tab-separated summary line (status, path, original, optimized,
+    /// saved) instead of the decorative output; with --verbose, one such line per file too
+    #[arg(long, conflicts_with_all = ["stdin", "compare_qualities"])]
+    pub porcelain: bool,
+
+    /// After the summary, list the files kept because optimization would increase their
+    /// size, with their original and attempted sizes
+    #[arg(long)]
+    pub list_skipped: bool,
+
+    /// Ring the terminal bell when the run finishes, and show a desktop notification with
+    /// the totals if built with the `desktop-notify` feature
+    #[arg(long)]
+    pub notify: bool,
+
+    /// After the summary, list the N files with the largest savings
+    #[arg(long, value_name = "N")]
+    pub top: Option<std::num::NonZeroUsize>,
+
+    /// Compare each optimized image with its original and report SSIM and PSNR in the
+    /// --verbose lines and the JSON report (raster formats only)
+    #[arg(long)]
+    pub measure_quality: bool,
+
+    /// Decode each optimized result again before it replaces or is kept as the output,
+    /// and keep the original if it does not decode to the expected dimensions
+    #[arg(long)]
+    pub verify: bool,
+
+    /// Instead of optimizing, encode the JPEG and WebP inputs at each of these qualities,
+    /// e.g. 60,75,85,95, and print a table of the resulting sizes (and SSIM with
+    /// --measure-quality); nothing is written
+    #[arg(
+        long,
+        value_name = "QUALITIES",
+        value_delimiter = ',',
+        value_parser = clap::value_parser!(u8).range(1..=100),
+        conflicts_with_all = ["watch", "stdin", "sizes", "dedup"]
+    )]
+    pub compare_qualities: Vec<u8>,
+
+    /// Write a side-by-side PNG of each image before and after optimization into this
+    /// directory, mirroring the input structure (raster formats only)
+    #[arg(long, value_name = "DIR")]
+    pub compare_out: Option<PathBuf>,
+
+    /// Write a JSON report with per-file results and run totals to this path
+    #[arg(long, value_name = "PATH")]
+    pub report_json: Option<PathBuf>,
+
+    /// After the summary, compare this run's results with a `--report-json` report of an
+    /// earlier run and list the files that got bigger, smaller or changed status
+    #[arg(long, value_name = "BASELINE")]
+    pub diff_report: Option<PathBuf>,
+
+    /// Append a timestamped JSON line with the run totals to this log file after each run
+    #[arg(long, value_name = "PATH")]
+    pub log_file: Option<PathBuf>,
+
+    /// Also append one line per file to the --log-file log
+    #[arg(long, requires = "log_file")]
+    pub log_per_file: bool,
+
+    /// Rotate the --log-file log to PATH.1 before it grows past this size, e.g. 10MB
+    #[arg(long, value_name = "SIZE", value_parser = parse_byte_size, requires = "log_file")]
+    pub log_max_bytes: Option<u64>,
+
+    /// Retry a file up to N times (0-10) after a transient I/O error (EAGAIN, EBUSY,
+    /// EINTR), e.g. on network filesystems
+    #[arg(long, value_name = "N", default_value_t = 0, value_parser = clap::value_parser!(u8).range(..=10))]
+    pub retry: u8,
+
+    /// Stop the batch at the first file that fails instead of continuing
+    #[arg(long)]
+    pub fail_fast: bool,
+
+    /// After the initial pass, keep optimizing images in the input directory as they are
+    /// created or modified (until Ctrl-C)
+    #[arg(long, conflicts_with_all = ["files_from", "stdin"])]
+    pub watch: bool,
+
+    /// Print a plain-text progress line to stderr after every N files (`500`) or at a time
+    /// interval (`30s`, `5m`), e.g. for CI logs where the progress bar is hidden
+    #[arg(long, value_name = "FILES|DURATION", value_parser = parse_progress_interval)]
+    pub progress_interval: Option<ProgressInterval>,
+
+    /// Hide the spinner counting scanned files while the input is discovered
+    #[arg(long)]
+    pub no_scan_progress: bool,
+
+    /// Give optimized files the current modification time instead of the original one
+    #[arg(long)]
+    pub no_preserve_timestamps: bool,
+
     /// Update to the latest version
     #[arg(long)]
     pub update: bool,
+
+    /// Only check whether a newer version is available (exit code 10 if it is)
+    #[arg(long, conflicts_with = "update")]
+    pub check_update: bool,
+
+    /// GitHub repository to update from, as OWNER/NAME (default: nixuuu/image-optimizer)
+    #[arg(long, value_name = "OWNER/NAME")]
+    pub update_repo: Option<Repository>,
+
+    /// Include prereleases when looking for the newest version to update to
+    #[arg(long)]
+    pub update_prerelease: bool,
+
+    /// Proxy URL for update requests (default: `HTTPS_PROXY`/`HTTP_PROXY`)
+    #[arg(long, value_name = "URL")]
+    pub update_proxy: Option<String>,
+
+    /// Seconds to wait when connecting and for each update request
+    #[arg(long, value_name = "SECS", default_value_t = 30, value_parser = clap::value_parser!(u64).range(1..))]
+    pub update_timeout: u64,
+
+    /// Restore the executable that was replaced by the last --update
+    #[arg(long, conflicts_with_all = ["update", "check_update"])]
+    pub rollback: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+impl Cli {
+    /// Returns the `--concurrency-per-format` width of `format`, if one is set.
+    ///
+    /// The last entry for the format wins.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use clap::Parser;
+    /// use image_optimizer::cli::Cli;
+    /// use image_optimizer::optimization::ImageFormat;
+    ///
+    /// let cli = Cli::parse_from(["image-optimizer", "--concurrency-per-format", "png=2"]);
+    /// assert_eq!(cli.concurrency_for(ImageFormat::Png).map(|n| n.get()), Some(2));
+    /// assert_eq!(cli.concurrency_for(ImageFormat::Jpeg), None);
+    /// ```
+    #[must_use]
+    pub fn concurrency_for(&self, format: ImageFormat) -> Option<std::num::NonZeroUsize> {
+        self.concurrency_per_format
+            .iter()
+            .rev()
+            .find(|(entry_format, _)| *entry_format == format)
+            .map(|&(_, width)| width)
+    }
+
+    /// Returns whether results replace their inputs, i.e. neither `--output` nor
+    /// `--output-file` is given.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use clap::Parser;
+    /// use image_optimizer::cli::Cli;
+    ///
+    /// assert!(Cli::parse_from(["image-optimizer"]).is_in_place());
+    /// assert!(!Cli::parse_from(["image-optimizer", "--output-file", "b.png"]).is_in_place());
+    /// ```
+    #[must_use]
+    pub const fn is_in_place(&self) -> bool {
+        self.output.is_none() && self.output_file.is_none()
+    }
+
+    /// Returns the quality to encode `format` with.
+    ///
+    /// The last `--quality` entry for the format wins; formats without an entry fall
+    /// back to `--jpeg-quality`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use clap::Parser;
+    /// use image_optimizer::cli::Cli;
+    /// use image_optimizer::optimization::ImageFormat;
+    ///
+    /// let cli = Cli::parse_from(["image-optimizer", "--jpeg-quality", "90", "--quality", "webp=75"]);
+    /// assert_eq!(cli.quality_for(ImageFormat::Webp), 75);
+    /// assert_eq!(cli.quality_for(ImageFormat::Jpeg), 90);
+    /// ```
+    #[must_use]
+    pub fn quality_for(&self, format: ImageFormat) -> u8 {
+        self.quality
+            .iter()
+            .rev()
+            .find(|(entry_format, _)| *entry_format == format)
+            .map_or(self.jpeg_quality, |&(_, quality)| quality)
+    }
 }
 
 #[cfg(test)]
@@ -78,15 +586,281 @@ mod tests {
     fn test_cli_defaults() {
         let cli = Cli::parse_from(["image-optimizer"]);
         assert_eq!(cli.input, None);
+        assert_eq!(cli.files_from, None);
+        assert!(!cli.null);
+        assert!(!cli.stdin);
+        assert_eq!(cli.format, None);
+        assert_eq!(cli.config, None);
+        assert!(!cli.no_config);
+        assert!(!cli.print_config);
+        assert_eq!(cli.preset, None);
+        assert!(!cli.keep_icc);
+        assert!(!cli.to_srgb);
+        assert_eq!(cli.background, BackgroundColor::default());
         assert_eq!(cli.output, None);
+        assert!(!cli.flatten);
+        assert_eq!(cli.flatten_collisions, FlattenCollisions::Suffix);
+        assert!(cli.flat_outputs.is_empty());
+        assert_eq!(cli.output_ext_normalize, None);
+        assert_eq!(cli.cache, None);
+        assert_eq!(cli.dedup, None);
+        assert!(!cli.mark);
+        assert_eq!(cli.lock_file, None);
+        assert!(!cli.wait);
+        assert!(!cli.skip_existing);
+        assert!(!cli.force);
         assert!(!cli.backup);
+        assert_eq!(cli.backup_dir, None);
+        assert_eq!(cli.backup_mode, BackupMode::Skip);
+        assert_eq!(cli.on_larger, LargerPolicy::KeepOriginal);
+        assert_eq!(cli.keep_larger_as, None);
+        assert_eq!(cli.prefer_smaller_than_original_by, 0);
+        assert_eq!(cli.output_file, None);
+        assert!(cli.exclude_dir.is_empty());
+        assert_eq!(cli.progress_interval, None);
         assert!(!cli.webp_lossless);
+        assert!(!cli.strip_only);
+        assert!(!cli.jpeg_lossless);
+        assert_eq!(cli.webp_near_lossless, None);
+        assert_eq!(cli.webp_method, 4);
         assert_eq!(cli.jpeg_quality, 85);
+        assert!(!cli.no_upscale_quality);
+        assert_eq!(cli.jpeg_trellis, Toggle::On);
+        assert_eq!(cli.jpeg_optimize_coding, Toggle::On);
         assert!(!cli.recursive);
+        assert!(cli.include.is_empty());
+        assert!(cli.exclude.is_empty());
+        assert!(!cli.respect_gitignore);
+        assert!(!cli.strict_scan);
+        assert_eq!(cli.sort, SortOrder::Path);
         assert_eq!(cli.max_size, None);
-        assert_eq!(cli.png_optimization_level, "2");
+        assert_eq!(cli.png_optimization_level.get(), 2);
         assert_eq!(cli.zopfli_iterations.get(), 15);
+        assert_eq!(cli.deflate_level, 12);
+        assert_eq!(cli.svg_precision, 2);
+        assert!(!cli.svgz);
+        assert_eq!(cli.svgz_level, 9);
         assert!(!cli.update);
+        assert!(!cli.check_update);
+        assert!(!cli.rollback);
+        assert_eq!(cli.update_repo, None);
+        assert!(!cli.update_prerelease);
+        assert_eq!(cli.update_proxy, None);
+        assert_eq!(cli.update_timeout, 30);
+        assert_eq!(cli.retry, 0);
+        assert!(!cli.fail_fast);
+        assert!(!cli.verbose);
+        assert!(!cli.quiet);
+        assert!(!cli.porcelain);
+        assert!(!cli.list_skipped);
+        assert!(!cli.notify);
+        assert!(!cli.follow_symlinks);
+        assert_eq!(cli.top, None);
+        assert_eq!(cli.modified_since, None);
+        assert_eq!(cli.max_files, None);
+        assert!(!cli.yes);
+        assert!(cli.quality.is_empty());
+        assert_eq!(cli.min_size, None);
+        assert_eq!(cli.sample, None);
+        assert_eq!(cli.seed, None);
+        assert_eq!(cli.max_input_size, None);
+        assert_eq!(cli.memory_limit, None);
+        assert!(cli.sizes.is_empty());
+        assert_eq!(cli.name_template, None);
+        assert_eq!(cli.organize_by_date, None);
+        assert!(cli.command.is_none());
+        assert_eq!(cli.report_json, None);
+        assert_eq!(cli.diff_report, None);
+        assert_eq!(cli.log_file, None);
+        assert!(!cli.log_per_file);
+        assert_eq!(cli.log_max_bytes, None);
+        assert_eq!(cli.compare_out, None);
+        assert!(!cli.measure_quality);
+        assert!(!cli.verify);
+        assert!(cli.compare_qualities.is_empty());
+        assert!(!cli.no_preserve_timestamps);
+        assert!(!cli.no_scan_progress);
+    }
+
+    #[test]
+    fn test_cli_backup_modes() {
+        let cli = Cli::parse_from(["image-optimizer", "--backup-mode", "number"]);
+        assert_eq!(cli.backup_mode, BackupMode::Number);
+
+        let cli = Cli::parse_from(["image-optimizer", "--backup-mode", "timestamp"]);
+        assert_eq!(cli.backup_mode, BackupMode::Timestamp);
+
+        assert!(Cli::try_parse_from(["image-optimizer", "--backup-mode", "overwrite"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_rejects_contradictory_flags() {
+        for command_line in [
+            &["--no-zopfli", "--zopfli-iterations", "50"][..],
+            &["--strip-only", "--jpeg-quality", "70"],
+            &["--strip-only", "--quality", "webp=70"],
+            &["--strip-only", "--max-size", "1024"],
+            &["--sizes", "320", "-o", "out", "--max-size", "1024"],
+        ] {
+            let error = Cli::try_parse_from(
+                std::iter::once("image-optimizer").chain(command_line.iter().copied()),
+            )
+            .err()
+            .unwrap_or_else(|| panic!("{command_line:?} was accepted"));
+            assert_eq!(error.kind(), clap::error::ErrorKind::ArgumentConflict);
+        }
+
+        // Defaults of the conflicting flags do not count
+        let cli = Cli::parse_from(["image-optimizer", "--no-zopfli"]);
+        assert_eq!(cli.zopfli_iterations.get(), 15);
+    }
+
+    #[test]
+    fn test_cli_parses_jpeg_toggles() {
+        let cli = Cli::parse_from([
+            "image-optimizer",
+            "--jpeg-trellis",
+            "off",
+            "--jpeg-optimize-coding",
+            "off",
+        ]);
+        assert_eq!(cli.jpeg_trellis, Toggle::Off);
+        assert_eq!(cli.jpeg_optimize_coding, Toggle::Off);
+        let cli = Cli::parse_from(["image-optimizer", "--jpeg-trellis", "on"]);
+        assert_eq!(cli.jpeg_trellis, Toggle::On);
+
+        let error = Cli::try_parse_from(["image-optimizer", "--jpeg-trellis", "yes"])
+            .err()
+            .unwrap();
+        assert_eq!(error.kind(), clap::error::ErrorKind::InvalidValue);
+    }
+
+    #[test]
+    fn test_cli_rejects_invalid_deflate_level() {
+        for level in ["0", "13", "max"] {
+            let error = Cli::try_parse_from(["image-optimizer", "--deflate-level", level])
+                .err()
+                .unwrap_or_else(|| panic!("level {level} was accepted"));
+            assert_eq!(error.kind(), clap::error::ErrorKind::ValueValidation);
+        }
+        let cli = Cli::parse_from(["image-optimizer", "--no-zopfli", "--deflate-level", "1"]);
+        assert_eq!(cli.deflate_level, 1);
+    }
+
+    #[test]
+    fn test_cli_rejects_invalid_png_level() {
+        for level in ["9", "7", "6.5", "fast"] {
+            let error = Cli::try_parse_from(["image-optimizer", "--png-optimization-level", level])
+                .err()
+                .unwrap_or_else(|| panic!("level {level} was accepted"));
+            assert_eq!(error.kind(), clap::error::ErrorKind::ValueValidation);
+            assert!(
+                error
+                    .to_string()
+                    .contains("expected a level from 0 to 6 or 'max'"),
+                "{error}"
+            );
+        }
+        let cli = Cli::parse_from(["image-optimizer", "--png-optimization-level", "0"]);
+        assert_eq!(cli.png_optimization_level.get(), 0);
+    }
+
+    #[test]
+    fn test_cli_repeatable_globs() {
+        let cli = Cli::parse_from([
+            "image-optimizer",
+            "--include",
+            "*.png",
+            "--include",
+            "*.jpg",
+            "--exclude",
+            "node_modules",
+        ]);
+        assert_eq!(cli.include, vec!["*.png", "*.jpg"]);
+        assert_eq!(cli.exclude, vec!["node_modules"]);
+    }
+
+    #[test]
+    fn test_cli_sort_order() {
+        let cli = Cli::parse_from(["image-optimizer", "--sort", "size"]);
+        assert_eq!(cli.sort, SortOrder::Size);
+
+        let cli = Cli::parse_from(["image-optimizer", "--sort", "mtime"]);
+        assert_eq!(cli.sort, SortOrder::Mtime);
+    }
+
+    #[test]
+    fn test_cli_seed_requires_sample() {
+        assert!(Cli::try_parse_from(["image-optimizer", "--seed", "7"]).is_err());
+
+        let cli = Cli::parse_from(["image-optimizer", "--sample", "20", "--seed", "7"]);
+        assert_eq!(cli.sample.map(std::num::NonZeroUsize::get), Some(20));
+        assert_eq!(cli.seed, Some(7));
+    }
+
+    #[test]
+    fn test_cli_null_requires_files_from() {
+        assert!(Cli::try_parse_from(["image-optimizer", "--null"]).is_err());
+
+        let cli = Cli::parse_from(["image-optimizer", "--files-from", "-", "--null"]);
+        assert_eq!(cli.files_from, Some(PathBuf::from("-")));
+        assert!(cli.null);
+    }
+
+    #[test]
+    fn test_cli_stdin_with_format() {
+        let cli = Cli::parse_from(["image-optimizer", "--stdin", "--format", "jpg"]);
+        assert!(cli.stdin);
+        assert_eq!(cli.format, Some(ImageFormat::Jpeg));
+
+        assert!(Cli::try_parse_from(["image-optimizer", "--stdin", "-i", "images"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_config_conflicts_with_no_config() {
+        let cli = Cli::parse_from(["image-optimizer", "--config", "team.toml"]);
+        assert_eq!(cli.config, Some(PathBuf::from("team.toml")));
+
+        let result = Cli::try_parse_from(["image-optimizer", "--config", "a.toml", "--no-config"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_cli_flatten_requires_output() {
+        assert!(Cli::try_parse_from(["image-optimizer", "--flatten"]).is_err());
+        assert!(
+            Cli::try_parse_from([
+                "image-optimizer",
+                "-o",
+                "out",
+                "--flatten-collisions",
+                "error"
+            ])
+            .is_err()
+        );
+
+        let cli = Cli::parse_from([
+            "image-optimizer",
+            "-o",
+            "out",
+            "--flatten",
+            "--flatten-collisions",
+            "error",
+        ]);
+        assert!(cli.flatten);
+        assert_eq!(cli.flatten_collisions, FlattenCollisions::Error);
+    }
+
+    #[test]
+    fn test_cli_skip_existing_conflicts_with_force() {
+        let result = Cli::try_parse_from(["image-optimizer", "--skip-existing", "--force"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_cli_disable_preserve_timestamps() {
+        let cli = Cli::parse_from(["image-optimizer", "--no-preserve-timestamps"]);
+        assert!(cli.no_preserve_timestamps);
     }
 
     #[test]
@@ -124,11 +898,12 @@ mod tests {
         assert_eq!(cli.jpeg_quality, 90);
         assert!(cli.recursive);
         assert_eq!(cli.max_size, Some(1024));
-        assert_eq!(cli.png_optimization_level, "max");
+        assert_eq!(cli.png_optimization_level, PngLevel::MAX);
         assert_eq!(cli.zopfli_iterations.get(), 25);
         assert!(cli.update);
         assert!(!cli.no_zopfli);
         assert!(!cli.no_parallel);
+        assert!(cli.concurrency_per_format.is_empty());
     }
 
     #[test]
@@ -140,6 +915,68 @@ mod tests {
         assert_eq!(cli.jpeg_quality, 100);
     }
 
+    #[test]
+    fn test_cli_webp_encoder_options() {
+        let cli = Cli::parse_from([
+            "image-optimizer",
+            "--webp-near-lossless",
+            "60",
+            "--webp-method",
+            "6",
+        ]);
+        assert_eq!(cli.webp_near_lossless, Some(60));
+        assert_eq!(cli.webp_method, 6);
+
+        assert!(Cli::try_parse_from(["image-optimizer", "--webp-near-lossless", "101"]).is_err());
+        assert!(Cli::try_parse_from(["image-optimizer", "--webp-method", "7"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_per_format_quality() {
+        let cli = Cli::parse_from([
+            "image-optimizer",
+            "--quality",
+            "jpg=82",
+            "--quality",
+            "webp=75",
+            "--quality",
+            "jpeg=88",
+        ]);
+        assert_eq!(cli.quality.len(), 3);
+        assert_eq!(cli.quality_for(ImageFormat::Jpeg), 88);
+        assert_eq!(cli.quality_for(ImageFormat::Webp), 75);
+
+        let cli = Cli::parse_from(["image-optimizer", "--jpeg-quality", "70"]);
+        assert_eq!(cli.quality_for(ImageFormat::Webp), 70);
+        assert_eq!(cli.quality_for(ImageFormat::Jpeg), 70);
+
+        assert!(Cli::try_parse_from(["image-optimizer", "--quality", "png=80"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_stats_subcommand() {
+        let cli = Cli::parse_from(["image-optimizer", "stats", "images", "-r"]);
+        let Some(Command::Stats(stats)) = cli.command else {
+            panic!("expected the stats subcommand");
+        };
+        assert_eq!(stats.path, PathBuf::from("images"));
+        assert!(stats.recursive);
+        assert_eq!(stats.top, 10);
+    }
+
+    #[test]
+    fn test_cli_update_repo() {
+        let cli = Cli::parse_from([
+            "image-optimizer",
+            "--check-update",
+            "--update-repo",
+            "acme/image-optimizer",
+        ]);
+        assert_eq!(cli.update_repo.unwrap().to_string(), "acme/image-optimizer");
+
+        assert!(Cli::try_parse_from(["image-optimizer", "--update-repo", "acme"]).is_err());
+    }
+
     #[test]
     fn test_cli_help_generation() {
         let mut cmd = Cli::command();
@@ -0,0 +1,19 @@
+use clap::Subcommand;
+
+use super::{CompletionsArgs, InfoArgs, StatsArgs};
+
+/// Subcommands that inspect images or support the tool instead of optimizing images.
+///
+/// Running without a subcommand optimizes the `--input` path as before.
+#[derive(Debug, Clone, Subcommand)]
+pub enum Command {
+    /// Show the format breakdown and sizes of the images in a directory without
+    /// modifying anything
+    Stats(StatsArgs),
+    /// Show the format, dimensions, color type and metadata of one image, and its
+    /// estimated size after optimization with the current settings
+    Info(InfoArgs),
+    /// Print a shell completion script to stdout
+    #[command(hide = true)]
+    Completions(CompletionsArgs),
+}
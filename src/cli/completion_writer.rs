@@ -0,0 +1,29 @@
+use clap::CommandFactory;
+use clap_complete::Shell;
+use std::io::Write;
+
+use super::Cli;
+
+/// Writes the shell completion script for the command-line interface.
+///
+/// The script is generated from [`Cli`]'s clap definition, so it always matches the
+/// flags and subcommands of the running version.
+///
+/// # Arguments
+///
+/// * `shell` - Shell to generate the script for
+/// * `out` - Where to write the script, usually standard output
+///
+/// # Examples
+///
+/// ```rust
+/// use clap_complete::Shell;
+/// use image_optimizer::cli::write_completions;
+///
+/// let mut script = Vec::new();
+/// write_completions(Shell::Bash, &mut script);
+/// assert!(String::from_utf8_lossy(&script).contains("--jpeg-quality"));
+/// ```
+pub fn write_completions(shell: Shell, out: &mut dyn Write) {
+    clap_complete::generate(shell, &mut Cli::command(), env!("CARGO_PKG_NAME"), out);
+}
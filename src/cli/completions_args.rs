@@ -0,0 +1,10 @@
+use clap::Args;
+use clap_complete::Shell;
+
+/// Arguments of the `completions` subcommand.
+#[derive(Debug, Clone, Args)]
+pub struct CompletionsArgs {
+    /// Shell to generate the completion script for
+    #[arg(value_enum)]
+    pub shell: Shell,
+}
@@ -0,0 +1,81 @@
+use clap::ValueEnum;
+use std::num::NonZeroUsize;
+
+use crate::optimization::ImageFormat;
+
+/// Parses a `--concurrency-per-format <FORMAT>=<N>` entry.
+///
+/// The format accepts the same names as `--format` (including the `jpg` alias). The
+/// value is the number of files of that format optimized at the same time and must be
+/// at least 1.
+///
+/// # Arguments
+///
+/// * `value` - Raw argument, e.g. `png=2`
+///
+/// # Returns
+///
+/// Returns the format and its number of parallel files.
+///
+/// # Errors
+///
+/// Returns a message suitable for a clap usage error if:
+/// - The entry is not in `FORMAT=N` form
+/// - The format is unknown
+/// - The value is not a positive number
+///
+/// # Examples
+///
+/// ```rust
+/// use std::num::NonZeroUsize;
+/// use image_optimizer::cli::parse_format_concurrency;
+/// use image_optimizer::optimization::ImageFormat;
+///
+/// assert_eq!(
+///     parse_format_concurrency("png=2"),
+///     Ok((ImageFormat::Png, NonZeroUsize::new(2).unwrap()))
+/// );
+/// assert!(parse_format_concurrency("png=0").is_err());
+/// ```
+pub fn parse_format_concurrency(value: &str) -> Result<(ImageFormat, NonZeroUsize), String> {
+    let (format, width) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected FORMAT=N, got '{value}'"))?;
+
+    let format = ImageFormat::from_str(format.trim(), true)
+        .map_err(|_| format!("unknown image format '{}'", format.trim()))?;
+    let width = width.trim().parse::<NonZeroUsize>().map_err(|_| {
+        format!(
+            "expected a positive number of files, got '{}'",
+            width.trim()
+        )
+    })?;
+
+    Ok((format, width))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_formats_and_aliases() {
+        assert_eq!(
+            parse_format_concurrency("JPG=8"),
+            Ok((ImageFormat::Jpeg, NonZeroUsize::new(8).unwrap()))
+        );
+        assert_eq!(
+            parse_format_concurrency("svg = 1"),
+            Ok((ImageFormat::Svg, NonZeroUsize::MIN))
+        );
+    }
+
+    #[test]
+    fn test_rejects_invalid_entries() {
+        assert!(parse_format_concurrency("png").is_err());
+        assert!(parse_format_concurrency("gif=2").is_err());
+        assert!(parse_format_concurrency("png=0").is_err());
+        assert!(parse_format_concurrency("png=-1").is_err());
+        assert!(parse_format_concurrency("png=two").is_err());
+    }
+}
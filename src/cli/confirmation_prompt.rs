@@ -0,0 +1,119 @@
+use std::io::{self, BufRead, Write};
+
+use super::Cli;
+
+/// Number of files an in-place run without backups may modify before asking first.
+pub const IN_PLACE_CONFIRM_THRESHOLD: usize = 100;
+
+/// Whether a run over `count` files must be confirmed before it starts.
+///
+/// Only runs that rewrite originals with no way back need confirming: in place (no
+/// `--output`) and without `--backup`/`--backup-dir`, over more than
+/// [`IN_PLACE_CONFIRM_THRESHOLD`] files. `--yes` skips the question. Whether there is a
+/// terminal to ask on is up to the caller.
+///
+/// # Examples
+///
+/// ```rust
+/// use clap::Parser;
+/// use image_optimizer::cli::{Cli, needs_in_place_confirmation};
+///
+/// let args = Cli::parse_from(["image-optimizer", "-i", "photos"]);
+/// assert!(needs_in_place_confirmation(&args, 500));
+/// assert!(!needs_in_place_confirmation(&args, 20));
+///
+/// let args = Cli::parse_from(["image-optimizer", "-i", "photos", "--backup"]);
+/// assert!(!needs_in_place_confirmation(&args, 500));
+/// ```
+#[must_use]
+pub const fn needs_in_place_confirmation(args: &Cli, count: usize) -> bool {
+    count > IN_PLACE_CONFIRM_THRESHOLD
+        && args.is_in_place()
+        && !args.backup
+        && args.backup_dir.is_none()
+        && !args.yes
+}
+
+/// Asks a yes/no question and returns whether it was answered with yes.
+///
+/// The question is written to `output` without a newline and one line is read from
+/// `input`; only `y` or `yes` (in any case) count as yes, so an empty answer or end of
+/// input declines.
+///
+/// # Errors
+///
+/// Returns an error if writing the question or reading the answer fails.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_optimizer::cli::ask_confirmation;
+///
+/// let mut shown = Vec::new();
+/// assert!(ask_confirmation("Continue? [y/N] ", &b"y\n"[..], &mut shown).unwrap());
+/// assert!(!ask_confirmation("Continue? [y/N] ", &b"\n"[..], &mut shown).unwrap());
+/// ```
+pub fn ask_confirmation(
+    question: &str,
+    mut input: impl BufRead,
+    mut output: impl Write,
+) -> io::Result<bool> {
+    output.write_all(question.as_bytes())?;
+    output.flush()?;
+    let mut answer = String::new();
+    input.read_line(&mut answer)?;
+    let answer = answer.trim();
+    Ok(answer.eq_ignore_ascii_case("y") || answer.eq_ignore_ascii_case("yes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn test_confirmation_needed_only_for_unprotected_in_place_runs() {
+        let many = IN_PLACE_CONFIRM_THRESHOLD + 1;
+        let needs = |extra: &[&str]| {
+            let args = Cli::parse_from(
+                ["image-optimizer", "-i", "photos"]
+                    .into_iter()
+                    .chain(extra.iter().copied()),
+            );
+            needs_in_place_confirmation(&args, many)
+        };
+
+        assert!(needs(&[]));
+        assert!(!needs(&["--yes"]));
+        assert!(!needs(&["-o", "out"]));
+        assert!(!needs(&["--backup"]));
+        assert!(!needs(&["--backup-dir", "backups"]));
+
+        let args = Cli::parse_from(["image-optimizer", "-i", "photos"]);
+        assert!(!needs_in_place_confirmation(
+            &args,
+            IN_PLACE_CONFIRM_THRESHOLD
+        ));
+    }
+
+    #[test]
+    fn test_answers() {
+        for (answer, expected) in [
+            ("y\n", true),
+            ("YES\n", true),
+            (" yes \r\n", true),
+            ("n\n", false),
+            ("\n", false),
+            ("", false),
+            ("yep\n", false),
+        ] {
+            let mut shown = Vec::new();
+            assert_eq!(
+                ask_confirmation("Continue? [y/N] ", answer.as_bytes(), &mut shown).unwrap(),
+                expected,
+                "{answer:?}"
+            );
+            assert_eq!(shown, b"Continue? [y/N] ");
+        }
+    }
+}
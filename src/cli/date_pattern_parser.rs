@@ -0,0 +1,49 @@
+use chrono::format::{Item, StrftimeItems};
+use std::path::{Component, Path};
+
+/// Parses an `--organize-by-date` pattern such as `%Y/%m`.
+///
+/// The pattern uses `strftime` syntax and is expanded into a relative directory path, so
+/// it may contain `/` but must not start with one or contain `..` components.
+///
+/// # Arguments
+///
+/// * `value` - Raw argument, e.g. `%Y/%m-%d`
+///
+/// # Returns
+///
+/// Returns the pattern unchanged.
+///
+/// # Errors
+///
+/// Returns a message suitable for a clap usage error if the pattern is empty, has an
+/// unknown `%` specifier or would leave the output directory.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_optimizer::cli::parse_date_pattern;
+///
+/// assert_eq!(parse_date_pattern("%Y/%m"), Ok("%Y/%m".to_string()));
+/// assert!(parse_date_pattern("%Y/%Q").is_err());
+/// assert!(parse_date_pattern("../%Y").is_err());
+/// ```
+pub fn parse_date_pattern(value: &str) -> Result<String, String> {
+    if value.trim().is_empty() {
+        return Err("the date pattern is empty".to_string());
+    }
+    if StrftimeItems::new(value).any(|item| item == Item::Error) {
+        return Err(format!(
+            "'{value}' is not a valid strftime pattern (e.g. %Y/%m)"
+        ));
+    }
+    let escapes = Path::new(value)
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir));
+    if escapes {
+        return Err(format!(
+            "'{value}' must be a relative path inside the output directory"
+        ));
+    }
+    Ok(value.to_string())
+}
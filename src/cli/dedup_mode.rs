@@ -0,0 +1,18 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// What `--dedup` does with inputs whose bytes are identical.
+///
+/// Asset trees often contain the same image under several names. Only the first file of
+/// each group (in path order) is optimized; the mode decides how its result reaches the
+/// other copies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DedupMode {
+    /// Hard-link the duplicates to the optimized result (copies across filesystems)
+    Link,
+    /// Copy the optimized result to the duplicates
+    Copy,
+    /// Only list the groups of duplicates and optimize every file as usual
+    Report,
+}
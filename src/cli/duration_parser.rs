@@ -0,0 +1,66 @@
+use std::time::Duration;
+
+/// Parses a duration made of `<number><unit>` parts, e.g. `1d12h`.
+///
+/// Units are `s`, `m`, `h`, `d` and `w` (case-insensitive), and parts can be combined.
+/// Used by the options that accept a relative time, such as `--modified-since` and
+/// `--progress-interval`.
+///
+/// # Arguments
+///
+/// * `value` - Raw duration, e.g. `90s` or `1h30m`
+///
+/// # Returns
+///
+/// Returns the total duration, or `None` if the value is empty, has a part without a
+/// number or unit, or overflows.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::time::Duration;
+/// use image_optimizer::cli::parse_duration;
+///
+/// assert_eq!(parse_duration("1m30s"), Some(Duration::from_secs(90)));
+/// assert_eq!(parse_duration("30"), None);
+/// ```
+#[must_use]
+pub fn parse_duration(value: &str) -> Option<Duration> {
+    let mut total = Duration::ZERO;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        if digits == 0 {
+            return None;
+        }
+        let amount: u64 = rest[..digits].parse().ok()?;
+        let unit = rest[digits..].chars().next()?;
+        let seconds = match unit.to_ascii_lowercase() {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86_400,
+            'w' => 7 * 86_400,
+            _ => return None,
+        };
+        total = total.checked_add(Duration::from_secs(amount.checked_mul(seconds)?))?;
+        rest = &rest[digits + unit.len_utf8()..];
+    }
+    (!value.is_empty()).then_some(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_durations() {
+        assert_eq!(parse_duration("24h"), Some(Duration::from_hours(24)));
+        assert_eq!(parse_duration("1d12h"), Some(Duration::from_hours(36)));
+        assert_eq!(parse_duration("2w"), Some(Duration::from_hours(14 * 24)));
+        assert_eq!(parse_duration("90S"), Some(Duration::from_secs(90)));
+        for invalid in ["", "h", "24", "24x", "1.5h"] {
+            assert_eq!(parse_duration(invalid), None, "{invalid}");
+        }
+    }
+}
@@ -0,0 +1,13 @@
+use clap::ValueEnum;
+
+/// How `--output-ext-normalize` rewrites the extension of output files.
+///
+/// Trees collected from cameras and several people mix `.JPG`, `.jpeg` and `.jpg`, which
+/// web servers may map to different MIME types. Only the output file name changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExtensionNormalization {
+    /// Lowercase the extension (`Photo.JPEG` becomes `Photo.jpeg`)
+    Lowercase,
+    /// Lowercase and use the format's canonical extension (`Photo.JPEG` becomes `Photo.jpg`)
+    Canonical,
+}
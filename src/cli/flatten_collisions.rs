@@ -0,0 +1,15 @@
+use clap::ValueEnum;
+
+/// What `--flatten` does when several inputs share a file name.
+///
+/// Flattening drops the input subdirectories, so `a/logo.png` and `b/logo.png` would
+/// both be written to `output/logo.png`. Names are assigned in path order, so repeated
+/// runs over the same tree always produce the same output names.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum FlattenCollisions {
+    /// Keep the first file's name and number the others (`logo-1.png`, `logo-2.png`, ...)
+    #[default]
+    Suffix,
+    /// Refuse to start the run and list the colliding files
+    Error,
+}
@@ -0,0 +1,13 @@
+use clap::Args;
+use std::path::PathBuf;
+
+/// Arguments of the `info` subcommand.
+#[derive(Debug, Clone, Args)]
+pub struct InfoArgs {
+    /// Image to describe
+    pub path: PathBuf,
+
+    /// Print the information as JSON
+    #[arg(long)]
+    pub json: bool,
+}
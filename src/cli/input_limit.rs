@@ -0,0 +1,22 @@
+use std::fmt;
+
+/// Upper bound on the inputs the optimizer is willing to decode (`--max-input-size`).
+///
+/// Decoding allocates the full pixel buffer, so a single huge image can exhaust memory
+/// in a parallel batch. Files above the limit are skipped before they are decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputLimit {
+    /// Maximum file size in bytes
+    Bytes(u64),
+    /// Maximum number of pixels (width × height), read from the image header
+    Pixels(u64),
+}
+
+impl fmt::Display for InputLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bytes(bytes) => write!(f, "{bytes} bytes"),
+            Self::Pixels(pixels) => write!(f, "{pixels} pixels"),
+        }
+    }
+}
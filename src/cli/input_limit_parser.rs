@@ -0,0 +1,84 @@
+use super::InputLimit;
+use crate::file_ops::parse_byte_size;
+
+/// Parses a `--max-input-size` limit.
+///
+/// A value ending in `px` is a pixel count and one ending in `MP` is in megapixels
+/// (millions of pixels, fractions allowed). Anything else is a file size in the syntax
+/// of [`parse_byte_size`], such as `50MB`. Suffixes are case-insensitive.
+///
+/// # Arguments
+///
+/// * `value` - Raw argument, e.g. `100MP`
+///
+/// # Returns
+///
+/// Returns the limit as a pixel count or a byte size.
+///
+/// # Errors
+///
+/// Returns a message suitable for a clap usage error if the number or unit is invalid.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_optimizer::cli::{InputLimit, parse_input_limit};
+///
+/// assert_eq!(parse_input_limit("4000000px"), Ok(InputLimit::Pixels(4_000_000)));
+/// assert_eq!(parse_input_limit("1.5MP"), Ok(InputLimit::Pixels(1_500_000)));
+/// assert_eq!(parse_input_limit("2KB"), Ok(InputLimit::Bytes(2048)));
+/// assert!(parse_input_limit("12 parsecs").is_err());
+/// ```
+pub fn parse_input_limit(value: &str) -> Result<InputLimit, String> {
+    let trimmed = value.trim();
+    let lowercase = trimmed.to_ascii_lowercase();
+
+    if let Some(pixels) = lowercase.strip_suffix("px") {
+        return pixels
+            .trim()
+            .parse()
+            .map(InputLimit::Pixels)
+            .map_err(|_| format!("invalid pixel count '{trimmed}'"));
+    }
+    if let Some(megapixels) = lowercase.strip_suffix("mp") {
+        let megapixels: f64 = megapixels
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid megapixel count '{trimmed}'"))?;
+        let pixels = (megapixels * 1_000_000.0).round();
+        #[allow(clippy::cast_precision_loss)]
+        if !(0.0..u64::MAX as f64).contains(&pixels) {
+            return Err(format!("pixel count '{trimmed}' is out of range"));
+        }
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        return Ok(InputLimit::Pixels(pixels as u64));
+    }
+
+    parse_byte_size(trimmed).map(InputLimit::Bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_units_are_case_insensitive() {
+        assert_eq!(
+            parse_input_limit("100mp"),
+            Ok(InputLimit::Pixels(100_000_000))
+        );
+        assert_eq!(parse_input_limit("640 PX"), Ok(InputLimit::Pixels(640)));
+        assert_eq!(
+            parse_input_limit("50mb"),
+            Ok(InputLimit::Bytes(50 * 1024 * 1024))
+        );
+        assert_eq!(parse_input_limit("512"), Ok(InputLimit::Bytes(512)));
+    }
+
+    #[test]
+    fn test_rejects_invalid_counts() {
+        assert!(parse_input_limit("px").is_err());
+        assert!(parse_input_limit("-1MP").is_err());
+        assert!(parse_input_limit("1.5px").is_err());
+    }
+}
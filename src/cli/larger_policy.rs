@@ -0,0 +1,19 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// What happens when the optimized result of a file is not smaller than the original.
+///
+/// By default the original is kept, so a run never grows a file. Re-encoding is still
+/// useful to normalize files (e.g. to progressive JPEG or stripped metadata), while
+/// strict pipelines may want a file that cannot be shrunk reported as a failure.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LargerPolicy {
+    /// Keep (or copy to the output directory) the original file
+    #[default]
+    KeepOriginal,
+    /// Keep the re-encoded file even though it is not smaller
+    KeepOptimized,
+    /// Discard the result, write nothing and report the file as failed
+    Skip,
+}
@@ -3,7 +3,63 @@
 //! This module provides the command-line argument parsing and configuration
 //! structures for the image optimizer tool.
 
+pub mod background_color;
+pub mod backup_mode;
 #[allow(clippy::module_name_repetitions)]
 pub mod cli_args;
+pub mod command;
+pub mod completion_writer;
+pub mod completions_args;
+pub mod concurrency_parser;
+pub mod confirmation_prompt;
+pub mod date_pattern_parser;
+pub mod dedup_mode;
+pub mod duration_parser;
+pub mod extension_normalization;
+pub mod flatten_collisions;
+pub mod info_args;
+pub mod input_limit;
+pub mod input_limit_parser;
+pub mod larger_policy;
+pub mod modified_since_parser;
+pub mod name_suffix_parser;
+pub mod png_level;
+pub mod png_level_parser;
+pub mod preset;
+pub mod progress_interval;
+pub mod progress_interval_parser;
+pub mod quality_parser;
+pub mod sort_order;
+pub mod stats_args;
+pub mod toggle;
 
+pub use background_color::BackgroundColor;
+pub use backup_mode::BackupMode;
 pub use cli_args::Cli;
+pub use command::Command;
+pub use completion_writer::write_completions;
+pub use completions_args::CompletionsArgs;
+pub use concurrency_parser::parse_format_concurrency;
+pub use confirmation_prompt::{
+    IN_PLACE_CONFIRM_THRESHOLD, ask_confirmation, needs_in_place_confirmation,
+};
+pub use date_pattern_parser::parse_date_pattern;
+pub use dedup_mode::DedupMode;
+pub use duration_parser::parse_duration;
+pub use extension_normalization::ExtensionNormalization;
+pub use flatten_collisions::FlattenCollisions;
+pub use info_args::InfoArgs;
+pub use input_limit::InputLimit;
+pub use input_limit_parser::parse_input_limit;
+pub use larger_policy::LargerPolicy;
+pub use modified_since_parser::parse_modified_since;
+pub use name_suffix_parser::parse_name_suffix;
+pub use png_level::PngLevel;
+pub use png_level_parser::parse_png_level;
+pub use preset::Preset;
+pub use progress_interval::ProgressInterval;
+pub use progress_interval_parser::parse_progress_interval;
+pub use quality_parser::parse_format_quality;
+pub use sort_order::SortOrder;
+pub use stats_args::StatsArgs;
+pub use toggle::Toggle;
@@ -0,0 +1,103 @@
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+use std::time::SystemTime;
+
+use super::parse_duration;
+
+/// Parses a `--modified-since` point in time.
+///
+/// A relative duration such as `24h`, `30m`, `7d` or `1d12h` counts back from now; units
+/// are `s`, `m`, `h`, `d` and `w`, and parts can be combined. An absolute point is an
+/// ISO date (`2024-05-01`, midnight in the local time zone), a local date and time
+/// (`2024-05-01T08:30` or `2024-05-01 08:30:00`) or an RFC 3339 timestamp with an offset
+/// (`2024-05-01T08:30:00+02:00`).
+///
+/// # Arguments
+///
+/// * `value` - Raw argument, e.g. `24h`
+///
+/// # Returns
+///
+/// Returns the point in time; relative durations are resolved when parsing.
+///
+/// # Errors
+///
+/// Returns a message suitable for a clap usage error if the value is neither a duration
+/// nor a date.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::time::{Duration, SystemTime};
+/// use image_optimizer::cli::parse_modified_since;
+///
+/// let since = parse_modified_since("2h").unwrap();
+/// let age = SystemTime::now().duration_since(since).unwrap();
+/// assert!(age >= Duration::from_secs(2 * 3600));
+/// assert!(parse_modified_since("2024-05-01").is_ok());
+/// assert!(parse_modified_since("yesterday").is_err());
+/// ```
+pub fn parse_modified_since(value: &str) -> Result<SystemTime, String> {
+    let value = value.trim();
+    if let Some(duration) = parse_duration(value) {
+        return SystemTime::now()
+            .checked_sub(duration)
+            .ok_or_else(|| format!("'{value}' reaches too far into the past"));
+    }
+    parse_date(value).ok_or_else(|| {
+        format!(
+            "'{value}' is neither a duration (e.g. 24h, 7d) nor a date (e.g. 2024-05-01 or \
+             2024-05-01T08:30)"
+        )
+    })
+}
+
+/// Parses an absolute date, with local dates and times in the local time zone.
+fn parse_date(value: &str) -> Option<SystemTime> {
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Some(date.into());
+    }
+    let local = [
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M",
+    ]
+    .into_iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+    .or_else(|| {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .ok()?
+            .and_hms_opt(0, 0, 0)
+    })?;
+    Local
+        .from_local_datetime(&local)
+        .earliest()
+        .map(SystemTime::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_absolute_dates() {
+        let utc = parse_modified_since("2024-05-01T08:30:00Z").unwrap();
+        let offset = parse_modified_since("2024-05-01T10:30:00+02:00").unwrap();
+        assert_eq!(utc, offset);
+        assert_eq!(
+            utc.duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            1_714_552_200
+        );
+
+        let midnight = parse_modified_since("2024-05-01").unwrap();
+        let morning = parse_modified_since("2024-05-01T08:30").unwrap();
+        assert_eq!(
+            morning.duration_since(midnight).unwrap(),
+            Duration::from_mins(8 * 60 + 30)
+        );
+        assert!(parse_modified_since("2024-13-01").is_err());
+    }
+}
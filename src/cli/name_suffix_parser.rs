@@ -0,0 +1,38 @@
+/// Parses a `--keep-larger-as` suffix such as `.larger`.
+///
+/// The suffix is inserted between the file stem and the extension, so it must stay inside
+/// one file name: it may not be empty or contain a path separator.
+///
+/// # Arguments
+///
+/// * `value` - Raw argument, e.g. `.larger` or `-reencoded`
+///
+/// # Returns
+///
+/// Returns the suffix unchanged.
+///
+/// # Errors
+///
+/// Returns a message suitable for a clap usage error if the suffix is empty or contains
+/// `/` or `\`.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_optimizer::cli::parse_name_suffix;
+///
+/// assert_eq!(parse_name_suffix(".larger"), Ok(".larger".to_string()));
+/// assert!(parse_name_suffix("").is_err());
+/// assert!(parse_name_suffix("/tmp/x").is_err());
+/// ```
+pub fn parse_name_suffix(value: &str) -> Result<String, String> {
+    if value.is_empty() {
+        return Err("the suffix is empty".to_string());
+    }
+    if value.contains(['/', '\\']) {
+        return Err(format!(
+            "'{value}' must not contain a path separator; it is appended to the file name"
+        ));
+    }
+    Ok(value.to_string())
+}
@@ -0,0 +1,106 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+use super::parse_png_level;
+
+/// Oxipng optimization level (`--png-optimization-level`), always within `0..=6`.
+///
+/// Values are validated when parsed, so an invalid level is rejected before any file is
+/// touched. `max` is accepted as an alias for 6. In a config file the level may be
+/// written as a number or a string, e.g. `png-optimization-level = 4` or `"max"`.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_optimizer::cli::{PngLevel, parse_png_level};
+///
+/// assert_eq!(parse_png_level("max").map(PngLevel::get), Ok(6));
+/// assert_eq!(PngLevel::default().get(), 2);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PngLevel(u8);
+
+impl PngLevel {
+    /// Highest level, also accepted as `max`.
+    pub const MAX: Self = Self(6);
+
+    /// Creates a level, or returns `None` if it is above 6.
+    #[must_use]
+    pub const fn new(level: u8) -> Option<Self> {
+        if level <= Self::MAX.0 {
+            Some(Self(level))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the level as the number oxipng expects.
+    #[must_use]
+    pub const fn get(self) -> u8 {
+        self.0
+    }
+}
+
+impl Default for PngLevel {
+    fn default() -> Self {
+        Self(2)
+    }
+}
+
+impl fmt::Display for PngLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Serialize for PngLevel {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for PngLevel {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Number(i64),
+            Text(String),
+        }
+
+        let text = match Raw::deserialize(deserializer)? {
+            Raw::Number(level) => level.to_string(),
+            Raw::Text(text) => text,
+        };
+        parse_png_level(&text).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize, Serialize)]
+    struct Config {
+        level: PngLevel,
+    }
+
+    #[test]
+    fn test_config_accepts_numbers_and_strings() {
+        for (text, expected) in [
+            ("level = 4", 4),
+            ("level = \"3\"", 3),
+            ("level = \"max\"", 6),
+        ] {
+            let config: Config = toml::from_str(text).unwrap();
+            assert_eq!(config.level.get(), expected, "{text}");
+        }
+        assert!(toml::from_str::<Config>("level = 7").is_err());
+        assert!(toml::from_str::<Config>("level = -1").is_err());
+
+        let config = Config {
+            level: PngLevel::MAX,
+        };
+        assert_eq!(toml::to_string(&config).unwrap().trim(), "level = 6");
+    }
+}
@@ -0,0 +1,39 @@
+use super::PngLevel;
+
+/// Parses a `--png-optimization-level` value.
+///
+/// Accepts a level from 0 to 6, or `max` (case-insensitive) for 6.
+///
+/// # Arguments
+///
+/// * `value` - Raw argument, e.g. `4` or `max`
+///
+/// # Returns
+///
+/// Returns the validated level.
+///
+/// # Errors
+///
+/// Returns a message suitable for a clap usage error if the value is neither a number
+/// from 0 to 6 nor `max`.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_optimizer::cli::parse_png_level;
+///
+/// assert_eq!(parse_png_level("4").map(|level| level.get()), Ok(4));
+/// assert_eq!(parse_png_level("MAX").map(|level| level.get()), Ok(6));
+/// assert!(parse_png_level("7").is_err());
+/// ```
+pub fn parse_png_level(value: &str) -> Result<PngLevel, String> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("max") {
+        return Ok(PngLevel::MAX);
+    }
+    value
+        .parse::<u8>()
+        .ok()
+        .and_then(PngLevel::new)
+        .ok_or_else(|| format!("expected a level from 0 to 6 or 'max', got '{value}'"))
+}
@@ -0,0 +1,17 @@
+use clap::ValueEnum;
+
+/// Named bundles of settings for common use cases.
+///
+/// A preset only supplies defaults: any flag given explicitly on the command line still
+/// overrides the value chosen by the preset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Preset {
+    /// Quality 80, max 2048px, metadata stripped, WebP output for --stdin
+    Web,
+    /// Quality 70, max 400px
+    Thumbnail,
+    /// Quality 95, ICC profile kept, never resized
+    Print,
+    /// Lossless WebP and JPEG (maximum JPEG quality when resizing)
+    Archive,
+}
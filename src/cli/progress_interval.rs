@@ -0,0 +1,26 @@
+use std::fmt;
+use std::num::NonZeroUsize;
+use std::time::Duration;
+
+/// How often `--progress-interval` prints a plain-text progress line.
+///
+/// The animated progress bar is hidden when stdout is not a terminal, which leaves long
+/// CI or cron runs without any output until the summary. Progress lines fill that gap
+/// in a form that reads well in logs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressInterval {
+    /// Print a line after every this many files
+    Files(NonZeroUsize),
+    /// Print a line after the first file that finishes once this much time has passed
+    /// since the previous line
+    Time(Duration),
+}
+
+impl fmt::Display for ProgressInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Files(files) => write!(f, "every {files} files"),
+            Self::Time(duration) => write!(f, "every {}s", duration.as_secs()),
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use std::num::NonZeroUsize;
+
+use super::{ProgressInterval, parse_duration};
+
+/// Parses a `--progress-interval` value.
+///
+/// A plain number is a file count (`500` prints a line after every 500 files); a value
+/// with a unit is a duration in the syntax of [`parse_duration`] (`30s`, `5m`).
+///
+/// # Arguments
+///
+/// * `value` - Raw argument, e.g. `500` or `1m`
+///
+/// # Returns
+///
+/// Returns the file count or time interval.
+///
+/// # Errors
+///
+/// Returns a message suitable for a clap usage error if the value is zero or neither a
+/// number nor a duration.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::time::Duration;
+/// use image_optimizer::cli::{ProgressInterval, parse_progress_interval};
+///
+/// assert!(matches!(parse_progress_interval("500"), Ok(ProgressInterval::Files(n)) if n.get() == 500));
+/// assert_eq!(
+///     parse_progress_interval("1m"),
+///     Ok(ProgressInterval::Time(Duration::from_secs(60)))
+/// );
+/// assert!(parse_progress_interval("0").is_err());
+/// ```
+pub fn parse_progress_interval(value: &str) -> Result<ProgressInterval, String> {
+    let value = value.trim();
+    if !value.is_empty() && value.bytes().all(|byte| byte.is_ascii_digit()) {
+        return value
+            .parse::<usize>()
+            .ok()
+            .and_then(NonZeroUsize::new)
+            .map(ProgressInterval::Files)
+            .ok_or_else(|| format!("'{value}' must be a file count of at least 1"));
+    }
+    parse_duration(value)
+        .filter(|duration| !duration.is_zero())
+        .map(ProgressInterval::Time)
+        .ok_or_else(|| {
+            format!("'{value}' is neither a file count (e.g. 500) nor a duration (e.g. 30s, 5m)")
+        })
+}
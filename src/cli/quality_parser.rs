@@ -0,0 +1,83 @@
+use clap::ValueEnum;
+
+use crate::optimization::ImageFormat;
+
+/// Parses a `--quality <FORMAT>=<VALUE>` entry.
+///
+/// The format accepts the same names as `--format` (including the `jpg` alias), but
+/// only formats with a lossy encoder take a quality. The value must be between 1 and
+/// 100.
+///
+/// # Arguments
+///
+/// * `value` - Raw argument, e.g. `webp=75`
+///
+/// # Returns
+///
+/// Returns the format and its quality.
+///
+/// # Errors
+///
+/// Returns a message suitable for a clap usage error if:
+/// - The entry is not in `FORMAT=VALUE` form
+/// - The format is unknown or has no quality setting
+/// - The value is not a number between 1 and 100
+///
+/// # Examples
+///
+/// ```rust
+/// use image_optimizer::cli::parse_format_quality;
+/// use image_optimizer::optimization::ImageFormat;
+///
+/// assert_eq!(parse_format_quality("jpg=82"), Ok((ImageFormat::Jpeg, 82)));
+/// assert!(parse_format_quality("png=80").is_err());
+/// ```
+pub fn parse_format_quality(value: &str) -> Result<(ImageFormat, u8), String> {
+    let (format, quality) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected FORMAT=VALUE, got '{value}'"))?;
+
+    let format = ImageFormat::from_str(format.trim(), true)
+        .map_err(|_| format!("unknown image format '{}'", format.trim()))?;
+    if !format.supports_quality() {
+        return Err(format!(
+            "quality is not supported for {}",
+            format.extension()
+        ));
+    }
+
+    let quality = quality
+        .trim()
+        .parse::<u8>()
+        .ok()
+        .filter(|quality| (1..=100).contains(quality))
+        .ok_or_else(|| {
+            format!(
+                "quality must be between 1 and 100, got '{}'",
+                quality.trim()
+            )
+        })?;
+
+    Ok((format, quality))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_formats_and_aliases() {
+        assert_eq!(parse_format_quality("jpeg=90"), Ok((ImageFormat::Jpeg, 90)));
+        assert_eq!(parse_format_quality("WEBP=75"), Ok((ImageFormat::Webp, 75)));
+    }
+
+    #[test]
+    fn test_rejects_invalid_entries() {
+        assert!(parse_format_quality("jpeg").is_err());
+        assert!(parse_format_quality("gif=80").is_err());
+        assert!(parse_format_quality("svg=80").is_err());
+        assert!(parse_format_quality("jpeg=0").is_err());
+        assert!(parse_format_quality("jpeg=101").is_err());
+        assert!(parse_format_quality("jpeg=high").is_err());
+    }
+}
@@ -0,0 +1,18 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// Order in which discovered images are processed.
+///
+/// Directory traversal order depends on the filesystem, so scan results are always
+/// sorted to keep progress output and reports reproducible across machines.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    /// Sort by path
+    #[default]
+    Path,
+    /// Largest files first (improves perceived progress on mixed trees)
+    Size,
+    /// Most recently modified files first
+    Mtime,
+}
@@ -0,0 +1,17 @@
+use clap::Args;
+use std::path::PathBuf;
+
+/// Arguments of the `stats` subcommand.
+#[derive(Debug, Clone, Args)]
+pub struct StatsArgs {
+    /// Directory or file to analyze
+    pub path: PathBuf,
+
+    /// Recursively scan subdirectories
+    #[arg(short, long)]
+    pub recursive: bool,
+
+    /// Number of largest files to list
+    #[arg(long, default_value_t = 10)]
+    pub top: usize,
+}
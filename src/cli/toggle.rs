@@ -0,0 +1,24 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// An encoder feature switched `on` or `off` on the command line.
+///
+/// Used for features that are enabled by default, where a `--no-…` flag alone would
+/// not let a config file turn them back on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Toggle {
+    /// Enable the feature
+    #[default]
+    On,
+    /// Disable the feature
+    Off,
+}
+
+impl Toggle {
+    /// Returns whether the feature is enabled.
+    #[must_use]
+    pub const fn is_on(self) -> bool {
+        matches!(self, Self::On)
+    }
+}
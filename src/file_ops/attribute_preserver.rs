@@ -0,0 +1,131 @@
+use anyhow::{Context, Result};
+use filetime::FileTime;
+use std::fs::Metadata;
+use std::path::Path;
+
+/// Restores the original file's permissions and timestamps onto an optimized file.
+///
+/// Writing an optimized file (either through a rename over the original or by creating
+/// a new file in the output directory) resets its modification time and permissions.
+/// This function copies them back from the metadata captured before optimization so
+/// that incremental build tools keep working and executable/readonly bits are retained.
+///
+/// Permissions are restored through the platform's native representation: mode bits on
+/// Unix and the readonly attribute on Windows.
+///
+/// # Arguments
+///
+/// * `target_path` - Path to the optimized file that should receive the attributes
+/// * `original_metadata` - Metadata of the original file captured before optimization
+/// * `restore_timestamps` - Whether to restore access and modification times
+///
+/// # Returns
+///
+/// Returns `Ok(())` when all requested attributes have been applied.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - Setting permissions on the target file fails
+/// - Setting access/modification times on the target file fails
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use image_optimizer::file_ops::restore_file_attributes;
+///
+/// # fn example() -> anyhow::Result<()> {
+/// let original_metadata = std::fs::metadata("image.jpg")?;
+/// // ... optimize image.jpg into optimized/image.jpg ...
+/// restore_file_attributes(Path::new("optimized/image.jpg"), &original_metadata, true)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn restore_file_attributes(
+    target_path: &Path,
+    original_metadata: &Metadata,
+    restore_timestamps: bool,
+) -> Result<()> {
+    if restore_timestamps {
+        let accessed = FileTime::from_last_access_time(original_metadata);
+        let modified = FileTime::from_last_modification_time(original_metadata);
+        filetime::set_file_times(target_path, accessed, modified).with_context(|| {
+            format!("Failed to restore timestamps on {}", target_path.display())
+        })?;
+    }
+
+    std::fs::set_permissions(target_path, original_metadata.permissions())
+        .with_context(|| format!("Failed to restore permissions on {}", target_path.display()))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_restores_modification_time() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_attr_mtime");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let original = temp_dir.join("original.jpg");
+        let target = temp_dir.join("target.jpg");
+        fs::write(&original, "original").unwrap();
+        fs::write(&target, "target").unwrap();
+
+        let old_time = FileTime::from_unix_time(1_600_000_000, 0);
+        filetime::set_file_mtime(&original, old_time).unwrap();
+        let metadata = fs::metadata(&original).unwrap();
+
+        restore_file_attributes(&target, &metadata, true).unwrap();
+
+        let restored = FileTime::from_last_modification_time(&fs::metadata(&target).unwrap());
+        assert_eq!(restored.unix_seconds(), old_time.unix_seconds());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_skips_timestamps_when_disabled() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_attr_no_mtime");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let original = temp_dir.join("original.jpg");
+        let target = temp_dir.join("target.jpg");
+        fs::write(&original, "original").unwrap();
+        fs::write(&target, "target").unwrap();
+
+        filetime::set_file_mtime(&original, FileTime::from_unix_time(1_600_000_000, 0)).unwrap();
+        let metadata = fs::metadata(&original).unwrap();
+
+        restore_file_attributes(&target, &metadata, false).unwrap();
+
+        let restored = FileTime::from_last_modification_time(&fs::metadata(&target).unwrap());
+        assert_ne!(restored.unix_seconds(), 1_600_000_000);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_restores_unix_mode_bits() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = std::env::temp_dir().join("image_optimizer_attr_mode");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let original = temp_dir.join("original.jpg");
+        let target = temp_dir.join("target.jpg");
+        fs::write(&original, "original").unwrap();
+        fs::write(&target, "target").unwrap();
+        fs::set_permissions(&original, fs::Permissions::from_mode(0o750)).unwrap();
+        let metadata = fs::metadata(&original).unwrap();
+
+        restore_file_attributes(&target, &metadata, true).unwrap();
+
+        let mode = fs::metadata(&target).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o750);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
@@ -1,45 +1,225 @@
 use anyhow::Result;
-use std::ffi::OsStr;
-use std::path::Path;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
 
-/// Creates a backup file by copying the original with a .bak extension.
+use super::{ensure_output_dir, extended_length_path};
+use crate::cli::BackupMode;
+
+/// Creates a backup copy of the original file before optimization.
+///
+/// By default the backup is written next to the original with `.bak` appended to the
+/// extension. For example, `image.jpg` becomes `image.jpg.bak`.
+///
+/// When a backup directory is given, the original is instead copied into that directory,
+/// mirroring its path relative to the input directory (the same layout used for
+/// `--output`). For example, with input `photos/` and backup directory `backups/`,
+/// `photos/trip/image.jpg` is copied to `backups/trip/image.jpg`.
 ///
-/// This function creates a safety backup of the original file before optimization
-/// by copying it to a new file with the same name but with `.bak` appended to the extension.
-/// For example, `image.jpg` becomes `image.jpg.bak`.
+/// Existing backups are never overwritten, so the true original survives repeated runs.
+/// The backup mode decides what happens instead:
+///
+/// - [`BackupMode::Skip`]: the existing backup is kept and no new backup is written
+/// - [`BackupMode::Number`]: a numeric suffix is appended (`image.jpg.bak.1`, `.bak.2`, ...)
+/// - [`BackupMode::Timestamp`]: a timestamp is inserted (`image.jpg.20240101T120000.bak`)
 ///
 /// # Arguments
 ///
 /// * `file_path` - Path to the file to backup
+/// * `backup_dir` - Optional directory collecting all backups
+/// * `input_dir` - Base input directory used to compute the mirrored relative path
+/// * `mode` - Naming strategy used to avoid clobbering existing backups
 ///
 /// # Returns
 ///
-/// Returns `Ok(())` on successful backup creation.
+/// Returns the path of the written backup, or `None` if an existing backup was kept.
 ///
 /// # Errors
 ///
-/// Returns an error if the file copy operation fails due to:
-/// - Insufficient disk space
-/// - Permission issues
-/// - I/O errors during file copying
+/// Returns an error if:
+/// - The mirrored backup directory structure cannot be created
+/// - The file copy operation fails (disk space, permissions, I/O errors)
 ///
 /// # Examples
 ///
 /// ```rust
 /// use std::path::Path;
+/// use image_optimizer::cli::BackupMode;
 /// use image_optimizer::file_ops::create_backup;
 ///
 /// # fn example() -> anyhow::Result<()> {
-/// let file_path = Path::new("image.jpg");
-/// create_backup(file_path)?; // Creates image.jpg.bak
+/// let input_dir = Path::new("photos");
+/// let file_path = Path::new("photos/image.jpg");
+///
+/// // Creates photos/image.jpg.bak unless it already exists
+/// create_backup(file_path, None, input_dir, BackupMode::Skip)?;
+/// // Creates backups/image.jpg, or backups/image.jpg.1 if that exists
+/// create_backup(file_path, Some(Path::new("backups")), input_dir, BackupMode::Number)?;
 /// # Ok(())
 /// # }
 /// ```
-pub fn create_backup(file_path: &Path) -> Result<()> {
-    let backup_path = file_path.with_extension(format!(
-        "{}.bak",
-        file_path.extension().and_then(OsStr::to_str).unwrap_or("")
-    ));
-    std::fs::copy(file_path, backup_path)?;
-    Ok(())
+pub fn create_backup(
+    file_path: &Path,
+    backup_dir: Option<&Path>,
+    input_dir: &Path,
+    mode: BackupMode,
+) -> Result<Option<PathBuf>> {
+    let (base_path, suffix) = if let Some(backup_dir) = backup_dir {
+        (ensure_output_dir(backup_dir, input_dir, file_path)?, "")
+    } else {
+        (file_path.to_path_buf(), ".bak")
+    };
+
+    let backup_path = match mode {
+        BackupMode::Skip => {
+            let path = append_to_path(&base_path, suffix);
+            if extended_length_path(&path).exists() {
+                return Ok(None);
+            }
+            path
+        }
+        BackupMode::Number => first_free_path(&append_to_path(&base_path, suffix)),
+        BackupMode::Timestamp => {
+            let timestamp = chrono::Local::now().format("%Y%m%dT%H%M%S");
+            first_free_path(&append_to_path(
+                &base_path,
+                &format!(".{timestamp}{suffix}"),
+            ))
+        }
+    };
+
+    std::fs::copy(
+        extended_length_path(file_path),
+        extended_length_path(&backup_path),
+    )?;
+    Ok(Some(backup_path))
+}
+
+/// Returns `path` if it is free, otherwise the first free `path.N` for N = 1, 2, ...
+fn first_free_path(path: &Path) -> PathBuf {
+    if !extended_length_path(path).exists() {
+        return path.to_path_buf();
+    }
+    (1..=u32::MAX)
+        .map(|n| append_to_path(path, &format!(".{n}")))
+        .find(|candidate| !extended_length_path(candidate).exists())
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Appends a raw suffix to the final component of a path.
+fn append_to_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut raw = OsString::from(path.as_os_str());
+    raw.push(suffix);
+    PathBuf::from(raw)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_backup_next_to_original() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_backup_sibling");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let file_path = temp_dir.join("image.jpg");
+        fs::write(&file_path, "original").unwrap();
+
+        create_backup(&file_path, None, &temp_dir, BackupMode::Skip).unwrap();
+
+        let backup = temp_dir.join("image.jpg.bak");
+        assert_eq!(fs::read_to_string(backup).unwrap(), "original");
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_backup_dir_mirrors_relative_paths() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_backup_dir");
+        let input_dir = temp_dir.join("input");
+        let backup_dir = temp_dir.join("backups");
+        fs::create_dir_all(input_dir.join("nested")).unwrap();
+        let file_path = input_dir.join("nested").join("image.jpg");
+        fs::write(&file_path, "original").unwrap();
+
+        create_backup(&file_path, Some(&backup_dir), &input_dir, BackupMode::Skip).unwrap();
+
+        let backup = backup_dir.join("nested").join("image.jpg");
+        assert_eq!(fs::read_to_string(backup).unwrap(), "original");
+        assert!(!input_dir.join("nested").join("image.jpg.bak").exists());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_skip_mode_keeps_first_backup() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_backup_skip");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let file_path = temp_dir.join("image.jpg");
+
+        fs::write(&file_path, "original").unwrap();
+        let first = create_backup(&file_path, None, &temp_dir, BackupMode::Skip).unwrap();
+        fs::write(&file_path, "optimized").unwrap();
+        let second = create_backup(&file_path, None, &temp_dir, BackupMode::Skip).unwrap();
+
+        assert_eq!(first, Some(temp_dir.join("image.jpg.bak")));
+        assert_eq!(second, None);
+        assert_eq!(
+            fs::read_to_string(temp_dir.join("image.jpg.bak")).unwrap(),
+            "original"
+        );
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_number_mode_appends_suffix() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_backup_number");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let file_path = temp_dir.join("image.jpg");
+
+        fs::write(&file_path, "original").unwrap();
+        create_backup(&file_path, None, &temp_dir, BackupMode::Number).unwrap();
+        fs::write(&file_path, "optimized").unwrap();
+        let second = create_backup(&file_path, None, &temp_dir, BackupMode::Number).unwrap();
+
+        assert_eq!(second, Some(temp_dir.join("image.jpg.bak.1")));
+        assert_eq!(
+            fs::read_to_string(temp_dir.join("image.jpg.bak")).unwrap(),
+            "original"
+        );
+        assert_eq!(
+            fs::read_to_string(temp_dir.join("image.jpg.bak.1")).unwrap(),
+            "optimized"
+        );
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_timestamp_mode_keeps_every_backup() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_backup_timestamp");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let file_path = temp_dir.join("image.jpg");
+
+        fs::write(&file_path, "original").unwrap();
+        let first = create_backup(&file_path, None, &temp_dir, BackupMode::Timestamp)
+            .unwrap()
+            .unwrap();
+        fs::write(&file_path, "optimized").unwrap();
+        let second = create_backup(&file_path, None, &temp_dir, BackupMode::Timestamp)
+            .unwrap()
+            .unwrap();
+
+        assert_ne!(first, second);
+        let first_name = first.file_name().unwrap().to_string_lossy().to_string();
+        assert!(first_name.starts_with("image.jpg."));
+        assert_eq!(
+            first.extension().and_then(std::ffi::OsStr::to_str),
+            Some("bak")
+        );
+        assert_eq!(fs::read_to_string(&first).unwrap(), "original");
+        assert_eq!(fs::read_to_string(&second).unwrap(), "optimized");
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
 }
@@ -1,8 +1,12 @@
 /// Formats a byte count into a human-readable string with appropriate units.
 ///
 /// This function converts raw byte counts into a more readable format using
-/// standard binary units (B, KB, MB, GB). It automatically selects the most
-/// appropriate unit and formats the output with appropriate decimal precision.
+/// 1024-based divisions labeled B, KB, MB, GB, TB and PB. It automatically selects the
+/// most appropriate unit and formats the output with appropriate decimal precision.
+///
+/// The labels are kept for compatibility even though they are ambiguous; use
+/// [`format_bytes_binary`] for matching IEC labels (KiB, MiB) or [`format_bytes_si`] for
+/// 1000-based SI units (kB, MB).
 ///
 /// # Arguments
 ///
@@ -22,24 +26,81 @@
 /// assert_eq!(format_bytes(512), "512 B");
 /// assert_eq!(format_bytes(1024), "1.0 KB");
 /// assert_eq!(format_bytes(1536), "1.5 KB");
-/// assert_eq!(format_bytes(1048576), "1.0 MB");
+/// assert_eq!(format_bytes(1_048_576), "1.0 MB");
 /// ```
 #[must_use]
 pub fn format_bytes(bytes: u64) -> String {
-    const UNITS: &[&str] = &["B", "KB", "MB", "GB"];
+    format_scaled(bytes, 1024.0, &["B", "KB", "MB", "GB", "TB", "PB"])
+}
+
+/// Formats a byte count using 1000-based SI units (B, kB, MB, GB, TB, PB).
+///
+/// # Examples
+///
+/// ```rust
+/// use image_optimizer::file_ops::format_bytes_si;
+///
+/// assert_eq!(format_bytes_si(999), "999 B");
+/// assert_eq!(format_bytes_si(1_500), "1.5 kB");
+/// assert_eq!(format_bytes_si(2_000_000), "2.0 MB");
+/// ```
+#[must_use]
+pub fn format_bytes_si(bytes: u64) -> String {
+    format_scaled(bytes, 1000.0, &["B", "kB", "MB", "GB", "TB", "PB"])
+}
+
+/// Formats a byte count using 1024-based IEC units (B, KiB, MiB, GiB, TiB, PiB).
+///
+/// # Examples
+///
+/// ```rust
+/// use image_optimizer::file_ops::format_bytes_binary;
+///
+/// assert_eq!(format_bytes_binary(1536), "1.5 KiB");
+/// assert_eq!(format_bytes_binary(1_048_576), "1.0 MiB");
+/// ```
+#[must_use]
+pub fn format_bytes_binary(bytes: u64) -> String {
+    format_scaled(bytes, 1024.0, &["B", "KiB", "MiB", "GiB", "TiB", "PiB"])
+}
+
+/// Formats a signed size change with an explicit sign, using the units of [`format_bytes`].
+///
+/// Negative deltas mean a file got smaller. A zero delta is formatted without a sign.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_optimizer::file_ops::format_delta;
+///
+/// assert_eq!(format_delta(-12_595), "-12.3 KB");
+/// assert_eq!(format_delta(512), "+512 B");
+/// assert_eq!(format_delta(0), "0 B");
+/// ```
+#[must_use]
+pub fn format_delta(delta: i64) -> String {
+    let magnitude = format_bytes(delta.unsigned_abs());
+    match delta.signum() {
+        1 => format!("+{magnitude}"),
+        -1 => format!("-{magnitude}"),
+        _ => magnitude,
+    }
+}
+
+fn format_scaled(bytes: u64, base: f64, units: &[&str]) -> String {
     #[allow(clippy::cast_precision_loss)]
     let mut size = bytes as f64;
     let mut unit_index = 0;
 
-    while size >= 1024.0 && unit_index < UNITS.len() - 1 {
-        size /= 1024.0;
+    while size >= base && unit_index < units.len() - 1 {
+        size /= base;
         unit_index += 1;
     }
 
     if unit_index == 0 {
-        format!("{} {}", bytes, UNITS[unit_index])
+        format!("{} {}", bytes, units[unit_index])
     } else {
-        format!("{:.1} {}", size, UNITS[unit_index])
+        format!("{:.1} {}", size, units[unit_index])
     }
 }
 
@@ -54,15 +115,45 @@ mod tests {
         assert_eq!(format_bytes(1023), "1023 B");
         assert_eq!(format_bytes(1024), "1.0 KB");
         assert_eq!(format_bytes(1536), "1.5 KB");
-        assert_eq!(format_bytes(1048576), "1.0 MB");
-        assert_eq!(format_bytes(1073741824), "1.0 GB");
-        assert_eq!(format_bytes(2147483648), "2.0 GB");
+        assert_eq!(format_bytes(1_048_576), "1.0 MB");
+        assert_eq!(format_bytes(1_073_741_824), "1.0 GB");
+        assert_eq!(format_bytes(2_147_483_648), "2.0 GB");
     }
 
     #[test]
     fn test_format_bytes_edge_cases() {
-        assert_eq!(format_bytes(u64::MAX), "17179869184.0 GB");
+        assert_eq!(format_bytes(u64::MAX), "16384.0 PB");
         assert_eq!(format_bytes(1), "1 B");
         assert_eq!(format_bytes(1025), "1.0 KB");
     }
+
+    #[test]
+    fn test_format_bytes_large_tiers() {
+        assert_eq!(format_bytes(1_099_511_627_776), "1.0 TB");
+        assert_eq!(format_bytes(1_125_899_906_842_624), "1.0 PB");
+        assert_eq!(format_bytes_binary(1_099_511_627_776), "1.0 TiB");
+        assert_eq!(format_bytes_si(1_000_000_000_000), "1.0 TB");
+        assert_eq!(format_bytes_si(1_000_000_000_000_000), "1.0 PB");
+        assert_eq!(format_bytes_si(u64::MAX), "18446.7 PB");
+    }
+
+    #[test]
+    fn test_format_delta() {
+        assert_eq!(format_delta(1536), "+1.5 KB");
+        assert_eq!(format_delta(-1536), "-1.5 KB");
+        assert_eq!(format_delta(-1), "-1 B");
+        assert_eq!(format_delta(0), "0 B");
+        assert_eq!(format_delta(i64::MIN), "-8192.0 PB");
+    }
+
+    #[test]
+    fn test_format_bytes_si_and_binary() {
+        assert_eq!(format_bytes_si(0), "0 B");
+        assert_eq!(format_bytes_si(1000), "1.0 kB");
+        assert_eq!(format_bytes_si(1024), "1.0 kB");
+        assert_eq!(format_bytes_si(1_048_576), "1.0 MB");
+        assert_eq!(format_bytes_binary(1023), "1023 B");
+        assert_eq!(format_bytes_binary(1024), "1.0 KiB");
+        assert_eq!(format_bytes_binary(1_073_741_824), "1.0 GiB");
+    }
 }
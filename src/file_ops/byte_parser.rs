@@ -0,0 +1,87 @@
+/// Parses a human-readable byte size such as `512`, `10KB` or `1.5 MB`.
+///
+/// Units are case-insensitive and 1024-based like [`format_bytes`](super::format_bytes):
+/// `B`, `K`/`KB`/`KiB`, `M`/`MB`/`MiB` and `G`/`GB`/`GiB`. A bare number is a byte
+/// count.
+///
+/// # Arguments
+///
+/// * `value` - Size to parse
+///
+/// # Returns
+///
+/// Returns the size in bytes, rounded down to a whole byte.
+///
+/// # Errors
+///
+/// Returns a message suitable for a clap usage error if the number or unit is invalid.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_optimizer::file_ops::parse_byte_size;
+///
+/// assert_eq!(parse_byte_size("512"), Ok(512));
+/// assert_eq!(parse_byte_size("10KB"), Ok(10 * 1024));
+/// assert_eq!(parse_byte_size("1.5 mb"), Ok(1024 * 1024 * 3 / 2));
+/// assert!(parse_byte_size("10 parsecs").is_err());
+/// ```
+pub fn parse_byte_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1024,
+        "m" | "mb" | "mib" => 1024 * 1024,
+        "g" | "gb" | "gib" => 1024 * 1024 * 1024,
+        _ => return Err(format!("unknown size unit '{}'", unit.trim())),
+    };
+
+    if let Ok(bytes) = number.parse::<u64>() {
+        return bytes
+            .checked_mul(multiplier)
+            .ok_or_else(|| format!("size '{value}' is too large"));
+    }
+
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid size '{value}'"))?;
+    #[allow(clippy::cast_precision_loss)]
+    let bytes = number * multiplier as f64;
+    #[allow(clippy::cast_precision_loss)]
+    if bytes >= u64::MAX as f64 {
+        return Err(format!("size '{value}' is too large"));
+    }
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    Ok(bytes as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_units() {
+        assert_eq!(parse_byte_size("0"), Ok(0));
+        assert_eq!(parse_byte_size("100B"), Ok(100));
+        assert_eq!(parse_byte_size("1k"), Ok(1024));
+        assert_eq!(parse_byte_size("2 KiB"), Ok(2048));
+        assert_eq!(parse_byte_size("3MB"), Ok(3 * 1024 * 1024));
+        assert_eq!(parse_byte_size("1GB"), Ok(1024 * 1024 * 1024));
+        assert_eq!(parse_byte_size("0.5KB"), Ok(512));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(parse_byte_size("").is_err());
+        assert!(parse_byte_size("KB").is_err());
+        assert!(parse_byte_size("1.2.3KB").is_err());
+        assert!(parse_byte_size("-1KB").is_err());
+        assert!(parse_byte_size("10TB").is_err());
+        assert!(parse_byte_size("99999999999999999999GB").is_err());
+    }
+}
@@ -0,0 +1,69 @@
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::path::Path;
+
+/// Computes a BLAKE3 hash of a file's contents.
+///
+/// The file is streamed rather than read into memory at once, so large images can be
+/// fingerprinted cheaply. The hash identifies content independently of the file's path,
+/// size or modification time.
+///
+/// # Arguments
+///
+/// * `path` - Path to the file to hash
+///
+/// # Returns
+///
+/// Returns the hash as a lowercase hexadecimal string.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened or read.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use image_optimizer::file_ops::hash_file;
+///
+/// # fn example() -> anyhow::Result<()> {
+/// let hash = hash_file(Path::new("photo.jpg"))?;
+/// println!("{hash}");
+/// # Ok(())
+/// # }
+/// ```
+pub fn hash_file(path: &Path) -> Result<String> {
+    let file = File::open(path)
+        .with_context(|| format!("Failed to open {} for hashing", path.display()))?;
+    let mut hasher = blake3::Hasher::new();
+    hasher
+        .update_reader(file)
+        .with_context(|| format!("Failed to read {} for hashing", path.display()))?;
+
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_hash_depends_only_on_content() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_content_hash");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let first = temp_dir.join("a.jpg");
+        let second = temp_dir.join("b.jpg");
+        let different = temp_dir.join("c.jpg");
+        fs::write(&first, "same bytes").unwrap();
+        fs::write(&second, "same bytes").unwrap();
+        fs::write(&different, "other bytes").unwrap();
+
+        let hash = hash_file(&first).unwrap();
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, hash_file(&second).unwrap());
+        assert_ne!(hash, hash_file(&different).unwrap());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
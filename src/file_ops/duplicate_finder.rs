@@ -0,0 +1,100 @@
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use super::hash_file;
+
+/// Groups files whose contents are byte-identical.
+///
+/// Files are first grouped by size, so only files sharing their size with another file
+/// are hashed (in parallel). Files that cannot be read are left out and reported by the
+/// optimizer later.
+///
+/// # Arguments
+///
+/// * `files` - Files to compare
+///
+/// # Returns
+///
+/// Returns one group per distinct content shared by at least two files. Each group is
+/// sorted by path, so its first file is the one optimized with `--dedup`; the groups
+/// are sorted by their first file.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::PathBuf;
+/// use image_optimizer::file_ops::find_duplicates;
+///
+/// let files = vec![PathBuf::from("logo.png"), PathBuf::from("assets/logo-copy.png")];
+/// for group in find_duplicates(&files) {
+///     println!("{} has {} copies", group[0].display(), group.len() - 1);
+/// }
+/// ```
+#[must_use]
+pub fn find_duplicates(files: &[PathBuf]) -> Vec<Vec<PathBuf>> {
+    let mut by_size: HashMap<u64, Vec<&PathBuf>> = HashMap::new();
+    for path in files {
+        if let Ok(metadata) = fs::metadata(path) {
+            by_size.entry(metadata.len()).or_default().push(path);
+        }
+    }
+
+    let candidates: Vec<&PathBuf> = by_size
+        .into_values()
+        .filter(|paths| paths.len() > 1)
+        .flatten()
+        .collect();
+    let hashes: Vec<(String, &PathBuf)> = candidates
+        .into_par_iter()
+        .filter_map(|path| hash_file(path).ok().map(|hash| (hash, path)))
+        .collect();
+
+    let mut by_hash: HashMap<String, Vec<PathBuf>> = HashMap::new();
+    for (hash, path) in hashes {
+        by_hash.entry(hash).or_default().push(path.clone());
+    }
+
+    let mut groups: Vec<Vec<PathBuf>> = by_hash
+        .into_values()
+        .filter(|paths| paths.len() > 1)
+        .map(|mut paths| {
+            paths.sort();
+            paths
+        })
+        .collect();
+    groups.sort();
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_groups_identical_files_only() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_duplicate_finder");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let paths: Vec<PathBuf> = ["b.png", "a.png", "c.png", "d.png"]
+            .iter()
+            .map(|name| temp_dir.join(name))
+            .collect();
+        fs::write(&paths[0], "same bytes").unwrap();
+        fs::write(&paths[1], "same bytes").unwrap();
+        // Same size as the duplicates but different content
+        fs::write(&paths[2], "diff bytes").unwrap();
+        fs::write(&paths[3], "unique").unwrap();
+        let mut files = paths;
+        files.push(temp_dir.join("missing.png"));
+
+        let groups = find_duplicates(&files);
+
+        assert_eq!(
+            groups,
+            vec![vec![temp_dir.join("a.png"), temp_dir.join("b.png")]]
+        );
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
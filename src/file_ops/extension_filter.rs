@@ -0,0 +1,111 @@
+use std::ffi::OsStr;
+use std::path::Path;
+
+/// List of supported image file extensions for optimization.
+///
+/// Only extensions of formats whose Cargo feature is enabled are listed, so files of a
+/// compiled-out format are not picked up when scanning.
+pub const SUPPORTED_EXTENSIONS: &[&str] = &[
+    #[cfg(feature = "jpeg")]
+    "jpg",
+    #[cfg(feature = "jpeg")]
+    "jpeg",
+    #[cfg(feature = "png")]
+    "png",
+    #[cfg(feature = "webp")]
+    "webp",
+    #[cfg(feature = "svg")]
+    "svg",
+    #[cfg(feature = "svg")]
+    "svgz",
+];
+
+/// Checks whether a path has one of the supported image extensions.
+///
+/// The comparison is case-insensitive, so `photo.JPG` and `photo.jpg` are both accepted.
+/// Extensions are compared as `OsStr`, so names that are not valid UTF-8 are still
+/// recognized by their extension.
+///
+/// # Arguments
+///
+/// * `path` - Path to check
+///
+/// # Returns
+///
+/// Returns `true` if the extension is one of [`SUPPORTED_EXTENSIONS`].
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use image_optimizer::file_ops::has_supported_extension;
+///
+/// assert!(has_supported_extension(Path::new("photo.JPG")));
+/// assert!(!has_supported_extension(Path::new("notes.txt")));
+/// ```
+#[must_use]
+pub fn has_supported_extension(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| {
+        SUPPORTED_EXTENSIONS
+            .iter()
+            .any(|supported| extension.eq_ignore_ascii_case(OsStr::new(supported)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(all(feature = "jpeg", feature = "png", feature = "webp", feature = "svg"))]
+    #[test]
+    fn test_supported_extensions() {
+        assert!(SUPPORTED_EXTENSIONS.contains(&"jpg"));
+        assert!(SUPPORTED_EXTENSIONS.contains(&"jpeg"));
+        assert!(SUPPORTED_EXTENSIONS.contains(&"png"));
+        assert!(SUPPORTED_EXTENSIONS.contains(&"webp"));
+        assert!(SUPPORTED_EXTENSIONS.contains(&"svg"));
+        assert!(SUPPORTED_EXTENSIONS.contains(&"svgz"));
+        assert!(!SUPPORTED_EXTENSIONS.contains(&"gif"));
+        assert!(!SUPPORTED_EXTENSIONS.contains(&"txt"));
+    }
+
+    #[test]
+    fn test_extensions_follow_enabled_features() {
+        use crate::optimization::ImageFormat;
+
+        for format in [
+            ImageFormat::Jpeg,
+            ImageFormat::Png,
+            ImageFormat::Webp,
+            ImageFormat::Svg,
+        ] {
+            let path = format!("image.{}", format.extension());
+            assert_eq!(
+                has_supported_extension(Path::new(&path)),
+                format.is_enabled()
+            );
+        }
+    }
+
+    #[cfg(all(feature = "jpeg", feature = "svg"))]
+    #[test]
+    fn test_has_supported_extension() {
+        assert!(has_supported_extension(Path::new("a/b/photo.jpeg")));
+        assert!(has_supported_extension(Path::new("icon.SVG")));
+        assert!(!has_supported_extension(Path::new("archive.tar.gz")));
+        assert!(!has_supported_extension(Path::new("no_extension")));
+    }
+
+    #[cfg(all(unix, feature = "png"))]
+    #[test]
+    fn test_non_utf8_names_keep_their_extension() {
+        use std::os::unix::ffi::OsStrExt;
+
+        assert!(has_supported_extension(Path::new(OsStr::from_bytes(
+            b"caf\xe9.PNG"
+        ))));
+        assert!(!has_supported_extension(Path::new(OsStr::from_bytes(
+            b"image.pn\xe9"
+        ))));
+    }
+}
@@ -0,0 +1,93 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+use super::{create_temp_path, move_file, release_temp_path};
+
+/// Replaces `target` with a hard link to, or a copy of, `source`.
+///
+/// The new file is first created next to `target` and then renamed over it, so an
+/// interrupted replacement never leaves `target` half-written. A hard link that cannot
+/// be created, for example because `source` lives on another filesystem, falls back to
+/// a copy.
+///
+/// # Arguments
+///
+/// * `source` - File whose content `target` should have
+/// * `target` - File to create or replace
+/// * `hard_link` - Link `target` to `source` instead of copying it
+///
+/// # Errors
+///
+/// Returns an error if the temporary file cannot be created, or if copying or moving it
+/// over `target` fails.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use image_optimizer::file_ops::link_or_copy;
+///
+/// # fn example() -> anyhow::Result<()> {
+/// link_or_copy(Path::new("optimized/logo.png"), Path::new("optimized/logo-copy.png"), true)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn link_or_copy(source: &Path, target: &Path, hard_link: bool) -> Result<()> {
+    let temp_path = create_temp_path(target)?;
+    let result = write_and_move(source, target, &temp_path, hard_link);
+    // Renaming a link onto another link of the same file succeeds without removing it
+    let _ = fs::remove_file(&temp_path);
+    release_temp_path(&temp_path);
+    result
+}
+
+fn write_and_move(source: &Path, target: &Path, temp_path: &Path, hard_link: bool) -> Result<()> {
+    let linked =
+        hard_link && fs::remove_file(temp_path).is_ok() && fs::hard_link(source, temp_path).is_ok();
+    if !linked {
+        fs::copy(source, temp_path).with_context(|| {
+            format!(
+                "Failed to copy {} to {}",
+                source.display(),
+                target.display()
+            )
+        })?;
+    }
+    move_file(temp_path, target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replaces_target_with_source_content() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_file_linker");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let source = temp_dir.join("source.png");
+        let linked = temp_dir.join("linked.png");
+        let copied = temp_dir.join("copied.png");
+        fs::write(&source, "optimized").unwrap();
+        fs::write(&linked, "original").unwrap();
+
+        link_or_copy(&source, &linked, true).unwrap();
+        link_or_copy(&source, &copied, false).unwrap();
+        // Linking again onto an existing link of the same file is a no-op
+        link_or_copy(&source, &linked, true).unwrap();
+
+        assert_eq!(fs::read(&linked).unwrap(), b"optimized");
+        assert_eq!(fs::read(&copied).unwrap(), b"optimized");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            let source_inode = fs::metadata(&source).unwrap().ino();
+            assert_eq!(fs::metadata(&linked).unwrap().ino(), source_inode);
+            assert_ne!(fs::metadata(&copied).unwrap().ino(), source_inode);
+        }
+        let leftovers = fs::read_dir(&temp_dir).unwrap().count();
+        assert_eq!(leftovers, 3);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
@@ -0,0 +1,202 @@
+use anyhow::{Context, Result, bail};
+use std::ffi::OsString;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+use super::{ScanOutcome, has_supported_extension};
+
+/// Reads an explicit list of input files instead of scanning a directory.
+///
+/// Build systems often know exactly which files changed; this lets them pass that list
+/// directly (`--files-from`). Entries are separated by newlines, or by NUL bytes when
+/// `null_delimited` is set (matching `find -print0`). Empty entries are ignored and a
+/// trailing `\r` is stripped from newline-delimited entries.
+///
+/// Entries without a supported image extension are silently skipped, matching the
+/// directory scanner. Entries that do not exist are reported as warnings. The order of
+/// the list is preserved.
+///
+/// # Arguments
+///
+/// * `source` - Path of the list file, or `-` to read from standard input
+/// * `null_delimited` - Split entries on NUL bytes instead of newlines
+///
+/// # Returns
+///
+/// A [`ScanOutcome`] with the listed images and warnings for missing entries.
+///
+/// # Errors
+///
+/// Returns an error if the list file (or standard input) cannot be read.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use image_optimizer::file_ops::read_file_list;
+///
+/// # fn example() -> anyhow::Result<()> {
+/// let outcome = read_file_list(Path::new("changed-files.txt"), false)?;
+/// println!("{} images listed", outcome.images.len());
+/// # Ok(())
+/// # }
+/// ```
+pub fn read_file_list(source: &Path, null_delimited: bool) -> Result<ScanOutcome> {
+    let mut contents = Vec::new();
+    if source == Path::new("-") {
+        std::io::stdin()
+            .read_to_end(&mut contents)
+            .context("Failed to read file list from stdin")?;
+    } else {
+        contents = std::fs::read(source)
+            .with_context(|| format!("Failed to read file list: {}", source.display()))?;
+    }
+
+    let delimiter = if null_delimited { b'\0' } else { b'\n' };
+    let mut outcome = ScanOutcome::default();
+
+    for entry in contents.split(|byte| *byte == delimiter) {
+        let entry = if null_delimited {
+            entry
+        } else {
+            entry.strip_suffix(b"\r").unwrap_or(entry)
+        };
+        if entry.is_empty() {
+            continue;
+        }
+
+        let path = path_from_bytes(entry);
+        if !has_supported_extension(&path) {
+            continue;
+        }
+        if path.is_file() {
+            outcome.images.push(path);
+        } else {
+            outcome
+                .warnings
+                .push(format!("Listed file does not exist: {}", path.display()));
+        }
+    }
+
+    Ok(outcome)
+}
+
+/// Checks that every listed file lies inside `base` before it is mirrored into `--output`.
+///
+/// Outputs are placed at the file's path relative to `base` (`--input`, or the current
+/// directory when only `--files-from` is given). An absolute path outside `base`, or a
+/// relative one that climbs out of it with `..`, has no such relative path: joined onto
+/// the output directory it would name the input file itself or a file outside the output
+/// directory, so the run is rejected before anything is written.
+///
+/// # Errors
+///
+/// Returns an error naming the first listed file that is not inside `base`.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::{Path, PathBuf};
+/// use image_optimizer::file_ops::check_listed_paths;
+///
+/// let base = Path::new("");
+/// assert!(check_listed_paths(&[PathBuf::from("photos/a.png")], base).is_ok());
+/// assert!(check_listed_paths(&[PathBuf::from("/srv/photos/a.png")], base).is_err());
+/// assert!(check_listed_paths(&[PathBuf::from("../a.png")], base).is_err());
+/// ```
+pub fn check_listed_paths(images: &[PathBuf], base: &Path) -> Result<()> {
+    for image in images {
+        let inside = image.strip_prefix(base).is_ok_and(|relative| {
+            relative
+                .components()
+                .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+                && relative.file_name().is_some()
+        });
+        if !inside {
+            let base = if base.as_os_str().is_empty() {
+                "the current directory".to_string()
+            } else {
+                base.display().to_string()
+            };
+            bail!(
+                "Listed file {} is not inside {base}; with -o/--output, list paths relative \
+                 to --input (or to the current directory without --input)",
+                image.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Converts raw list bytes into a path, keeping non-UTF-8 names intact on Unix.
+#[cfg(unix)]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    use std::os::unix::ffi::OsStringExt;
+    PathBuf::from(OsString::from_vec(bytes.to_vec()))
+}
+
+/// Converts raw list bytes into a path, keeping non-UTF-8 names intact on Unix.
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(OsString::from(String::from_utf8_lossy(bytes).into_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_reads_newline_delimited_list() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_file_list_newline");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let first = temp_dir.join("first.png");
+        let second = temp_dir.join("second.jpg");
+        fs::write(&first, "png").unwrap();
+        fs::write(&second, "jpg").unwrap();
+        fs::write(temp_dir.join("notes.txt"), "text").unwrap();
+
+        let list = temp_dir.join("list.txt");
+        fs::write(
+            &list,
+            format!(
+                "{}\r\n\n{}\n{}\n",
+                second.display(),
+                temp_dir.join("notes.txt").display(),
+                first.display()
+            ),
+        )
+        .unwrap();
+
+        let outcome = read_file_list(&list, false).unwrap();
+        assert_eq!(outcome.images, vec![second, first]);
+        assert!(outcome.warnings.is_empty());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_reads_null_delimited_list_and_warns_on_missing() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_file_list_null");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let existing = temp_dir.join("with\nnewline.png");
+        fs::write(&existing, "png").unwrap();
+
+        let list = temp_dir.join("list.bin");
+        fs::write(
+            &list,
+            format!(
+                "{}\0{}\0",
+                existing.display(),
+                temp_dir.join("missing.png").display()
+            ),
+        )
+        .unwrap();
+
+        let outcome = read_file_list(&list, true).unwrap();
+        assert_eq!(outcome.images, vec![existing]);
+        assert_eq!(outcome.warnings.len(), 1);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
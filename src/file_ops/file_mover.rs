@@ -0,0 +1,125 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Moves a file into place, falling back to copy-and-remove across filesystems.
+///
+/// `fs::rename` is atomic but only works when source and destination live on the same
+/// filesystem. When the temporary file had to be created elsewhere (for example in the
+/// system temp directory because the source directory is read-only), the rename fails
+/// with a cross-device error (`EXDEV`). In that case the file is copied over the
+/// destination and the source is removed afterwards.
+///
+/// # Arguments
+///
+/// * `from` - Path of the file to move (usually the optimized temporary file)
+/// * `to` - Destination path (usually the original file being replaced)
+///
+/// # Returns
+///
+/// Returns `Ok(())` once the destination contains the moved file and the source is gone.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - The rename fails for a reason other than crossing filesystems
+/// - The fallback copy or removal of the source fails
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use image_optimizer::file_ops::move_file;
+///
+/// # fn example() -> anyhow::Result<()> {
+/// move_file(Path::new("/tmp/image.tmp.jpg"), Path::new("photos/image.jpg"))?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn move_file(from: &Path, to: &Path) -> Result<()> {
+    move_file_with(from, to, |from, to| fs::rename(from, to))
+}
+
+/// Moves a file using the given rename operation, copying when it crosses filesystems.
+fn move_file_with<F>(from: &Path, to: &Path, rename: F) -> Result<()>
+where
+    F: Fn(&Path, &Path) -> io::Result<()>,
+{
+    match rename(from, to) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+            fs::copy(from, to).with_context(|| {
+                format!("Failed to copy {} to {}", from.display(), to.display())
+            })?;
+            fs::remove_file(from)
+                .with_context(|| format!("Failed to remove {}", from.display()))?;
+            Ok(())
+        }
+        Err(e) => {
+            Err(e).with_context(|| format!("Failed to move {} to {}", from.display(), to.display()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_move_file_renames() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_move_rename");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let from = temp_dir.join("from.tmp.jpg");
+        let to = temp_dir.join("to.jpg");
+        fs::write(&from, "optimized").unwrap();
+        fs::write(&to, "original").unwrap();
+
+        move_file(&from, &to).unwrap();
+
+        assert!(!from.exists());
+        assert_eq!(fs::read_to_string(&to).unwrap(), "optimized");
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_cross_device_rename_falls_back_to_copy() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_move_exdev");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let from = temp_dir.join("from.tmp.jpg");
+        let to = temp_dir.join("to.jpg");
+        fs::write(&from, "optimized").unwrap();
+        fs::write(&to, "original").unwrap();
+
+        move_file_with(&from, &to, |_, _| {
+            Err(io::Error::from(io::ErrorKind::CrossesDevices))
+        })
+        .unwrap();
+
+        assert!(!from.exists());
+        assert_eq!(fs::read_to_string(&to).unwrap(), "optimized");
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_other_rename_errors_are_reported() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_move_error");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let from = temp_dir.join("from.tmp.jpg");
+        let to = temp_dir.join("to.jpg");
+        fs::write(&from, "optimized").unwrap();
+        fs::write(&to, "original").unwrap();
+
+        let result = move_file_with(&from, &to, |_, _| {
+            Err(io::Error::from(io::ErrorKind::PermissionDenied))
+        });
+
+        assert!(result.is_err());
+        assert!(from.exists());
+        assert_eq!(fs::read_to_string(&to).unwrap(), "original");
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
@@ -0,0 +1,109 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+use super::ScanOptions;
+
+/// Walks a directory while honoring `.gitignore`, `.ignore` and global git excludes.
+///
+/// This is the `--respect-gitignore` counterpart of the plain `walkdir` traversal used
+/// by [`scan_images`](super::scan_images). It uses the `ignore` crate's `WalkBuilder`,
+/// keeps hidden files (matching the default scanner) and applies ignore files even
+/// outside of a git checkout. Recursion depth, `--exclude` and `--exclude-dir` pruning follow the same
+/// semantics as the default walker.
+///
+/// # Arguments
+///
+/// * `path` - Directory to walk
+/// * `options` - Recursion, exclude and strictness settings
+/// * `warnings` - Collects descriptions of entries that could not be read
+/// * `on_file` - Called for every file that is not ignored, e.g. to count scan progress
+///
+/// # Returns
+///
+/// All regular files that are neither ignored nor excluded. Extension and include
+/// filtering is left to the caller.
+///
+/// # Errors
+///
+/// Returns an error for the first unreadable entry when `options.strict` is set.
+pub fn walk_respecting_gitignore(
+    path: &Path,
+    options: &ScanOptions,
+    warnings: &mut Vec<String>,
+    on_file: &dyn Fn(),
+) -> Result<Vec<PathBuf>> {
+    let root = path.to_path_buf();
+    let filter_options = options.clone();
+
+    let walker = ignore::WalkBuilder::new(path)
+        .max_depth(options.depth_limit())
+        .follow_links(options.follow_symlinks)
+        .hidden(false)
+        .require_git(false)
+        .filter_entry(move |entry| {
+            let is_excluded_dir = entry
+                .file_type()
+                .is_some_and(|file_type| file_type.is_dir())
+                && filter_options.is_excluded_dir(entry.file_name());
+            entry.depth() == 0
+                || !is_excluded_dir
+                    && filter_options.exclude.as_ref().is_none_or(|exclude| {
+                        entry
+                            .path()
+                            .strip_prefix(&root)
+                            .is_ok_and(|relative| !exclude.is_match(relative))
+                    })
+        })
+        .build();
+
+    let mut files = Vec::new();
+    for entry in walker {
+        match entry {
+            Ok(entry)
+                if entry
+                    .file_type()
+                    .is_some_and(|file_type| file_type.is_file()) =>
+            {
+                on_file();
+                files.push(entry.into_path());
+            }
+            Ok(_) => {}
+            Err(e) if options.strict => {
+                return Err(anyhow::anyhow!("Failed to scan directory: {}", e));
+            }
+            Err(e) => warnings.push(e.to_string()),
+        }
+    }
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_skips_gitignored_files() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_gitignore_walk");
+        fs::create_dir_all(temp_dir.join("build")).unwrap();
+        fs::write(temp_dir.join(".gitignore"), "build/\nignored.png\n").unwrap();
+        fs::write(temp_dir.join("kept.png"), "png").unwrap();
+        fs::write(temp_dir.join("ignored.png"), "png").unwrap();
+        fs::write(temp_dir.join("build").join("artifact.png"), "png").unwrap();
+
+        let options = ScanOptions {
+            recursive: true,
+            ..ScanOptions::default()
+        };
+        let mut warnings = Vec::new();
+        let files = walk_respecting_gitignore(&temp_dir, &options, &mut warnings, &|| {}).unwrap();
+
+        assert!(files.contains(&temp_dir.join("kept.png")));
+        assert!(!files.contains(&temp_dir.join("ignored.png")));
+        assert!(!files.contains(&temp_dir.join("build").join("artifact.png")));
+        assert!(warnings.is_empty());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
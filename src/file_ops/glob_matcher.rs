@@ -0,0 +1,76 @@
+use anyhow::{Context, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+
+/// Compiles a list of glob patterns into a single matcher.
+///
+/// Patterns are matched against paths relative to the scanned input directory,
+/// for example `*.png`, `icons/**` or `**/thumbnails`.
+///
+/// # Arguments
+///
+/// * `patterns` - Glob patterns as given on the command line
+///
+/// # Returns
+///
+/// Returns `None` if no patterns were given, otherwise the compiled `GlobSet`.
+///
+/// # Errors
+///
+/// Returns an error if any pattern is not a valid glob.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use image_optimizer::file_ops::build_glob_set;
+///
+/// # fn example() -> anyhow::Result<()> {
+/// let globs = build_glob_set(&["*.png".to_string()])?.unwrap();
+/// assert!(globs.is_match(Path::new("icons/logo.png")));
+/// assert!(build_glob_set(&[])?.is_none());
+/// # Ok(())
+/// # }
+/// ```
+pub fn build_glob_set(patterns: &[String]) -> Result<Option<GlobSet>> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob =
+            Glob::new(pattern).with_context(|| format!("Invalid glob pattern: {pattern}"))?;
+        builder.add(glob);
+    }
+
+    Ok(Some(
+        builder.build().context("Failed to compile glob patterns")?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_empty_patterns() {
+        assert!(build_glob_set(&[]).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_matches_relative_paths() {
+        let globs = build_glob_set(&["*.png".to_string(), "thumbnails/**".to_string()])
+            .unwrap()
+            .unwrap();
+        assert!(globs.is_match(Path::new("logo.png")));
+        assert!(globs.is_match(Path::new("nested/logo.png")));
+        assert!(globs.is_match(Path::new("thumbnails/photo.jpg")));
+        assert!(!globs.is_match(Path::new("photo.jpg")));
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        assert!(build_glob_set(&["[unclosed".to_string()]).is_err());
+    }
+}
@@ -1,9 +1,15 @@
-use std::ffi::OsStr;
-use std::path::PathBuf;
+use anyhow::Result;
+use std::cmp::Reverse;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 use walkdir::WalkDir;
 
-/// List of supported image file extensions for optimization.
-const SUPPORTED_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "webp", "svg"];
+use super::{
+    ScanOptions, ScanOutcome, extended_length_path, has_supported_extension,
+    walk_respecting_gitignore,
+};
+use crate::cli::SortOrder;
 
 /// Scans a directory or file for supported image formats.
 ///
@@ -11,15 +17,38 @@ const SUPPORTED_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "webp", "svg"];
 /// It supports both single file input and directory scanning with optional recursion.
 /// Only files with supported extensions (JPEG, PNG, WebP, SVG) are returned.
 ///
+/// When scanning a directory, `--include`/`--exclude` globs from the options are matched
+/// against each path relative to the scanned directory. Excludes win over includes, and
+/// excluded directories are pruned without being descended into, as are directories named
+/// in `options.exclude_dirs` at any depth. Filters are not applied
+/// to an explicitly given single file. With `respect_gitignore`, files ignored by
+/// `.gitignore`, `.ignore` or global git excludes are skipped as well. Symbolic links are
+/// only followed with `follow_symlinks`; cyclic links then produce a warning, and when
+/// optimizing in place, images whose real location is outside `path` are skipped with a
+/// warning so their targets are left untouched. Images smaller
+/// than `options.min_size` are listed in [`ScanOutcome::too_small`] instead of being
+/// returned for processing, and images not modified after `options.modified_since` are
+/// left out.
+///
+/// Results are sorted according to `options.sort` (by path unless configured otherwise),
+/// so the processing order is deterministic regardless of filesystem traversal order.
+///
 /// # Arguments
 ///
 /// * `path` - Path to scan (can be a file or directory)
-/// * `recursive` - Whether to recursively scan subdirectories (ignored for single files)
+/// * `options` - Recursion and filter settings (ignored for single files)
 ///
 /// # Returns
 ///
-/// A vector of `PathBuf` containing all discovered image files with supported formats.
-/// Returns an empty vector if no supported images are found or if the path doesn't exist.
+/// A [`ScanOutcome`] containing all discovered image files with supported formats and
+/// warnings for entries that could not be read (for example permission-denied
+/// directories). The image list is empty if no supported images are found or if the
+/// path doesn't exist.
+///
+/// # Errors
+///
+/// Returns an error for the first unreadable entry when `options.strict` is set
+/// (`--strict-scan`); otherwise traversal errors are reported as warnings.
 ///
 /// # Supported Formats
 ///
@@ -28,75 +57,212 @@ const SUPPORTED_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "webp", "svg"];
 /// - **WebP**: `.webp` (case-insensitive)
 /// - **SVG**: `.svg` (case-insensitive)
 ///
+/// Only formats whose Cargo feature is enabled are included.
+///
 /// # Examples
 ///
 /// ```rust
 /// use std::path::Path;
-/// use image_optimizer::file_ops::scan_images;
+/// use image_optimizer::file_ops::{scan_images, ScanOptions};
 ///
+/// # fn example() -> anyhow::Result<()> {
 /// // Scan a single file
-/// let images = scan_images(Path::new("photo.jpg"), false);
+/// let outcome = scan_images(Path::new("photo.jpg"), &ScanOptions::default())?;
 ///
 /// // Scan directory recursively
-/// let images = scan_images(Path::new("./photos"), true);
+/// let options = ScanOptions { recursive: true, ..ScanOptions::default() };
+/// let outcome = scan_images(Path::new("./photos"), &options)?;
 ///
 /// // Scan directory non-recursively
-/// let images = scan_images(Path::new("./photos"), false);
+/// let outcome = scan_images(Path::new("./photos"), &ScanOptions::default())?;
+/// for warning in &outcome.warnings {
+///     eprintln!("Warning: {warning}");
+/// }
+/// # Ok(())
+/// # }
 /// ```
-pub fn scan_images(path: &std::path::Path, recursive: bool) -> Vec<PathBuf> {
-    let mut image_files = Vec::new();
+pub fn scan_images(path: &Path, options: &ScanOptions) -> Result<ScanOutcome> {
+    scan_images_with_progress(path, options, &|| {})
+}
 
-    if path.is_file() {
-        if let Some(extension) = path.extension().and_then(OsStr::to_str)
-            && SUPPORTED_EXTENSIONS.contains(&extension.to_lowercase().as_str())
-        {
-            image_files.push(path.to_path_buf());
+/// Scans like [`scan_images`], calling `on_file` for every file visited during the walk.
+///
+/// The callback runs before any extension or filter check, so it can drive a "files
+/// scanned" counter on large trees where the walk takes a while.
+///
+/// # Errors
+///
+/// Returns the same errors as [`scan_images`].
+///
+/// # Examples
+///
+/// ```rust
+/// use std::cell::Cell;
+/// use std::path::Path;
+/// use image_optimizer::file_ops::{scan_images_with_progress, ScanOptions};
+///
+/// # fn example() -> anyhow::Result<()> {
+/// let visited = Cell::new(0);
+/// let outcome = scan_images_with_progress(Path::new("./photos"), &ScanOptions::default(), &|| {
+///     visited.set(visited.get() + 1);
+/// })?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn scan_images_with_progress(
+    path: &Path,
+    options: &ScanOptions,
+    on_file: &dyn Fn(),
+) -> Result<ScanOutcome> {
+    let mut outcome = ScanOutcome::default();
+
+    // Walk the extended-length form so deep trees work on Windows, but hand back paths
+    // under `path` as given
+    let walk_root = extended_length_path(path);
+    if walk_root.is_file() {
+        if has_supported_extension(path) {
+            outcome.images.push(path.to_path_buf());
         }
-        return image_files;
+        return Ok(outcome);
     }
 
-    let walker = if recursive {
-        WalkDir::new(path)
+    let candidates = if options.respect_gitignore {
+        walk_respecting_gitignore(path, options, &mut outcome.warnings, on_file)?
     } else {
-        WalkDir::new(path).max_depth(1)
+        let walker = WalkDir::new(&walk_root)
+            .max_depth(options.depth_limit().unwrap_or(usize::MAX))
+            .follow_links(options.follow_symlinks);
+
+        let is_excluded = |entry: &walkdir::DirEntry| {
+            options.exclude.as_ref().is_some_and(|exclude| {
+                entry
+                    .path()
+                    .strip_prefix(&walk_root)
+                    .is_ok_and(|relative| exclude.is_match(relative))
+            })
+        };
+
+        let mut files = Vec::new();
+        for entry in walker.into_iter().filter_entry(|entry| {
+            entry.depth() == 0
+                || !(is_excluded(entry)
+                    || entry.file_type().is_dir() && options.is_excluded_dir(entry.file_name()))
+        }) {
+            match entry {
+                Ok(entry) if entry.file_type().is_file() => {
+                    on_file();
+                    let relative = entry
+                        .path()
+                        .strip_prefix(&walk_root)
+                        .unwrap_or_else(|_| entry.path());
+                    files.push(path.join(relative));
+                }
+                Ok(_) => {}
+                Err(e) if options.strict => {
+                    return Err(anyhow::anyhow!("Failed to scan directory: {}", e));
+                }
+                Err(e) => outcome.warnings.push(e.to_string()),
+            }
+        }
+        files
     };
 
-    for entry in walker.into_iter().filter_map(Result::ok) {
-        if entry.file_type().is_file()
-            && let Some(extension) = entry.path().extension().and_then(OsStr::to_str)
-            && SUPPORTED_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+    // Rewriting in place through a followed link would modify files outside the input
+    let real_root = if options.follow_symlinks && options.in_place {
+        path.canonicalize().ok()
+    } else {
+        None
+    };
+
+    for candidate in candidates {
+        if let Some(ref real_root) = real_root
+            && has_supported_extension(&candidate)
+            && !resolves_within(&candidate, real_root)
+        {
+            outcome.warnings.push(format!(
+                "{} links outside the input and is not optimized in place (use --output)",
+                candidate.display()
+            ));
+            continue;
+        }
+        if has_supported_extension(&candidate)
+            && options.include.as_ref().is_none_or(|include| {
+                candidate
+                    .strip_prefix(path)
+                    .is_ok_and(|relative| include.is_match(relative))
+            })
+            && !is_unmodified_since(&candidate, options.modified_since)
         {
-            image_files.push(entry.path().to_path_buf());
+            if is_below_min_size(&candidate, options.min_size) {
+                outcome.too_small.push(candidate);
+            } else {
+                outcome.images.push(candidate);
+            }
         }
     }
 
-    image_files
+    sort_images(&mut outcome.images, options.sort);
+    outcome.too_small.sort();
+
+    Ok(outcome)
+}
+
+/// Whether the real location of `path`, with all symlinks resolved, lies inside `real_root`.
+fn resolves_within(path: &Path, real_root: &Path) -> bool {
+    path.canonicalize()
+        .is_ok_and(|real_path| real_path.starts_with(real_root))
+}
+
+/// Whether a file is smaller than the `--min-size` threshold. Files whose size cannot be
+/// read are kept so that optimization reports the underlying error.
+fn is_below_min_size(path: &Path, min_size: Option<u64>) -> bool {
+    min_size
+        .is_some_and(|min_size| fs::metadata(path).is_ok_and(|metadata| metadata.len() < min_size))
+}
+
+/// Whether a file was last modified at or before the `--modified-since` point. Files
+/// whose modification time cannot be read are kept.
+fn is_unmodified_since(path: &Path, since: Option<SystemTime>) -> bool {
+    since.is_some_and(|since| {
+        fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .is_ok_and(|modified| modified <= since)
+    })
+}
+
+/// Sorts images in place, breaking ties by path so the order is always stable.
+fn sort_images(images: &mut [PathBuf], order: SortOrder) {
+    match order {
+        SortOrder::Path => images.sort(),
+        SortOrder::Size => images.sort_by_cached_key(|image| {
+            let size = fs::metadata(image).map_or(0, |metadata| metadata.len());
+            (Reverse(size), image.clone())
+        }),
+        SortOrder::Mtime => images.sort_by_cached_key(|image| {
+            let modified = fs::metadata(image)
+                .and_then(|metadata| metadata.modified())
+                .ok();
+            (Reverse(modified), image.clone())
+        }),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::file_ops::build_glob_set;
     use std::fs;
     use std::path::Path;
 
-    #[test]
-    fn test_supported_extensions() {
-        assert!(SUPPORTED_EXTENSIONS.contains(&"jpg"));
-        assert!(SUPPORTED_EXTENSIONS.contains(&"jpeg"));
-        assert!(SUPPORTED_EXTENSIONS.contains(&"png"));
-        assert!(SUPPORTED_EXTENSIONS.contains(&"webp"));
-        assert!(SUPPORTED_EXTENSIONS.contains(&"svg"));
-        assert!(!SUPPORTED_EXTENSIONS.contains(&"gif"));
-        assert!(!SUPPORTED_EXTENSIONS.contains(&"txt"));
-    }
-
     #[test]
     fn test_scan_single_file() {
         let temp_dir = std::env::temp_dir();
         let test_file = temp_dir.join("test.jpg");
         fs::write(&test_file, "fake jpg content").unwrap();
 
-        let result = scan_images(&test_file, false);
+        let result = scan_images(&test_file, &ScanOptions::default())
+            .unwrap()
+            .images;
         assert_eq!(result.len(), 1);
         assert_eq!(result[0], test_file);
 
@@ -109,7 +275,9 @@ mod tests {
         let test_file = temp_dir.join("test.txt");
         fs::write(&test_file, "text content").unwrap();
 
-        let result = scan_images(&test_file, false);
+        let result = scan_images(&test_file, &ScanOptions::default())
+            .unwrap()
+            .images;
         assert_eq!(result.len(), 0);
 
         fs::remove_file(&test_file).unwrap();
@@ -118,8 +286,94 @@ mod tests {
     #[test]
     fn test_scan_nonexistent_path() {
         let nonexistent = Path::new("/nonexistent/path");
-        let result = scan_images(nonexistent, false);
-        assert_eq!(result.len(), 0);
+        let result = scan_images(nonexistent, &ScanOptions::default()).unwrap();
+        assert_eq!(result.images.len(), 0);
+        assert_eq!(result.warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_strict_scan_fails_on_traversal_error() {
+        let nonexistent = Path::new("/nonexistent/path");
+        let options = ScanOptions {
+            strict: true,
+            ..ScanOptions::default()
+        };
+        assert!(scan_images(nonexistent, &options).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unreadable_subdirectory_produces_warning() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = std::env::temp_dir().join("image_optimizer_scan_unreadable");
+        let locked = temp_dir.join("locked");
+        fs::create_dir_all(&locked).unwrap();
+        fs::write(temp_dir.join("visible.png"), "png").unwrap();
+        fs::write(locked.join("hidden.png"), "png").unwrap();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+
+        // Permission bits are not enforced for privileged users.
+        let enforced = fs::read_dir(&locked).is_err();
+        let options = ScanOptions {
+            recursive: true,
+            ..ScanOptions::default()
+        };
+        let result = scan_images(&temp_dir, &options).unwrap();
+
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+        fs::remove_dir_all(&temp_dir).unwrap();
+
+        assert!(result.images.contains(&temp_dir.join("visible.png")));
+        if enforced {
+            assert_eq!(result.warnings.len(), 1);
+            assert!(!result.images.contains(&locked.join("hidden.png")));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_follow_symlinks_traverses_linked_directory() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_scan_symlinks");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let input = temp_dir.join("input");
+        let shared = temp_dir.join("shared");
+        fs::create_dir_all(&input).unwrap();
+        fs::create_dir_all(&shared).unwrap();
+        fs::write(input.join("own.png"), "png").unwrap();
+        fs::write(shared.join("linked.png"), "png").unwrap();
+        std::os::unix::fs::symlink(&shared, input.join("shared")).unwrap();
+        // A cycle back to the input must not be walked forever
+        std::os::unix::fs::symlink(&input, input.join("loop")).unwrap();
+
+        let scan = |follow_symlinks, in_place| {
+            let options = ScanOptions {
+                recursive: true,
+                follow_symlinks,
+                in_place,
+                ..ScanOptions::default()
+            };
+            scan_images(&input, &options).unwrap()
+        };
+
+        let not_followed = scan(false, false);
+        assert_eq!(not_followed.images, vec![input.join("own.png")]);
+
+        let followed = scan(true, false);
+        assert_eq!(
+            followed.images,
+            vec![
+                input.join("own.png"),
+                input.join("shared").join("linked.png")
+            ]
+        );
+        assert!(followed.warnings.iter().any(|w| w.contains("loop")));
+
+        let in_place = scan(true, true);
+        assert_eq!(in_place.images, vec![input.join("own.png")]);
+        assert!(in_place.warnings.iter().any(|w| w.contains("linked.png")));
+
+        fs::remove_dir_all(&temp_dir).unwrap();
     }
 
     #[test]
@@ -137,11 +391,272 @@ mod tests {
             let test_file = temp_dir.join(filename);
             fs::write(&test_file, "fake content").unwrap();
 
-            let result = scan_images(&test_file, false);
-            assert_eq!(result.len(), 1, "Failed for file: {}", filename);
+            let result = scan_images(&test_file, &ScanOptions::default())
+                .unwrap()
+                .images;
+            assert_eq!(result.len(), 1, "Failed for file: {filename}");
             assert_eq!(result[0], test_file);
 
             fs::remove_file(&test_file).unwrap();
         }
     }
+
+    #[test]
+    fn test_max_depth_limits_recursion() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_scan_max_depth");
+        fs::create_dir_all(temp_dir.join("one").join("two")).unwrap();
+        fs::write(temp_dir.join("top.png"), "png").unwrap();
+        fs::write(temp_dir.join("one").join("mid.png"), "png").unwrap();
+        fs::write(temp_dir.join("one").join("two").join("deep.png"), "png").unwrap();
+
+        for respect_gitignore in [false, true] {
+            let found = |max_depth| {
+                let options = ScanOptions {
+                    recursive: true,
+                    max_depth,
+                    respect_gitignore,
+                    ..ScanOptions::default()
+                };
+                scan_images(&temp_dir, &options).unwrap().images.len()
+            };
+            assert_eq!(found(Some(1)), 1);
+            assert_eq!(found(Some(2)), 2);
+            assert_eq!(found(None), 3);
+        }
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_include_only_one_extension() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_scan_include");
+        fs::create_dir_all(temp_dir.join("nested")).unwrap();
+        fs::write(temp_dir.join("a.png"), "png").unwrap();
+        fs::write(temp_dir.join("b.jpg"), "jpg").unwrap();
+        fs::write(temp_dir.join("nested").join("c.png"), "png").unwrap();
+
+        let options = ScanOptions {
+            recursive: true,
+            include: build_glob_set(&["*.png".to_string()]).unwrap(),
+            ..ScanOptions::default()
+        };
+        let mut result = scan_images(&temp_dir, &options).unwrap().images;
+        result.sort();
+
+        assert_eq!(
+            result,
+            vec![
+                temp_dir.join("a.png"),
+                temp_dir.join("nested").join("c.png")
+            ]
+        );
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_exclude_subdirectory_wins_over_include() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_scan_exclude");
+        fs::create_dir_all(temp_dir.join("thumbnails").join("deep")).unwrap();
+        fs::write(temp_dir.join("a.png"), "png").unwrap();
+        fs::write(temp_dir.join("thumbnails").join("b.png"), "png").unwrap();
+        fs::write(
+            temp_dir.join("thumbnails").join("deep").join("c.png"),
+            "png",
+        )
+        .unwrap();
+
+        let options = ScanOptions {
+            recursive: true,
+            include: build_glob_set(&["**/*.png".to_string()]).unwrap(),
+            exclude: build_glob_set(&["thumbnails".to_string()]).unwrap(),
+            ..ScanOptions::default()
+        };
+        let result = scan_images(&temp_dir, &options).unwrap().images;
+
+        assert_eq!(result, vec![temp_dir.join("a.png")]);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_scan_order_is_stable() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_scan_order");
+        fs::create_dir_all(temp_dir.join("b")).unwrap();
+        fs::write(temp_dir.join("c.png"), "small").unwrap();
+        fs::write(temp_dir.join("a.png"), "medium size").unwrap();
+        fs::write(temp_dir.join("b").join("z.png"), "the largest file here").unwrap();
+
+        let options = ScanOptions {
+            recursive: true,
+            ..ScanOptions::default()
+        };
+        let expected = vec![
+            temp_dir.join("a.png"),
+            temp_dir.join("b").join("z.png"),
+            temp_dir.join("c.png"),
+        ];
+        for _ in 0..3 {
+            assert_eq!(scan_images(&temp_dir, &options).unwrap().images, expected);
+        }
+
+        let options = ScanOptions {
+            recursive: true,
+            sort: SortOrder::Size,
+            ..ScanOptions::default()
+        };
+        assert_eq!(
+            scan_images(&temp_dir, &options).unwrap().images,
+            vec![
+                temp_dir.join("b").join("z.png"),
+                temp_dir.join("a.png"),
+                temp_dir.join("c.png"),
+            ]
+        );
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_respect_gitignore_skips_ignored_images() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_scan_gitignore");
+        fs::create_dir_all(&temp_dir).unwrap();
+        fs::write(temp_dir.join(".gitignore"), "generated.png\n").unwrap();
+        fs::write(temp_dir.join("source.png"), "png").unwrap();
+        fs::write(temp_dir.join("generated.png"), "png").unwrap();
+
+        let options = ScanOptions {
+            respect_gitignore: true,
+            ..ScanOptions::default()
+        };
+        assert_eq!(
+            scan_images(&temp_dir, &options).unwrap().images,
+            vec![temp_dir.join("source.png")]
+        );
+
+        let mut all = scan_images(&temp_dir, &ScanOptions::default())
+            .unwrap()
+            .images;
+        all.sort();
+        assert_eq!(
+            all,
+            vec![temp_dir.join("generated.png"), temp_dir.join("source.png")]
+        );
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_exclude_dir_never_enters_the_directory() {
+        use std::cell::Cell;
+
+        let temp_dir = std::env::temp_dir().join("image_optimizer_scan_exclude_dir");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(temp_dir.join("src")).unwrap();
+        fs::write(temp_dir.join("logo.png"), "test").unwrap();
+        fs::write(temp_dir.join("src/icon.png"), "test").unwrap();
+        // A large excluded subtree, nested both at the top and deeper down
+        for root in ["node_modules", "src/node_modules"] {
+            for package in 0..20 {
+                let dir = temp_dir.join(root).join(format!("package_{package}"));
+                fs::create_dir_all(&dir).unwrap();
+                for file in 0..10 {
+                    fs::write(dir.join(format!("image_{file}.png")), "test").unwrap();
+                }
+            }
+        }
+
+        for respect_gitignore in [false, true] {
+            let options = ScanOptions {
+                recursive: true,
+                respect_gitignore,
+                exclude_dirs: vec!["node_modules".into()],
+                ..ScanOptions::default()
+            };
+            let visited = Cell::new(0);
+            let outcome =
+                scan_images_with_progress(&temp_dir, &options, &|| visited.set(visited.get() + 1))
+                    .unwrap();
+
+            assert_eq!(visited.get(), 2);
+            assert_eq!(
+                outcome.images,
+                vec![temp_dir.join("logo.png"), temp_dir.join("src/icon.png")]
+            );
+        }
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_progress_counts_visited_files() {
+        use std::cell::Cell;
+
+        let temp_dir = std::env::temp_dir().join("image_optimizer_scan_progress");
+        fs::create_dir_all(temp_dir.join("nested")).unwrap();
+        for name in ["a.png", "b.jpg", "nested/c.webp", "notes.txt"] {
+            fs::write(temp_dir.join(name), "test").unwrap();
+        }
+
+        let options = ScanOptions {
+            recursive: true,
+            ..ScanOptions::default()
+        };
+        let visited = Cell::new(0);
+        let outcome =
+            scan_images_with_progress(&temp_dir, &options, &|| visited.set(visited.get() + 1))
+                .unwrap();
+
+        assert_eq!(visited.get(), 4);
+        assert_eq!(
+            outcome.images,
+            scan_images(&temp_dir, &options).unwrap().images
+        );
+        assert_eq!(outcome.images.len(), 3);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_min_size_sets_small_files_aside() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_scan_min_size");
+        fs::create_dir_all(&temp_dir).unwrap();
+        fs::write(temp_dir.join("icon.png"), [0u8; 100]).unwrap();
+        fs::write(temp_dir.join("photo.png"), [0u8; 2048]).unwrap();
+
+        let options = ScanOptions {
+            min_size: Some(1024),
+            ..ScanOptions::default()
+        };
+        let outcome = scan_images(&temp_dir, &options).unwrap();
+        assert_eq!(outcome.images, vec![temp_dir.join("photo.png")]);
+        assert_eq!(outcome.too_small, vec![temp_dir.join("icon.png")]);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_modified_since_skips_old_files() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_scan_modified_since");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        fs::write(temp_dir.join("old.png"), [0u8; 16]).unwrap();
+        fs::write(temp_dir.join("fresh.png"), [0u8; 16]).unwrap();
+        let two_days_ago = SystemTime::now() - std::time::Duration::from_hours(48);
+        filetime::set_file_mtime(
+            temp_dir.join("old.png"),
+            filetime::FileTime::from_system_time(two_days_ago),
+        )
+        .unwrap();
+
+        let options = ScanOptions {
+            modified_since: Some(crate::cli::parse_modified_since("24h").unwrap()),
+            ..ScanOptions::default()
+        };
+        let outcome = scan_images(&temp_dir, &options).unwrap();
+        assert_eq!(outcome.images, vec![temp_dir.join("fresh.png")]);
+        assert!(outcome.too_small.is_empty());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
 }
@@ -0,0 +1,143 @@
+use std::borrow::Cow;
+use std::path::Path;
+
+/// Converts a path to the Windows extended-length form so it may exceed `MAX_PATH`.
+///
+/// On Windows, paths longer than 260 characters fail with most file APIs unless they use
+/// the `\\?\` prefix. The path is first made absolute (resolving `.` and `..` and turning
+/// `/` into `\`, which the prefix no longer does for us) and then prefixed: `C:\photos`
+/// becomes `\\?\C:\photos` and `\\server\share\photos` becomes
+/// `\\?\UNC\server\share\photos`. Paths that are already verbatim, device paths and
+/// paths that cannot be made absolute are returned unchanged.
+///
+/// On every other platform the path is returned unchanged.
+///
+/// The result is meant for file system calls only; paths shown to the user and returned
+/// from the file operations keep their original form.
+///
+/// # Arguments
+///
+/// * `path` - Path about to be passed to a file system call
+///
+/// # Returns
+///
+/// The extended-length path, or `path` itself where no conversion applies.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use image_optimizer::file_ops::extended_length_path;
+///
+/// let path = extended_length_path(Path::new("photos/deep/image.jpg"));
+/// # #[cfg(not(windows))]
+/// assert_eq!(path, Path::new("photos/deep/image.jpg"));
+/// ```
+#[must_use]
+#[allow(clippy::missing_const_for_fn)] // Only const without the Windows branch
+pub fn extended_length_path(path: &Path) -> Cow<'_, Path> {
+    #[cfg(windows)]
+    {
+        use std::ffi::OsString;
+        use std::path::{Component, PathBuf, Prefix};
+
+        let Ok(absolute) = std::path::absolute(path) else {
+            return Cow::Borrowed(path);
+        };
+        let mut components = absolute.components();
+        let Some(Component::Prefix(prefix)) = components.next() else {
+            return Cow::Borrowed(path);
+        };
+        match prefix.kind() {
+            Prefix::Disk(_) => {
+                let mut raw = OsString::from(r"\\?\");
+                raw.push(absolute.as_os_str());
+                Cow::Owned(PathBuf::from(raw))
+            }
+            Prefix::UNC(server, share) => {
+                let mut raw = OsString::from(r"\\?\UNC\");
+                raw.push(server);
+                raw.push(r"\");
+                raw.push(share);
+                let mut extended = PathBuf::from(raw);
+                extended.extend(components.filter(|c| matches!(c, Component::Normal(_))));
+                Cow::Owned(extended)
+            }
+            _ => Cow::Borrowed(path),
+        }
+    }
+    #[cfg(not(windows))]
+    {
+        Cow::Borrowed(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_unchanged_outside_windows() {
+        let long = Path::new("/tmp").join("d".repeat(300)).join("image.jpg");
+        assert!(matches!(extended_length_path(&long), Cow::Borrowed(_)));
+        assert_eq!(
+            extended_length_path(Path::new("photos")),
+            Path::new("photos")
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_prefixes_absolute_paths() {
+        assert_eq!(
+            extended_length_path(Path::new(r"C:\photos\..\images/a.jpg")),
+            Path::new(r"\\?\C:\images\a.jpg")
+        );
+        assert_eq!(
+            extended_length_path(Path::new(r"\\server\share\photos\a.jpg")),
+            Path::new(r"\\?\UNC\server\share\photos\a.jpg")
+        );
+        let verbatim = Path::new(r"\\?\C:\photos");
+        assert_eq!(extended_length_path(verbatim), verbatim);
+    }
+
+    #[cfg(all(windows, feature = "jpeg"))]
+    #[test]
+    fn test_deep_tree_beyond_max_path() {
+        use crate::cli::BackupMode;
+        use crate::file_ops::{ScanOptions, create_backup, ensure_output_dir, scan_images};
+
+        let temp_dir = std::env::temp_dir().join("image_optimizer_long_path");
+        let _ = std::fs::remove_dir_all(extended_length_path(&temp_dir));
+        // Each level is 60 characters, so five levels push the file well past 260
+        let mut deep = temp_dir.clone();
+        for level in 0..5 {
+            deep.push(format!("{level}{}", "d".repeat(59)));
+        }
+        let input = deep.join("image.jpg");
+        assert!(input.as_os_str().len() > 260);
+
+        std::fs::create_dir_all(extended_length_path(&deep)).unwrap();
+        std::fs::write(extended_length_path(&input), "original").unwrap();
+
+        let options = ScanOptions {
+            recursive: true,
+            ..ScanOptions::default()
+        };
+        let outcome = scan_images(&temp_dir, &options).unwrap();
+        assert_eq!(outcome.images, vec![input.clone()]);
+
+        let output = ensure_output_dir(&temp_dir.join("out"), &temp_dir, &input).unwrap();
+        assert!(output.starts_with(temp_dir.join("out")));
+        let backup = create_backup(&input, None, &temp_dir, BackupMode::Skip)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(extended_length_path(&backup)).unwrap(),
+            "original"
+        );
+
+        std::fs::remove_dir_all(extended_length_path(&temp_dir)).unwrap();
+    }
+}
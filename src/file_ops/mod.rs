@@ -2,20 +2,82 @@
 //!
 //! This module provides file system utilities for the image optimizer including:
 //!
-//! - **Image scanning**: Discovering image files in directories with extension filtering
+//! - **Image scanning**: Discovering image files in directories with extension and glob filtering
+//! - **File lists**: Reading explicit input lists instead of scanning
+//! - **Sampling**: Picking a reproducible random subset of the inputs
 //! - **Backup management**: Creating backup copies of original files
-//! - **Output management**: Ensuring output directory structure exists
+//! - **Output management**: Ensuring output directory structure exists, flattening outputs
+//!   into one directory, naming outputs from a template, and detecting an output directory
+//!   inside the input
+//! - **Overwrite protection**: Deciding whether existing outputs may be replaced
 //! - **Size calculations**: Computing resize dimensions while preserving aspect ratio
-//! - **Byte formatting**: Converting byte counts to human-readable format
+//! - **Byte formatting**: Converting byte counts and size changes to human-readable format,
+//!   and parsing human-readable sizes
+//! - **Percentages**: Computing relative size changes
+//! - **Attribute preservation**: Restoring timestamps and permissions on optimized files
+//! - **Optimization cache**: Content hashing and a manifest of already optimized files
+//! - **Duplicates**: Grouping byte-identical inputs and linking or copying results to them
+//! - **Safe replacement**: Temporary file creation and cross-filesystem moves
+//! - **Run lock**: Keeping concurrent runs from optimizing the same tree
+//! - **Long paths**: Extended-length (`\\?\`) paths so deep trees work on Windows
 
+pub mod attribute_preserver;
 pub mod backup_manager;
 pub mod byte_formatter;
+pub mod byte_parser;
+pub mod content_hasher;
+pub mod duplicate_finder;
+pub mod extension_filter;
+pub mod file_linker;
+pub mod file_list_reader;
+pub mod file_mover;
+pub mod gitignore_walker;
+pub mod glob_matcher;
 pub mod image_scanner;
+pub mod long_path;
+pub mod name_template;
+pub mod optimization_cache;
 pub mod output_manager;
+pub mod output_nesting;
+pub mod overwrite_guard;
+pub mod percent_calculator;
+pub mod run_lock;
+pub mod sample_selector;
+pub mod scan_options;
+pub mod scan_outcome;
 pub mod size_calculator;
+pub mod temp_file;
+pub mod temp_manager;
 
+pub use attribute_preserver::restore_file_attributes;
 pub use backup_manager::create_backup;
-pub use byte_formatter::format_bytes;
-pub use image_scanner::scan_images;
-pub use output_manager::ensure_output_dir;
+pub use byte_formatter::{format_bytes, format_bytes_binary, format_bytes_si, format_delta};
+pub use byte_parser::parse_byte_size;
+pub use content_hasher::hash_file;
+pub use duplicate_finder::find_duplicates;
+pub use extension_filter::has_supported_extension;
+pub use file_linker::link_or_copy;
+pub use file_list_reader::{check_listed_paths, read_file_list};
+pub use file_mover::move_file;
+pub use gitignore_walker::walk_respecting_gitignore;
+pub use glob_matcher::build_glob_set;
+pub use image_scanner::{scan_images, scan_images_with_progress};
+pub use long_path::extended_length_path;
+pub use name_template::NameTemplate;
+pub use optimization_cache::OptimizationCache;
+pub use output_manager::{
+    apply_name_template, ensure_output_dir, normalize_extension, plan_flat_outputs,
+    svgz_output_path,
+};
+pub use output_nesting::{is_same_file, output_within_input};
+pub use overwrite_guard::should_keep_existing_output;
+pub use percent_calculator::percent_change;
+pub use run_lock::RunLock;
+pub use sample_selector::sample_files;
+pub use scan_options::ScanOptions;
+pub use scan_outcome::ScanOutcome;
 pub use size_calculator::calculate_resize_dimensions;
+pub use temp_file::TempFile;
+pub use temp_manager::{
+    create_temp_path, create_unique_temp, release_temp_path, remove_active_temp_files,
+};
@@ -0,0 +1,143 @@
+use std::str::FromStr;
+
+/// Placeholders a [`NameTemplate`] may contain.
+const PLACEHOLDERS: [&str; 5] = ["stem", "ext", "parent", "width", "height"];
+
+/// Pattern for output file names (`--name-template`), e.g. `{stem}.min.{ext}`.
+///
+/// Supported placeholders are `{stem}` (file name without extension), `{ext}` (extension
+/// without the dot), `{parent}` (name of the input's directory) and `{width}`/`{height}`
+/// (pixel size of the written image). Every template must contain `{stem}` so that
+/// different inputs cannot be written to the same file, and it may not contain path
+/// separators, so outputs stay in their mirrored directory.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_optimizer::file_ops::NameTemplate;
+///
+/// let template: NameTemplate = "{stem}-{width}w.{ext}".parse().unwrap();
+/// assert_eq!(template.render("photo", "jpg", "gallery", Some((640, 480))), "photo-640w.jpg");
+/// assert!("{parent}.{ext}".parse::<NameTemplate>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameTemplate {
+    pattern: String,
+}
+
+impl FromStr for NameTemplate {
+    type Err = String;
+
+    fn from_str(pattern: &str) -> Result<Self, Self::Err> {
+        if pattern.contains(['/', '\\']) {
+            return Err(format!(
+                "name template '{pattern}' must not contain path separators"
+            ));
+        }
+        let mut has_stem = false;
+        let mut rest = pattern;
+        while let Some(start) = rest.find(['{', '}']) {
+            let after = &rest[start + 1..];
+            let end = after
+                .find('}')
+                .filter(|_| rest[start..].starts_with('{'))
+                .ok_or_else(|| format!("unbalanced braces in name template '{pattern}'"))?;
+            let name = &after[..end];
+            if !PLACEHOLDERS.contains(&name) {
+                return Err(format!(
+                    "unknown placeholder '{{{name}}}' in name template (expected one of {})",
+                    PLACEHOLDERS.map(|name| format!("{{{name}}}")).join(", ")
+                ));
+            }
+            has_stem |= name == "stem";
+            rest = &after[end + 1..];
+        }
+        if !has_stem {
+            return Err(format!(
+                "name template '{pattern}' must contain {{stem}} to keep output names unique"
+            ));
+        }
+        Ok(Self {
+            pattern: pattern.to_string(),
+        })
+    }
+}
+
+impl NameTemplate {
+    /// Returns the pattern as given on the command line.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Returns `true` if the template contains `{width}` or `{height}`.
+    #[must_use]
+    pub fn uses_dimensions(&self) -> bool {
+        self.pattern.contains("{width}") || self.pattern.contains("{height}")
+    }
+
+    /// Renders the file name for one output.
+    ///
+    /// `{width}` and `{height}` render as empty strings when `dimensions` is `None`, as
+    /// for SVG images, which have no pixel size.
+    #[must_use]
+    pub fn render(
+        &self,
+        stem: &str,
+        extension: &str,
+        parent: &str,
+        dimensions: Option<(u32, u32)>,
+    ) -> String {
+        let mut name = String::with_capacity(self.pattern.len() + stem.len());
+        let mut rest = self.pattern.as_str();
+        // The pattern was validated when parsed, so every `{` starts a known placeholder
+        while let Some((literal, after)) = rest.split_once('{') {
+            name.push_str(literal);
+            let (placeholder, remainder) = after.split_once('}').unwrap_or((after, ""));
+            match placeholder {
+                "stem" => name.push_str(stem),
+                "ext" => name.push_str(extension),
+                "parent" => name.push_str(parent),
+                "width" => name.extend(dimensions.map(|(width, _)| width.to_string())),
+                "height" => name.extend(dimensions.map(|(_, height)| height.to_string())),
+                _ => {}
+            }
+            rest = remainder;
+        }
+        name.push_str(rest);
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(pattern: &str, dimensions: Option<(u32, u32)>) -> String {
+        pattern
+            .parse::<NameTemplate>()
+            .unwrap()
+            .render("photo", "jpg", "gallery", dimensions)
+    }
+
+    #[test]
+    fn test_renders_placeholders() {
+        assert_eq!(render("{stem}.min.{ext}", None), "photo.min.jpg");
+        assert_eq!(render("{stem}-opt.{ext}", None), "photo-opt.jpg");
+        assert_eq!(render("{parent}_{stem}.{ext}", None), "gallery_photo.jpg");
+        assert_eq!(
+            render("{stem}-{width}x{height}.{ext}", Some((640, 480))),
+            "photo-640x480.jpg"
+        );
+        assert_eq!(render("{stem}-{width}.{ext}", None), "photo-.jpg");
+    }
+
+    #[test]
+    fn test_rejects_invalid_templates() {
+        assert!("{ext}".parse::<NameTemplate>().is_err());
+        assert!("{stem}.{extension}".parse::<NameTemplate>().is_err());
+        assert!("{stem.{ext}".parse::<NameTemplate>().is_err());
+        assert!("{stem}}.{ext}".parse::<NameTemplate>().is_err());
+        assert!("min/{stem}.{ext}".parse::<NameTemplate>().is_err());
+    }
+}
@@ -0,0 +1,153 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// Manifest of content hashes of files that are already optimized.
+///
+/// The cache is stored as JSON and tied to a settings fingerprint: when the settings
+/// that affect the output change, the previous entries no longer describe optimized
+/// files and [`OptimizationCache::load`] starts with an empty cache instead.
+///
+/// Entries are content hashes (see [`hash_file`](super::hash_file)) rather than paths, so
+/// renamed or moved files are still recognized while any change to a file's bytes
+/// invalidates its entry.
+///
+/// ## Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use image_optimizer::file_ops::{OptimizationCache, hash_file};
+///
+/// # fn example() -> anyhow::Result<()> {
+/// let cache_path = Path::new(".image-optimizer-cache.json");
+/// let mut cache = OptimizationCache::load(cache_path, "jpeg-quality=85")?;
+/// let hash = hash_file(Path::new("photo.jpg"))?;
+/// if !cache.contains(&hash) {
+///     // ... optimize photo.jpg ...
+///     cache.insert(hash_file(Path::new("photo.jpg"))?);
+/// }
+/// cache.save(cache_path)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct OptimizationCache {
+    settings: String,
+    hashes: BTreeSet<String>,
+}
+
+impl OptimizationCache {
+    /// Loads the cache from `path`, starting empty if the file does not exist or was
+    /// written with different settings.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read or is not a valid cache.
+    pub fn load(path: &Path, settings: &str) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::empty(settings));
+        }
+
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read cache file {}", path.display()))?;
+        let cache: Self = serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid cache file {}", path.display()))?;
+
+        if cache.settings == settings {
+            Ok(cache)
+        } else {
+            Ok(Self::empty(settings))
+        }
+    }
+
+    fn empty(settings: &str) -> Self {
+        Self {
+            settings: settings.to_string(),
+            hashes: BTreeSet::new(),
+        }
+    }
+
+    /// Returns whether a file with this content hash is already optimized.
+    #[must_use]
+    pub fn contains(&self, hash: &str) -> bool {
+        self.hashes.contains(hash)
+    }
+
+    /// Records a content hash as optimized.
+    pub fn insert(&mut self, hash: String) {
+        self.hashes.insert(hash);
+    }
+
+    /// Writes the cache to `path`.
+    ///
+    /// The cache is written to a sibling temporary file first and then renamed over
+    /// `path`, so an interrupted run never leaves a truncated cache behind.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization or writing the file fails.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = serde_json::to_string_pretty(self)?;
+        let mut temp_path = path.as_os_str().to_owned();
+        temp_path.push(".tmp");
+
+        std::fs::write(&temp_path, contents)
+            .with_context(|| format!("Failed to write cache file {}", path.display()))?;
+        std::fs::rename(&temp_path, path)
+            .with_context(|| format!("Failed to write cache file {}", path.display()))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_round_trip() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_cache_round_trip");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let cache_path = temp_dir.join("cache.json");
+
+        let mut cache = OptimizationCache::load(&cache_path, "q=85").unwrap();
+        assert!(!cache.contains("abc"));
+        cache.insert("abc".to_string());
+        cache.save(&cache_path).unwrap();
+
+        let cache = OptimizationCache::load(&cache_path, "q=85").unwrap();
+        assert!(cache.contains("abc"));
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_settings_change_invalidates_entries() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_cache_settings");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let cache_path = temp_dir.join("cache.json");
+
+        let mut cache = OptimizationCache::load(&cache_path, "q=85").unwrap();
+        cache.insert("abc".to_string());
+        cache.save(&cache_path).unwrap();
+
+        let cache = OptimizationCache::load(&cache_path, "q=70").unwrap();
+        assert!(!cache.contains("abc"));
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_corrupt_cache() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_cache_corrupt");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let cache_path = temp_dir.join("cache.json");
+        fs::write(&cache_path, "not json").unwrap();
+
+        assert!(OptimizationCache::load(&cache_path, "q=85").is_err());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
@@ -1,16 +1,24 @@
 use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
 use std::path::{Path, PathBuf};
 
+use super::{NameTemplate, extended_length_path, is_same_file};
+use crate::cli::{ExtensionNormalization, FlattenCollisions};
+use crate::optimization::{ImageFormat, SVGZ_EXTENSION, is_svgz_path};
+
 /// Ensures the output directory structure exists and returns the output file path.
 ///
 /// This function creates the necessary directory structure in the output directory
 /// to mirror the input directory structure, then returns the full path where the
-/// optimized file should be written.
+/// optimized file should be written. When the input is the file itself (a single file
+/// passed as `--input`), the output is the file name directly under `output_path`.
 ///
 /// # Arguments
 ///
 /// * `output_path` - Base output directory path
-/// * `input_path` - Base input directory path (used to calculate relative paths)
+/// * `input_path` - Base input directory path (used to calculate relative paths), or the
+///   file itself
 /// * `file_path` - Path to the specific file being processed
 ///
 /// # Returns
@@ -21,6 +29,7 @@ use std::path::{Path, PathBuf};
 ///
 /// Returns an error if:
 /// - Path stripping fails (`file_path` is not under `input_path`)
+/// - The output path is `file_path` itself (e.g. `-i dir/photo.jpg -o dir`)
 /// - Directory creation fails due to permissions or I/O errors
 ///
 /// # Examples
@@ -45,11 +54,426 @@ pub fn ensure_output_dir(
     file_path: &Path,
 ) -> Result<PathBuf> {
     let relative_path = file_path.strip_prefix(input_path)?;
-    let output_file_path = output_path.join(relative_path);
+    let output_file_path = if relative_path.as_os_str().is_empty() {
+        let file_name = file_path
+            .file_name()
+            .ok_or_else(|| anyhow::anyhow!("{} has no file name", file_path.display()))?;
+        output_path.join(file_name)
+    } else {
+        output_path.join(relative_path)
+    };
+    if is_same_file(&output_file_path, file_path) {
+        anyhow::bail!(
+            "the output path {} is the input file itself; omit -o/--output to optimize in place",
+            output_file_path.display()
+        );
+    }
 
     if let Some(parent) = output_file_path.parent() {
-        std::fs::create_dir_all(parent)?;
+        std::fs::create_dir_all(extended_length_path(parent))?;
     }
 
     Ok(output_file_path)
 }
+
+/// Assigns each input its output path for `--flatten`.
+///
+/// Every output is placed directly in the output directory under the input's file name.
+/// Files are taken in path order so the assignment does not depend on `--sort` or on
+/// scan order. The first file keeps its name; with [`FlattenCollisions::Suffix`] later
+/// files with the same name get a numeric suffix that is not used by any other output.
+/// Names are compared case-insensitively, since they would still collide on
+/// case-insensitive filesystems.
+///
+/// # Arguments
+///
+/// * `output_path` - Output directory all files are written into
+/// * `files` - Input files of the run
+/// * `collisions` - Whether duplicate names are numbered or rejected
+/// * `normalization` - Optional `--output-ext-normalize` mode, applied before names are
+///   compared
+/// * `svgz` - Whether `.svg` inputs are written as `.svgz` (`--svgz`), also applied before
+///   names are compared
+///
+/// # Returns
+///
+/// Returns a map from each input path to its output path.
+///
+/// # Errors
+///
+/// Returns an error if a file has no file name, or if two files share a name and
+/// `collisions` is [`FlattenCollisions::Error`]
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::{Path, PathBuf};
+/// use image_optimizer::cli::FlattenCollisions;
+/// use image_optimizer::file_ops::plan_flat_outputs;
+///
+/// # fn example() -> anyhow::Result<()> {
+/// let files = [PathBuf::from("photos/a/logo.png"), PathBuf::from("photos/b/logo.png")];
+/// let outputs = plan_flat_outputs(Path::new("out"), &files, FlattenCollisions::Suffix, None, false)?;
+/// assert_eq!(outputs[&files[0]], Path::new("out/logo.png"));
+/// assert_eq!(outputs[&files[1]], Path::new("out/logo-1.png"));
+/// # Ok(())
+/// # }
+/// ```
+pub fn plan_flat_outputs(
+    output_path: &Path,
+    files: &[PathBuf],
+    collisions: FlattenCollisions,
+    normalization: Option<ExtensionNormalization>,
+    svgz: bool,
+) -> Result<HashMap<PathBuf, PathBuf>> {
+    let mut sorted: Vec<&PathBuf> = files.iter().collect();
+    sorted.sort();
+    sorted.dedup();
+
+    let mut first_owner: HashMap<OsString, &PathBuf> = HashMap::new();
+    let mut names = Vec::with_capacity(sorted.len());
+    for file in sorted {
+        let name = file
+            .file_name()
+            .ok_or_else(|| anyhow::anyhow!("{} has no file name", file.display()))?;
+        let mut name = normalization.map_or_else(
+            || name.to_os_string(),
+            |normalization| normalize_extension(Path::new(name), normalization).into_os_string(),
+        );
+        if svgz {
+            name = svgz_output_path(Path::new(&name)).into_os_string();
+        }
+        let key = collision_key(&name);
+        if let Some(owner) = first_owner.get(&key) {
+            if collisions == FlattenCollisions::Error {
+                return Err(anyhow::anyhow!(
+                    "--flatten would write both {} and {} to {}; rename one of them or use \
+                     --flatten-collisions suffix",
+                    owner.display(),
+                    file.display(),
+                    output_path.join(&name).display()
+                ));
+            }
+            names.push((file, name, true));
+        } else {
+            first_owner.insert(key, file);
+            names.push((file, name, false));
+        }
+    }
+
+    let mut taken: HashSet<OsString> = first_owner.into_keys().collect();
+    let mut outputs = HashMap::with_capacity(names.len());
+    for (file, name, duplicate) in names {
+        let name = if duplicate {
+            numbered_name(&name, &mut taken)
+        } else {
+            name
+        };
+        outputs.insert(file.clone(), output_path.join(name));
+    }
+    Ok(outputs)
+}
+
+/// Rewrites the extension of an output path for `--output-ext-normalize`.
+///
+/// The extension is lowercased; [`ExtensionNormalization::Canonical`] also replaces the
+/// extension of a known image format with its canonical spelling (`.jpeg` becomes
+/// `.jpg`); `.svgz` is kept, since its contents are compressed. Paths without an
+/// extension are returned unchanged.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use image_optimizer::cli::ExtensionNormalization;
+/// use image_optimizer::file_ops::normalize_extension;
+///
+/// let path = Path::new("out/Photo.JPEG");
+/// assert_eq!(
+///     normalize_extension(path, ExtensionNormalization::Lowercase),
+///     Path::new("out/Photo.jpeg")
+/// );
+/// assert_eq!(
+///     normalize_extension(path, ExtensionNormalization::Canonical),
+///     Path::new("out/Photo.jpg")
+/// );
+/// ```
+#[must_use]
+pub fn normalize_extension(path: &Path, normalization: ExtensionNormalization) -> PathBuf {
+    let Some(extension) = path.extension() else {
+        return path.to_path_buf();
+    };
+    let lowercase = extension.to_string_lossy().to_lowercase();
+    let extension = match normalization {
+        ExtensionNormalization::Lowercase => lowercase,
+        ExtensionNormalization::Canonical if lowercase == SVGZ_EXTENSION => lowercase,
+        ExtensionNormalization::Canonical => ImageFormat::from_extension(&lowercase)
+            .map_or(lowercase, |format| format.extension().to_string()),
+    };
+    path.with_extension(extension)
+}
+
+/// Rewrites the path of an uncompressed SVG output to `.svgz` for `--svgz`.
+///
+/// Paths of other formats, and paths that already end in `.svgz`, are returned unchanged.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use image_optimizer::file_ops::svgz_output_path;
+///
+/// assert_eq!(svgz_output_path(Path::new("out/Logo.SVG")), Path::new("out/Logo.svgz"));
+/// assert_eq!(svgz_output_path(Path::new("out/photo.png")), Path::new("out/photo.png"));
+/// ```
+#[must_use]
+pub fn svgz_output_path(path: &Path) -> PathBuf {
+    if ImageFormat::from_path(path) == Some(ImageFormat::Svg) && !is_svgz_path(path) {
+        path.with_extension(SVGZ_EXTENSION)
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// Renames an output path according to `--name-template`.
+///
+/// The stem and extension come from `output_path`, so names already made unique by
+/// `--flatten` stay unique; `{parent}` is the name of the directory containing
+/// `input_path`. The directory of `output_path` is kept.
+///
+/// # Arguments
+///
+/// * `output_path` - Output path computed by mirroring or flattening
+/// * `input_path` - Path of the input file
+/// * `template` - Validated file name template
+/// * `dimensions` - Pixel size of the written image, if known
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use image_optimizer::file_ops::{NameTemplate, apply_name_template};
+///
+/// let template: NameTemplate = "{parent}-{stem}.min.{ext}".parse().unwrap();
+/// assert_eq!(
+///     apply_name_template(Path::new("out/icons/logo.png"), Path::new("in/icons/logo.png"), &template, None),
+///     Path::new("out/icons/icons-logo.min.png")
+/// );
+/// ```
+#[must_use]
+pub fn apply_name_template(
+    output_path: &Path,
+    input_path: &Path,
+    template: &NameTemplate,
+    dimensions: Option<(u32, u32)>,
+) -> PathBuf {
+    let text =
+        |part: Option<&std::ffi::OsStr>| part.unwrap_or_default().to_string_lossy().into_owned();
+    let parent = input_path
+        .parent()
+        .and_then(|parent| {
+            parent
+                .canonicalize()
+                .ok()
+                .or_else(|| Some(parent.to_path_buf()))
+        })
+        .and_then(|parent| parent.file_name().map(std::ffi::OsStr::to_os_string));
+    output_path.with_file_name(template.render(
+        &text(output_path.file_stem()),
+        &text(output_path.extension()),
+        &text(parent.as_deref()),
+        dimensions,
+    ))
+}
+
+/// Returns the first `stem-N.ext` name not in `taken` and marks it as taken.
+fn numbered_name(name: &std::ffi::OsStr, taken: &mut HashSet<OsString>) -> OsString {
+    let name = Path::new(name);
+    let mut n = 1_u64;
+    loop {
+        let mut candidate = name.file_stem().unwrap_or_default().to_os_string();
+        candidate.push(format!("-{n}"));
+        if let Some(extension) = name.extension() {
+            candidate.push(".");
+            candidate.push(extension);
+        }
+        if taken.insert(collision_key(&candidate)) {
+            return candidate;
+        }
+        n += 1;
+    }
+}
+
+/// Key under which two file names collide on a case-insensitive filesystem.
+///
+/// Names that are not valid UTF-8 are only folded in their ASCII letters, so distinct
+/// names never share a key through lossy conversion.
+fn collision_key(name: &std::ffi::OsStr) -> OsString {
+    name.to_str().map_or_else(
+        || name.to_ascii_lowercase(),
+        |name| OsString::from(name.to_lowercase()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mirrors_input_structure() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_output_mirror_test");
+        let input = temp_dir.join("in");
+        let output = temp_dir.join("out");
+        let output_file =
+            ensure_output_dir(&output, &input, &input.join("nested/photo.jpg")).unwrap();
+
+        assert_eq!(output_file, output.join("nested/photo.jpg"));
+        assert!(output.join("nested").is_dir());
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_single_file_input_uses_file_name() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_output_single_file_test");
+        let input = temp_dir.join("photos/photo.jpg");
+        let output = temp_dir.join("out");
+        let output_file = ensure_output_dir(&output, &input, &input).unwrap();
+
+        assert_eq!(output_file, output.join("photo.jpg"));
+        assert!(output.is_dir());
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_single_file_output_into_its_own_directory_is_rejected() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_output_same_file_test");
+        let dir = temp_dir.join("dir");
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("photo.jpg");
+        std::fs::write(&input, b"jpeg").unwrap();
+
+        let error = ensure_output_dir(&dir, &input, &input).unwrap_err();
+
+        assert!(error.to_string().contains("is the input file itself"));
+        assert_eq!(std::fs::read(&input).unwrap(), b"jpeg");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_flatten_without_collisions() {
+        let output = Path::new("out");
+        let files = [
+            PathBuf::from("in/a/one.png"),
+            PathBuf::from("in/b/c/two.jpg"),
+        ];
+        let outputs =
+            plan_flat_outputs(output, &files, FlattenCollisions::Error, None, false).unwrap();
+
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(outputs[&files[0]], output.join("one.png"));
+        assert_eq!(outputs[&files[1]], output.join("two.jpg"));
+    }
+
+    #[test]
+    fn test_flatten_numbers_colliding_names() {
+        let output = Path::new("out");
+        // Given out of path order, and with a real file already named like a suffix
+        let files = [
+            PathBuf::from("in/c/Logo.PNG"),
+            PathBuf::from("in/a/logo.png"),
+            PathBuf::from("in/logo-1.png"),
+            PathBuf::from("in/b/logo.png"),
+        ];
+        let outputs =
+            plan_flat_outputs(output, &files, FlattenCollisions::Suffix, None, false).unwrap();
+
+        assert_eq!(outputs[&files[1]], output.join("logo.png"));
+        assert_eq!(outputs[&files[2]], output.join("logo-1.png"));
+        assert_eq!(outputs[&files[3]], output.join("logo-2.png"));
+        assert_eq!(outputs[&files[0]], output.join("Logo-3.PNG"));
+    }
+
+    #[test]
+    fn test_normalize_extension() {
+        let canonical = ExtensionNormalization::Canonical;
+        let lowercase = ExtensionNormalization::Lowercase;
+        assert_eq!(
+            normalize_extension(Path::new("out/Photo.JPEG"), canonical),
+            Path::new("out/Photo.jpg")
+        );
+        assert_eq!(
+            normalize_extension(Path::new("out/Photo.JPEG"), lowercase),
+            Path::new("out/Photo.jpeg")
+        );
+        assert_eq!(
+            normalize_extension(Path::new("a.b.PNG"), canonical),
+            Path::new("a.b.png")
+        );
+        assert_eq!(
+            normalize_extension(Path::new("README"), canonical),
+            Path::new("README")
+        );
+        assert_eq!(
+            normalize_extension(Path::new("Logo.SVGZ"), canonical),
+            Path::new("Logo.svgz")
+        );
+    }
+
+    #[test]
+    fn test_flatten_compares_normalized_names() {
+        let files = [
+            PathBuf::from("in/a/photo.jpeg"),
+            PathBuf::from("in/b/photo.JPG"),
+        ];
+        let outputs = plan_flat_outputs(
+            Path::new("out"),
+            &files,
+            FlattenCollisions::Suffix,
+            Some(ExtensionNormalization::Canonical),
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(outputs[&files[0]], Path::new("out/photo.jpg"));
+        assert_eq!(outputs[&files[1]], Path::new("out/photo-1.jpg"));
+    }
+
+    #[test]
+    fn test_flatten_compares_svgz_names() {
+        let files = [
+            PathBuf::from("in/a/logo.svg"),
+            PathBuf::from("in/b/logo.svgz"),
+        ];
+        let outputs = plan_flat_outputs(
+            Path::new("out"),
+            &files,
+            FlattenCollisions::Suffix,
+            None,
+            true,
+        )
+        .unwrap();
+
+        assert_eq!(outputs[&files[0]], Path::new("out/logo.svgz"));
+        assert_eq!(outputs[&files[1]], Path::new("out/logo-1.svgz"));
+    }
+
+    #[test]
+    fn test_flatten_rejects_collisions_in_error_mode() {
+        let files = [
+            PathBuf::from("in/a/logo.png"),
+            PathBuf::from("in/b/logo.png"),
+        ];
+        let error = plan_flat_outputs(
+            Path::new("out"),
+            &files,
+            FlattenCollisions::Error,
+            None,
+            false,
+        )
+        .unwrap_err();
+
+        let message = error.to_string();
+        assert!(message.contains("in/a/logo.png"));
+        assert!(message.contains("in/b/logo.png"));
+    }
+}
@@ -0,0 +1,137 @@
+use std::path::{Path, PathBuf};
+
+/// Returns where the output directory lies inside the input directory, if it does.
+///
+/// Both paths are canonicalized, so `./images` and `images/` or a symlink to the same
+/// directory are recognized as equal. The output directory does not have to exist yet:
+/// its nearest existing ancestor is canonicalized and the remaining components appended.
+///
+/// # Arguments
+///
+/// * `input` - Input directory being scanned
+/// * `output` - Directory given with `--output`
+///
+/// # Returns
+///
+/// Returns the output directory relative to the input (an empty path when both are the
+/// same directory), or `None` when the output is outside the input or the input cannot
+/// be resolved.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use image_optimizer::file_ops::output_within_input;
+///
+/// let input = std::env::temp_dir();
+/// let nested = output_within_input(&input, &input.join("optimized"));
+/// assert_eq!(nested.as_deref(), Some(Path::new("optimized")));
+/// assert_eq!(output_within_input(&input, &input), Some(Path::new("").to_path_buf()));
+/// ```
+#[must_use]
+pub fn output_within_input(input: &Path, output: &Path) -> Option<PathBuf> {
+    let input = input.canonicalize().ok()?;
+    let output = canonicalize_existing_prefix(output)?;
+    output.strip_prefix(&input).ok().map(Path::to_path_buf)
+}
+
+/// Returns whether `output` names the existing file `input`, however it is spelled.
+///
+/// Both paths are canonicalized, so relative and absolute spellings, `.`/`..` components
+/// and symlinks are resolved. An output that does not exist yet cannot be the input.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_optimizer::file_ops::is_same_file;
+///
+/// let dir = std::env::temp_dir();
+/// assert!(is_same_file(&dir.join("."), &dir));
+/// assert!(!is_same_file(&dir.join("no-such-output.png"), &dir));
+/// ```
+#[must_use]
+pub fn is_same_file(output: &Path, input: &Path) -> bool {
+    output
+        .canonicalize()
+        .is_ok_and(|output| input.canonicalize().is_ok_and(|input| input == output))
+}
+
+/// Canonicalizes the longest existing prefix of `path` and appends the rest unchanged.
+fn canonicalize_existing_prefix(path: &Path) -> Option<PathBuf> {
+    let absolute = std::path::absolute(path).ok()?;
+    let mut missing = Vec::new();
+    let mut existing = absolute.as_path();
+    loop {
+        if let Ok(canonical) = existing.canonicalize() {
+            return Some(
+                missing
+                    .iter()
+                    .rev()
+                    .fold(canonical, |acc, part| acc.join(part)),
+            );
+        }
+        missing.push(existing.file_name()?);
+        existing = existing.parent()?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_identical_paths_with_different_spelling() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_nesting_same");
+        fs::create_dir_all(temp_dir.join("images")).unwrap();
+
+        let nested = output_within_input(
+            &temp_dir.join("images"),
+            &temp_dir.join("images/../images/."),
+        );
+        assert_eq!(nested, Some(PathBuf::new()));
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_output_file_spelled_differently_is_the_input() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_nesting_same_file");
+        fs::create_dir_all(temp_dir.join("images")).unwrap();
+        let input = temp_dir.join("images/a.png");
+        fs::write(&input, b"png").unwrap();
+
+        assert!(is_same_file(
+            &temp_dir.join("images/../images/a.png"),
+            &input
+        ));
+        assert!(!is_same_file(&temp_dir.join("out/a.png"), &input));
+        assert!(!is_same_file(&temp_dir.join("images/b.png"), &input));
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_missing_nested_output() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_nesting_nested");
+        fs::create_dir_all(&temp_dir).unwrap();
+
+        let nested = output_within_input(&temp_dir, &temp_dir.join("out/webp"));
+        assert_eq!(nested, Some(PathBuf::from("out/webp")));
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_sibling_output_is_not_nested() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_nesting_sibling");
+        fs::create_dir_all(temp_dir.join("images")).unwrap();
+
+        assert_eq!(
+            output_within_input(&temp_dir.join("images"), &temp_dir.join("images-out")),
+            None
+        );
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
@@ -0,0 +1,143 @@
+use std::fs::Metadata;
+use std::path::Path;
+
+/// Decides whether an existing output file should be left untouched.
+///
+/// In output-directory mode the destination may already exist from an earlier run.
+/// This check runs before any decoding or encoding so that no work is wasted on files
+/// that will not be written:
+///
+/// - With `force`, existing outputs are always overwritten
+/// - With `skip_existing`, any existing output is kept
+/// - Otherwise, an existing output is kept when it is at least as new as the input,
+///   so newer outputs are never clobbered without `--force`
+///
+/// # Arguments
+///
+/// * `output_path` - Destination path of the optimized file
+/// * `input_metadata` - Metadata of the input file
+/// * `skip_existing` - Keep any existing output (`--skip-existing`)
+/// * `force` - Always overwrite existing outputs (`--force`)
+///
+/// # Returns
+///
+/// Returns `true` if the output already exists and must not be overwritten.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use image_optimizer::file_ops::should_keep_existing_output;
+///
+/// # fn example() -> std::io::Result<()> {
+/// let input_metadata = std::fs::metadata("photos/image.jpg")?;
+/// let keep = should_keep_existing_output(
+///     Path::new("optimized/image.jpg"),
+///     &input_metadata,
+///     false,
+///     false,
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[must_use]
+pub fn should_keep_existing_output(
+    output_path: &Path,
+    input_metadata: &Metadata,
+    skip_existing: bool,
+    force: bool,
+) -> bool {
+    if force {
+        return false;
+    }
+
+    let Ok(output_metadata) = std::fs::metadata(output_path) else {
+        return false;
+    };
+
+    if skip_existing {
+        return true;
+    }
+
+    match (output_metadata.modified(), input_metadata.modified()) {
+        (Ok(output_modified), Ok(input_modified)) => output_modified >= input_modified,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use filetime::FileTime;
+    use std::fs;
+
+    fn setup(name: &str, input_time: i64, output_time: i64) -> (std::path::PathBuf, Metadata) {
+        let temp_dir = std::env::temp_dir().join(name);
+        fs::create_dir_all(&temp_dir).unwrap();
+        let input = temp_dir.join("input.jpg");
+        let output = temp_dir.join("output.jpg");
+        fs::write(&input, "input").unwrap();
+        fs::write(&output, "output").unwrap();
+        filetime::set_file_mtime(&input, FileTime::from_unix_time(input_time, 0)).unwrap();
+        filetime::set_file_mtime(&output, FileTime::from_unix_time(output_time, 0)).unwrap();
+        (temp_dir, fs::metadata(&input).unwrap())
+    }
+
+    #[test]
+    fn test_missing_output_is_written() {
+        let (temp_dir, metadata) = setup("image_optimizer_guard_missing", 1_000, 2_000);
+        let missing = temp_dir.join("missing.jpg");
+
+        assert!(!should_keep_existing_output(
+            &missing, &metadata, true, false
+        ));
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_skip_existing_keeps_older_output() {
+        let (temp_dir, metadata) = setup("image_optimizer_guard_skip", 2_000, 1_000);
+        let output = temp_dir.join("output.jpg");
+
+        assert!(should_keep_existing_output(&output, &metadata, true, false));
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_force_overwrites_newer_output() {
+        let (temp_dir, metadata) = setup("image_optimizer_guard_force", 1_000, 2_000);
+        let output = temp_dir.join("output.jpg");
+
+        assert!(!should_keep_existing_output(
+            &output, &metadata, false, true
+        ));
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_default_keeps_newer_output() {
+        let (temp_dir, metadata) = setup("image_optimizer_guard_default_newer", 1_000, 2_000);
+        let output = temp_dir.join("output.jpg");
+
+        assert!(should_keep_existing_output(
+            &output, &metadata, false, false
+        ));
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_default_overwrites_stale_output() {
+        let (temp_dir, metadata) = setup("image_optimizer_guard_default_stale", 2_000, 1_000);
+        let output = temp_dir.join("output.jpg");
+
+        assert!(!should_keep_existing_output(
+            &output, &metadata, false, false
+        ));
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
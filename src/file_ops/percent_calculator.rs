@@ -0,0 +1,49 @@
+/// Computes the relative size change from `original` to `new` as a percentage.
+///
+/// Negative values mean the size decreased (bytes were saved). An empty original has no
+/// meaningful ratio, so 0.0 is returned instead of dividing by zero.
+///
+/// # Arguments
+///
+/// * `original` - Size before optimization in bytes
+/// * `new` - Size after optimization in bytes
+///
+/// # Returns
+///
+/// Returns the change as a percentage of the original size.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_optimizer::file_ops::percent_change;
+///
+/// assert_eq!(percent_change(1000, 920), -8.0);
+/// assert_eq!(percent_change(1000, 1100), 10.0);
+/// assert_eq!(percent_change(0, 100), 0.0);
+/// ```
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn percent_change(original: u64, new: u64) -> f64 {
+    if original == 0 {
+        return 0.0;
+    }
+    (new as f64 - original as f64) / original as f64 * 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percent_change() {
+        assert!((percent_change(200, 100) + 50.0).abs() < f64::EPSILON);
+        assert!((percent_change(100, 150) - 50.0).abs() < f64::EPSILON);
+        assert!(percent_change(100, 100).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_percent_change_zero_original() {
+        assert!(percent_change(0, 0).abs() < f64::EPSILON);
+        assert!(percent_change(0, 1).abs() < f64::EPSILON);
+    }
+}
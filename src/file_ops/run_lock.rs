@@ -0,0 +1,216 @@
+use anyhow::{Context, Result, bail};
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Advisory lock that keeps two runs from optimizing the same tree at once.
+///
+/// Two runs over the same files would race on the same temporary files and renames. The
+/// lock is an operating system file lock on a lock file (by default
+/// `.image-optimizer.lock` in the input directory), so it is released even when a run
+/// crashes; the lock file such a run leaves behind is simply taken over by the next one.
+/// The lock file records the process ID of its holder and is removed when the lock is
+/// dropped.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::path::Path;
+/// use image_optimizer::file_ops::RunLock;
+///
+/// # fn example() -> anyhow::Result<()> {
+/// let lock = RunLock::acquire_for_input(Path::new("photos"), false)?;
+/// // ... optimize photos/ ...
+/// drop(lock);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct RunLock {
+    file: File,
+    path: PathBuf,
+}
+
+impl RunLock {
+    /// File name of the lock file created in the input directory.
+    pub const FILE_NAME: &str = ".image-optimizer.lock";
+
+    /// Returns the default lock file of a run over `input`: inside the input directory,
+    /// or next to a single input file.
+    #[must_use]
+    pub fn default_path(input: &Path) -> PathBuf {
+        let dir = if input.is_dir() {
+            input
+        } else {
+            input
+                .parent()
+                .filter(|parent| !parent.as_os_str().is_empty())
+                .unwrap_or_else(|| Path::new("."))
+        };
+        dir.join(Self::FILE_NAME)
+    }
+
+    /// Acquires the default lock of a run over `input`.
+    ///
+    /// When the input directory is not writable (for example a read-only tree optimized
+    /// into an output directory), the lock file is created in the system temp directory
+    /// instead, named after the input directory so runs over the same tree still meet.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`RunLock::acquire`].
+    pub fn acquire_for_input(input: &Path, wait: bool) -> Result<Self> {
+        let path = Self::default_path(input);
+        match Self::acquire(&path, wait) {
+            Err(e) if is_unwritable_location(&e) => {
+                let dir = path.parent().unwrap_or_else(|| Path::new("."));
+                let dir = fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+                let hash = blake3::hash(dir.as_os_str().as_encoded_bytes()).to_hex();
+                let fallback =
+                    std::env::temp_dir().join(format!("image-optimizer-{}.lock", &hash[..16]));
+                Self::acquire(&fallback, wait)
+            }
+            result => result,
+        }
+    }
+
+    /// Acquires the lock at `path`, creating the lock file if needed.
+    ///
+    /// If another run holds the lock, this fails immediately, or with `wait` blocks until
+    /// the other run releases it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if another run holds the lock and `wait` is not set, or if the
+    /// lock file cannot be created, locked or written.
+    pub fn acquire(path: &Path, wait: bool) -> Result<Self> {
+        loop {
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(path)
+                .with_context(|| format!("Failed to create lock file {}", path.display()))?;
+            match file.try_lock() {
+                Ok(()) => {}
+                Err(TryLockError::WouldBlock) if wait => {
+                    eprintln!("Waiting for {} to be released...", path.display());
+                    file.lock()
+                        .with_context(|| format!("Failed to lock {}", path.display()))?;
+                }
+                Err(TryLockError::WouldBlock) => {
+                    let holder = fs::read_to_string(path).unwrap_or_default();
+                    bail!(
+                        "Another run (process {}) holds {}; pass --wait to wait for it",
+                        holder.trim(),
+                        path.display()
+                    );
+                }
+                Err(TryLockError::Error(e)) => {
+                    return Err(e).with_context(|| format!("Failed to lock {}", path.display()));
+                }
+            }
+
+            // The previous holder removes the lock file on release, possibly after it
+            // was opened here: lock the file that is now at the path instead
+            if !is_same_file(&file, path) {
+                continue;
+            }
+            file.set_len(0)
+                .and_then(|()| writeln!(&file, "{}", std::process::id()))
+                .with_context(|| format!("Failed to write lock file {}", path.display()))?;
+            return Ok(Self {
+                file,
+                path: path.to_path_buf(),
+            });
+        }
+    }
+
+    /// Returns the path of the lock file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for RunLock {
+    fn drop(&mut self) {
+        // Removed while still locked, so no other run locks the file being removed
+        let _ = fs::remove_file(&self.path);
+        let _ = self.file.unlock();
+    }
+}
+
+#[cfg(unix)]
+fn is_same_file(file: &File, path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    match (file.metadata(), fs::metadata(path)) {
+        (Ok(locked), Ok(current)) => locked.dev() == current.dev() && locked.ino() == current.ino(),
+        _ => false,
+    }
+}
+
+/// Open files cannot be removed on other platforms, so the file is still the lock file.
+#[cfg(not(unix))]
+fn is_same_file(_file: &File, path: &Path) -> bool {
+    path.exists()
+}
+
+/// Returns `true` if the lock file could not be created because its directory is not
+/// writable.
+fn is_unwritable_location(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<io::Error>().is_some_and(|e| {
+        matches!(
+            e.kind(),
+            io::ErrorKind::PermissionDenied | io::ErrorKind::ReadOnlyFilesystem
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_second_lock_fails_until_released() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_run_lock");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let path = RunLock::default_path(&temp_dir);
+
+        let lock = RunLock::acquire(&path, false).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap().trim(),
+            std::process::id().to_string()
+        );
+        let error = RunLock::acquire(&path, false).unwrap_err();
+        assert!(error.to_string().contains("--wait"));
+
+        drop(lock);
+        assert!(!path.exists());
+        RunLock::acquire(&path, false).unwrap();
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_stale_lock_file_is_taken_over() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_run_lock_stale");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let path = temp_dir.join("custom.lock");
+        // Left behind by a crashed run: the file exists but nobody locks it
+        fs::write(&path, "999999\n").unwrap();
+
+        let lock = RunLock::acquire(&path, false).unwrap();
+
+        assert_eq!(lock.path(), path);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap().trim(),
+            std::process::id().to_string()
+        );
+
+        drop(lock);
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
@@ -0,0 +1,99 @@
+use std::path::PathBuf;
+
+/// Picks `count` files at random for `--sample`, keeping their original order.
+///
+/// The selection depends only on the files and `seed`, so passing the same `--seed`
+/// again reproduces it. Files keep the order they were given in (the `--sort` order of
+/// the scan), only the others are dropped.
+///
+/// # Arguments
+///
+/// * `files` - Candidate files
+/// * `count` - Number of files to keep
+/// * `seed` - Seed of the pseudo-random selection
+///
+/// # Returns
+///
+/// Returns `count` of the files, or all of them if there are not more than `count`.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::PathBuf;
+/// use image_optimizer::file_ops::sample_files;
+///
+/// let files: Vec<PathBuf> = (0..10).map(|i| PathBuf::from(format!("{i}.jpg"))).collect();
+/// let sample = sample_files(files.clone(), 3, 42);
+/// assert_eq!(sample.len(), 3);
+/// assert_eq!(sample, sample_files(files, 3, 42));
+/// ```
+#[must_use]
+pub fn sample_files(files: Vec<PathBuf>, count: usize, seed: u64) -> Vec<PathBuf> {
+    if files.len() <= count {
+        return files;
+    }
+
+    // Partial Fisher-Yates shuffle of the indices, driven by SplitMix64
+    let mut state = seed;
+    let mut next = || {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    };
+    let mut indices: Vec<usize> = (0..files.len()).collect();
+    for i in 0..count {
+        let remaining = (indices.len() - i) as u64;
+        // The remainder is below the number of indices, which fits in a usize
+        let offset = usize::try_from(next() % remaining).unwrap_or(0);
+        indices.swap(i, i + offset);
+    }
+    let mut selected = indices[..count].to_vec();
+    selected.sort_unstable();
+
+    let mut files: Vec<Option<PathBuf>> = files.into_iter().map(Some).collect();
+    selected
+        .into_iter()
+        .filter_map(|index| files[index].take())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(count: usize) -> Vec<PathBuf> {
+        (0..count)
+            .map(|i| PathBuf::from(format!("image-{i:03}.png")))
+            .collect()
+    }
+
+    #[test]
+    fn test_selects_exactly_count_files() {
+        let sample = sample_files(files(100), 7, 1);
+        assert_eq!(sample.len(), 7);
+        let mut unique = sample.clone();
+        unique.dedup();
+        assert_eq!(unique.len(), 7);
+        assert!(sample.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_fewer_files_than_count_keeps_all() {
+        assert_eq!(sample_files(files(3), 10, 1), files(3));
+        assert_eq!(sample_files(files(3), 3, 1), files(3));
+    }
+
+    #[test]
+    fn test_fixed_seed_is_stable() {
+        assert_eq!(
+            sample_files(files(50), 5, 1234),
+            sample_files(files(50), 5, 1234)
+        );
+        assert_ne!(
+            sample_files(files(50), 5, 1234),
+            sample_files(files(50), 5, 4321)
+        );
+    }
+}
@@ -0,0 +1,183 @@
+use anyhow::Result;
+use globset::GlobSet;
+use std::ffi::{OsStr, OsString};
+use std::time::SystemTime;
+
+use super::{build_glob_set, output_within_input};
+use crate::cli::{Cli, SortOrder};
+
+/// Options controlling which files [`scan_images`](super::scan_images) discovers.
+///
+/// The default options scan only the top level of a directory without any filters.
+#[derive(Debug, Clone, Default)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct ScanOptions {
+    /// Recursively scan subdirectories.
+    pub recursive: bool,
+    /// Deepest directory level a recursive scan descends to (1 = only the top level).
+    /// `None` scans without a limit.
+    pub max_depth: Option<usize>,
+    /// Follow symbolic links to files and directories. Cyclic links are detected and
+    /// reported as warnings instead of being walked forever.
+    pub follow_symlinks: bool,
+    /// The images are rewritten in place, so followed links whose target lies outside
+    /// the scanned directory are set aside with a warning rather than returned.
+    pub in_place: bool,
+    /// If set, only files whose relative path matches one of these globs are returned.
+    pub include: Option<GlobSet>,
+    /// Files and directories whose relative path matches one of these globs are skipped.
+    /// Excludes win over includes, and excluded directories are not descended into.
+    pub exclude: Option<GlobSet>,
+    /// Directories with exactly one of these names are pruned at any depth without being
+    /// descended into (`--exclude-dir`), without matching a glob against every path.
+    pub exclude_dirs: Vec<OsString>,
+    /// Skip files ignored by `.gitignore`, `.ignore` and global git excludes.
+    pub respect_gitignore: bool,
+    /// Fail on the first unreadable entry instead of collecting a warning.
+    pub strict: bool,
+    /// Order of the returned images.
+    pub sort: SortOrder,
+    /// Files smaller than this many bytes are set aside instead of being returned.
+    pub min_size: Option<u64>,
+    /// Files last modified at or before this point are skipped (`--modified-since`).
+    pub modified_since: Option<SystemTime>,
+}
+
+impl ScanOptions {
+    /// Builds scan options from the parsed command-line arguments.
+    ///
+    /// An `--output`, `--backup-dir` or `--compare-out` directory inside the input
+    /// directory is added to the excludes, so recursive runs do not pick up previously
+    /// written outputs or backups.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any `--include` or `--exclude` pattern is not a valid glob.
+    pub fn from_cli(args: &Cli) -> Result<Self> {
+        Ok(Self {
+            recursive: args.recursive || args.max_depth.is_some(),
+            max_depth: args.max_depth.map(std::num::NonZeroUsize::get),
+            follow_symlinks: args.follow_symlinks,
+            in_place: args.is_in_place(),
+            include: build_glob_set(&args.include)?,
+            exclude: build_glob_set(&excludes_with_output(args))?,
+            exclude_dirs: args.exclude_dir.iter().map(OsString::from).collect(),
+            respect_gitignore: args.respect_gitignore,
+            strict: args.strict_scan,
+            sort: args.sort,
+            min_size: args.min_size,
+            modified_since: args.modified_since,
+        })
+    }
+
+    /// Returns whether a directory named `name` is pruned by `--exclude-dir`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::ffi::OsStr;
+    /// use image_optimizer::file_ops::ScanOptions;
+    ///
+    /// let options = ScanOptions {
+    ///     exclude_dirs: vec!["node_modules".into()],
+    ///     ..ScanOptions::default()
+    /// };
+    /// assert!(options.is_excluded_dir(OsStr::new("node_modules")));
+    /// assert!(!options.is_excluded_dir(OsStr::new("photos")));
+    /// ```
+    #[must_use]
+    pub fn is_excluded_dir(&self, name: &OsStr) -> bool {
+        self.exclude_dirs.iter().any(|excluded| excluded == name)
+    }
+
+    /// Returns the deepest directory level to scan, or `None` for no limit.
+    ///
+    /// Non-recursive scans only look at the top level.
+    #[must_use]
+    pub const fn depth_limit(&self) -> Option<usize> {
+        if self.recursive {
+            self.max_depth
+        } else {
+            Some(1)
+        }
+    }
+}
+
+/// The `--exclude` patterns plus the directories the run writes to (the output,
+/// `--backup-dir` and `--compare-out` directories) when they lie inside the input.
+fn excludes_with_output(args: &Cli) -> Vec<String> {
+    let mut excludes = args.exclude.clone();
+    let Some(input) = args.input.as_deref() else {
+        return excludes;
+    };
+    for output in [
+        args.output.as_deref(),
+        args.backup_dir.as_deref(),
+        args.compare_out.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    {
+        if let Some(relative) = output_within_input(input, output)
+            && let Some(relative) = relative.to_str()
+            && !relative.is_empty()
+        {
+            excludes.push(globset::escape(relative));
+        }
+    }
+    excludes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use std::path::Path;
+
+    #[test]
+    fn test_max_depth_implies_recursion() {
+        let args = Cli::parse_from(["image-optimizer", "--max-depth", "2"]);
+        let options = ScanOptions::from_cli(&args).unwrap();
+        assert!(options.recursive);
+        assert_eq!(options.depth_limit(), Some(2));
+
+        let args = Cli::parse_from(["image-optimizer", "--recursive"]);
+        assert_eq!(ScanOptions::from_cli(&args).unwrap().depth_limit(), None);
+
+        let args = Cli::parse_from(["image-optimizer"]);
+        assert_eq!(ScanOptions::from_cli(&args).unwrap().depth_limit(), Some(1));
+    }
+
+    #[test]
+    fn test_nested_output_is_excluded() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_scan_options_nested");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+
+        let args = Cli::parse_from([
+            "image-optimizer",
+            "-i",
+            temp_dir.to_str().unwrap(),
+            "-o",
+            temp_dir.join("optimized").to_str().unwrap(),
+            "--compare-out",
+            temp_dir.join("compare").to_str().unwrap(),
+        ]);
+        let exclude = ScanOptions::from_cli(&args).unwrap().exclude.unwrap();
+        assert!(exclude.is_match(Path::new("optimized")));
+        assert!(exclude.is_match(Path::new("compare")));
+        assert!(!exclude.is_match(Path::new("backups")));
+
+        let args = Cli::parse_from([
+            "image-optimizer",
+            "-i",
+            temp_dir.to_str().unwrap(),
+            "--backup-dir",
+            temp_dir.join("./backups").to_str().unwrap(),
+        ]);
+        let exclude = ScanOptions::from_cli(&args).unwrap().exclude.unwrap();
+        assert!(exclude.is_match(Path::new("backups")));
+        assert!(!exclude.is_match(Path::new("photos")));
+
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
@@ -0,0 +1,16 @@
+use std::path::PathBuf;
+
+/// Result of scanning an input path for images.
+///
+/// Traversal problems such as permission-denied directories do not abort a scan by
+/// default; they are collected as warnings so the caller can report them while still
+/// processing every image that could be discovered.
+#[derive(Debug, Default)]
+pub struct ScanOutcome {
+    /// Discovered image files with supported formats.
+    pub images: Vec<PathBuf>,
+    /// Human-readable descriptions of entries that could not be read.
+    pub warnings: Vec<String>,
+    /// Supported images skipped because they are smaller than `--min-size`.
+    pub too_small: Vec<PathBuf>,
+}
@@ -0,0 +1,77 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::create_unique_temp;
+
+/// A scratch file in the system temp directory that is removed when dropped.
+///
+/// The file is created with a random name by [`create_unique_temp`], so concurrent
+/// users in one process never share it and a file or symlink planted in the shared temp
+/// directory is never written through. Dropping the guard removes the file, also when an
+/// encoder returns early or panics.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_optimizer::file_ops::TempFile;
+///
+/// # fn example() -> std::io::Result<()> {
+/// let temp_file = TempFile::new("image-optimizer-example", "png")?;
+/// std::fs::write(temp_file.path(), b"data")?;
+/// let path = temp_file.path().to_path_buf();
+/// drop(temp_file);
+/// assert!(!path.exists());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct TempFile {
+    path: PathBuf,
+}
+
+impl TempFile {
+    /// Creates a new, empty temp file named after `prefix` with the given extension.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be created.
+    pub fn new(prefix: &str, extension: &str) -> io::Result<Self> {
+        create_unique_temp(prefix, extension).map(|path| Self { path })
+    }
+
+    /// Returns the path of the temp file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_temp_files_are_unique_and_removed_on_drop() {
+        let first = TempFile::new("image-optimizer-test", "png").unwrap();
+        let second = TempFile::new("image-optimizer-test", "png").unwrap();
+
+        assert_ne!(first.path(), second.path());
+        assert!(first.path().starts_with(std::env::temp_dir()));
+        assert_eq!(
+            first.path().extension().and_then(|ext| ext.to_str()),
+            Some("png")
+        );
+
+        let path = first.path().to_path_buf();
+        drop(first);
+        assert!(!path.exists());
+        assert!(second.path().exists());
+    }
+}
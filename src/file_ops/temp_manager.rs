@@ -0,0 +1,211 @@
+use anyhow::{Context, Result};
+use std::ffi::OsStr;
+use std::fs::OpenOptions;
+use std::hash::{BuildHasher, RandomState};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Counter mixed into the random suffix of temp file names in the system temp directory.
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Names tried in the system temp directory before giving up on existing files.
+const FALLBACK_ATTEMPTS: u32 = 16;
+
+/// Temporary files that have been created but not yet moved over or removed.
+static ACTIVE_TEMP_FILES: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Creates the temporary file used while optimizing an image in place.
+///
+/// The temporary file is preferably created next to the input (`image.tmp.jpg` for
+/// `image.jpg`) so the final rename stays on the same filesystem. If that is not
+/// possible, for example because the source directory is read-only, the file is created
+/// in the system temp directory instead and the caller is expected to move it back with
+/// [`move_file`](super::move_file), which handles the cross-device case. The fallback
+/// name has a random suffix and is never an existing file or symlink, since the shared
+/// temp directory is writable by other users.
+///
+/// The original extension is kept as the last extension so format-specific encoders
+/// can still infer the output format from the path.
+///
+/// The path is tracked until [`release_temp_path`] is called, so that
+/// [`remove_active_temp_files`] can clean it up if the process has to exit early.
+///
+/// # Arguments
+///
+/// * `input_path` - Path to the image being optimized in place
+///
+/// # Returns
+///
+/// Returns the path of the newly created, empty temporary file.
+///
+/// # Errors
+///
+/// Returns an error if the temporary file can be created neither next to the input
+/// nor in the system temp directory.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use image_optimizer::file_ops::create_temp_path;
+///
+/// # fn example() -> anyhow::Result<()> {
+/// let temp_path = create_temp_path(Path::new("photos/image.jpg"))?;
+/// // Usually "photos/image.tmp.jpg"
+/// # Ok(())
+/// # }
+/// ```
+pub fn create_temp_path(input_path: &Path) -> Result<PathBuf> {
+    let extension = input_path
+        .extension()
+        .and_then(OsStr::to_str)
+        .unwrap_or("jpg");
+
+    let sibling = input_path.with_extension(format!("tmp.{extension}"));
+    if OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&sibling)
+        .is_ok()
+    {
+        track_temp_path(&sibling);
+        return Ok(sibling);
+    }
+
+    let fallback = create_unique_temp("image-optimizer", &format!("tmp.{extension}"))
+        .with_context(|| {
+            format!(
+                "Failed to create temporary file for {}",
+                input_path.display()
+            )
+        })?;
+
+    track_temp_path(&fallback);
+    Ok(fallback)
+}
+
+/// Creates a new, empty file with a random name in the system temp directory, e.g.
+/// `image-optimizer-stdin-4242-1f3a5c7e9b2d4f60.png` for the prefix
+/// `image-optimizer-stdin` and the extension `png`.
+///
+/// The shared temp directory is writable by other users, so the name is never an
+/// existing file or symlink: the file is created with `create_new`, and another random
+/// name is tried if it exists. The file is not tracked or removed; see
+/// [`TempFile`](super::TempFile) for a file that is removed when dropped.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be created, or if every name tried exists.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_optimizer::file_ops::create_unique_temp;
+///
+/// # fn example() -> std::io::Result<()> {
+/// let path = create_unique_temp("image-optimizer-example", "png")?;
+/// assert!(path.starts_with(std::env::temp_dir()));
+/// std::fs::remove_file(path)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn create_unique_temp(prefix: &str, extension: &str) -> io::Result<PathBuf> {
+    let mut attempt = 1;
+    loop {
+        let suffix = RandomState::new().hash_one(TEMP_COUNTER.fetch_add(1, Ordering::Relaxed));
+        let path = std::env::temp_dir().join(format!(
+            "{prefix}-{}-{suffix:016x}.{extension}",
+            std::process::id()
+        ));
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(_) => return Ok(path),
+            Err(error)
+                if error.kind() == io::ErrorKind::AlreadyExists && attempt < FALLBACK_ATTEMPTS =>
+            {
+                attempt += 1;
+            }
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+fn track_temp_path(path: &Path) {
+    if let Ok(mut active) = ACTIVE_TEMP_FILES.lock() {
+        active.push(path.to_path_buf());
+    }
+}
+
+/// Stops tracking a temporary file once it has been moved over the original or removed.
+pub fn release_temp_path(path: &Path) {
+    if let Ok(mut active) = ACTIVE_TEMP_FILES.lock() {
+        active.retain(|active_path| active_path != path);
+    }
+}
+
+/// Removes every temporary file that is still being written.
+///
+/// Used when the process exits without waiting for in-flight optimizations, so no
+/// half-written `.tmp.<ext>` files are left next to the originals.
+pub fn remove_active_temp_files() {
+    if let Ok(mut active) = ACTIVE_TEMP_FILES.lock() {
+        for path in active.drain(..) {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_temp_path_next_to_input() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_temp_sibling");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let input = temp_dir.join("image.png");
+
+        let temp_path = create_temp_path(&input).unwrap();
+
+        assert_eq!(temp_path, temp_dir.join("image.tmp.png"));
+        assert!(temp_path.exists());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_temp_path_falls_back_to_system_temp() {
+        let input = Path::new("/nonexistent/unwritable/image.png");
+
+        let temp_path = create_temp_path(input).unwrap();
+
+        assert!(temp_path.starts_with(std::env::temp_dir()));
+        assert_eq!(temp_path.extension().and_then(OsStr::to_str), Some("png"));
+        assert!(temp_path.exists());
+
+        let second = create_temp_path(input).unwrap();
+        assert_ne!(second, temp_path);
+        assert!(second.exists());
+
+        fs::remove_file(&temp_path).unwrap();
+        fs::remove_file(&second).unwrap();
+    }
+
+    #[test]
+    fn test_remove_active_temp_files_skips_released() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_temp_active");
+        fs::create_dir_all(&temp_dir).unwrap();
+
+        let released = create_temp_path(&temp_dir.join("released.png")).unwrap();
+        let active = create_temp_path(&temp_dir.join("active.png")).unwrap();
+        release_temp_path(&released);
+        remove_active_temp_files();
+
+        assert!(released.exists());
+        assert!(!active.exists());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
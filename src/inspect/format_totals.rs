@@ -0,0 +1,8 @@
+/// Number of files and their combined size for one image format.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FormatTotals {
+    /// Number of files
+    pub files: usize,
+    /// Combined size in bytes
+    pub bytes: u64,
+}
@@ -0,0 +1,194 @@
+use anyhow::{Context, Result};
+use image::{ImageDecoder, ImageReader};
+use serde::Serialize;
+use std::fmt::Write;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::file_ops::{format_bytes, format_delta, percent_change};
+use crate::optimization::{ImageFormat, InvalidImage};
+
+/// Metadata of a single image, shown by the `info` subcommand.
+///
+/// Only the image header is read, so pixel data is not decoded. Raster-only fields
+/// are `None` for SVG files.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use image_optimizer::inspect::ImageInfo;
+///
+/// # fn example() -> anyhow::Result<()> {
+/// let info = ImageInfo::read(Path::new("photo.jpg"))?;
+/// println!("{}", info.render());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Serialize)]
+pub struct ImageInfo {
+    /// Path of the image
+    pub path: PathBuf,
+    /// Format detected from the file contents (or the extension as a fallback)
+    pub format: ImageFormat,
+    /// File size in bytes
+    pub file_size: u64,
+    /// Width in pixels
+    pub width: Option<u32>,
+    /// Height in pixels
+    pub height: Option<u32>,
+    /// Decoded color type, e.g. `Rgb8` or `La16`
+    pub color_type: Option<String>,
+    /// Bits per channel
+    pub bit_depth: Option<u16>,
+    /// Whether the image has an alpha channel
+    pub has_alpha: Option<bool>,
+    /// Whether the image carries EXIF metadata
+    pub has_exif: Option<bool>,
+    /// Whether the image embeds an ICC color profile
+    pub has_icc_profile: Option<bool>,
+    /// Size after optimization with the current settings, if it could be estimated
+    pub estimated_size: Option<u64>,
+}
+
+impl ImageInfo {
+    /// Reads the format, size and header fields of an image.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The file cannot be read
+    /// - The format is not supported
+    /// - The raster image header cannot be parsed (reported as [`InvalidImage`])
+    pub fn read(path: &Path) -> Result<Self> {
+        let file_size = fs::metadata(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?
+            .len();
+
+        let mut head = Vec::with_capacity(1024);
+        fs::File::open(path)?.take(1024).read_to_end(&mut head)?;
+        let format = ImageFormat::from_magic_bytes(&head)
+            .or_else(|| ImageFormat::from_path(path))
+            .with_context(|| format!("Unsupported image format: {}", path.display()))?;
+
+        let mut info = Self {
+            path: path.to_path_buf(),
+            format,
+            file_size,
+            width: None,
+            height: None,
+            color_type: None,
+            bit_depth: None,
+            has_alpha: None,
+            has_exif: None,
+            has_icc_profile: None,
+            estimated_size: None,
+        };
+        if !format.is_raster() {
+            return Ok(info);
+        }
+
+        let mut decoder = ImageReader::open(path)?
+            .with_guessed_format()?
+            .into_decoder()
+            .map_err(|e| InvalidImage::new(e.to_string()))?;
+        let (width, height) = decoder.dimensions();
+        let color_type = decoder.color_type();
+
+        info.width = Some(width);
+        info.height = Some(height);
+        info.color_type = Some(format!("{color_type:?}"));
+        info.bit_depth = Some(color_type.bits_per_pixel() / u16::from(color_type.channel_count()));
+        info.has_alpha = Some(color_type.has_alpha());
+        info.has_exif = Some(decoder.exif_metadata()?.is_some());
+        info.has_icc_profile = Some(decoder.icc_profile()?.is_some());
+
+        Ok(info)
+    }
+
+    /// Formats the information as the human-readable `info` report.
+    #[must_use]
+    pub fn render(&self) -> String {
+        let yes_no = |value: bool| if value { "yes" } else { "no" };
+
+        let mut out = format!(
+            "File: {}\nFormat: {}\nSize: {}\n",
+            self.path.display(),
+            self.format.name(),
+            format_bytes(self.file_size)
+        );
+        if let (Some(width), Some(height)) = (self.width, self.height) {
+            let _ = writeln!(out, "Dimensions: {width}x{height}");
+        }
+        if let Some(ref color_type) = self.color_type {
+            let _ = writeln!(out, "Color type: {color_type}");
+        }
+        if let Some(bit_depth) = self.bit_depth {
+            let _ = writeln!(out, "Bit depth: {bit_depth}");
+        }
+        if let Some(has_alpha) = self.has_alpha {
+            let _ = writeln!(out, "Alpha: {}", yes_no(has_alpha));
+        }
+        if let Some(has_exif) = self.has_exif {
+            let _ = writeln!(out, "EXIF: {}", yes_no(has_exif));
+        }
+        if let Some(has_icc_profile) = self.has_icc_profile {
+            let _ = writeln!(out, "ICC profile: {}", yes_no(has_icc_profile));
+        }
+        if let Some(estimated_size) = self.estimated_size {
+            let delta = i64::try_from(estimated_size).unwrap_or(i64::MAX)
+                - i64::try_from(self.file_size).unwrap_or(i64::MAX);
+            let _ = writeln!(
+                out,
+                "Estimated size: {} ({}, {:+.1}%)",
+                format_bytes(estimated_size),
+                format_delta(delta),
+                percent_change(self.file_size, estimated_size)
+            );
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reads_png_header() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_image_info");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let path = temp_dir.join("alpha.png");
+        image::RgbaImage::new(12, 7).save(&path).unwrap();
+
+        let info = ImageInfo::read(&path).unwrap();
+
+        assert_eq!(info.format, ImageFormat::Png);
+        assert_eq!((info.width, info.height), (Some(12), Some(7)));
+        assert_eq!(info.color_type.as_deref(), Some("Rgba8"));
+        assert_eq!(info.bit_depth, Some(8));
+        assert_eq!(info.has_alpha, Some(true));
+        assert_eq!(info.has_exif, Some(false));
+        assert!(info.render().contains("Dimensions: 12x7"));
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_svg_has_no_raster_fields() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_image_info_svg");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let path = temp_dir.join("icon.svg");
+        fs::write(&path, "<svg xmlns=\"http://www.w3.org/2000/svg\"></svg>").unwrap();
+
+        let info = ImageInfo::read(&path).unwrap();
+
+        assert_eq!(info.format, ImageFormat::Svg);
+        assert_eq!(info.width, None);
+        assert!(!info.render().contains("Dimensions"));
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
@@ -0,0 +1,21 @@
+//! Read-only analysis of images.
+//!
+//! This module backs the inspection subcommands, which report on images without
+//! optimizing or modifying them:
+//!
+//! - **Tree statistics**: Format breakdown, size distribution and largest files (`stats`)
+//! - **Image information**: Header fields and estimated savings for one image (`info`)
+//! - **Quality comparison**: Encoded sizes of a set of images at several qualities
+//!   (`--compare-qualities`)
+
+pub mod format_totals;
+pub mod image_info;
+pub mod quality_comparison;
+pub mod quality_row;
+pub mod tree_stats;
+
+pub use format_totals::FormatTotals;
+pub use image_info::ImageInfo;
+pub use quality_comparison::QualityComparison;
+pub use quality_row::QualityRow;
+pub use tree_stats::TreeStats;
@@ -0,0 +1,232 @@
+use anyhow::{Context, Result};
+use image::imageops::FilterType;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::QualityRow;
+use crate::cli::Cli;
+use crate::file_ops::{TempFile, calculate_resize_dimensions, format_bytes, percent_change};
+use crate::optimization::{ImageFormat, QualityScore, decode_image, encode_image};
+
+/// Size (and optionally quality) of a set of images encoded at several qualities, shown
+/// by `--compare-qualities`.
+///
+/// Only JPEG and WebP files are compared, since the quality setting does not affect the
+/// other formats. Every image is decoded once, resized with `--max-size`, and encoded at
+/// each quality into a temporary file that is removed right away; no output is written
+/// and the inputs are never modified.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use clap::Parser;
+/// use std::path::PathBuf;
+/// use image_optimizer::cli::Cli;
+/// use image_optimizer::inspect::QualityComparison;
+///
+/// # fn example() -> anyhow::Result<()> {
+/// let args = Cli::parse_from(["image-optimizer", "--compare-qualities", "60,85"]);
+/// let comparison = QualityComparison::run(&[PathBuf::from("photo.jpg")], &args, || {})?;
+/// print!("{}", comparison.render());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QualityComparison {
+    /// One row per `--compare-qualities` entry, highest quality first
+    pub rows: Vec<QualityRow>,
+    /// Number of images that were compared
+    pub compared: usize,
+    /// Images that could not be decoded or encoded, with the reason
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+impl QualityComparison {
+    /// Encodes every JPEG and WebP in `files` at each quality of `--compare-qualities`.
+    ///
+    /// Lossless encoding flags (`--jpeg-lossless`, `--webp-lossless`,
+    /// `--webp-near-lossless`, `--strip-only`) are ignored so the quality takes effect.
+    /// Failing images are recorded in [`failed`](Self::failed) and left out of every row.
+    ///
+    /// # Arguments
+    ///
+    /// * `files` - Images to compare
+    /// * `args` - CLI configuration with the qualities and other encoder settings
+    /// * `on_file` - Called after each file, e.g. to advance a progress bar
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the size of a temporary file cannot be read.
+    pub fn run(files: &[PathBuf], args: &Cli, on_file: impl Fn()) -> Result<Self> {
+        let mut qualities = args.compare_qualities.clone();
+        qualities.sort_unstable_by(|a, b| b.cmp(a));
+        qualities.dedup();
+
+        let mut comparison = Self {
+            rows: qualities
+                .iter()
+                .map(|&quality| QualityRow {
+                    quality,
+                    ..QualityRow::default()
+                })
+                .collect(),
+            ..Self::default()
+        };
+        let mut ssim_totals = vec![0.0; qualities.len()];
+
+        for file in files {
+            let lossy = ImageFormat::from_path(file)
+                .filter(|format| matches!(format, ImageFormat::Jpeg | ImageFormat::Webp));
+            if let Some(format) = lossy {
+                match encode_at_qualities(file, format, args, &qualities) {
+                    Ok(results) => {
+                        let original_bytes = fs::metadata(file)?.len();
+                        for ((row, ssim_total), (encoded_bytes, score)) in comparison
+                            .rows
+                            .iter_mut()
+                            .zip(&mut ssim_totals)
+                            .zip(results)
+                        {
+                            row.original_bytes += original_bytes;
+                            row.encoded_bytes += encoded_bytes;
+                            *ssim_total += score.map_or(0.0, |score| score.ssim);
+                        }
+                        comparison.compared += 1;
+                    }
+                    Err(e) => comparison.failed.push((file.clone(), format!("{e:#}"))),
+                }
+            }
+            on_file();
+        }
+
+        if args.measure_quality && comparison.compared > 0 {
+            #[allow(clippy::cast_precision_loss)]
+            let compared = comparison.compared as f64;
+            for (row, total) in comparison.rows.iter_mut().zip(ssim_totals) {
+                row.ssim = Some(total / compared);
+            }
+        }
+        Ok(comparison)
+    }
+
+    /// Formats the comparison as a table with one row per quality.
+    #[must_use]
+    pub fn render(&self) -> String {
+        let mut out = format!(
+            "Compared {} JPEG/WebP images\n\n{:>7} {:>12} {:>10}",
+            self.compared, "Quality", "Size", "Change"
+        );
+        let with_ssim = self.rows.iter().any(|row| row.ssim.is_some());
+        if with_ssim {
+            out.push_str("     SSIM");
+        }
+        out.push('\n');
+
+        for row in &self.rows {
+            let _ = write!(
+                out,
+                "{:>7} {:>12} {:>9.1}%",
+                row.quality,
+                format_bytes(row.encoded_bytes),
+                percent_change(row.original_bytes, row.encoded_bytes)
+            );
+            if let Some(ssim) = row.ssim {
+                let _ = write!(out, " {ssim:>8.4}");
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Encodes one image at each quality, returning the encoded size and, with
+/// `--measure-quality`, the score of each.
+fn encode_at_qualities(
+    path: &Path,
+    format: ImageFormat,
+    args: &Cli,
+    qualities: &[u8],
+) -> Result<Vec<(u64, Option<QualityScore>)>> {
+    let img = decode_image(path, format)?
+        .with_context(|| format!("{} has no pixels to encode", path.display()))?;
+    let img = match args.max_size {
+        Some(max_size) => {
+            let (width, height) = calculate_resize_dimensions(img.width(), img.height(), max_size);
+            if (width, height) == (img.width(), img.height()) {
+                img
+            } else {
+                img.resize(width, height, FilterType::Lanczos3)
+            }
+        }
+        None => img,
+    };
+
+    let temp_file = TempFile::new("image-optimizer-compare", format.extension())?;
+    let temp_path = temp_file.path();
+    let mut quality_args = args.clone();
+    quality_args.jpeg_lossless = false;
+    quality_args.webp_lossless = false;
+    quality_args.webp_near_lossless = None;
+    quality_args.strip_only = false;
+
+    qualities
+        .iter()
+        .map(|&quality| {
+            quality_args.quality = vec![(format, quality)];
+            encode_image(format, path, temp_path, &quality_args, Some(img.clone()))?;
+            let size = fs::metadata(temp_path)?.len();
+            let score = if args.measure_quality {
+                decode_image(temp_path, format)?
+                    .and_then(|encoded| QualityScore::measure(&img, &encoded))
+            } else {
+                None
+            };
+            Ok((size, score))
+        })
+        .collect()
+}
+
+#[cfg(all(test, feature = "jpeg"))]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn test_one_row_per_quality_with_decreasing_sizes() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_compare_qualities");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        let image_path = temp_dir.join("detail.jpg");
+        let img = image::RgbImage::from_fn(128, 128, |x, y| {
+            let noise = u8::try_from((x * 31 + y * 17) % 64).unwrap();
+            image::Rgb([noise * 3, u8::try_from(x).unwrap() + noise, 128])
+        });
+        img.save(&image_path).unwrap();
+        let original = fs::read(&image_path).unwrap();
+        fs::write(temp_dir.join("notes.svg"), "<svg/>").unwrap();
+
+        let args = Cli::parse_from([
+            "image-optimizer",
+            "--compare-qualities",
+            "60,95,75,85",
+            "--measure-quality",
+        ]);
+        let files = [image_path.clone(), temp_dir.join("notes.svg")];
+        let comparison = QualityComparison::run(&files, &args, || {}).unwrap();
+
+        assert_eq!(comparison.compared, 1);
+        let qualities: Vec<u8> = comparison.rows.iter().map(|row| row.quality).collect();
+        assert_eq!(qualities, [95, 85, 75, 60]);
+        for pair in comparison.rows.windows(2) {
+            assert!(pair[1].encoded_bytes < pair[0].encoded_bytes, "{pair:?}");
+            assert!(pair[1].ssim.unwrap() <= pair[0].ssim.unwrap(), "{pair:?}");
+        }
+        let table = comparison.render();
+        assert_eq!(table.lines().filter(|line| line.contains('%')).count(), 4);
+        assert_eq!(fs::read(&image_path).unwrap(), original);
+        assert_eq!(fs::read_dir(&temp_dir).unwrap().count(), 2);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
@@ -0,0 +1,12 @@
+/// Combined results of encoding the compared images at one quality.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct QualityRow {
+    /// Encoder quality (1-100)
+    pub quality: u8,
+    /// Combined size of the originals in bytes
+    pub original_bytes: u64,
+    /// Combined size of the encoded images in bytes
+    pub encoded_bytes: u64,
+    /// Average SSIM against the originals, with `--measure-quality`
+    pub ssim: Option<f64>,
+}
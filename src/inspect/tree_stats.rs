@@ -0,0 +1,173 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+
+use super::FormatTotals;
+use crate::file_ops::format_bytes;
+use crate::optimization::ImageFormat;
+
+/// Size statistics for a set of images, shown by the `stats` subcommand.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::PathBuf;
+/// use image_optimizer::inspect::TreeStats;
+///
+/// let stats = TreeStats::from_files(&[PathBuf::from("photo.jpg")], 10);
+/// println!("{}", stats.render());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TreeStats {
+    /// Files and bytes per format, ordered by format
+    pub by_format: BTreeMap<ImageFormat, FormatTotals>,
+    /// Number of files
+    pub total_files: usize,
+    /// Combined size of all files in bytes
+    pub total_bytes: u64,
+    /// Median file size in bytes
+    pub median_bytes: u64,
+    /// The largest files with their sizes, largest first
+    pub largest: Vec<(PathBuf, u64)>,
+}
+
+impl TreeStats {
+    /// Collects statistics for the given images.
+    ///
+    /// Files whose size cannot be read, or whose format is not supported, are left out.
+    ///
+    /// # Arguments
+    ///
+    /// * `files` - Images to analyze, typically from [`scan_images`](crate::file_ops::scan_images)
+    /// * `top` - Number of largest files to keep
+    #[must_use]
+    pub fn from_files(files: &[PathBuf], top: usize) -> Self {
+        let mut stats = Self::default();
+        let mut files_with_sizes = Vec::with_capacity(files.len());
+
+        for file in files {
+            let Some(format) = ImageFormat::from_path(file) else {
+                continue;
+            };
+            let Ok(metadata) = fs::metadata(file) else {
+                continue;
+            };
+            let totals = stats.by_format.entry(format).or_default();
+            totals.files += 1;
+            totals.bytes += metadata.len();
+            files_with_sizes.push((file.clone(), metadata.len()));
+        }
+
+        stats.total_files = files_with_sizes.len();
+        stats.total_bytes = files_with_sizes.iter().map(|(_, size)| size).sum();
+
+        let mut sizes: Vec<u64> = files_with_sizes.iter().map(|&(_, size)| size).collect();
+        sizes.sort_unstable();
+        stats.median_bytes = match sizes.len() {
+            0 => 0,
+            len if len % 2 == 0 => u64::midpoint(sizes[len / 2 - 1], sizes[len / 2]),
+            len => sizes[len / 2],
+        };
+
+        files_with_sizes.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        files_with_sizes.truncate(top);
+        stats.largest = files_with_sizes;
+
+        stats
+    }
+
+    /// Returns the average file size in bytes, or 0 for an empty set.
+    #[must_use]
+    pub fn average_bytes(&self) -> u64 {
+        self.total_bytes
+            .checked_div(self.total_files as u64)
+            .unwrap_or(0)
+    }
+
+    /// Formats the statistics as the human-readable `stats` report.
+    #[must_use]
+    pub fn render(&self) -> String {
+        let mut out = format!(
+            "{} image files, {}\n\n{:<6} {:>7} {:>12}\n",
+            self.total_files,
+            format_bytes(self.total_bytes),
+            "Format",
+            "Files",
+            "Size"
+        );
+        for (format, totals) in &self.by_format {
+            let _ = writeln!(
+                out,
+                "{:<6} {:>7} {:>12}",
+                format.name(),
+                totals.files,
+                format_bytes(totals.bytes)
+            );
+        }
+
+        let _ = write!(
+            out,
+            "\nAverage size: {}\nMedian size: {}\n",
+            format_bytes(self.average_bytes()),
+            format_bytes(self.median_bytes)
+        );
+
+        if !self.largest.is_empty() {
+            out.push_str("\nLargest files:\n");
+            for (path, size) in &self.largest {
+                let _ = writeln!(out, "{:>12}  {}", format_bytes(*size), path.display());
+            }
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collects_per_format_totals() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_tree_stats");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let files = vec![
+            temp_dir.join("a.jpg"),
+            temp_dir.join("b.jpg"),
+            temp_dir.join("c.png"),
+            temp_dir.join("d.svg"),
+        ];
+        for (file, size) in files.iter().zip([100, 400, 300, 50]) {
+            fs::write(file, vec![0u8; size]).unwrap();
+        }
+
+        let stats = TreeStats::from_files(&files, 2);
+
+        assert_eq!(stats.total_files, 4);
+        assert_eq!(stats.total_bytes, 850);
+        assert_eq!(
+            stats.by_format[&ImageFormat::Jpeg],
+            FormatTotals {
+                files: 2,
+                bytes: 500
+            }
+        );
+        assert_eq!(stats.average_bytes(), 212);
+        assert_eq!(stats.median_bytes, 200);
+        assert_eq!(
+            stats.largest,
+            vec![(files[1].clone(), 400), (files[2].clone(), 300)]
+        );
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_empty_set() {
+        let stats = TreeStats::from_files(&[], 10);
+        assert_eq!(stats.average_bytes(), 0);
+        assert_eq!(stats.median_bytes, 0);
+        assert!(stats.render().starts_with("0 image files, 0 B"));
+    }
+}
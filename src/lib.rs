@@ -7,7 +7,7 @@
 //!
 //! ## Features
 //!
-//! - **Multi-format support**: JPEG (mozjpeg), PNG (oxipng with zopfli), WebP, SVG (regex-based)
+//! - **Multi-format support**: JPEG (mozjpeg), PNG (oxipng with zopfli), WebP, SVG (XML-aware)
 //! - **Parallel processing**: Concurrent optimization using rayon
 //! - **Flexible output**: In-place optimization or separate output directory
 //! - **Quality control**: Adjustable quality settings and lossless mode (raster formats only)
@@ -21,9 +21,13 @@
 //! The crate is organized into distinct modules following a one-function-per-file pattern:
 //!
 //! - [`cli`] - Command-line interface components
+//! - [`settings`] - Configuration file loading and merging
 //! - [`file_ops`] - File system operations and utilities
+//! - [`inspect`] - Read-only image analysis for the inspection subcommands
 //! - [`optimization`] - Image optimization functionality
+//! - [`reporting`] - Run statistics and summary output
 //! - [`updater`] - Self-update functionality
+//! - [`watcher`] - Watch mode that optimizes images as they change
 //!
 //! ## Usage
 //!
@@ -40,5 +44,9 @@
 
 pub mod cli;
 pub mod file_ops;
+pub mod inspect;
 pub mod optimization;
+pub mod reporting;
+pub mod settings;
 pub mod updater;
+pub mod watcher;
@@ -1,135 +1,554 @@
 use anyhow::Result;
-use clap::Parser;
-use indicatif::{ProgressBar, ProgressStyle};
-use rayon::prelude::*;
-use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
-
-mod cli;
-mod file_ops;
-mod optimization;
-mod updater;
-
-use cli::Cli;
-use file_ops::{format_bytes, scan_images};
-use optimization::optimize_image;
-use updater::update_self;
+use clap::CommandFactory;
+use clap::error::ErrorKind;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use image_optimizer::cli::{
+    Cli, Command, DedupMode, InfoArgs, StatsArgs, ask_confirmation, needs_in_place_confirmation,
+    write_completions,
+};
+use image_optimizer::file_ops::{
+    OptimizationCache, RunLock, ScanOptions, ScanOutcome, check_listed_paths, find_duplicates,
+    format_bytes, output_within_input, plan_flat_outputs, read_file_list, sample_files,
+    scan_images, scan_images_with_progress,
+};
+use image_optimizer::inspect::{ImageInfo, QualityComparison, TreeStats};
+use image_optimizer::optimization::{
+    CancelToken, INTERRUPTED_EXIT_CODE, estimate_optimized_size, optimize_batch,
+    optimize_deduplicated, optimize_stdin,
+};
+use image_optimizer::reporting::{
+    FileResult, FileStatus, ProgressLog, ReportDiff, RunStats, append_run_log, create_progress_bar,
+    create_scan_spinner, format_duplicate_groups, format_file_line, format_porcelain_line,
+    format_porcelain_summary, notify_completion, write_json_report,
+};
+use image_optimizer::settings::{
+    format_effective_config, output_fingerprint, resolve_args, resolve_input,
+};
+use image_optimizer::updater::{
+    UpdateFailure, UpdateSource, check_update, rollback_self, update_self,
+};
+use image_optimizer::watcher::watch_directory;
+
+/// Exit code of `--check-update` when a newer version is available.
+const UPDATE_AVAILABLE: u8 = 10;
 
 /// Main entry point for the image optimizer CLI application.
 ///
 /// This function coordinates the entire optimization process including:
-/// - Command-line argument parsing
+/// - Command-line argument parsing and config file merging
+/// - The `stats` and `info` subcommands, which only report on images
+/// - `--print-config`, which only prints the merged settings
+/// - `--compare-qualities`, which only reports encoded sizes per quality
+/// - Watch mode, which keeps optimizing changed images after the initial pass
 /// - Input validation
 /// - Image file discovery
 /// - Parallel optimization with progress tracking
-/// - Results summary display
+/// - Results summary display and the optional JSON report
 ///
 /// The application supports self-updating, batch processing with parallel execution,
 /// and comprehensive error handling.
 ///
 /// # Returns
 ///
-/// Returns `Ok(())` on successful completion or an error if any critical operation fails.
+/// Returns exit code 0 when every file was handled, or 1 when any file failed to
+/// optimize. Usage errors (missing or invalid arguments) exit with code 2,
+/// `--check-update` exits with code 10 when an update is available, `--update` exits
+/// with code 11 when no binary is published for this platform, and a batch
+/// interrupted with Ctrl-C exits with code 130.
 ///
 /// # Errors
 ///
-/// Returns an error if:
-/// - Required input parameters are missing
-/// - Input file or directory doesn't exist
-/// - Quality parameter is out of range (1-100)
+/// Returns an error (exit code 1) if:
+/// - The config file cannot be read or contains invalid settings
+/// - Another run holds the lock on the input (without `--wait`)
 /// - Progress bar template formatting fails
 /// - Any critical file I/O operations fail
-fn main() -> Result<()> {
-    let args = Cli::parse();
+fn main() -> Result<ExitCode> {
+    let matches = Cli::command().get_matches();
+    let mut args = resolve_args(&matches)?;
+    // The porcelain lines replace all decorative output, exactly as with --quiet
+    if args.porcelain {
+        args.quiet = true;
+    }
+
+    if let Some(ref command) = args.command {
+        run_command(command, &args)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if args.print_config {
+        print!("{}", format_effective_config(&args)?);
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if args.check_update {
+        return Ok(if check_update(&UpdateSource::from_cli(&args))? {
+            ExitCode::from(UPDATE_AVAILABLE)
+        } else {
+            ExitCode::SUCCESS
+        });
+    }
+
+    if args.rollback {
+        return Ok(if rollback_self()? {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        });
+    }
 
     if args.update {
-        return update_self();
+        return update(&args);
+    }
+
+    if args.stdin {
+        optimize_stdin(&args)?;
+        return Ok(ExitCode::SUCCESS);
     }
 
-    let input = args
-        .input
-        .as_ref()
-        .ok_or_else(|| anyhow::anyhow!("Input file or directory is required"))?;
+    let input = resolve_input(&args)
+        .unwrap_or_else(|e| e.exit())
+        .to_path_buf();
 
-    if args.jpeg_quality > 100 {
-        return Err(anyhow::anyhow!("Quality must be between 1 and 100"));
+    // Held until the run (including watch mode) ends
+    let _lock = match args.lock_file {
+        Some(ref lock_file) => RunLock::acquire(lock_file, args.wait)?,
+        None => RunLock::acquire_for_input(&input, args.wait)?,
+    };
+
+    let cancel = CancelToken::default();
+    cancel.cancel_on_interrupt()?;
+
+    let Some(mut scan_outcome) = discover_images(&args, &input)? else {
+        if args.watch {
+            watch_directory(&args, &input, &cancel)?;
+        }
+        return Ok(ExitCode::SUCCESS);
+    };
+
+    select_images(&args, &mut scan_outcome);
+    if !args.compare_qualities.is_empty() {
+        compare_qualities(&args, &scan_outcome.images)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+    if !approve_run(&args, scan_outcome.images.len())? {
+        eprintln!("Aborted; no files were changed");
+        return Ok(ExitCode::FAILURE);
+    }
+    if args.flatten
+        && let Some(ref output) = args.output
+    {
+        args.flat_outputs = plan_flat_outputs(
+            output,
+            &scan_outcome.images,
+            args.flatten_collisions,
+            args.output_ext_normalize,
+            args.svgz,
+        )?;
     }
 
-    if !input.exists() {
-        return Err(anyhow::anyhow!("Input file or directory does not exist"));
+    let stats = RunStats::default();
+    for path in &scan_outcome.too_small {
+        stats.record(FileResult::too_small(path));
+    }
+    let started = Instant::now();
+    process_images(&args, &input, scan_outcome.images, &stats, &cancel)?;
+    report_results(&args, &input, &stats, started.elapsed())?;
+    if args.notify {
+        notify_completion(&stats.summary());
     }
 
-    let image_files = scan_images(input, args.recursive);
+    if cancel.is_cancelled() {
+        return Ok(ExitCode::from(INTERRUPTED_EXIT_CODE));
+    }
+    if args.watch {
+        // Stopping watch mode with Ctrl-C is the normal way to end the run
+        watch_directory(&args, &input, &cancel)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+    if stats.failed() > 0 {
+        return Ok(ExitCode::FAILURE);
+    }
+    Ok(ExitCode::SUCCESS)
+}
 
-    if image_files.is_empty() {
-        if input.is_file() {
-            println!("The specified file is not a supported image format");
-        } else {
-            println!("No image files found in the specified directory");
+/// Runs a subcommand (`stats`, `info` or `completions`).
+fn run_command(command: &Command, args: &Cli) -> Result<()> {
+    match command {
+        Command::Stats(stats_args) => show_stats(stats_args)?,
+        Command::Info(info_args) => show_info(info_args, args)?,
+        Command::Completions(completions_args) => {
+            write_completions(completions_args.shell, &mut std::io::stdout());
         }
-        return Ok(());
     }
+    Ok(())
+}
 
-    println!("Found {} image files", image_files.len());
+/// Runs `--update`, exiting with [`UpdateFailure::EXIT_CODE`] when no binary is
+/// published for this platform.
+fn update(args: &Cli) -> Result<ExitCode> {
+    match update_self(&UpdateSource::from_cli(args)) {
+        Ok(()) => Ok(ExitCode::SUCCESS),
+        Err(error) => match error.downcast::<UpdateFailure>() {
+            Ok(failure) => {
+                eprintln!("Error: {failure}");
+                Ok(ExitCode::from(UpdateFailure::EXIT_CODE))
+            }
+            Err(error) => Err(error),
+        },
+    }
+}
 
-    let pb = ProgressBar::new(image_files.len() as u64);
-    pb.set_style(
-        ProgressStyle::default_bar()
-            .template(
-                "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} {msg}",
-            )?
-            .progress_chars("█▉▊▋▌▍▎▏  "),
+/// Aborts the run when more images were found than `--max-files` allows, unless the
+/// check was overridden with `--yes` or `--force`.
+fn check_max_files(args: &Cli, found: usize) -> Result<()> {
+    if let Some(max_files) = args.max_files
+        && found > max_files.get()
+        && !args.yes
+        && !args.force
+    {
+        anyhow::bail!(
+            "Found {found} image files, more than --max-files {max_files}; nothing was \
+             changed. Check the input path, or pass --yes to optimize them anyway"
+        );
+    }
+    Ok(())
+}
+
+/// Runs the safety checks before any file is changed: enforces `--max-files` and asks
+/// before a large in-place run without backups rewrites the originals. Returns whether
+/// the run may go ahead.
+///
+/// There is no one to ask when stdin is not a terminal (scripts, pipes, CI), so the run
+/// then goes ahead as before; use `--yes` to skip the question on a terminal.
+fn approve_run(args: &Cli, count: usize) -> Result<bool> {
+    check_max_files(args, count)?;
+    if !needs_in_place_confirmation(args, count) || !std::io::stdin().is_terminal() {
+        return Ok(true);
+    }
+    let question =
+        format!("This will modify {count} files in place with no backup. Continue? [y/N] ");
+    Ok(ask_confirmation(
+        &question,
+        std::io::stdin().lock(),
+        std::io::stderr(),
+    )?)
+}
+
+/// Narrows the discovered images down to the `--sample`, and reports how many there are
+/// (with the groups of duplicates for `--dedup report`).
+///
+/// Files set aside as too small are dropped from a sample, so the summary only covers
+/// sampled files.
+fn select_images(args: &Cli, scan_outcome: &mut ScanOutcome) {
+    let found = scan_outcome.images.len();
+    if let Some(count) = args.sample {
+        let seed = args.seed.unwrap_or_else(random_seed);
+        scan_outcome.images =
+            sample_files(std::mem::take(&mut scan_outcome.images), count.get(), seed);
+        scan_outcome.too_small.clear();
+        if !args.quiet {
+            println!(
+                "Sampled {} of {found} image files (--seed {seed})",
+                scan_outcome.images.len()
+            );
+        }
+    } else if !args.quiet {
+        println!("Found {found} image files");
+    }
+
+    if !args.quiet && args.dedup == Some(DedupMode::Report) {
+        print!(
+            "{}",
+            format_duplicate_groups(&find_duplicates(&scan_outcome.images))
+        );
+    }
+}
+
+/// Returns a seed for `--sample` without `--seed` that differs between runs.
+fn random_seed() -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos());
+    // Truncating keeps the fast-changing low bits of the clock
+    #[allow(clippy::cast_possible_truncation)]
+    let seed = nanos as u64;
+    seed ^ u64::from(std::process::id()).rotate_left(32)
+}
+
+/// Prints the summary (or the `--porcelain` summary line), the optional `--top` and
+/// `--list-skipped` lists and the `--diff-report` comparison, writes the `--report-json`
+/// report and appends the run to the `--log-file` log.
+fn report_results(args: &Cli, input: &Path, stats: &RunStats, elapsed: Duration) -> Result<()> {
+    if !args.quiet {
+        stats.print_summary(elapsed);
+        if let Some(count) = args.top {
+            stats.print_top_savings(count.get());
+        }
+        if args.list_skipped {
+            stats.print_skipped_larger();
+        }
+        if let Some(ref baseline_path) = args.diff_report {
+            print!(
+                "{}",
+                ReportDiff::load(baseline_path, &stats.results())?.format()
+            );
+        }
+    }
+    if args.porcelain {
+        println!("{}", format_porcelain_summary(input, &stats.summary()));
+    }
+    if let Some(ref report_path) = args.report_json {
+        write_json_report(report_path, stats, elapsed)?;
+    }
+    if let Some(ref log_path) = args.log_file {
+        append_run_log(
+            log_path,
+            input,
+            stats,
+            elapsed,
+            args.log_per_file,
+            args.log_max_bytes,
+        )?;
+    }
+    Ok(())
+}
+
+/// Prints the `--compare-qualities` table for the discovered images without writing any
+/// output file.
+///
+/// Images that cannot be compared are reported on stderr and left out of the table.
+fn compare_qualities(args: &Cli, images: &[PathBuf]) -> Result<()> {
+    let pb = create_progress_bar(images.len() as u64, args.quiet)?;
+    let comparison = QualityComparison::run(images, args, || pb.inc(1))?;
+    pb.finish_and_clear();
+    for (path, error) in &comparison.failed {
+        eprintln!("Warning: skipping {}: {error}", path.display());
+    }
+    print!("{}", comparison.render());
+    Ok(())
+}
+
+/// Prints the `stats` report for a directory without modifying any file.
+///
+/// A nonexistent path is a usage error (exit code 2).
+fn show_stats(stats_args: &StatsArgs) -> Result<()> {
+    if !stats_args.path.exists() {
+        Cli::command()
+            .error(
+                ErrorKind::InvalidValue,
+                "Input file or directory does not exist",
+            )
+            .exit();
+    }
+
+    let scan_options = ScanOptions {
+        recursive: stats_args.recursive,
+        ..ScanOptions::default()
+    };
+    let scan_outcome = scan_images(&stats_args.path, &scan_options)?;
+    for warning in &scan_outcome.warnings {
+        eprintln!("Warning: {warning}");
+    }
+
+    print!(
+        "{}",
+        TreeStats::from_files(&scan_outcome.images, stats_args.top).render()
     );
+    Ok(())
+}
 
-    let total_saved = Arc::new(Mutex::new(0u64));
-    let processed = Arc::new(Mutex::new(0usize));
-    let skipped = Arc::new(Mutex::new(0usize));
+/// Prints the `info` report for one image without modifying it.
+///
+/// The estimated size uses the quality and resize settings of the run; if it cannot be
+/// computed the reason is printed to stderr and the estimate is left out.
+fn show_info(info_args: &InfoArgs, args: &Cli) -> Result<()> {
+    if !info_args.path.is_file() {
+        Cli::command()
+            .error(ErrorKind::InvalidValue, "Input file does not exist")
+            .exit();
+    }
 
-    let image_processor = |image_path: PathBuf| {
-        pb.set_message(format!(
-            "Processing: {}",
-            image_path.file_name().unwrap_or_default().to_string_lossy()
-        ));
+    let mut info = ImageInfo::read(&info_args.path)?;
+    match estimate_optimized_size(&info_args.path, args) {
+        Ok(estimated_size) => info.estimated_size = Some(estimated_size),
+        Err(e) => eprintln!("Could not estimate the optimized size: {e}"),
+    }
 
-        match optimize_image(&image_path, &args, input) {
-            Ok(saved_bytes) => {
-                if saved_bytes > 0 {
-                    if let Ok(mut saved) = total_saved.lock() {
-                        *saved += saved_bytes;
-                    }
-                    if let Ok(mut proc) = processed.lock() {
-                        *proc += 1;
-                    }
-                } else if let Ok(mut skip) = skipped.lock() {
-                    *skip += 1;
-                }
-            }
-            Err(e) => {
-                eprintln!("Error processing {}: {}", image_path.display(), e);
+    if info_args.json {
+        println!("{}", serde_json::to_string_pretty(&info)?);
+    } else {
+        print!("{}", info.render());
+    }
+    Ok(())
+}
+
+/// Collects the images to process from `--files-from` or by scanning the input.
+///
+/// Scan warnings are printed to stderr. Returns `None` (after telling the user why)
+/// when no images were found at all.
+fn discover_images(args: &Cli, input: &Path) -> Result<Option<ScanOutcome>> {
+    let scan_outcome = if let Some(ref files_from) = args.files_from {
+        let scan_outcome = read_file_list(files_from, args.null)?;
+        if args.output.is_some() {
+            check_listed_paths(&scan_outcome.images, input)?;
+        }
+        scan_outcome
+    } else {
+        let scan_options = ScanOptions::from_cli(args)?;
+        if scan_options.recursive
+            && let Some(ref output) = args.output
+            && output_within_input(input, output).is_some()
+        {
+            eprintln!(
+                "Warning: output directory {} is inside the input and will not be scanned",
+                output.display()
+            );
+        }
+        let spinner = create_scan_spinner(args.quiet || args.no_scan_progress)?;
+        let scan_outcome = scan_images_with_progress(input, &scan_options, &|| spinner.inc(1));
+        spinner.finish_and_clear();
+        scan_outcome?
+    };
+    for warning in &scan_outcome.warnings {
+        eprintln!("Warning: {warning}");
+    }
+
+    if scan_outcome.images.is_empty() && scan_outcome.too_small.is_empty() {
+        if !args.quiet {
+            if args.files_from.is_some() {
+                println!("No supported image files found in the file list");
+            } else if input.is_file() {
+                println!("The specified file is not a supported image format");
+            } else {
+                println!("No image files found in the specified directory");
             }
         }
+        return Ok(None);
+    }
 
+    Ok(Some(scan_outcome))
+}
+
+/// Optimizes all images with a progress bar, sequentially or in parallel.
+///
+/// Results are recorded in `stats`. With `--dedup link` or `copy`, identical files are
+/// optimized once and the result is shared with their copies. Per-file errors are
+/// printed to stderr and recorded rather than returned, unless `--fail-fast` stops the
+/// batch. Once `cancel` is set no further files are started;
+/// files already in progress finish, so the summary covers exactly the files handled.
+/// The `--cache` manifest is saved once the batch ends.
+fn process_images(
+    args: &Cli,
+    input: &Path,
+    image_files: Vec<PathBuf>,
+    stats: &RunStats,
+    cancel: &CancelToken,
+) -> Result<()> {
+    let total_files = image_files.len();
+    let pb = create_progress_bar(image_files.len() as u64, args.quiet)?;
+    let verbose = args.verbose && !args.quiet;
+    let porcelain_files = args.porcelain && args.verbose;
+
+    let cache = args
+        .cache
+        .as_deref()
+        .map(|cache_path| OptimizationCache::load(cache_path, &output_fingerprint(args)))
+        .transpose()?
+        .map(Mutex::new);
+
+    let progress_log = args
+        .progress_interval
+        .map(|interval| ProgressLog::new(interval, total_files));
+
+    let show_progress = |result: &FileResult| {
+        stats.record(result.clone());
+        if let Some(line) = progress_log
+            .as_ref()
+            .and_then(|log| log.record(stats.saved_so_far()))
+        {
+            pb.suspend(|| eprintln!("{line}"));
+        }
         pb.inc(1);
+        pb.set_message(format!(
+            "Done: {} (saved {} so far)",
+            result
+                .path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy(),
+            format_bytes(stats.saved_so_far())
+        ));
+        let path = result.path.display();
+        if let Some(ref error) = result.error {
+            if result.is_failure() {
+                pb.suspend(|| eprintln!("Error processing {path}: {error}"));
+            } else {
+                pb.suspend(|| eprintln!("Warning: skipping {path}: {error}"));
+            }
+        } else if porcelain_files {
+            pb.suspend(|| println!("{}", format_porcelain_line(result)));
+        } else if verbose {
+            match result.outcome() {
+                Some(outcome) if result.status == FileStatus::SkippedThreshold => {
+                    let line = format_file_line(&result.path, outcome);
+                    pb.suspend(|| println!("{line}, kept original (below threshold)"));
+                }
+                Some(outcome) => {
+                    pb.suspend(|| println!("{}", format_file_line(&result.path, outcome)));
+                }
+                None if result.status == FileStatus::Marked => {
+                    pb.suspend(|| println!("{path}  unchanged (marked)"));
+                }
+                None if result.status == FileStatus::SkippedExisting => {
+                    pb.suspend(|| println!("{path}  unchanged (output exists)"));
+                }
+                None => pb.suspend(|| println!("{path}  unchanged (cached)")),
+            }
+        }
     };
 
-    if args.no_parallel {
-        image_files.into_iter().for_each(image_processor);
+    let completed = if matches!(args.dedup, Some(DedupMode::Link | DedupMode::Copy)) {
+        optimize_deduplicated(
+            image_files,
+            args,
+            input,
+            cache.as_ref(),
+            cancel,
+            show_progress,
+        )
     } else {
-        image_files.into_par_iter().for_each(image_processor);
+        optimize_batch(
+            image_files,
+            args,
+            input,
+            cache.as_ref(),
+            cancel,
+            show_progress,
+        )
+    };
+    if cancel.is_cancelled() {
+        let handled = pb.position();
+        pb.suspend(|| eprintln!("Interrupted after {handled} of {total_files} files"));
+    } else if !completed {
+        pb.suspend(|| eprintln!("Aborting after the first error (--fail-fast)"));
     }
 
-    let total_saved = total_saved.lock().map(|guard| *guard).unwrap_or(0);
-    let processed = processed.lock().map(|guard| *guard).unwrap_or(0);
-    let skipped = skipped.lock().map(|guard| *guard).unwrap_or(0);
-
-    pb.finish_with_message("Optimization complete");
-
-    println!("\nProcessed {processed} files");
-    if skipped > 0 {
-        println!("Skipped {skipped} files (optimization would increase size)");
+    if let (Some(cache), Some(cache_path)) = (&cache, &args.cache)
+        && let Ok(cache) = cache.lock()
+    {
+        cache.save(cache_path)?;
     }
-    if total_saved > 0 {
-        println!("Total space saved: {}", format_bytes(total_saved));
+
+    if cancel.is_cancelled() {
+        pb.abandon_with_message("Interrupted");
+    } else {
+        pb.finish_with_message("Optimization complete");
     }
 
     Ok(())
@@ -0,0 +1,53 @@
+use image::{DynamicImage, RgbImage};
+
+use crate::cli::BackgroundColor;
+
+/// Composites an image with transparency over a solid background color.
+///
+/// Encoders for formats without an alpha channel need opaque pixels. Dropping the alpha
+/// channel (as `to_rgb8` does) shows whatever color the transparent pixels happen to
+/// store, which is usually black; blending over `--background` instead gives the result
+/// the image has on a page of that color.
+///
+/// # Arguments
+///
+/// * `img` - Image to flatten
+/// * `background` - Color that transparent pixels are blended with
+///
+/// # Returns
+///
+/// Returns an RGB image, or `None` if `img` has no alpha channel and can be encoded as is.
+///
+/// # Examples
+///
+/// ```rust
+/// use image::{DynamicImage, RgbaImage};
+/// use image_optimizer::cli::BackgroundColor;
+/// use image_optimizer::optimization::flatten_alpha;
+///
+/// let img = DynamicImage::ImageRgba8(RgbaImage::from_pixel(1, 1, image::Rgba([0, 0, 0, 0])));
+/// let flat = flatten_alpha(&img, BackgroundColor::default()).unwrap();
+/// assert_eq!(flat.get_pixel(0, 0).0, [255, 255, 255]);
+/// ```
+#[must_use]
+pub fn flatten_alpha(img: &DynamicImage, background: BackgroundColor) -> Option<RgbImage> {
+    if !img.color().has_alpha() {
+        return None;
+    }
+
+    let rgba = img.to_rgba8();
+    Some(RgbImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let [red, green, blue, alpha] = rgba.get_pixel(x, y).0;
+        let blend = |value: u8, background: u8| {
+            let mixed = u16::from(value) * u16::from(alpha)
+                + u16::from(background) * u16::from(u8::MAX - alpha);
+            // Rounded division by 255 of a value up to 255 * 255 always fits in a u8
+            u8::try_from((mixed + 127) / 255).unwrap_or(u8::MAX)
+        };
+        image::Rgb([
+            blend(red, background.rgb[0]),
+            blend(green, background.rgb[1]),
+            blend(blue, background.rgb[2]),
+        ])
+    }))
+}
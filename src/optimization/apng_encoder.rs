@@ -0,0 +1,111 @@
+use anyhow::{Context, Result};
+use image::codecs::png::PngDecoder;
+use image::imageops::FilterType;
+use image::{AnimationDecoder, DynamicImage};
+use std::fs;
+use std::io::{BufWriter, Cursor};
+use std::path::Path;
+
+use super::{convert_to_srgb, read_icc_profile};
+use crate::cli::Cli;
+
+/// Rewrites every frame of an animated PNG (APNG) at a new size.
+///
+/// Saving a decoded PNG keeps only its first frame, so resizing (or converting with
+/// `--to-srgb`) an APNG that way would silently drop the animation. Instead each frame
+/// is decoded onto the full canvas, resized to `width` x `height`, converted to sRGB
+/// with `--to-srgb`, and written back as an APNG with the original frame delays and loop
+/// count. The frames are stored as full 8-bit RGBA canvases; oxipng recompresses them
+/// afterwards.
+///
+/// # Arguments
+///
+/// * `input_path` - Path to the source APNG
+/// * `output_path` - Path where the resized APNG is written
+/// * `width` - Width of the output frames
+/// * `height` - Height of the output frames
+/// * `args` - CLI configuration; only `--to-srgb` is used
+///
+/// # Errors
+///
+/// Returns an error if the frames cannot be decoded, or the output cannot be encoded or
+/// written.
+pub fn write_apng(
+    input_path: &Path,
+    output_path: &Path,
+    width: u32,
+    height: u32,
+    args: &Cli,
+) -> Result<()> {
+    let data = fs::read(input_path)?;
+    let frames = PngDecoder::new(Cursor::new(&data))?
+        .apng()?
+        .into_frames()
+        .collect_frames()
+        .with_context(|| format!("Failed to decode the frames of {}", input_path.display()))?;
+    let profile = if args.to_srgb {
+        read_icc_profile(input_path)?
+    } else {
+        None
+    };
+
+    let frame_count = u32::try_from(frames.len()).context("Too many APNG frames")?;
+    let mut encoder = png::Encoder::new(
+        BufWriter::new(fs::File::create(output_path)?),
+        width,
+        height,
+    );
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_animated(frame_count, read_num_plays(&data))?;
+    let mut writer = encoder.write_header()?;
+
+    for frame in frames {
+        let (numerator, denominator) = frame.delay().numer_denom_ms();
+        let delay_ms = (f64::from(numerator) / f64::from(denominator.max(1))).round();
+        let mut canvas = DynamicImage::ImageRgba8(frame.into_buffer());
+        if (canvas.width(), canvas.height()) != (width, height) {
+            canvas = canvas.resize_exact(width, height, FilterType::Lanczos3);
+        }
+        if let Some(converted) = profile
+            .as_deref()
+            .and_then(|profile| convert_to_srgb(&canvas, profile))
+        {
+            canvas = converted;
+        }
+
+        // Delays above about a minute are clamped to what the fcTL chunk can hold
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        writer.set_frame_delay(delay_ms.min(f64::from(u16::MAX)) as u16, 1000)?;
+        writer.write_image_data(canvas.to_rgba8().as_raw())?;
+    }
+    writer.finish()?;
+
+    Ok(())
+}
+
+/// Returns whether the file is an animated PNG (it has an `acTL` chunk).
+///
+/// Files that are not PNGs at all, or whose header cannot be read, are not animated.
+#[must_use]
+pub fn is_apng(path: &Path) -> bool {
+    fs::read(path)
+        .ok()
+        .and_then(|data| PngDecoder::new(Cursor::new(data)).ok()?.is_apng().ok())
+        .unwrap_or(false)
+}
+
+/// Reads how often the animation repeats from the `acTL` chunk (0 means forever).
+fn read_num_plays(data: &[u8]) -> u32 {
+    let mut offset = 8;
+    while let Some(header) = data.get(offset..offset + 8) {
+        let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        if &header[4..8] == b"acTL" {
+            return data.get(offset + 12..offset + 16).map_or(0, |plays| {
+                u32::from_be_bytes([plays[0], plays[1], plays[2], plays[3]])
+            });
+        }
+        offset += 12 + length;
+    }
+    0
+}
@@ -0,0 +1,286 @@
+use rayon::prelude::*;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use super::{
+    CancelToken, FormatGroup, MemoryBudget, estimate_image_memory, optimize_cached, optimize_image,
+};
+use crate::cli::Cli;
+use crate::file_ops::OptimizationCache;
+use crate::reporting::FileResult;
+
+/// Optimizes a batch of images, sequentially or in parallel, reporting each result.
+///
+/// Files are processed in parallel with rayon unless `--no-parallel` is set. After each
+/// file `on_progress` is called with its result, from whichever worker thread handled
+/// it, so the callback must be `Send + Sync`. Failures are reported through the
+/// callback rather than returned; with `--fail-fast` the first failure stops the batch.
+/// Once `cancel` is set no further files are started. With `--memory-limit`, each file
+/// first reserves its estimated memory from a shared [`MemoryBudget`] and holds it until
+/// its result is written, so a run of huge images is throttled.
+///
+/// With `--concurrency-per-format`, the files are split into [`FormatGroup`]s that run
+/// side by side: each configured format in its own pool of the configured width, all
+/// other formats in the shared pool. A slow format then cannot take every worker, and
+/// `--fail-fast` or cancellation still stops all groups.
+///
+/// # Arguments
+///
+/// * `files` - Images to optimize
+/// * `args` - CLI configuration used for every file
+/// * `input_dir` - Base input directory used to mirror paths into the output directory
+/// * `cache` - Optional `--cache` manifest shared between worker threads
+/// * `cancel` - Token that stops the batch from starting new files
+/// * `on_progress` - Called once for every processed file
+///
+/// # Returns
+///
+/// Returns `true` if every file was processed, or `false` if the batch stopped early
+/// because of `--fail-fast` or cancellation.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use clap::Parser;
+/// use std::path::{Path, PathBuf};
+/// use image_optimizer::cli::Cli;
+/// use image_optimizer::optimization::{CancelToken, optimize_batch};
+///
+/// let args = Cli::parse_from(["image-optimizer"]);
+/// let files = vec![PathBuf::from("a.png"), PathBuf::from("b.jpg")];
+/// optimize_batch(files, &args, Path::new("."), None, &CancelToken::default(), |result| {
+///     println!("{}: {:?}", result.path.display(), result.status);
+/// });
+/// ```
+pub fn optimize_batch<F>(
+    files: Vec<PathBuf>,
+    args: &Cli,
+    input_dir: &Path,
+    cache: Option<&Mutex<OptimizationCache>>,
+    cancel: &CancelToken,
+    on_progress: F,
+) -> bool
+where
+    F: Fn(&FileResult) + Send + Sync,
+{
+    let budget = args
+        .memory_limit
+        .map(|megabytes| MemoryBudget::new(megabytes.get().saturating_mul(1024 * 1024)));
+    // Set by --fail-fast so groups running in other pools stop as well
+    let stopped = AtomicBool::new(false);
+    let process = |image_path: PathBuf| {
+        // Files waiting for memory must still notice a cancellation once they get it
+        let _permit = budget
+            .as_ref()
+            .map(|budget| budget.acquire(estimate_image_memory(&image_path)));
+        if cancel.is_cancelled() || stopped.load(Ordering::Relaxed) {
+            return Err(());
+        }
+
+        let result = cache.map_or_else(
+            || optimize_image(&image_path, args, input_dir).map(Some),
+            |cache| optimize_cached(&image_path, args, input_dir, cache),
+        );
+        let file_result = match result {
+            Ok(Some(outcome)) => FileResult::from_outcome(&image_path, outcome),
+            Ok(None) => FileResult::cached(&image_path),
+            Err(e) => FileResult::from_error(&image_path, &e.into()),
+        };
+        on_progress(&file_result);
+
+        // Stops the batch: rayon's try_for_each short-circuits on the first Err
+        if args.fail_fast && file_result.is_failure() {
+            stopped.store(true, Ordering::Relaxed);
+            return Err(());
+        }
+        Ok(())
+    };
+
+    if args.no_parallel {
+        files.into_iter().try_for_each(process).is_ok()
+    } else if args.concurrency_per_format.is_empty() {
+        files.into_par_iter().try_for_each(process).is_ok()
+    } else {
+        run_format_groups(FormatGroup::split(files, args), &process)
+    }
+}
+
+/// Runs each group on its own thread, in a pool of the group's width, and returns
+/// whether every group completed.
+fn run_format_groups<P>(groups: Vec<FormatGroup>, process: &P) -> bool
+where
+    P: Fn(PathBuf) -> Result<(), ()> + Sync,
+{
+    std::thread::scope(|scope| {
+        // Collected so every group is started before the first one is joined
+        #[allow(clippy::needless_collect)]
+        let handles: Vec<_> = groups
+            .into_iter()
+            .map(|group| {
+                scope.spawn(move || {
+                    let run = || group.files.into_par_iter().try_for_each(process);
+                    // Without a dedicated pool the group still runs, on the shared one
+                    match group.width.map(|width| {
+                        rayon::ThreadPoolBuilder::new()
+                            .num_threads(width.get())
+                            .build()
+                    }) {
+                        Some(Ok(pool)) => pool.install(run),
+                        _ => run(),
+                    }
+                })
+            })
+            .collect();
+        handles.into_iter().fold(true, |completed, handle| {
+            let group_completed = handle
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+                .is_ok();
+            completed && group_completed
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reporting::FileStatus;
+    use clap::Parser;
+    use std::fs;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_callback_runs_once_per_file() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_batch_callback");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let mut files = Vec::new();
+        for index in 0..4 {
+            let path = temp_dir.join(format!("gradient_{index}.png"));
+            let img = image::RgbImage::from_fn(32, 32, |x, y| {
+                #[allow(clippy::cast_possible_truncation)]
+                image::Rgb([(x * 8) as u8, (y * 8) as u8, 128])
+            });
+            img.save_with_format(&path, image::ImageFormat::Png)
+                .unwrap();
+            files.push(path);
+        }
+        let corrupt = temp_dir.join("corrupt.png");
+        fs::write(&corrupt, "not a png").unwrap();
+        files.push(corrupt);
+
+        let args = Cli::parse_from(["image-optimizer", "--no-zopfli"]);
+        let calls = AtomicUsize::new(0);
+        let invalid = AtomicUsize::new(0);
+        let completed = optimize_batch(
+            files,
+            &args,
+            &temp_dir,
+            None,
+            &CancelToken::default(),
+            |result| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                if result.status == FileStatus::Invalid {
+                    invalid.fetch_add(1, Ordering::SeqCst);
+                }
+            },
+        );
+
+        assert!(completed);
+        assert_eq!(calls.load(Ordering::SeqCst), 5);
+        assert_eq!(invalid.load(Ordering::SeqCst), 1);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_cancelled_batch_starts_no_files() {
+        let args = Cli::parse_from(["image-optimizer"]);
+        let cancel = CancelToken::default();
+        cancel.cancel();
+        let calls = AtomicUsize::new(0);
+
+        let completed = optimize_batch(
+            vec![PathBuf::from("a.png"), PathBuf::from("b.png")],
+            &args,
+            Path::new("."),
+            None,
+            &cancel,
+            |_| {
+                calls.fetch_add(1, Ordering::SeqCst);
+            },
+        );
+
+        assert!(!completed);
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_per_format_pools_match_single_pool_totals() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_batch_per_format");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        let mut files = Vec::new();
+        for index in 0..6_u32 {
+            let img = image::RgbImage::from_fn(48, 48, |x, y| {
+                #[allow(clippy::cast_possible_truncation)]
+                image::Rgb([(x * 5) as u8, (y * 5) as u8, (index * 40) as u8])
+            });
+            let (extension, format) = if cfg!(feature = "jpeg") && index % 2 == 0 {
+                ("jpg", image::ImageFormat::Jpeg)
+            } else {
+                ("png", image::ImageFormat::Png)
+            };
+            let path = temp_dir.join(format!("image_{index}.{extension}"));
+            img.save_with_format(&path, format).unwrap();
+            files.push(path);
+        }
+
+        let run = |output: &str, extra: &[&str]| {
+            let output_dir = temp_dir.join(output);
+            let mut command_line = vec![
+                "image-optimizer",
+                "--no-zopfli",
+                "-o",
+                output_dir.to_str().unwrap(),
+            ];
+            command_line.extend_from_slice(extra);
+            let args = Cli::parse_from(command_line);
+            let results = Mutex::new(Vec::new());
+            let completed = optimize_batch(
+                files.clone(),
+                &args,
+                &temp_dir,
+                None,
+                &CancelToken::default(),
+                |result| {
+                    results.lock().unwrap().push((
+                        result.path.clone(),
+                        result.status,
+                        result.original_size,
+                        result.optimized_size,
+                    ));
+                },
+            );
+            assert!(completed);
+            let mut results = results.into_inner().unwrap();
+            results.sort_by(|a, b| a.0.cmp(&b.0));
+            results
+        };
+
+        let shared = run("shared", &[]);
+        let grouped = run(
+            "grouped",
+            &[
+                "--concurrency-per-format",
+                "png=1",
+                "--concurrency-per-format",
+                "jpeg=2",
+            ],
+        );
+        assert_eq!(shared.len(), files.len());
+        assert_eq!(grouped, shared);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
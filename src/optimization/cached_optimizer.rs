@@ -0,0 +1,149 @@
+use std::path::Path;
+use std::sync::Mutex;
+
+use super::{ImageOutcome, OptimizeError, optimize_image, resolved_output_path};
+use crate::cli::Cli;
+use crate::file_ops::{OptimizationCache, hash_file};
+
+/// Optimizes an image unless the cache records its current content as already optimized.
+///
+/// The file is hashed before optimization. On a cache hit no decoding or encoding
+/// happens at all. After a successful optimization the hash of the resulting file is
+/// recorded: the new content for in-place runs, or the input content when writing to
+/// an output directory (the input is what the next run will see). Files that could not
+/// be made smaller, or not by `--prefer-smaller-than-original-by`, are recorded too, so
+/// they are not retried with the same settings.
+/// A hit only counts while the output written by the earlier run still exists, so a
+/// deleted output is written again; the several outputs of `--sizes` are not checked.
+/// With `--force` the cache is not consulted but is still updated.
+///
+/// # Arguments
+///
+/// * `input_path` - Path to the image to optimize
+/// * `args` - CLI configuration
+/// * `input_dir` - Base input directory used to mirror paths into the output directory
+/// * `cache` - Cache shared between worker threads
+///
+/// # Returns
+///
+/// Returns the outcome when the image was processed, or `None` when it was skipped
+/// because of a cache hit.
+///
+/// # Errors
+///
+/// Returns [`OptimizeError::Io`] if hashing the file fails, or the error of
+/// [`optimize_image`].
+pub fn optimize_cached(
+    input_path: &Path,
+    args: &Cli,
+    input_dir: &Path,
+    cache: &Mutex<OptimizationCache>,
+) -> Result<Option<ImageOutcome>, OptimizeError> {
+    let input_hash = hash_file(input_path).map_err(OptimizeError::io)?;
+    let is_cached = cache.lock().is_ok_and(|cache| cache.contains(&input_hash));
+    if is_cached && !args.force && output_exists(input_path, args, input_dir) {
+        return Ok(None);
+    }
+
+    let outcome = match optimize_image(input_path, args, input_dir) {
+        Ok(outcome) => outcome,
+        Err(error @ OptimizeError::BelowThreshold { .. }) => {
+            if let Ok(mut cache) = cache.lock() {
+                cache.insert(input_hash);
+            }
+            return Err(error);
+        }
+        Err(error) => return Err(error),
+    };
+
+    let optimized_hash = if args.is_in_place() && outcome.saved_bytes() > 0 {
+        hash_file(input_path).map_err(OptimizeError::io)?
+    } else {
+        input_hash
+    };
+    if let Ok(mut cache) = cache.lock() {
+        cache.insert(optimized_hash);
+    }
+
+    Ok(Some(outcome))
+}
+
+/// Returns whether the output of `input_path` exists, or `true` when it is optimized in
+/// place. A path that cannot be resolved counts as missing, so the error is reported by
+/// [`optimize_image`].
+fn output_exists(input_path: &Path, args: &Cli, input_dir: &Path) -> bool {
+    resolved_output_path(input_path, args, input_dir)
+        .is_ok_and(|output_path| output_path.is_none_or(|output_path| output_path.exists()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use std::fs;
+
+    #[test]
+    fn test_second_run_is_a_cache_hit() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_cached_second_run");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let image_path = temp_dir.join("gradient.png");
+        let img = image::RgbImage::from_fn(64, 64, |x, y| {
+            #[allow(clippy::cast_possible_truncation)]
+            image::Rgb([(x * 4) as u8, (y * 4) as u8, 128])
+        });
+        img.save_with_format(&image_path, image::ImageFormat::Png)
+            .unwrap();
+
+        let args = Cli::parse_from(["image-optimizer", "--no-zopfli"]);
+        let cache = Mutex::new(OptimizationCache::default());
+
+        let first = optimize_cached(&image_path, &args, &temp_dir, &cache).unwrap();
+        assert!(first.is_some());
+        let optimized = fs::read(&image_path).unwrap();
+
+        let second = optimize_cached(&image_path, &args, &temp_dir, &cache).unwrap();
+        assert_eq!(second, None);
+        assert_eq!(fs::read(&image_path).unwrap(), optimized);
+
+        let changed = image::RgbImage::from_pixel(64, 64, image::Rgb([10, 20, 30]));
+        changed
+            .save_with_format(&image_path, image::ImageFormat::Png)
+            .unwrap();
+        let third = optimize_cached(&image_path, &args, &temp_dir, &cache).unwrap();
+        assert!(third.is_some());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_deleted_output_is_written_again() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_cached_deleted_output");
+        let input_dir = temp_dir.join("in");
+        let output_dir = temp_dir.join("out");
+        fs::create_dir_all(&input_dir).unwrap();
+        let image_path = input_dir.join("gradient.png");
+        let img = image::RgbImage::from_fn(64, 64, |x, y| {
+            #[allow(clippy::cast_possible_truncation)]
+            image::Rgb([(x * 4) as u8, (y * 4) as u8, 128])
+        });
+        img.save_with_format(&image_path, image::ImageFormat::Png)
+            .unwrap();
+
+        let output = output_dir.to_str().unwrap();
+        let args = Cli::parse_from(["image-optimizer", "-o", output, "--no-zopfli"]);
+        let cache = Mutex::new(OptimizationCache::default());
+        let output_path = output_dir.join("gradient.png");
+
+        let first = optimize_cached(&image_path, &args, &input_dir, &cache).unwrap();
+        assert!(first.is_some());
+        let second = optimize_cached(&image_path, &args, &input_dir, &cache).unwrap();
+        assert_eq!(second, None);
+
+        fs::remove_file(&output_path).unwrap();
+        let third = optimize_cached(&image_path, &args, &input_dir, &cache).unwrap();
+        assert!(third.is_some());
+        assert!(output_path.exists());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
@@ -0,0 +1,65 @@
+use anyhow::{Context, Result};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::file_ops::remove_active_temp_files;
+
+/// Exit code used when the run was interrupted with Ctrl-C (128 + SIGINT).
+pub const INTERRUPTED_EXIT_CODE: u8 = 130;
+
+/// Shared flag that stops a batch from dispatching new files once set.
+///
+/// The batch loop checks [`is_cancelled`](Self::is_cancelled) before starting each
+/// file; files already being optimized are allowed to finish, so originals are only
+/// ever replaced by a complete optimized file through the final rename.
+///
+/// ## Examples
+///
+/// ```rust
+/// use image_optimizer::optimization::CancelToken;
+///
+/// let cancel = CancelToken::default();
+/// let worker_view = cancel.clone();
+/// cancel.cancel();
+/// assert!(worker_view.is_cancelled());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    /// Cancels the token when the user presses Ctrl-C.
+    ///
+    /// The first Ctrl-C lets in-flight files finish. A second one exits immediately
+    /// with code 130 after removing the temporary files that are still being written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the signal handler cannot be installed, for example because
+    /// another handler was already installed in this process.
+    pub fn cancel_on_interrupt(&self) -> Result<()> {
+        let cancelled = Arc::clone(&self.cancelled);
+        ctrlc::set_handler(move || {
+            if cancelled.swap(true, Ordering::SeqCst) {
+                remove_active_temp_files();
+                std::process::exit(i32::from(INTERRUPTED_EXIT_CODE));
+            }
+            eprintln!(
+                "\nInterrupted: finishing files in progress (press Ctrl-C again to quit now)"
+            );
+        })
+        .context("Failed to install the Ctrl-C handler")
+    }
+
+    /// Stops the batch from starting any further files.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns `true` once the batch has been cancelled.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
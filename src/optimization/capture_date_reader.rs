@@ -0,0 +1,216 @@
+use chrono::{DateTime, Local, NaiveDateTime};
+use image::{ImageDecoder, ImageReader};
+use std::fs;
+use std::path::Path;
+
+/// EXIF tag of the pointer from IFD0 to the Exif sub-IFD.
+const EXIF_IFD_POINTER: u16 = 0x8769;
+/// EXIF tag of the date and time the photo was taken.
+const DATE_TIME_ORIGINAL: u16 = 0x9003;
+/// IFD0 tag of the date and time the file was last changed by the camera or an editor.
+const DATE_TIME: u16 = 0x0132;
+
+/// Returns when an image was captured, for `--organize-by-date`.
+///
+/// The EXIF `DateTimeOriginal` tag is used when the image has one (JPEG, PNG and WebP
+/// can carry EXIF data), then the `DateTime` tag. Files without a readable EXIF date,
+/// such as screenshots or SVGs, fall back to their modification time in the local time
+/// zone. EXIF dates have no time zone and are used as they are.
+///
+/// # Arguments
+///
+/// * `path` - Path to the image file
+///
+/// # Returns
+///
+/// Returns the capture date, or `None` if the file has neither an EXIF date nor a
+/// readable modification time.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::path::Path;
+/// use image_optimizer::optimization::read_capture_date;
+///
+/// if let Some(date) = read_capture_date(Path::new("photo.jpg")) {
+///     println!("Taken in {}", date.format("%B %Y"));
+/// }
+/// ```
+#[must_use]
+pub fn read_capture_date(path: &Path) -> Option<NaiveDateTime> {
+    read_exif_date(path).or_else(|| {
+        let modified = fs::metadata(path).ok()?.modified().ok()?;
+        Some(DateTime::<Local>::from(modified).naive_local())
+    })
+}
+
+/// Reads the EXIF capture date without decoding the pixels.
+fn read_exif_date(path: &Path) -> Option<NaiveDateTime> {
+    let mut decoder = ImageReader::open(path)
+        .ok()?
+        .with_guessed_format()
+        .ok()?
+        .into_decoder()
+        .ok()?;
+    parse_exif_date(&decoder.exif_metadata().ok()??)
+}
+
+/// Finds the capture date in a raw EXIF (TIFF) block.
+fn parse_exif_date(exif: &[u8]) -> Option<NaiveDateTime> {
+    let exif = exif.strip_prefix(b"Exif\0\0").unwrap_or(exif);
+    let big_endian = match exif.get(0..2)? {
+        b"MM" => true,
+        b"II" => false,
+        _ => return None,
+    };
+    let read_u16 = |offset: usize| {
+        let bytes: [u8; 2] = exif.get(offset..offset + 2)?.try_into().ok()?;
+        Some(if big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    };
+    let read_u32 = |offset: usize| {
+        let bytes: [u8; 4] = exif.get(offset..offset + 4)?.try_into().ok()?;
+        let value = if big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        };
+        usize::try_from(value).ok()
+    };
+    // Returns the offset of the value field of `tag` in the IFD at `ifd`
+    let find_value = |ifd: usize, tag: u16| {
+        let count = usize::from(read_u16(ifd)?);
+        (0..count)
+            .map(|index| ifd + 2 + index * 12)
+            .find(|&entry| read_u16(entry) == Some(tag))
+            .map(|entry| entry + 8)
+    };
+
+    // Dates are 20-byte ASCII strings, stored outside the entry; unknown dates are
+    // written as blanks or zeros and do not parse
+    let read_date = |value: usize| {
+        let offset = read_u32(value)?;
+        let text = std::str::from_utf8(exif.get(offset..offset + 19)?).ok()?;
+        NaiveDateTime::parse_from_str(text, "%Y:%m:%d %H:%M:%S").ok()
+    };
+
+    let ifd0 = read_u32(4)?;
+    find_value(ifd0, EXIF_IFD_POINTER)
+        .and_then(read_u32)
+        .and_then(|exif_ifd| find_value(exif_ifd, DATE_TIME_ORIGINAL))
+        .and_then(read_date)
+        .or_else(|| find_value(ifd0, DATE_TIME).and_then(read_date))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a big-endian EXIF block with `DateTimeOriginal` in the Exif sub-IFD and
+    /// `DateTime` in IFD0.
+    fn exif_with_dates(original: &str, modified: &str) -> Vec<u8> {
+        let mut exif = b"MM\0\x2a\0\0\0\x08".to_vec();
+        // IFD0 at 8: two entries, next IFD, then the DateTime string at 38
+        exif.extend_from_slice(&2u16.to_be_bytes());
+        exif.extend_from_slice(&DATE_TIME.to_be_bytes());
+        exif.extend_from_slice(&[0, 2, 0, 0, 0, 20, 0, 0, 0, 38]);
+        exif.extend_from_slice(&EXIF_IFD_POINTER.to_be_bytes());
+        exif.extend_from_slice(&[0, 4, 0, 0, 0, 1, 0, 0, 0, 58]);
+        exif.extend_from_slice(&[0; 4]);
+        exif.extend_from_slice(modified.as_bytes());
+        exif.push(0);
+        // Exif sub-IFD at 58 with the DateTimeOriginal string at 76
+        exif.extend_from_slice(&1u16.to_be_bytes());
+        exif.extend_from_slice(&DATE_TIME_ORIGINAL.to_be_bytes());
+        exif.extend_from_slice(&[0, 2, 0, 0, 0, 20, 0, 0, 0, 76]);
+        exif.extend_from_slice(&[0; 4]);
+        exif.extend_from_slice(original.as_bytes());
+        exif.push(0);
+        exif
+    }
+
+    #[test]
+    fn test_date_time_original_wins() {
+        let exif = exif_with_dates("2023:05:14 09:30:00", "2024:01:02 03:04:05");
+        let date = parse_exif_date(&exif).unwrap();
+        assert_eq!(date.to_string(), "2023-05-14 09:30:00");
+
+        let mut prefixed = b"Exif\0\0".to_vec();
+        prefixed.extend_from_slice(&exif);
+        assert_eq!(parse_exif_date(&prefixed), Some(date));
+    }
+
+    #[test]
+    fn test_falls_back_to_date_time_tag() {
+        let exif = exif_with_dates("0000:00:00 00:00:00", "2024:01:02 03:04:05");
+        assert_eq!(
+            parse_exif_date(&exif).unwrap().to_string(),
+            "2024-01-02 03:04:05"
+        );
+        assert_eq!(parse_exif_date(b"not exif"), None);
+    }
+
+    #[test]
+    fn test_file_without_exif_uses_modification_time() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_capture_date_mtime");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let path = temp_dir.join("plain.png");
+        image::RgbImage::new(4, 4).save(&path).unwrap();
+        let modified = filetime::FileTime::from_unix_time(1_600_000_000, 0);
+        filetime::set_file_mtime(&path, modified).unwrap();
+
+        let expected = DateTime::<Local>::from(
+            std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_600_000_000),
+        )
+        .naive_local();
+        assert_eq!(read_capture_date(&path), Some(expected));
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[cfg(feature = "jpeg")]
+    #[test]
+    fn test_jpeg_is_organized_by_exif_date() {
+        use crate::cli::Cli;
+        use clap::Parser;
+        use img_parts::ImageEXIF;
+
+        let temp_dir = std::env::temp_dir().join("image_optimizer_organize_by_date");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let input_dir = temp_dir.join("camera");
+        let output_dir = temp_dir.join("out");
+        fs::create_dir_all(&input_dir).unwrap();
+        let image_path = input_dir.join("photo.jpg");
+
+        let img = image::RgbImage::from_fn(32, 32, |x, y| {
+            #[allow(clippy::cast_possible_truncation)]
+            image::Rgb([(x * 8) as u8, (y * 8) as u8, 90])
+        });
+        let mut data = Vec::new();
+        image::codecs::jpeg::JpegEncoder::new_with_quality(&mut data, 95)
+            .encode_image(&img)
+            .unwrap();
+        let mut jpeg = img_parts::jpeg::Jpeg::from_bytes(data.into()).unwrap();
+        jpeg.set_exif(Some(
+            exif_with_dates("2023:05:14 09:30:00", "2024:01:02 03:04:05").into(),
+        ));
+        jpeg.encoder()
+            .write_to(fs::File::create(&image_path).unwrap())
+            .unwrap();
+
+        let args = Cli::parse_from([
+            "image-optimizer",
+            "--organize-by-date",
+            "%Y/%m",
+            "-o",
+            output_dir.to_str().unwrap(),
+        ]);
+        crate::optimization::optimize_image(&image_path, &args, &input_dir).unwrap();
+        assert!(output_dir.join("2023/05/photo.jpg").is_file());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
@@ -0,0 +1,35 @@
+use std::borrow::Cow;
+use std::path::Path;
+
+use super::read_capture_date;
+use crate::cli::Cli;
+
+/// Returns the output directory of `input_path` with its `--organize-by-date`
+/// subdirectory appended, e.g. `optimized/2023/05` for `--organize-by-date %Y/%m`.
+///
+/// The date comes from [`read_capture_date`]. Without `--organize-by-date`, or for a file
+/// whose date cannot be read at all, `output_dir` is returned unchanged.
+///
+/// # Examples
+///
+/// ```rust
+/// use clap::Parser;
+/// use std::path::Path;
+/// use image_optimizer::cli::Cli;
+/// use image_optimizer::optimization::date_output_dir;
+///
+/// let args = Cli::parse_from(["image-optimizer", "-o", "optimized"]);
+/// let output_dir = date_output_dir(Path::new("optimized"), Path::new("photo.jpg"), &args);
+/// assert_eq!(output_dir, Path::new("optimized"));
+/// ```
+#[must_use]
+pub fn date_output_dir<'a>(output_dir: &'a Path, input_path: &Path, args: &Cli) -> Cow<'a, Path> {
+    let date = args
+        .organize_by_date
+        .as_ref()
+        .and_then(|pattern| Some((pattern, read_capture_date(input_path)?)));
+    match date {
+        Some((pattern, date)) => Cow::Owned(output_dir.join(date.format(pattern).to_string())),
+        None => Cow::Borrowed(output_dir),
+    }
+}
@@ -0,0 +1,206 @@
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::{CancelToken, optimize_batch, resolve_output_path};
+use crate::cli::{Cli, DedupMode};
+use crate::file_ops::{
+    OptimizationCache, create_backup, find_duplicates, link_or_copy, restore_file_attributes,
+    should_keep_existing_output,
+};
+use crate::reporting::{FileResult, FileStatus};
+
+/// Optimizes a batch of images once per distinct content with `--dedup link` or `copy`.
+///
+/// Byte-identical inputs are grouped with [`find_duplicates`]; only the first file of
+/// each group is handed to [`optimize_batch`]. Once it is done, its result is hard-linked
+/// or copied to the other files of the group and each duplicate is reported with a
+/// [`FileResult::duplicate`] result:
+///
+/// - With an output directory, the optimized output is linked or copied to each
+///   duplicate's own output path (existing outputs are protected as usual)
+/// - In place, duplicates are only replaced when the first file actually got smaller,
+///   after being backed up like any other file
+///
+/// Copies get the permissions (and, unless `--no-preserve-timestamps` is set, the
+/// timestamps) of the duplicate they replace; hard links share everything with the
+/// optimized file. When the first file could not be optimized, its duplicates share that
+/// result untouched.
+///
+/// # Arguments
+///
+/// * `files` - Images to optimize
+/// * `args` - CLI configuration used for every file
+/// * `input_dir` - Base input directory used to mirror paths into the output directory
+/// * `cache` - Optional `--cache` manifest shared between worker threads
+/// * `cancel` - Token that stops the batch from starting new files
+/// * `on_progress` - Called once for every file, duplicates included
+///
+/// # Returns
+///
+/// Returns `true` if every file was processed, or `false` if the batch stopped early.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use clap::Parser;
+/// use std::path::{Path, PathBuf};
+/// use image_optimizer::cli::Cli;
+/// use image_optimizer::optimization::{CancelToken, optimize_deduplicated};
+///
+/// let args = Cli::parse_from(["image-optimizer", "--dedup", "link"]);
+/// let files = vec![PathBuf::from("logo.png"), PathBuf::from("logo-copy.png")];
+/// optimize_deduplicated(files, &args, Path::new("."), None, &CancelToken::default(), |result| {
+///     println!("{}: {:?}", result.path.display(), result.duplicate_of);
+/// });
+/// ```
+pub fn optimize_deduplicated<F>(
+    files: Vec<PathBuf>,
+    args: &Cli,
+    input_dir: &Path,
+    cache: Option<&Mutex<OptimizationCache>>,
+    cancel: &CancelToken,
+    on_progress: F,
+) -> bool
+where
+    F: Fn(&FileResult) + Send + Sync,
+{
+    let groups = find_duplicates(&files);
+    let duplicates: HashSet<&PathBuf> = groups.iter().flat_map(|group| &group[1..]).collect();
+    let first_files: HashMap<&PathBuf, &[PathBuf]> = groups
+        .iter()
+        .map(|group| (&group[0], &group[1..]))
+        .collect();
+    let files: Vec<PathBuf> = files
+        .into_iter()
+        .filter(|path| !duplicates.contains(path))
+        .collect();
+
+    optimize_batch(files, args, input_dir, cache, cancel, |result| {
+        on_progress(result);
+        for duplicate in first_files.get(&result.path).copied().unwrap_or_default() {
+            let shared = share_result(result, duplicate, args, input_dir)
+                .unwrap_or_else(|e| FileResult::from_error(duplicate, &e));
+            on_progress(&shared);
+        }
+    })
+}
+
+/// Gives `duplicate` the result of the identical file behind `original`.
+fn share_result(
+    original: &FileResult,
+    duplicate: &Path,
+    args: &Cli,
+    input_dir: &Path,
+) -> Result<FileResult> {
+    let result = FileResult::duplicate(duplicate, original);
+    if !matches!(
+        original.status,
+        FileStatus::Optimized
+            | FileStatus::SkippedLarger
+            | FileStatus::SkippedThreshold
+            | FileStatus::Cached
+    ) {
+        return Ok(result);
+    }
+
+    let hard_link = args.dedup == Some(DedupMode::Link);
+    let metadata = fs::metadata(duplicate)?;
+    let target = if let Some(ref output_dir) = args.output {
+        let source = resolve_output_path(output_dir, &original.path, args, input_dir)?;
+        let target = resolve_output_path(output_dir, duplicate, args, input_dir)?;
+        // A cache hit may refer to an output that has been removed since
+        if !source.is_file() {
+            return Ok(result);
+        }
+        if should_keep_existing_output(&target, &metadata, args.skip_existing, args.force) {
+            return Ok(FileResult::existing(duplicate));
+        }
+        link_or_copy(&source, &target, hard_link)?;
+        target
+    } else {
+        // In place, a duplicate is already identical to a file that was left unchanged
+        if original.status != FileStatus::Optimized {
+            return Ok(result);
+        }
+        if args.backup || args.backup_dir.is_some() {
+            create_backup(
+                duplicate,
+                args.backup_dir.as_deref(),
+                input_dir,
+                args.backup_mode,
+            )?;
+        }
+        link_or_copy(&original.path, duplicate, hard_link)?;
+        duplicate.to_path_buf()
+    };
+
+    if !hard_link {
+        restore_file_attributes(&target, &metadata, !args.no_preserve_timestamps)?;
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_identical_files_are_encoded_once() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_dedup_once");
+        let input_dir = temp_dir.join("input");
+        let output_dir = temp_dir.join("output");
+        fs::create_dir_all(&input_dir).unwrap();
+        let img = image::RgbImage::from_fn(48, 48, |x, y| {
+            #[allow(clippy::cast_possible_truncation)]
+            image::Rgb([(x * 5) as u8, (y * 5) as u8, 90])
+        });
+        let first = input_dir.join("a.png");
+        let second = input_dir.join("b.png");
+        img.save_with_format(&first, image::ImageFormat::Png)
+            .unwrap();
+        fs::copy(&first, &second).unwrap();
+
+        let output = output_dir.to_str().unwrap();
+        let args = Cli::parse_from([
+            "image-optimizer",
+            "--no-zopfli",
+            "-o",
+            output,
+            "--dedup",
+            "copy",
+        ]);
+        let encoded = AtomicUsize::new(0);
+        let shared = AtomicUsize::new(0);
+        let completed = optimize_deduplicated(
+            vec![first, second.clone()],
+            &args,
+            &input_dir,
+            None,
+            &CancelToken::default(),
+            |result| {
+                assert!(!result.is_failure(), "{result:?}");
+                if result.duplicate_of.is_some() {
+                    assert_eq!(result.path, second);
+                    shared.fetch_add(1, Ordering::SeqCst);
+                } else {
+                    encoded.fetch_add(1, Ordering::SeqCst);
+                }
+            },
+        );
+
+        assert!(completed);
+        assert_eq!(encoded.load(Ordering::SeqCst), 1);
+        assert_eq!(shared.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            fs::read(output_dir.join("a.png")).unwrap(),
+            fs::read(output_dir.join("b.png")).unwrap()
+        );
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
@@ -0,0 +1,177 @@
+use image::DynamicImage;
+use std::path::Path;
+
+#[cfg(feature = "svg")]
+use super::svg_optimizer;
+use super::{ImageFormat, OptimizeError};
+#[cfg(feature = "webp")]
+use super::{is_animated_webp, webp_optimizer, webp_stripper};
+#[cfg(feature = "jpeg")]
+use super::{jpeg_optimizer, jpeg_stripper};
+#[cfg(feature = "png")]
+use super::{png_optimizer, png_stripper};
+use crate::cli::Cli;
+
+/// Writes the optimized image with the encoder for `format`.
+///
+/// Each encoder is behind a Cargo feature of the same name (`jpeg`, `png`, `webp`,
+/// `svg`). For a format whose feature was not compiled in this returns
+/// [`OptimizeError::FormatDisabled`] without touching any file.
+///
+/// With `--strip-only`, raster formats are not re-encoded: only their metadata is
+/// removed and the compressed pixel data is copied unchanged.
+///
+/// # Arguments
+///
+/// * `format` - Output format
+/// * `input_path` - Original file, used by encoders that work on the source bytes
+/// * `output_path` - Where the optimized image is written
+/// * `args` - CLI configuration with quality and compression settings
+/// * `img` - Decoded (and possibly resized) image, if one was needed
+///
+/// # Errors
+///
+/// Returns [`OptimizeError::FormatDisabled`] for a format that was compiled out and
+/// [`OptimizeError::Encode`] if the encoder fails.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use clap::Parser;
+/// use std::path::Path;
+/// use image_optimizer::cli::Cli;
+/// use image_optimizer::optimization::{ImageFormat, encode_image};
+///
+/// # fn example() -> Result<(), image_optimizer::optimization::OptimizeError> {
+/// let args = Cli::parse_from(["image-optimizer"]);
+/// encode_image(ImageFormat::Png, Path::new("in.png"), Path::new("out.png"), &args, None)?;
+/// # Ok(())
+/// # }
+/// ```
+#[cfg_attr(
+    not(all(feature = "jpeg", feature = "png", feature = "webp", feature = "svg")),
+    allow(unused_variables, clippy::needless_pass_by_value)
+)]
+pub fn encode_image(
+    format: ImageFormat,
+    input_path: &Path,
+    output_path: &Path,
+    args: &Cli,
+    img: Option<DynamicImage>,
+) -> Result<(), OptimizeError> {
+    match format {
+        #[cfg(feature = "jpeg")]
+        ImageFormat::Jpeg if args.strip_only => {
+            jpeg_stripper::strip_jpeg(input_path, output_path, args).map_err(OptimizeError::Encode)
+        }
+        #[cfg(feature = "png")]
+        ImageFormat::Png if args.strip_only => {
+            png_stripper::strip_png(input_path, output_path, args).map_err(OptimizeError::Encode)
+        }
+        #[cfg(feature = "webp")]
+        ImageFormat::Webp if args.strip_only => {
+            webp_stripper::strip_webp(input_path, output_path, args).map_err(OptimizeError::Encode)
+        }
+        #[cfg(feature = "jpeg")]
+        ImageFormat::Jpeg => jpeg_optimizer::optimize_jpeg(input_path, output_path, args, img)
+            .map_err(OptimizeError::Encode),
+        #[cfg(feature = "png")]
+        ImageFormat::Png => png_optimizer::optimize_png(input_path, output_path, args, img)
+            .map_err(OptimizeError::Encode),
+        #[cfg(feature = "webp")]
+        ImageFormat::Webp => webp_optimizer::optimize_webp(input_path, output_path, args, img)
+            .map_err(OptimizeError::Encode),
+        #[cfg(feature = "svg")]
+        ImageFormat::Svg => svg_optimizer::optimize_svg(input_path, output_path, args, img)
+            .map_err(OptimizeError::Encode),
+        #[allow(unreachable_patterns)]
+        disabled => Err(OptimizeError::FormatDisabled(disabled)),
+    }
+}
+
+/// Returns whether the encoder for `format` decodes `input_path` like
+/// [`decode_image`](super::decode_image).
+///
+/// If so, the image decoded to validate the input is passed to [`encode_image`] instead
+/// of being decoded a second time.
+///
+/// PNG and SVG are optimized from their source bytes, and `--strip-only` and
+/// `--jpeg-lossless` keep the compressed data. Animated WebP and CMYK JPEG inputs are
+/// decoded by the encoder itself, since the `image` crate keeps neither their frames nor
+/// their color space.
+#[must_use]
+#[cfg_attr(
+    not(all(feature = "jpeg", feature = "webp")),
+    allow(unused_variables, clippy::missing_const_for_fn)
+)]
+pub fn reuses_decoded_pixels(format: ImageFormat, input_path: &Path, args: &Cli) -> bool {
+    if args.strip_only {
+        return false;
+    }
+    match format {
+        #[cfg(feature = "jpeg")]
+        ImageFormat::Jpeg => {
+            !args.jpeg_lossless
+                && std::fs::read(input_path).is_ok_and(|data| !jpeg_optimizer::is_cmyk_jpeg(&data))
+        }
+        #[cfg(feature = "webp")]
+        ImageFormat::Webp => !is_animated_webp(input_path),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn test_disabled_formats_report_missing_feature() {
+        let args = Cli::parse_from(["image-optimizer"]);
+        let output = std::env::temp_dir().join("image_optimizer_disabled_format.out");
+
+        for format in [
+            ImageFormat::Jpeg,
+            ImageFormat::Png,
+            ImageFormat::Webp,
+            ImageFormat::Svg,
+        ] {
+            if format.is_enabled() {
+                continue;
+            }
+            let error = encode_image(format, Path::new("input"), &output, &args, None).unwrap_err();
+            assert!(matches!(error, OptimizeError::FormatDisabled(disabled) if disabled == format));
+            assert!(error.to_string().contains(format.feature()));
+            assert!(!output.exists());
+        }
+    }
+
+    #[cfg(all(feature = "jpeg", feature = "webp"))]
+    #[test]
+    fn test_reuses_decoded_pixels_of_re_encoded_formats() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_reuse_decoded");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let img = image::RgbImage::from_pixel(8, 8, image::Rgb([200, 100, 50]));
+        let jpeg = temp_dir.join("photo.jpg");
+        img.save(&jpeg).unwrap();
+        let png = temp_dir.join("photo.png");
+        img.save(&png).unwrap();
+        let webp = temp_dir.join("photo.webp");
+        img.save(&webp).unwrap();
+
+        let args = Cli::parse_from(["image-optimizer"]);
+        assert!(reuses_decoded_pixels(ImageFormat::Jpeg, &jpeg, &args));
+        assert!(reuses_decoded_pixels(ImageFormat::Webp, &webp, &args));
+        assert!(!reuses_decoded_pixels(ImageFormat::Png, &png, &args));
+        let lossless = Cli::parse_from(["image-optimizer", "--jpeg-lossless"]);
+        assert!(!reuses_decoded_pixels(ImageFormat::Jpeg, &jpeg, &lossless));
+        let strip_only = Cli::parse_from(["image-optimizer", "--strip-only"]);
+        assert!(!reuses_decoded_pixels(
+            ImageFormat::Webp,
+            &webp,
+            &strip_only
+        ));
+
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
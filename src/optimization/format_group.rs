@@ -0,0 +1,121 @@
+use std::collections::BTreeMap;
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+
+use super::ImageFormat;
+use crate::cli::Cli;
+
+/// Files of a batch that share a worker pool under `--concurrency-per-format`.
+///
+/// Every format with a configured width gets its own group; all remaining files form
+/// one group without a width, which runs on the shared pool.
+///
+/// # Examples
+///
+/// ```rust
+/// use clap::Parser;
+/// use std::path::PathBuf;
+/// use image_optimizer::cli::Cli;
+/// use image_optimizer::optimization::{FormatGroup, ImageFormat};
+///
+/// let args = Cli::parse_from(["image-optimizer", "--concurrency-per-format", "png=2"]);
+/// let files = vec![PathBuf::from("a.png"), PathBuf::from("b.jpg"), PathBuf::from("c.png")];
+/// let groups = FormatGroup::split(files, &args);
+/// assert_eq!(groups[0].format, None);
+/// assert_eq!(groups[1].format, Some(ImageFormat::Png));
+/// assert_eq!(groups[1].files.len(), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatGroup {
+    /// Format of the files, or `None` for the group of formats without a width
+    pub format: Option<ImageFormat>,
+    /// Number of files optimized at the same time, or `None` for the shared pool
+    pub width: Option<NonZeroUsize>,
+    /// Files of the group, in batch order
+    pub files: Vec<PathBuf>,
+}
+
+impl FormatGroup {
+    /// Splits a batch into groups by the `--concurrency-per-format` widths in `args`.
+    ///
+    /// Groups are returned in a fixed order (the shared group first, then by format)
+    /// and empty groups are left out. Files keep their batch order within a group.
+    #[must_use]
+    pub fn split(files: Vec<PathBuf>, args: &Cli) -> Vec<Self> {
+        let mut groups: BTreeMap<Option<ImageFormat>, Vec<PathBuf>> = BTreeMap::new();
+        for file in files {
+            let format = ImageFormat::from_path(&file)
+                .filter(|&format| args.concurrency_for(format).is_some());
+            groups.entry(format).or_default().push(file);
+        }
+        groups
+            .into_iter()
+            .map(|(format, files)| Self {
+                format,
+                width: format.and_then(|format| args.concurrency_for(format)),
+                files,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn test_split_groups_configured_formats() {
+        let args = Cli::parse_from([
+            "image-optimizer",
+            "--concurrency-per-format",
+            "png=1",
+            "--concurrency-per-format",
+            "webp=3",
+            "--concurrency-per-format",
+            "png=2",
+        ]);
+        let files = ["a.png", "b.jpg", "c.webp", "d.PNG", "e.svg"]
+            .into_iter()
+            .map(PathBuf::from)
+            .collect();
+
+        let groups = FormatGroup::split(files, &args);
+        let summary: Vec<_> = groups
+            .iter()
+            .map(|group| {
+                (
+                    group.format,
+                    group.width.map(NonZeroUsize::get),
+                    group.files.len(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (None, None, 2),
+                (Some(ImageFormat::Png), Some(2), 2),
+                (Some(ImageFormat::Webp), Some(3), 1),
+            ]
+        );
+        assert_eq!(
+            groups[1].files,
+            [PathBuf::from("a.png"), PathBuf::from("d.PNG")]
+        );
+    }
+
+    #[test]
+    fn test_split_without_widths_is_one_group() {
+        let args = Cli::parse_from(["image-optimizer"]);
+        let files = vec![PathBuf::from("a.png"), PathBuf::from("b.jpg")];
+        assert_eq!(
+            FormatGroup::split(files.clone(), &args),
+            [FormatGroup {
+                format: None,
+                width: None,
+                files
+            }]
+        );
+    }
+}
@@ -0,0 +1,31 @@
+use image::ImageReader;
+use std::path::Path;
+
+/// Reads the width and height of a raster image from its header without decoding it.
+///
+/// The `image` crate's default allocation limit is lifted for this read: it would reject
+/// exactly the huge headers callers want to inspect before committing to a decode.
+///
+/// # Arguments
+///
+/// * `path` - Path to the image file
+///
+/// # Returns
+///
+/// Returns `None` if the file cannot be opened or its header is not a readable image,
+/// which includes SVG files.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use image_optimizer::optimization::read_image_dimensions;
+///
+/// assert_eq!(read_image_dimensions(Path::new("missing.png")), None);
+/// ```
+#[must_use]
+pub fn read_image_dimensions(path: &Path) -> Option<(u32, u32)> {
+    let mut reader = ImageReader::open(path).ok()?.with_guessed_format().ok()?;
+    reader.no_limits();
+    reader.into_dimensions().ok()
+}
@@ -0,0 +1,44 @@
+use anyhow::{Context, Result};
+use image::{ImageDecoder, ImageReader};
+use std::path::Path;
+
+/// Reads the embedded ICC color profile from an image file.
+///
+/// Only the image header is parsed; pixel data is not decoded. The format is detected
+/// from the file contents, so this works for any raster format the `image` crate can
+/// read (JPEG, PNG and WebP).
+///
+/// # Arguments
+///
+/// * `path` - Path to the image file
+///
+/// # Returns
+///
+/// Returns the raw ICC profile bytes, or `None` if the image has no embedded profile.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened or its header cannot be decoded.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use image_optimizer::optimization::read_icc_profile;
+///
+/// # fn example() -> anyhow::Result<()> {
+/// if let Some(profile) = read_icc_profile(Path::new("photo.jpg"))? {
+///     println!("Embedded ICC profile: {} bytes", profile.len());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn read_icc_profile(path: &Path) -> Result<Option<Vec<u8>>> {
+    let mut decoder = ImageReader::open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?
+        .with_guessed_format()?
+        .into_decoder()
+        .with_context(|| format!("Failed to read image header of {}", path.display()))?;
+
+    Ok(decoder.icc_profile()?)
+}
@@ -0,0 +1,173 @@
+use anyhow::Result;
+use image::error::UnsupportedErrorKind;
+use image::{DynamicImage, ImageError, ImageReader};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use super::{ImageFormat, InvalidImage, decompress_svgz};
+
+/// Decodes an image fully to verify it is readable before anything is written.
+///
+/// Raster images are decoded with the `image` crate, which rejects garbage and truncated
+/// data with an error instead of aborting (mozjpeg, used for encoding, cannot safely
+/// decode corrupt JPEGs). SVG files are checked to be UTF-8 text containing an `<svg`
+/// element, after decompressing gzipped (`.svgz`) contents.
+///
+/// The `image` crate is built with decoders for JPEG, PNG and WebP only. A file whose
+/// contents are detected as another format (for example a GIF renamed to `.png`) is
+/// reported as invalid with a message naming the format that has no decoder, instead of a
+/// generic decode failure.
+///
+/// # Arguments
+///
+/// * `path` - Path to the image file
+/// * `format` - Format expected from the file extension
+///
+/// # Returns
+///
+/// Returns the decoded pixels for raster formats, or `None` for SVG.
+///
+/// # Errors
+///
+/// Returns an [`InvalidImage`] error if the contents cannot be decoded, or a plain I/O
+/// error if the file cannot be opened or read.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use image_optimizer::optimization::{ImageFormat, InvalidImage, decode_image};
+///
+/// match decode_image(Path::new("photo.jpg"), ImageFormat::Jpeg) {
+///     Ok(_) => println!("readable"),
+///     Err(e) if e.downcast_ref::<InvalidImage>().is_some() => println!("corrupt: {e}"),
+///     Err(e) => println!("I/O error: {e}"),
+/// }
+/// ```
+pub fn decode_image(path: &Path, format: ImageFormat) -> Result<Option<DynamicImage>> {
+    if !format.is_raster() {
+        let contents = decompress_svgz(fs::read(path)?)
+            .map_err(|e| InvalidImage::new(format!("SVGZ cannot be decompressed: {e}")))?;
+        let text = std::str::from_utf8(&contents)
+            .map_err(|e| InvalidImage::new(format!("SVG is not valid UTF-8: {e}")))?;
+        if !text.contains("<svg") {
+            return Err(InvalidImage::new("no <svg> element found").into());
+        }
+        return Ok(None);
+    }
+
+    let img = ImageReader::open(path)?
+        .with_guessed_format()?
+        .decode()
+        .map_err(|e| match e {
+            ImageError::IoError(io_error) if io_error.kind() != io::ErrorKind::UnexpectedEof => {
+                anyhow::Error::from(io_error)
+            }
+            ImageError::Unsupported(unsupported) => match unsupported.kind() {
+                UnsupportedErrorKind::Format(hint) => InvalidImage::new(format!(
+                    "contents are {hint}, which this build cannot decode (expected {})",
+                    format.name()
+                ))
+                .into(),
+                _ => InvalidImage::new(unsupported.to_string()).into(),
+            },
+            other => InvalidImage::new(other.to_string()).into(),
+        })?;
+
+    Ok(Some(img))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_garbage_is_invalid_image() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_decode_garbage");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let path = temp_dir.join("garbage.jpg");
+        fs::write(&path, b"definitely not a jpeg").unwrap();
+
+        let error = decode_image(&path, ImageFormat::Jpeg).unwrap_err();
+        assert!(error.downcast_ref::<InvalidImage>().is_some());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_truncated_png_is_invalid_image() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_decode_truncated");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let path = temp_dir.join("truncated.png");
+        let img = image::RgbImage::from_pixel(64, 64, image::Rgb([1, 2, 3]));
+        img.save(&path).unwrap();
+        let data = fs::read(&path).unwrap();
+        fs::write(&path, &data[..data.len() / 2]).unwrap();
+
+        let error = decode_image(&path, ImageFormat::Png).unwrap_err();
+        assert!(error.downcast_ref::<InvalidImage>().is_some());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_decodes_every_raster_format() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_decode_formats");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let img = image::RgbImage::from_pixel(16, 8, image::Rgb([40, 120, 200]));
+
+        for (format, encoding) in [
+            (ImageFormat::Jpeg, image::ImageFormat::Jpeg),
+            (ImageFormat::Png, image::ImageFormat::Png),
+            (ImageFormat::Webp, image::ImageFormat::WebP),
+        ] {
+            let path = temp_dir.join(format!("image.{}", format.extension()));
+            img.save_with_format(&path, encoding).unwrap();
+
+            let decoded = decode_image(&path, format).unwrap().unwrap();
+            assert_eq!((decoded.width(), decoded.height()), (16, 8), "{format:?}");
+        }
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_format_without_decoder_is_named() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_decode_unsupported");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let path = temp_dir.join("renamed.png");
+        fs::write(&path, b"GIF89a\x01\x00\x01\x00\x00\x00\x00;").unwrap();
+
+        let error = decode_image(&path, ImageFormat::Png).unwrap_err();
+        assert!(error.downcast_ref::<InvalidImage>().is_some());
+        assert!(error.to_string().contains("Gif"), "{error}");
+        assert!(error.to_string().contains("cannot decode"), "{error}");
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_missing_file_is_io_error() {
+        let error =
+            decode_image(Path::new("/nonexistent/image.png"), ImageFormat::Png).unwrap_err();
+        assert!(error.downcast_ref::<InvalidImage>().is_none());
+        assert!(error.downcast_ref::<io::Error>().is_some());
+    }
+
+    #[test]
+    fn test_svg_validation() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_decode_svg");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let valid = temp_dir.join("valid.svg");
+        let invalid = temp_dir.join("invalid.svg");
+        fs::write(&valid, r#"<svg xmlns="http://www.w3.org/2000/svg"></svg>"#).unwrap();
+        fs::write(&invalid, [0xff, 0xfe, 0x00]).unwrap();
+
+        assert!(decode_image(&valid, ImageFormat::Svg).unwrap().is_none());
+        let error = decode_image(&invalid, ImageFormat::Svg).unwrap_err();
+        assert!(error.downcast_ref::<InvalidImage>().is_some());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
@@ -0,0 +1,165 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::ffi::OsStr;
+use std::path::Path;
+
+/// Image formats supported by the optimizer.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, ValueEnum, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFormat {
+    /// JPEG, optimized with mozjpeg
+    #[value(alias = "jpg")]
+    #[serde(alias = "jpg")]
+    Jpeg,
+    /// PNG, optimized with oxipng
+    Png,
+    /// WebP, encoded with libwebp
+    Webp,
+    /// SVG, optimized as text (also read from and written to gzipped `.svgz` files)
+    Svg,
+}
+
+impl ImageFormat {
+    /// Determines the format from a file extension (case-insensitive).
+    #[must_use]
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_lowercase().as_str() {
+            "jpg" | "jpeg" => Some(Self::Jpeg),
+            "png" => Some(Self::Png),
+            "webp" => Some(Self::Webp),
+            "svg" | "svgz" => Some(Self::Svg),
+            _ => None,
+        }
+    }
+
+    /// Determines the format from a path's extension.
+    ///
+    /// Only the extension has to be valid UTF-8; the rest of the name may be any bytes.
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()
+            .and_then(OsStr::to_str)
+            .and_then(Self::from_extension)
+    }
+
+    /// Sniffs the format from the leading bytes of the file contents.
+    ///
+    /// Raster formats are recognized by their signatures; SVG is recognized by an
+    /// `<svg` element or XML declaration near the start of the data.
+    #[must_use]
+    pub fn from_magic_bytes(data: &[u8]) -> Option<Self> {
+        if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            return Some(Self::Jpeg);
+        }
+        if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+            return Some(Self::Png);
+        }
+        if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+            return Some(Self::Webp);
+        }
+
+        let head = String::from_utf8_lossy(&data[..data.len().min(1024)]);
+        let head = head.trim_start_matches('\u{feff}').trim_start();
+        if head.starts_with("<?xml") || head.starts_with("<svg") || head.contains("<svg") {
+            return Some(Self::Svg);
+        }
+
+        None
+    }
+
+    /// Canonical file extension for the format.
+    #[must_use]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Jpeg => "jpg",
+            Self::Png => "png",
+            Self::Webp => "webp",
+            Self::Svg => "svg",
+        }
+    }
+
+    /// Display name of the format, e.g. `JPEG` or `WebP`.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Jpeg => "JPEG",
+            Self::Png => "PNG",
+            Self::Webp => "WebP",
+            Self::Svg => "SVG",
+        }
+    }
+
+    /// Name of the Cargo feature that compiles in the format's optimizer.
+    #[must_use]
+    pub const fn feature(self) -> &'static str {
+        match self {
+            Self::Jpeg => "jpeg",
+            Self::Png => "png",
+            Self::Webp => "webp",
+            Self::Svg => "svg",
+        }
+    }
+
+    /// Whether the format's optimizer was compiled into this build.
+    #[must_use]
+    pub const fn is_enabled(self) -> bool {
+        match self {
+            Self::Jpeg => cfg!(feature = "jpeg"),
+            Self::Png => cfg!(feature = "png"),
+            Self::Webp => cfg!(feature = "webp"),
+            Self::Svg => cfg!(feature = "svg"),
+        }
+    }
+
+    /// Whether the format is a raster (pixel-based) format.
+    #[must_use]
+    pub const fn is_raster(self) -> bool {
+        !matches!(self, Self::Svg)
+    }
+
+    /// Whether the format's encoder takes a quality setting (`--quality`).
+    #[must_use]
+    pub const fn supports_quality(self) -> bool {
+        matches!(self, Self::Jpeg | Self::Webp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_extension() {
+        assert_eq!(ImageFormat::from_extension("JPG"), Some(ImageFormat::Jpeg));
+        assert_eq!(ImageFormat::from_extension("jpeg"), Some(ImageFormat::Jpeg));
+        assert_eq!(ImageFormat::from_extension("png"), Some(ImageFormat::Png));
+        assert_eq!(ImageFormat::from_extension("webp"), Some(ImageFormat::Webp));
+        assert_eq!(ImageFormat::from_extension("svg"), Some(ImageFormat::Svg));
+        assert_eq!(ImageFormat::from_extension("SVGZ"), Some(ImageFormat::Svg));
+        assert_eq!(ImageFormat::from_extension("gif"), None);
+    }
+
+    #[test]
+    fn test_from_magic_bytes() {
+        assert_eq!(
+            ImageFormat::from_magic_bytes(&[0xFF, 0xD8, 0xFF, 0xE0]),
+            Some(ImageFormat::Jpeg)
+        );
+        assert_eq!(
+            ImageFormat::from_magic_bytes(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"),
+            Some(ImageFormat::Png)
+        );
+        assert_eq!(
+            ImageFormat::from_magic_bytes(b"RIFF\x24\0\0\0WEBPVP8 "),
+            Some(ImageFormat::Webp)
+        );
+        assert_eq!(
+            ImageFormat::from_magic_bytes(b"<?xml version=\"1.0\"?>\n<svg></svg>"),
+            Some(ImageFormat::Svg)
+        );
+        assert_eq!(ImageFormat::from_magic_bytes(b"GIF89a"), None);
+        assert_eq!(ImageFormat::from_magic_bytes(b""), None);
+    }
+}
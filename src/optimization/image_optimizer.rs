@@ -1,84 +1,1440 @@
-use anyhow::Result;
+use anyhow::Context;
 use image;
 use std::ffi::OsStr;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
 
-use super::{jpeg_optimizer, png_optimizer, svg_optimizer, webp_optimizer};
-use crate::cli::Cli;
-use crate::file_ops::{calculate_resize_dimensions, create_backup, ensure_output_dir};
+use super::{
+    ImageFormat, ImageOutcome, OptimizeError, QualityScore, SettingsMarker, compress_svgz,
+    convert_to_srgb, decode_image, encode_image, is_svgz_path, optimize_responsive,
+    read_icc_profile, read_image_dimensions, resolve_output_path, reuses_decoded_pixels,
+    verify_output,
+};
+use crate::cli::{Cli, InputLimit, LargerPolicy};
+use crate::file_ops::{
+    calculate_resize_dimensions, create_backup, create_temp_path, ensure_output_dir,
+    extended_length_path, format_bytes, is_same_file, move_file, release_temp_path,
+    restore_file_attributes, should_keep_existing_output,
+};
+use crate::reporting::write_comparison;
+
+/// Delay before the first `--retry` of a file; it doubles with every further attempt.
+const RETRY_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Temporary file of an in-place optimization that is removed unless committed.
+///
+/// Any early return or panic between creating the temporary file and renaming it over
+/// the original drops the guard, which deletes the half-written file.
+struct TempFileGuard {
+    path: PathBuf,
+    committed: bool,
+}
+
+impl TempFileGuard {
+    const fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            committed: false,
+        }
+    }
+
+    /// Marks the temporary file as moved over the original, so it is kept on drop.
+    fn commit(mut self) {
+        self.committed = true;
+        release_temp_path(&self.path);
+    }
+}
+
+impl Drop for TempFileGuard {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = fs::remove_file(&self.path);
+            release_temp_path(&self.path);
+        }
+    }
+}
 
 /// Optimizes an image file using the appropriate format-specific optimizer
 ///
+/// The image is decoded before any temporary file, backup or output is created, so a
+/// corrupt or truncated file fails with [`OptimizeError::Decode`] and is left untouched.
+/// Partially written output files are removed when encoding or any later step fails,
+/// and the temporary file of an in-place run is removed on any error or panic before it
+/// replaces the original. The decoded pixels are passed on to encoders that would
+/// otherwise decode the file again (see [`reuses_decoded_pixels`]).
+///
+/// Returns the original and optimized sizes. The original is kept (in place) or copied
+/// (output directory) when the optimized result is not smaller, unless `--on-larger`
+/// says otherwise.
+///
+/// With `--mark`, the marker is embedded in the result before its size is compared, and
+/// an in-place input that already carries the marker of the current settings is skipped
+/// without being decoded (unless `--force`).
+///
+/// Files in a subdirectory with its own `.image-optimizer.toml` are optimized with the
+/// settings from [`SidecarConfigs`](crate::settings::SidecarConfigs).
+///
+/// With `--verify`, the written result is decoded again and must have the dimensions of
+/// the encoded image; otherwise it is removed like a failed encode.
+///
+/// With `--retry`, a file that fails with a transient I/O error (see
+/// [`OptimizeError::is_transient`]) is optimized again from the start, after a backoff
+/// that doubles with each attempt. Other errors are returned right away.
+///
 /// # Errors
-/// Returns an error if file I/O operations fail, image processing fails, or unsupported format
-pub fn optimize_image(input_path: &Path, args: &Cli, input_dir: &Path) -> Result<u64> {
-    let original_size = fs::metadata(input_path)?.len();
+/// Returns [`OptimizeError::UnsupportedFormat`] for an unsupported extension,
+/// [`OptimizeError::FormatDisabled`] for a format whose Cargo feature is disabled,
+/// [`OptimizeError::AlreadyMarked`] for a marked in-place input,
+/// [`OptimizeError::OutputExists`] for an existing output that is kept,
+/// [`OptimizeError::NotSmaller`] for a result that did not shrink with `--on-larger skip`,
+/// [`OptimizeError::Decode`] if the image cannot be decoded, [`OptimizeError::Encode`] if
+/// the format-specific optimizer fails, [`OptimizeError::VerificationFailed`] if the result
+/// fails `--verify`, [`OptimizeError::InvalidConfig`] if the output path is the input file
+/// itself, and [`OptimizeError::Io`] if file I/O fails
+pub fn optimize_image(
+    input_path: &Path,
+    args: &Cli,
+    input_dir: &Path,
+) -> Result<ImageOutcome, OptimizeError> {
+    let sidecar_args = sidecar_args(input_path, args)?;
+    let args = sidecar_args.as_deref().unwrap_or(args);
 
-    let is_in_place = args.output.is_none();
-    let output_path = if let Some(ref output_dir) = args.output {
-        ensure_output_dir(output_dir, input_dir, input_path)?
-    } else {
-        input_path.with_extension(format!(
-            "tmp.{}",
+    let mut attempt = 0;
+    loop {
+        match optimize_attempt(input_path, args, input_dir) {
+            Err(error) if error.is_transient() && attempt < args.retry => {
+                thread::sleep(RETRY_BACKOFF * 2_u32.pow(attempt.into()));
+                attempt += 1;
+            }
+            Err(OptimizeError::Io { message, source }) if attempt > 0 => {
+                return Err(OptimizeError::Io {
+                    message: format!("{message} (gave up after {} attempts)", attempt + 1),
+                    source,
+                });
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Returns the path [`optimize_image`] writes `input_path` to, or `None` when the file is
+/// optimized in place or resized into several `--sizes` outputs.
+///
+/// # Errors
+///
+/// Returns the error [`optimize_image`] would fail with before writing: an unsupported
+/// or disabled format, an invalid sidecar or `--output-file`, an output path that is the
+/// input file itself, or an I/O error while creating the output directory.
+pub fn resolved_output_path(
+    input_path: &Path,
+    args: &Cli,
+    input_dir: &Path,
+) -> Result<Option<PathBuf>, OptimizeError> {
+    let sidecar_args = sidecar_args(input_path, args)?;
+    let args = sidecar_args.as_deref().unwrap_or(args);
+    let format = enabled_format(input_path)?;
+    if !args.sizes.is_empty() && format.is_raster() {
+        return Ok(None);
+    }
+    output_path_for(input_path, format, args, input_dir)
+}
+
+/// Runs one attempt of [`optimize_image`] with the settings that apply to the file.
+fn optimize_attempt(
+    input_path: &Path,
+    args: &Cli,
+    input_dir: &Path,
+) -> Result<ImageOutcome, OptimizeError> {
+    let format = enabled_format(input_path)?;
+    let original_metadata = fs::metadata(input_path)
+        .with_context(|| format!("Failed to read {}", input_path.display()))
+        .map_err(OptimizeError::io)?;
+    let original_size = original_metadata.len();
+
+    let marker = marker_for(input_path, args)?;
+
+    let existing_output = output_path_for(input_path, format, args, input_dir)?;
+    if let Some(ref output_path) = existing_output
+        && should_keep_existing_output(
+            output_path,
+            &original_metadata,
+            args.skip_existing,
+            args.force,
+        )
+    {
+        return Err(OptimizeError::OutputExists(output_path.clone()));
+    }
+
+    if let Some(limit) = args.max_input_size {
+        check_input_limit(input_path, format, original_size, limit)?;
+    }
+    if !args.sizes.is_empty() && format.is_raster() {
+        return optimize_responsive(input_path, format, &original_metadata, args, input_dir);
+    }
+    let decoded = decode_image(input_path, format).map_err(OptimizeError::from_decode)?;
+    let (decoded, converted) = match decoded {
+        Some(img) if args.to_srgb && !args.strip_only => {
+            srgb_pixels(input_path, &img)?.map_or((Some(img), false), |srgb| (Some(srgb), true))
+        }
+        decoded => (decoded, false),
+    };
+    // SVGs have no decoded pixels and are neither measured nor compared
+    let inspect = args.measure_quality || args.compare_out.is_some();
+    let original = decoded.as_ref().filter(|_| inspect).cloned();
+    let decoded_size = decoded.as_ref().map(|img| (img.width(), img.height()));
+    let img = decoded.and_then(|img| {
+        let reuse = reuses_decoded_pixels(format, input_path, args);
+        pixels_to_encode(img, args.max_size, converted || reuse)
+    });
+    // SVGs have no dimensions to check and are not verified
+    let expected_size = img
+        .as_ref()
+        .map(|img| (img.width(), img.height()))
+        .or(decoded_size)
+        .filter(|_| args.verify);
+
+    let is_in_place = existing_output.is_none();
+    let (output_path, temp_file) = prepare_write(input_path, existing_output, args, input_dir)?;
+
+    let written = write_result(
+        format,
+        input_path,
+        &output_path,
+        args,
+        img,
+        marker,
+        expected_size,
+    );
+    let finished = written.and_then(|()| {
+        let optimized_size = fs::metadata(&output_path)?.len();
+        let mut outcome = ImageOutcome {
+            original_size,
+            optimized_size,
+            quality: None,
+        };
+        #[cfg(test)]
+        tests::fail_if_requested()?;
+        if let Some(ref original) = original {
+            outcome.quality = inspect_result(
+                args,
+                input_dir,
+                input_path,
+                &output_path,
+                format,
+                original,
+                outcome,
+            )
+            .map_err(OptimizeError::io)?;
+        }
+        commit_result(
+            input_path,
+            &output_path,
+            temp_file,
+            &original_metadata,
+            args,
+            &mut outcome,
+        )?;
+        Ok(outcome)
+    });
+    // A partial output would be kept as an existing output by a retry or the next run;
+    // below the threshold the output is a copy of the original
+    if let Err(ref error) = finished
+        && !is_in_place
+        && !matches!(error, OptimizeError::BelowThreshold { .. })
+    {
+        let _ = remove_output(&output_path, input_path);
+    }
+    finished
+}
+
+/// Returns where the result of `input_path` is written with `--output` or `--output-file`,
+/// or `None` when it is optimized in place.
+///
+/// An output path that resolves to the input file itself is rejected: writing it would
+/// truncate the input before it is read.
+fn output_path_for(
+    input_path: &Path,
+    format: ImageFormat,
+    args: &Cli,
+    input_dir: &Path,
+) -> Result<Option<PathBuf>, OptimizeError> {
+    let output_path = match (&args.output, &args.output_file) {
+        (Some(output_dir), _) => resolve_output_path(output_dir, input_path, args, input_dir)
+            .map_err(OptimizeError::io)?,
+        (None, Some(output_file)) => prepare_output_file(output_file, format)?,
+        (None, None) => return Ok(None),
+    };
+    if is_same_file(&output_path, input_path) {
+        return Err(OptimizeError::InvalidConfig(format!(
+            "the output path {} is the input file itself; omit -o/--output to optimize in place",
+            output_path.display()
+        )));
+    }
+    Ok(Some(output_path))
+}
+
+/// Returns the path to encode into: the output path, or in place a guarded temporary
+/// file next to the input, after backing up the input with `--backup`.
+fn prepare_write(
+    input_path: &Path,
+    existing_output: Option<PathBuf>,
+    args: &Cli,
+    input_dir: &Path,
+) -> Result<(PathBuf, Option<TempFileGuard>), OptimizeError> {
+    if let Some(output_path) = existing_output {
+        return Ok((output_path, None));
+    }
+    let temp_path = create_temp_path(input_path).map_err(OptimizeError::io)?;
+    let temp_file = TempFileGuard::new(temp_path.clone());
+    if args.backup || args.backup_dir.is_some() {
+        create_backup(
+            input_path,
+            args.backup_dir.as_deref(),
+            input_dir,
+            args.backup_mode,
+        )
+        .map_err(OptimizeError::io)?;
+    }
+    Ok((temp_path, Some(temp_file)))
+}
+
+/// Encodes the result, embeds the `--mark` marker and, with `--verify`, checks that the
+/// written file decodes to `expected_size`.
+fn write_result(
+    format: ImageFormat,
+    input_path: &Path,
+    output_path: &Path,
+    args: &Cli,
+    img: Option<image::DynamicImage>,
+    marker: Option<SettingsMarker>,
+    expected_size: Option<(u32, u32)>,
+) -> Result<(), OptimizeError> {
+    encode_image(format, input_path, output_path, args, img)?;
+    if let Some(marker) = marker {
+        marker.embed(output_path).map_err(OptimizeError::io)?;
+    }
+    #[cfg(test)]
+    tests::corrupt_output_if_requested(output_path);
+    expected_size.map_or(Ok(()), |expected| {
+        verify_output(output_path, format, expected)
+    })
+}
+
+/// Returns the settings of the nearest `.image-optimizer.toml` sidecar of `input_path`,
+/// or `None` if the run's settings apply.
+fn sidecar_args(input_path: &Path, args: &Cli) -> Result<Option<Arc<Cli>>, OptimizeError> {
+    args.sidecar_configs
+        .as_ref()
+        .map_or(Ok(None), |sidecars| sidecars.args_for(args, input_path))
+        .map_err(|error| OptimizeError::InvalidConfig(format!("{error:#}")))
+}
+
+/// Returns the format of `input_path` from its extension if it is compiled in.
+fn enabled_format(input_path: &Path) -> Result<ImageFormat, OptimizeError> {
+    let format = ImageFormat::from_path(input_path).ok_or_else(|| {
+        OptimizeError::UnsupportedFormat(
             input_path
                 .extension()
                 .and_then(OsStr::to_str)
-                .unwrap_or("jpg")
-        ))
-    };
+                .unwrap_or("")
+                .to_lowercase(),
+        )
+    })?;
+    if !format.is_enabled() {
+        return Err(OptimizeError::FormatDisabled(format));
+    }
+    Ok(format)
+}
 
-    if args.backup && is_in_place {
-        create_backup(input_path)?;
+/// Checks that an `--output-file` path keeps the input format and creates its parent
+/// directories.
+fn prepare_output_file(output_file: &Path, format: ImageFormat) -> Result<PathBuf, OptimizeError> {
+    let output_format = ImageFormat::from_path(output_file);
+    if output_format != Some(format) {
+        return Err(OptimizeError::InvalidConfig(format!(
+            "--output-file {} must have a {} extension like the input",
+            output_file.display(),
+            format.name()
+        )));
     }
+    if let Some(parent) = output_file.parent() {
+        fs::create_dir_all(extended_length_path(parent))
+            .with_context(|| format!("Failed to create {}", parent.display()))
+            .map_err(OptimizeError::io)?;
+    }
+    Ok(output_file.to_path_buf())
+}
 
-    let extension = input_path
-        .extension()
-        .and_then(OsStr::to_str)
-        .unwrap_or("")
-        .to_lowercase();
+/// Returns the `--mark` marker of the run, rejecting an in-place input that carries it
+/// already unless `--force` is set.
+fn marker_for(input_path: &Path, args: &Cli) -> Result<Option<SettingsMarker>, OptimizeError> {
+    let marker = args.mark.then(|| SettingsMarker::for_settings(args));
+    if let Some(ref marker) = marker
+        && args.is_in_place()
+        && !args.force
+        && marker.is_present(input_path)
+    {
+        return Err(OptimizeError::AlreadyMarked);
+    }
+    Ok(marker)
+}
 
-    let img = if extension == "svg" {
+/// Keeps the optimized result if it is smaller than the original, or applies
+/// `--on-larger` otherwise.
+///
+/// In place the temporary file replaces the original; in an output directory the
+/// original is copied over a result that is not smaller (`keep-original`, the default).
+/// `keep-optimized` keeps the result anyway, and `skip` removes it, leaves the output
+/// directory without the file and fails with [`OptimizeError::NotSmaller`]. An `.svg`
+/// written as `.svgz` is compared with, and falls back to, the gzipped original, so
+/// compressed sizes are compared with each other.
+///
+/// With `--keep-larger-as`, a result that `keep-original` or `skip` would discard is
+/// moved next to the kept file instead, under the name from [`larger_result_path`].
+///
+/// A smaller result that saves less than `--prefer-smaller-than-original-by` is
+/// discarded regardless of `--on-larger`: the original is kept as above and the file
+/// is reported as skipped with [`OptimizeError::BelowThreshold`].
+fn commit_result(
+    input_path: &Path,
+    output_path: &Path,
+    temp_file: Option<TempFileGuard>,
+    original_metadata: &fs::Metadata,
+    args: &Cli,
+    outcome: &mut ImageOutcome,
+) -> Result<(), OptimizeError> {
+    let compressed_original = if is_svgz_path(output_path) && !is_svgz_path(input_path) {
+        Some(compress_svgz(&fs::read(input_path)?, args.svgz_level)?)
+    } else {
         None
-    } else if args.max_size.is_some() {
-        let img = image::open(input_path)?;
-        let (width, height) = (img.width(), img.height());
-
-        if let Some(max_size) = args.max_size {
-            let (new_width, new_height) = calculate_resize_dimensions(width, height, max_size);
-            if new_width != width || new_height != height {
-                Some(img.resize(new_width, new_height, image::imageops::FilterType::Lanczos3))
-            } else {
-                Some(img)
-            }
+    };
+    let baseline_size = compressed_original
+        .as_ref()
+        .map_or(outcome.original_size, |data| data.len() as u64);
+
+    let smaller = outcome.optimized_size < baseline_size;
+    let in_place = temp_file.is_some();
+    let threshold = args.prefer_smaller_than_original_by;
+    if smaller && !saves_enough(baseline_size, outcome.optimized_size, threshold) {
+        // Dropping the temp file guard removes an in-place result
+        drop(temp_file);
+        if !in_place {
+            copy_original(
+                input_path,
+                output_path,
+                compressed_original,
+                original_metadata,
+                args,
+            )?;
+        }
+        return Err(OptimizeError::BelowThreshold {
+            original: baseline_size,
+            optimized: outcome.optimized_size,
+            threshold,
+        });
+    }
+    if smaller || args.on_larger == LargerPolicy::KeepOptimized {
+        let final_path = if let Some(temp_file) = temp_file {
+            move_file(output_path, input_path).map_err(OptimizeError::io)?;
+            temp_file.commit();
+            input_path
         } else {
-            Some(img)
+            output_path
+        };
+        restore_file_attributes(final_path, original_metadata, !args.no_preserve_timestamps)
+            .map_err(OptimizeError::io)?;
+        return Ok(());
+    }
+
+    let kept_larger = if let Some(ref suffix) = args.keep_larger_as {
+        let larger_path =
+            larger_result_path(if in_place { input_path } else { output_path }, suffix);
+        move_file(output_path, &larger_path).map_err(OptimizeError::io)?;
+        if let Some(temp_file) = temp_file {
+            temp_file.commit();
+        }
+        restore_file_attributes(
+            &larger_path,
+            original_metadata,
+            !args.no_preserve_timestamps,
+        )
+        .map_err(OptimizeError::io)?;
+        true
+    } else {
+        // Dropping the temp file guard removes an in-place result
+        drop(temp_file);
+        false
+    };
+
+    if args.on_larger == LargerPolicy::Skip {
+        if !in_place && !kept_larger {
+            remove_output(output_path, input_path)?;
+        }
+        return Err(OptimizeError::NotSmaller {
+            original: baseline_size,
+            optimized: outcome.optimized_size,
+        });
+    }
+
+    if !in_place {
+        if compressed_original.is_some() {
+            outcome.optimized_size = baseline_size;
         }
+        copy_original(
+            input_path,
+            output_path,
+            compressed_original,
+            original_metadata,
+            args,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Removes a discarded result from the output directory.
+///
+/// An output path that resolves to the input is left alone, so a failed or discarded
+/// result can never take the original with it.
+fn remove_output(output_path: &Path, input_path: &Path) -> std::io::Result<()> {
+    if is_same_file(output_path, input_path) {
+        return Ok(());
+    }
+    fs::remove_file(output_path)
+}
+
+/// Returns whether a result of `optimized` bytes is smaller than `original` by at least
+/// `percent` percent of it. With 0 any smaller result is enough.
+fn saves_enough(original: u64, optimized: u64, percent: u8) -> bool {
+    let saved = u128::from(original.saturating_sub(optimized));
+    saved > 0 && saved * 100 >= u128::from(original) * u128::from(percent)
+}
+
+/// Writes the original over a discarded result in the output directory, gzipped if the
+/// output is an `.svgz` of an `.svg` input.
+fn copy_original(
+    input_path: &Path,
+    output_path: &Path,
+    compressed_original: Option<Vec<u8>>,
+    original_metadata: &fs::Metadata,
+    args: &Cli,
+) -> Result<(), OptimizeError> {
+    if let Some(data) = compressed_original {
+        fs::write(output_path, data)?;
+    } else {
+        fs::copy(input_path, output_path)?;
+    }
+    restore_file_attributes(output_path, original_metadata, !args.no_preserve_timestamps)
+        .map_err(OptimizeError::io)
+}
+
+/// Returns the `--keep-larger-as` path for a discarded result of `kept_path`, with
+/// `suffix` inserted before the extension: `photo.jpg` and `.larger` give
+/// `photo.larger.jpg`.
+fn larger_result_path(kept_path: &Path, suffix: &str) -> PathBuf {
+    // Built as `OsString` so stems that are not valid UTF-8 survive
+    let mut name = kept_path.file_stem().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    if let Some(extension) = kept_path.extension() {
+        name.push(".");
+        name.push(extension);
+    }
+    kept_path.with_file_name(name)
+}
+
+/// Returns the `--to-srgb` conversion of a decoded image, if its ICC profile needs one.
+fn srgb_pixels(
+    input_path: &Path,
+    img: &image::DynamicImage,
+) -> Result<Option<image::DynamicImage>, OptimizeError> {
+    let profile = read_icc_profile(input_path).map_err(OptimizeError::from_decode)?;
+    Ok(profile.and_then(|profile| convert_to_srgb(img, &profile)))
+}
+
+/// Returns the image to hand to the encoder, resized if it is larger than `--max-size`.
+///
+/// An image that is not resized is only kept when `keep` is set: its pixels were
+/// converted (`--to-srgb`), or the encoder would decode the same pixels from the file
+/// again. Otherwise this returns `None` and the encoder reads the file itself.
+fn pixels_to_encode(
+    img: image::DynamicImage,
+    max_size: Option<u32>,
+    keep: bool,
+) -> Option<image::DynamicImage> {
+    let (width, height) = (img.width(), img.height());
+    let (new_width, new_height) = max_size.map_or((width, height), |max_size| {
+        calculate_resize_dimensions(width, height, max_size)
+    });
+    if new_width != width || new_height != height {
+        Some(img.resize(new_width, new_height, image::imageops::FilterType::Lanczos3))
+    } else {
+        keep.then_some(img)
+    }
+}
+
+/// Measures quality and writes the comparison image of a freshly encoded result.
+///
+/// The result is decoded once for `--measure-quality` and `--compare-out`. The comparison
+/// mirrors the input path with `.png` appended, so `photo.jpg` and `photo.png` in one
+/// directory get separate comparisons.
+///
+/// Returns the quality score when `--measure-quality` is set.
+fn inspect_result(
+    args: &Cli,
+    input_dir: &Path,
+    input_path: &Path,
+    encoded_path: &Path,
+    format: ImageFormat,
+    original: &image::DynamicImage,
+    outcome: ImageOutcome,
+) -> anyhow::Result<Option<QualityScore>> {
+    let optimized = decode_image(encoded_path, format)?
+        .with_context(|| format!("{} has no pixels to compare", encoded_path.display()))?;
+    let quality = if args.measure_quality {
+        QualityScore::measure(original, &optimized)
     } else {
         None
     };
 
-    match extension.as_str() {
-        "jpg" | "jpeg" => jpeg_optimizer::optimize_jpeg(input_path, &output_path, args, img)?,
-        "png" => png_optimizer::optimize_png(input_path, &output_path, args, img)?,
-        "webp" => webp_optimizer::optimize_webp(input_path, &output_path, args, img)?,
-        "svg" => svg_optimizer::optimize_svg(input_path, &output_path, args, img)?,
-        _ => return Err(anyhow::anyhow!("Unsupported file format: {}", extension)),
+    if let Some(ref compare_dir) = args.compare_out {
+        let mut comparison_path =
+            ensure_output_dir(compare_dir, input_dir, input_path)?.into_os_string();
+        comparison_path.push(".png");
+        write_comparison(original, &optimized, outcome, Path::new(&comparison_path))?;
     }
+    Ok(quality)
+}
 
-    let optimized_size = fs::metadata(&output_path)?.len();
+/// Rejects an input above `--max-input-size` before it is decoded.
+///
+/// Pixel limits only read the image header. Headers that cannot be read are let through,
+/// so decoding reports the actual problem; SVGs have no pixel size and always pass.
+fn check_input_limit(
+    input_path: &Path,
+    format: ImageFormat,
+    original_size: u64,
+    limit: InputLimit,
+) -> Result<(), OptimizeError> {
+    match limit {
+        InputLimit::Bytes(max_bytes) if original_size > max_bytes => Err(
+            OptimizeError::InputTooLarge(format!("file of {}", format_bytes(original_size))),
+        ),
+        InputLimit::Pixels(max_pixels) if format.is_raster() => {
+            match read_image_dimensions(input_path) {
+                Some((width, height)) if u64::from(width) * u64::from(height) > max_pixels => Err(
+                    OptimizeError::InputTooLarge(format!("image of {width}x{height} pixels")),
+                ),
+                _ => Ok(()),
+            }
+        }
+        _ => Ok(()),
+    }
+}
 
-    if optimized_size < original_size {
-        if is_in_place {
-            fs::rename(&output_path, input_path)?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use filetime::FileTime;
+    use std::cell::Cell;
+
+    thread_local! {
+        /// Makes [`optimize_image`] truncate its next written result, as a broken encoder would
+        static CORRUPT_NEXT_OUTPUT: Cell<bool> = const { Cell::new(false) };
+        /// Makes the next attempts of [`optimize_image`] fail with a transient I/O error
+        /// after encoding, as a flaky network filesystem would
+        static FAIL_NEXT_ATTEMPTS: Cell<u32> = const { Cell::new(0) };
+    }
+
+    /// Test hook called once the result is written, before it is inspected and committed.
+    pub(super) fn fail_if_requested() -> Result<(), OptimizeError> {
+        let failures = FAIL_NEXT_ATTEMPTS.get();
+        if failures == 0 {
+            return Ok(());
         }
-        Ok(original_size - optimized_size)
-    } else {
-        if is_in_place {
-            fs::remove_file(&output_path)?;
-        } else {
-            fs::copy(input_path, &output_path)?;
+        FAIL_NEXT_ATTEMPTS.set(failures - 1);
+        Err(std::io::Error::from(std::io::ErrorKind::ResourceBusy).into())
+    }
+
+    /// Test hook called between encoding and `--verify`.
+    pub(super) fn corrupt_output_if_requested(output_path: &Path) {
+        if CORRUPT_NEXT_OUTPUT.take() {
+            let data = fs::read(output_path).unwrap();
+            fs::write(output_path, &data[..data.len() / 2]).unwrap();
+        }
+    }
+
+    fn write_test_png(path: &Path) {
+        let img = image::RgbImage::from_fn(64, 64, |x, y| {
+            #[allow(clippy::cast_possible_truncation)]
+            image::Rgb([(x * 4) as u8, (y * 4) as u8, 128])
+        });
+        img.save_with_format(path, image::ImageFormat::Png).unwrap();
+    }
+
+    #[test]
+    fn test_corrupt_image_is_left_untouched() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_corrupt_untouched");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let image_path = temp_dir.join("corrupt.jpg");
+        let garbage = b"this is not a jpeg at all".to_vec();
+        fs::write(&image_path, &garbage).unwrap();
+
+        let args = Cli::parse_from(["image-optimizer", "--backup"]);
+        let error = optimize_image(&image_path, &args, &temp_dir).unwrap_err();
+
+        assert!(matches!(error, OptimizeError::Decode(_)));
+        assert_eq!(fs::read(&image_path).unwrap(), garbage);
+        let leftovers: Vec<_> = fs::read_dir(&temp_dir).unwrap().collect();
+        assert_eq!(leftovers.len(), 1);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_max_input_size_skips_before_decoding() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_max_input_size");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let image_path = temp_dir.join("huge.png");
+        // A header announcing 20000x20000 pixels without any pixel data: decoding it
+        // would fail, so the limit must reject it from the header alone
+        let mut ihdr = b"IHDR".to_vec();
+        ihdr.extend_from_slice(&20_000u32.to_be_bytes());
+        ihdr.extend_from_slice(&20_000u32.to_be_bytes());
+        ihdr.extend_from_slice(&[8, 2, 0, 0, 0]);
+        let mut crc = flate2::Crc::new();
+        crc.update(&ihdr);
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png.extend_from_slice(&13u32.to_be_bytes());
+        png.extend_from_slice(&ihdr);
+        png.extend_from_slice(&crc.sum().to_be_bytes());
+        // The header is only reported once the first (here empty) IDAT chunk is reached
+        let mut crc = flate2::Crc::new();
+        crc.update(b"IDAT");
+        png.extend_from_slice(&0u32.to_be_bytes());
+        png.extend_from_slice(b"IDAT");
+        png.extend_from_slice(&crc.sum().to_be_bytes());
+        fs::write(&image_path, &png).unwrap();
+
+        let args = Cli::parse_from(["image-optimizer", "--max-input-size", "100MP"]);
+        let error = optimize_image(&image_path, &args, &temp_dir).unwrap_err();
+        assert!(matches!(error, OptimizeError::InputTooLarge(_)));
+        assert!(error.to_string().contains("20000x20000"));
+
+        let args = Cli::parse_from(["image-optimizer", "--max-input-size", "10B"]);
+        let error = optimize_image(&image_path, &args, &temp_dir).unwrap_err();
+        assert!(matches!(error, OptimizeError::InputTooLarge(_)));
+
+        let args = Cli::parse_from(["image-optimizer", "--max-input-size", "500MP"]);
+        let error = optimize_image(&image_path, &args, &temp_dir).unwrap_err();
+        assert!(matches!(error, OptimizeError::Decode(_)));
+        assert_eq!(fs::read(&image_path).unwrap(), png);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_unsupported_extension_is_unsupported_format() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_unsupported_format");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let path = temp_dir.join("image.bmp");
+        fs::write(&path, "BM").unwrap();
+
+        let args = Cli::parse_from(["image-optimizer"]);
+        let error = optimize_image(&path, &args, &temp_dir).unwrap_err();
+        assert!(matches!(error, OptimizeError::UnsupportedFormat(ref ext) if ext == "bmp"));
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[cfg(not(all(feature = "jpeg", feature = "png", feature = "webp", feature = "svg")))]
+    #[test]
+    fn test_disabled_format_leaves_file_untouched() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_disabled_format");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let format = [
+            ImageFormat::Jpeg,
+            ImageFormat::Png,
+            ImageFormat::Webp,
+            ImageFormat::Svg,
+        ]
+        .into_iter()
+        .find(|format| !format.is_enabled())
+        .unwrap();
+        let path = temp_dir.join(format!("image.{}", format.extension()));
+        fs::write(&path, "original").unwrap();
+
+        let args = Cli::parse_from(["image-optimizer", "--backup"]);
+        let error = optimize_image(&path, &args, &temp_dir).unwrap_err();
+
+        assert!(matches!(error, OptimizeError::FormatDisabled(disabled) if disabled == format));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "original");
+        assert_eq!(fs::read_dir(&temp_dir).unwrap().count(), 1);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_nonexistent_file_is_io_error() {
+        let path = std::env::temp_dir().join("image_optimizer_missing/image.png");
+
+        let args = Cli::parse_from(["image-optimizer"]);
+        let error = optimize_image(&path, &args, &std::env::temp_dir()).unwrap_err();
+        let OptimizeError::Io { ref source, .. } = error else {
+            panic!("expected an I/O error, got {error:?}");
+        };
+        assert_eq!(source.kind(), std::io::ErrorKind::NotFound);
+        assert!(error.to_string().contains("image.png"));
+    }
+
+    #[test]
+    fn test_temp_file_removed_when_backup_fails() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_temp_guard");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let image_path = temp_dir.join("gradient.png");
+        write_test_png(&image_path);
+        let original = fs::read(&image_path).unwrap();
+        // A file where the backup directory should be makes the backup fail after the
+        // temporary file was created
+        let blocked_dir = temp_dir.join("backups");
+        fs::write(&blocked_dir, "not a directory").unwrap();
+
+        let args = Cli::parse_from([
+            "image-optimizer",
+            "--backup-dir",
+            blocked_dir.to_str().unwrap(),
+        ]);
+        assert!(optimize_image(&image_path, &args, &temp_dir).is_err());
+
+        assert!(!temp_dir.join("gradient.tmp.png").exists());
+        assert_eq!(fs::read(&image_path).unwrap(), original);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_in_place_preserves_modification_time() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_preserve_mtime");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let image_path = temp_dir.join("gradient.png");
+        write_test_png(&image_path);
+
+        let old_time = FileTime::from_unix_time(1_600_000_000, 0);
+        filetime::set_file_mtime(&image_path, old_time).unwrap();
+
+        let args = Cli::parse_from(["image-optimizer", "--no-zopfli"]);
+        optimize_image(&image_path, &args, &temp_dir).unwrap();
+
+        let mtime = FileTime::from_last_modification_time(&fs::metadata(&image_path).unwrap());
+        assert!((mtime.unix_seconds() - old_time.unix_seconds()).abs() <= 1);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_output_dir_preserves_modification_time() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_preserve_mtime_output");
+        let output_dir = temp_dir.join("out");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let image_path = temp_dir.join("gradient.png");
+        write_test_png(&image_path);
+
+        let old_time = FileTime::from_unix_time(1_600_000_000, 0);
+        filetime::set_file_mtime(&image_path, old_time).unwrap();
+
+        let args = Cli::parse_from([
+            "image-optimizer",
+            "--no-zopfli",
+            "-o",
+            output_dir.to_str().unwrap(),
+        ]);
+        optimize_image(&image_path, &args, &temp_dir).unwrap();
+
+        let output_path = output_dir.join("gradient.png");
+        let mtime = FileTime::from_last_modification_time(&fs::metadata(&output_path).unwrap());
+        assert!((mtime.unix_seconds() - old_time.unix_seconds()).abs() <= 1);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[cfg(feature = "jpeg")]
+    #[test]
+    fn test_output_ext_normalize_renames_output() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_ext_normalize_output");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let output_dir = temp_dir.join("out");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let image_path = temp_dir.join("Photo.JPEG");
+        let img = image::RgbImage::from_fn(64, 64, |x, y| {
+            #[allow(clippy::cast_possible_truncation)]
+            image::Rgb([(x * 4) as u8, (y * 4) as u8, 128])
+        });
+        img.save_with_format(&image_path, image::ImageFormat::Jpeg)
+            .unwrap();
+
+        let args = Cli::parse_from([
+            "image-optimizer",
+            "-o",
+            output_dir.to_str().unwrap(),
+            "--output-ext-normalize",
+            "canonical",
+        ]);
+        optimize_image(&image_path, &args, &temp_dir).unwrap();
+
+        assert!(output_dir.join("Photo.jpg").is_file());
+        assert!(!output_dir.join("Photo.JPEG").exists());
+        assert!(image_path.is_file());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_compare_out_writes_side_by_side_png() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_compare_out");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let compare_dir = temp_dir.join("compare");
+        fs::create_dir_all(temp_dir.join("nested")).unwrap();
+        let image_path = temp_dir.join("nested/gradient.png");
+        write_test_png(&image_path);
+
+        let args = Cli::parse_from([
+            "image-optimizer",
+            "--no-zopfli",
+            "--compare-out",
+            compare_dir.to_str().unwrap(),
+        ]);
+        optimize_image(&image_path, &args, &temp_dir).unwrap();
+
+        // A 64x64 image is not enlarged, so the two halves hold 64x64 thumbnails
+        let comparison = image::open(compare_dir.join("nested/gradient.png.png")).unwrap();
+        assert!(comparison.width() >= 2 * 64);
+        assert!(comparison.height() > 64);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn test_measure_quality_of_lossless_round_trip() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_quality_lossless");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        let image_path = temp_dir.join("gradient.png");
+        write_test_png(&image_path);
+
+        let args = Cli::parse_from(["image-optimizer", "--no-zopfli", "--measure-quality"]);
+        let quality = optimize_image(&image_path, &args, &temp_dir)
+            .unwrap()
+            .quality
+            .unwrap();
+
+        assert!((quality.ssim - 1.0).abs() < 1e-9);
+        assert!((quality.psnr - crate::optimization::MAX_PSNR).abs() < 1e-9);
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[cfg(feature = "jpeg")]
+    #[test]
+    fn test_measure_quality_of_heavy_lossy_encode() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_quality_lossy");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let output_dir = temp_dir.join("out");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let image_path = temp_dir.join("detail.jpg");
+        let img = image::RgbImage::from_fn(128, 128, |x, y| {
+            let value = if (x / 2 + y / 3) % 2 == 0 { 230 } else { 20 };
+            image::Rgb([value, 255 - value, 128])
+        });
+        img.save_with_format(&image_path, image::ImageFormat::Jpeg)
+            .unwrap();
+
+        let args = Cli::parse_from([
+            "image-optimizer",
+            "--jpeg-quality",
+            "5",
+            "--measure-quality",
+            "-o",
+            output_dir.to_str().unwrap(),
+        ]);
+        let quality = optimize_image(&image_path, &args, &temp_dir)
+            .unwrap()
+            .quality
+            .unwrap();
+
+        assert!(quality.ssim < 0.95, "SSIM {}", quality.ssim);
+        assert!(quality.psnr < 40.0, "PSNR {}", quality.psnr);
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[cfg(feature = "svg")]
+    const COMMENTED_SVG: &str = "<svg xmlns=\"http://www.w3.org/2000/svg\">\n  <!-- drawn by hand -->\n  <rect width=\"10\" height=\"10\"/>\n</svg>\n";
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_svgz_round_trip_stays_gzipped() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_svgz_round_trip");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        let image_path = temp_dir.join("icon.svgz");
+        // A comment that does not compress away, so dropping it shrinks the gzipped file
+        let notes: String = (0..500).map(|i| (i * 7919 % 1000).to_string()).collect();
+        let markup = COMMENTED_SVG.replace("drawn by hand", &notes);
+        fs::write(&image_path, compress_svgz(markup.as_bytes(), 9).unwrap()).unwrap();
+
+        let args = Cli::parse_from(["image-optimizer"]);
+        let outcome = optimize_image(&image_path, &args, &temp_dir).unwrap();
+        assert!(outcome.optimized_size < outcome.original_size);
+
+        let written = fs::read(&image_path).unwrap();
+        assert_eq!(written[..2], [0x1f, 0x8b]);
+        let optimized = String::from_utf8(super::super::decompress_svgz(written).unwrap()).unwrap();
+        assert!(optimized.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\"><rect"));
+        assert!(!optimized.contains(&notes));
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_svgz_flag_compresses_svg_output() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_svgz_flag");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let output_dir = temp_dir.join("out");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let image_path = temp_dir.join("icon.svg");
+        fs::write(&image_path, COMMENTED_SVG).unwrap();
+
+        let args = Cli::parse_from([
+            "image-optimizer",
+            "--svgz",
+            "-o",
+            output_dir.to_str().unwrap(),
+        ]);
+        let outcome = optimize_image(&image_path, &args, &temp_dir).unwrap();
+
+        let output_path = output_dir.join("icon.svgz");
+        assert!(!output_dir.join("icon.svg").exists());
+        let written = fs::read(&output_path).unwrap();
+        assert_eq!(outcome.optimized_size, written.len() as u64);
+        let markup = String::from_utf8(super::super::decompress_svgz(written).unwrap()).unwrap();
+        assert!(markup.contains("<rect width=\"10\" height=\"10\"/>"));
+        assert_eq!(fs::read_to_string(&image_path).unwrap(), COMMENTED_SVG);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_skip_existing_leaves_output_untouched() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_skip_existing");
+        let output_dir = temp_dir.join("out");
+        fs::create_dir_all(&output_dir).unwrap();
+        let image_path = temp_dir.join("gradient.png");
+        write_test_png(&image_path);
+        let existing_output = output_dir.join("gradient.png");
+        fs::write(&existing_output, "existing").unwrap();
+        filetime::set_file_mtime(&existing_output, FileTime::from_unix_time(1_000, 0)).unwrap();
+
+        let args = Cli::parse_from([
+            "image-optimizer",
+            "--skip-existing",
+            "-o",
+            output_dir.to_str().unwrap(),
+        ]);
+        let error = optimize_image(&image_path, &args, &temp_dir).unwrap_err();
+
+        assert!(matches!(error, OptimizeError::OutputExists(ref path) if *path == existing_output));
+        assert_eq!(fs::read_to_string(&existing_output).unwrap(), "existing");
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_force_overwrites_existing_output() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_force_overwrite");
+        let output_dir = temp_dir.join("out");
+        fs::create_dir_all(&output_dir).unwrap();
+        let image_path = temp_dir.join("gradient.png");
+        write_test_png(&image_path);
+        let existing_output = output_dir.join("gradient.png");
+        fs::write(&existing_output, "existing").unwrap();
+
+        let args = Cli::parse_from([
+            "image-optimizer",
+            "--no-zopfli",
+            "--force",
+            "-o",
+            output_dir.to_str().unwrap(),
+        ]);
+        optimize_image(&image_path, &args, &temp_dir).unwrap();
+
+        assert!(image::open(&existing_output).is_ok());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_marked_file_is_skipped_until_forced() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_marked_skip");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let image_path = temp_dir.join("gradient.png");
+        write_test_png(&image_path);
+
+        let args = Cli::parse_from(["image-optimizer", "--no-zopfli", "--mark"]);
+        let outcome = optimize_image(&image_path, &args, &temp_dir).unwrap();
+        assert!(outcome.saved_bytes() > 0);
+        assert!(SettingsMarker::for_settings(&args).is_present(&image_path));
+        let marked = fs::read(&image_path).unwrap();
+
+        let error = optimize_image(&image_path, &args, &temp_dir).unwrap_err();
+        assert!(matches!(error, OptimizeError::AlreadyMarked));
+        assert_eq!(fs::read(&image_path).unwrap(), marked);
+
+        // --force optimizes the marked file again and keeps it marked
+        let args = Cli::parse_from(["image-optimizer", "--no-zopfli", "--mark", "--force"]);
+        optimize_image(&image_path, &args, &temp_dir).unwrap();
+        assert!(SettingsMarker::for_settings(&args).is_present(&image_path));
+        let leftovers = fs::read_dir(&temp_dir).unwrap().count();
+        assert_eq!(leftovers, 1);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    /// Writes a noisy JPEG at quality 10 that grows when re-encoded at quality 100.
+    #[cfg(feature = "jpeg")]
+    fn write_growing_jpeg(path: &Path) -> Vec<u8> {
+        let img = image::RgbImage::from_fn(64, 64, |x, y| {
+            #[allow(clippy::cast_possible_truncation)]
+            image::Rgb([(x * 37 + y * 11) as u8, (x * y) as u8, (y * 53) as u8])
+        });
+        let mut data = Vec::new();
+        image::codecs::jpeg::JpegEncoder::new_with_quality(&mut data, 10)
+            .encode_image(&img)
+            .unwrap();
+        fs::write(path, &data).unwrap();
+        data
+    }
+
+    #[cfg(feature = "jpeg")]
+    fn on_larger_args(policy: &str, output_dir: Option<&Path>) -> Cli {
+        let mut args = vec![
+            "image-optimizer",
+            "--jpeg-quality",
+            "100",
+            "--on-larger",
+            policy,
+        ];
+        if let Some(output_dir) = output_dir {
+            args.extend(["-o", output_dir.to_str().unwrap()]);
         }
-        Ok(0)
+        Cli::parse_from(args)
+    }
+
+    #[cfg(feature = "jpeg")]
+    #[test]
+    fn test_on_larger_keep_original() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_on_larger_original");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let output_dir = temp_dir.join("out");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let image_path = temp_dir.join("photo.jpg");
+        let original = write_growing_jpeg(&image_path);
+
+        let args = on_larger_args("keep-original", None);
+        let outcome = optimize_image(&image_path, &args, &temp_dir).unwrap();
+        assert!(outcome.optimized_size > outcome.original_size);
+        assert_eq!(fs::read(&image_path).unwrap(), original);
+
+        let args = on_larger_args("keep-original", Some(&output_dir));
+        optimize_image(&image_path, &args, &temp_dir).unwrap();
+        assert_eq!(fs::read(output_dir.join("photo.jpg")).unwrap(), original);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[cfg(feature = "jpeg")]
+    #[test]
+    fn test_on_larger_keep_optimized() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_on_larger_optimized");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let output_dir = temp_dir.join("out");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let image_path = temp_dir.join("photo.jpg");
+        let original = write_growing_jpeg(&image_path);
+
+        let args = on_larger_args("keep-optimized", Some(&output_dir));
+        let outcome = optimize_image(&image_path, &args, &temp_dir).unwrap();
+        let written = fs::metadata(output_dir.join("photo.jpg")).unwrap().len();
+        assert_eq!(written, outcome.optimized_size);
+        assert!(written > original.len() as u64);
+
+        let args = on_larger_args("keep-optimized", None);
+        let outcome = optimize_image(&image_path, &args, &temp_dir).unwrap();
+        assert_eq!(
+            fs::metadata(&image_path).unwrap().len(),
+            outcome.optimized_size
+        );
+        assert_ne!(fs::read(&image_path).unwrap(), original);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[cfg(feature = "jpeg")]
+    #[test]
+    fn test_keep_larger_as_keeps_the_discarded_result() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_keep_larger_as");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let output_dir = temp_dir.join("out");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let image_path = temp_dir.join("photo.jpg");
+        let original = write_growing_jpeg(&image_path);
+
+        let mut args = on_larger_args("keep-original", Some(&output_dir));
+        args.keep_larger_as = Some(".larger".to_string());
+        let outcome = optimize_image(&image_path, &args, &temp_dir).unwrap();
+        assert_eq!(fs::read(output_dir.join("photo.jpg")).unwrap(), original);
+        let larger = fs::metadata(output_dir.join("photo.larger.jpg")).unwrap();
+        assert_eq!(larger.len(), outcome.optimized_size);
+
+        let mut args = on_larger_args("skip", None);
+        args.keep_larger_as = Some("-reencoded".to_string());
+        let error = optimize_image(&image_path, &args, &temp_dir).unwrap_err();
+        assert!(matches!(error, OptimizeError::NotSmaller { .. }));
+        assert_eq!(fs::read(&image_path).unwrap(), original);
+        let larger = fs::read(temp_dir.join("photo-reencoded.jpg")).unwrap();
+        assert!(larger.len() > original.len());
+        // No temporary file is left behind next to the input
+        assert_eq!(fs::read_dir(&temp_dir).unwrap().count(), 3);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[cfg(feature = "jpeg")]
+    #[test]
+    fn test_on_larger_skip_fails_and_writes_nothing() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_on_larger_skip");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let output_dir = temp_dir.join("out");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let image_path = temp_dir.join("photo.jpg");
+        let original = write_growing_jpeg(&image_path);
+
+        let args = on_larger_args("skip", Some(&output_dir));
+        let error = optimize_image(&image_path, &args, &temp_dir).unwrap_err();
+        assert!(matches!(error, OptimizeError::NotSmaller { .. }));
+        assert!(!output_dir.join("photo.jpg").exists());
+
+        let args = on_larger_args("skip", None);
+        let error = optimize_image(&image_path, &args, &temp_dir).unwrap_err();
+        assert!(matches!(error, OptimizeError::NotSmaller { .. }));
+        assert_eq!(fs::read(&image_path).unwrap(), original);
+        // Only the input and the emptied output directory are left
+        assert_eq!(fs::read_dir(&temp_dir).unwrap().count(), 2);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn test_verify_keeps_original_when_result_is_corrupt() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_verify_corrupt");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let output_dir = temp_dir.join("out");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let image_path = temp_dir.join("gradient.png");
+        write_test_png(&image_path);
+        let original = fs::read(&image_path).unwrap();
+
+        let args = Cli::parse_from(["image-optimizer", "--no-zopfli", "--verify"]);
+        CORRUPT_NEXT_OUTPUT.set(true);
+        let error = optimize_image(&image_path, &args, &temp_dir).unwrap_err();
+        assert!(matches!(error, OptimizeError::VerificationFailed(_)));
+        assert_eq!(fs::read(&image_path).unwrap(), original);
+        // The temporary result is gone
+        assert_eq!(fs::read_dir(&temp_dir).unwrap().count(), 1);
+
+        let args = Cli::parse_from([
+            "image-optimizer",
+            "--no-zopfli",
+            "--verify",
+            "-o",
+            output_dir.to_str().unwrap(),
+        ]);
+        CORRUPT_NEXT_OUTPUT.set(true);
+        let error = optimize_image(&image_path, &args, &temp_dir).unwrap_err();
+        assert!(matches!(error, OptimizeError::VerificationFailed(_)));
+        assert!(!output_dir.join("gradient.png").exists());
+
+        // An intact result passes
+        optimize_image(&image_path, &args, &temp_dir).unwrap();
+        assert!(output_dir.join("gradient.png").is_file());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_retry_recovers_from_a_transient_failure() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_retry");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        let image_path = temp_dir.join("gradient.png");
+        write_test_png(&image_path);
+        let original = fs::read(&image_path).unwrap();
+
+        let args = Cli::parse_from(["image-optimizer", "--no-zopfli"]);
+        FAIL_NEXT_ATTEMPTS.set(1);
+        let error = optimize_image(&image_path, &args, &temp_dir).unwrap_err();
+        assert!(error.is_transient());
+        assert_eq!(fs::read(&image_path).unwrap(), original);
+        assert_eq!(fs::read_dir(&temp_dir).unwrap().count(), 1);
+
+        let args = Cli::parse_from(["image-optimizer", "--no-zopfli", "--retry", "2"]);
+        FAIL_NEXT_ATTEMPTS.set(1);
+        let outcome = optimize_image(&image_path, &args, &temp_dir).unwrap();
+        assert!(outcome.optimized_size < outcome.original_size);
+        assert_eq!(FAIL_NEXT_ATTEMPTS.get(), 0);
+        assert_eq!(fs::read_dir(&temp_dir).unwrap().count(), 1);
+
+        // A file that keeps failing is reported with the number of attempts
+        FAIL_NEXT_ATTEMPTS.set(3);
+        let error = optimize_image(&image_path, &args, &temp_dir).unwrap_err();
+        assert!(error.to_string().ends_with("(gave up after 3 attempts)"));
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_retry_does_not_keep_the_output_of_a_failed_attempt() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_retry_output");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let input_dir = temp_dir.join("in");
+        fs::create_dir_all(&input_dir).unwrap();
+        let image_path = input_dir.join("gradient.png");
+        write_test_png(&image_path);
+        let output_dir = temp_dir.join("out");
+        let output_path = output_dir.join("gradient.png");
+        let output = output_dir.to_str().unwrap();
+
+        let args = Cli::parse_from(["image-optimizer", "-o", output, "--no-zopfli"]);
+        FAIL_NEXT_ATTEMPTS.set(1);
+        let error = optimize_image(&image_path, &args, &input_dir).unwrap_err();
+        assert!(error.is_transient());
+        assert!(!output_path.exists());
+
+        let args = Cli::parse_from([
+            "image-optimizer",
+            "-o",
+            output,
+            "--no-zopfli",
+            "--retry",
+            "1",
+        ]);
+        FAIL_NEXT_ATTEMPTS.set(1);
+        let outcome = optimize_image(&image_path, &args, &input_dir).unwrap();
+        assert!(outcome.optimized_size < outcome.original_size);
+        assert_eq!(
+            fs::metadata(&output_path).unwrap().len(),
+            outcome.optimized_size
+        );
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_output_that_is_the_input_file_is_rejected() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_output_is_input");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        let image_path = temp_dir.join("gradient.png");
+        write_test_png(&image_path);
+        let original = fs::read(&image_path).unwrap();
+
+        // `-i gradient.png -o .`, spelled differently from the input
+        let output = temp_dir.join("sub/..");
+        fs::create_dir_all(temp_dir.join("sub")).unwrap();
+        for extra in [&[][..], &["--force"][..]] {
+            let args = Cli::parse_from(
+                [
+                    "image-optimizer",
+                    "--no-zopfli",
+                    "-o",
+                    output.to_str().unwrap(),
+                ]
+                .into_iter()
+                .chain(extra.iter().copied()),
+            );
+            let error = optimize_image(&image_path, &args, &image_path).unwrap_err();
+            assert!(error.to_string().contains("is the input file itself"));
+            assert_eq!(fs::read(&image_path).unwrap(), original);
+        }
+
+        // The same in a mirrored directory run, reached through a symlinked output
+        #[cfg(unix)]
+        {
+            let link = temp_dir.join("link");
+            std::os::unix::fs::symlink(&temp_dir, &link).unwrap();
+            let args = Cli::parse_from([
+                "image-optimizer",
+                "--no-zopfli",
+                "--force",
+                "-o",
+                link.to_str().unwrap(),
+            ]);
+            let error = optimize_image(&image_path, &args, &temp_dir).unwrap_err();
+            assert!(error.to_string().contains("is the input file itself"));
+            assert_eq!(fs::read(&image_path).unwrap(), original);
+        }
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_saves_enough_compares_with_percentage_of_original() {
+        assert!(saves_enough(1000, 999, 0));
+        assert!(!saves_enough(1000, 1000, 0));
+        assert!(!saves_enough(1000, 990, 5));
+        assert!(saves_enough(1000, 950, 5));
+        assert!(!saves_enough(1000, 951, 5));
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_small_saving_below_threshold_keeps_original() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_savings_threshold");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        let image_path = temp_dir.join("shapes.svg");
+        let rects = r##"<rect x="4" y="8" width="10" height="10" fill="#123456"/>"##.repeat(60);
+        fs::write(
+            &image_path,
+            format!(r#"<svg xmlns="http://www.w3.org/2000/svg">{rects}</svg>"#),
+        )
+        .unwrap();
+        let args = Cli::parse_from(["image-optimizer"]);
+        optimize_image(&image_path, &args, &temp_dir).unwrap();
+
+        // A comment of 1% of the optimized file is all a second pass can remove
+        let optimized = fs::read_to_string(&image_path).unwrap();
+        let comment = format!("<!--{}-->", "x".repeat(optimized.len() / 100 - 7));
+        let with_comment = optimized.replacen('>', &format!(">{comment}"), 1);
+        fs::write(&image_path, &with_comment).unwrap();
+
+        let args = Cli::parse_from(["image-optimizer", "--prefer-smaller-than-original-by", "5"]);
+        let error = optimize_image(&image_path, &args, &temp_dir).unwrap_err();
+        assert!(matches!(
+            error,
+            OptimizeError::BelowThreshold { threshold: 5, .. }
+        ));
+        assert_eq!(fs::read_to_string(&image_path).unwrap(), with_comment);
+        assert_eq!(fs::read_dir(&temp_dir).unwrap().count(), 1);
+
+        let args = Cli::parse_from(["image-optimizer"]);
+        let outcome = optimize_image(&image_path, &args, &temp_dir).unwrap();
+        assert!(outcome.saved_bytes() > 0);
+        assert_eq!(fs::read_to_string(&image_path).unwrap(), optimized);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
     }
 }
@@ -0,0 +1,56 @@
+use super::QualityScore;
+
+/// Sizes of an image before and after optimization.
+///
+/// `optimized_size` is the size the encoder produced, even when that result was
+/// discarded because it was not smaller than the original. This lets callers report
+/// files that would have grown.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageOutcome {
+    /// Size of the input file in bytes
+    pub original_size: u64,
+    /// Size of the optimized result in bytes
+    pub optimized_size: u64,
+    /// Visual quality of the result, measured with `--measure-quality` (raster formats only)
+    pub quality: Option<QualityScore>,
+}
+
+impl ImageOutcome {
+    /// Returns the number of bytes saved, or 0 if the optimized result was not smaller.
+    #[must_use]
+    pub const fn saved_bytes(&self) -> u64 {
+        self.original_size.saturating_sub(self.optimized_size)
+    }
+
+    /// Returns the signed size change (`optimized - original`); negative means smaller.
+    #[must_use]
+    pub fn size_delta(&self) -> i64 {
+        let original = i64::try_from(self.original_size).unwrap_or(i64::MAX);
+        let optimized = i64::try_from(self.optimized_size).unwrap_or(i64::MAX);
+        optimized.saturating_sub(original)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_saved_bytes_and_delta() {
+        let smaller = ImageOutcome {
+            original_size: 1000,
+            optimized_size: 800,
+            quality: None,
+        };
+        assert_eq!(smaller.saved_bytes(), 200);
+        assert_eq!(smaller.size_delta(), -200);
+
+        let larger = ImageOutcome {
+            original_size: 1000,
+            optimized_size: 1100,
+            quality: None,
+        };
+        assert_eq!(larger.saved_bytes(), 0);
+        assert_eq!(larger.size_delta(), 100);
+    }
+}
@@ -0,0 +1,42 @@
+use std::fmt;
+
+/// Error returned when a file cannot be decoded as the image its extension claims.
+///
+/// This distinguishes corrupt, truncated or mislabeled files from I/O and encoder
+/// failures. The optimizer functions return it as
+/// [`OptimizeError::Decode`](super::OptimizeError::Decode); lower-level functions such as
+/// [`decode_image`](super::decode_image) carry it inside an [`anyhow::Error`], where
+/// callers check for it with `error.downcast_ref::<InvalidImage>()`. An `InvalidImage`
+/// is always reported before any temporary file, backup or output is written, so the
+/// original file is untouched.
+///
+/// ## Examples
+///
+/// ```rust
+/// use image_optimizer::optimization::InvalidImage;
+///
+/// let error = anyhow::Error::from(InvalidImage::new("unexpected end of file"));
+/// assert!(error.downcast_ref::<InvalidImage>().is_some());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidImage {
+    reason: String,
+}
+
+impl InvalidImage {
+    /// Creates an error describing why the image could not be decoded.
+    #[must_use]
+    pub fn new(reason: impl Into<String>) -> Self {
+        Self {
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for InvalidImage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid or corrupt image: {}", self.reason)
+    }
+}
+
+impl std::error::Error for InvalidImage {}
@@ -1,8 +1,16 @@
 use anyhow::{Context, Result};
 use image::DynamicImage;
+use img_parts::Bytes;
+use img_parts::jpeg::Jpeg;
+use mozjpeg::{ColorSpace, ColorSpaceExt};
 use std::fs;
+use std::panic::AssertUnwindSafe;
 use std::path::Path;
 
+use super::{
+    ImageFormat, estimate_jpeg_quality, flatten_alpha, jpeg_transcoder, needs_srgb_conversion,
+    read_icc_profile,
+};
 use crate::cli::Cli;
 
 /// Optimizes a JPEG image using mozjpeg compression.
@@ -10,6 +18,20 @@ use crate::cli::Cli;
 /// This function uses the mozjpeg library to achieve superior compression compared to
 /// standard libjpeg implementations. It supports both quality-based compression and
 /// lossless mode, and can work with either the original image data or a pre-resized image.
+/// Grayscale and CMYK inputs are re-encoded in their own color space rather than expanded
+/// to RGB. A resized image comes from the `image` crate, which has no CMYK
+/// representation, so resized CMYK files are written as RGB, with a warning on stderr
+/// unless `--quiet` is set. Images with transparency
+/// (a PNG converted with `--format jpeg`) are composited over `--background`.
+/// With `--jpeg-lossless` and no resize, the image is not re-encoded at all but
+/// transcoded on its DCT coefficients (see [`jpeg_transcoder::transcode_jpeg`]).
+/// With `--keep-icc`, the ICC color profile embedded in the input is written to the output
+/// unless `--to-srgb` converted the pixels away from it; otherwise all metadata is dropped
+/// by the re-encode. With `--no-upscale-quality`, a JPEG input is never re-encoded above
+/// the quality estimated from its quantization tables (see [`estimate_jpeg_quality`]).
+/// `--jpeg-trellis off` and `--jpeg-optimize-coding off` trade file size for encoding
+/// speed; optimized Huffman tables can only be turned off together with trellis
+/// quantization, as mozjpeg's default progressive output always computes them.
 ///
 /// # Arguments
 ///
@@ -28,42 +50,387 @@ use crate::cli::Cli;
 /// - JPEG decompression or compression fails
 /// - File I/O operations fail (reading input or writing output)
 /// - Image dimensions are too large to convert to u32
-/// - RGB color space conversion fails
+/// - Color space conversion fails
+/// - The ICC profile of the input cannot be read when `--keep-icc` is set
 pub fn optimize_jpeg(
     input_path: &Path,
     output_path: &Path,
     args: &Cli,
     resized_img: Option<DynamicImage>,
 ) -> Result<()> {
-    let quality = args.jpeg_quality;
+    // Resizing needs pixels, so it falls back to a re-encode at the configured quality
+    if args.jpeg_lossless && resized_img.is_none() {
+        return jpeg_transcoder::transcode_jpeg(input_path, output_path, args);
+    }
 
-    let (width, height, rgb_data) = if let Some(img) = resized_img {
-        let rgb_img = img.to_rgb8();
-        (rgb_img.width(), rgb_img.height(), rgb_img.into_raw())
+    let mut quality = args.quality_for(ImageFormat::Jpeg);
+    if args.no_upscale_quality
+        && let Some(source_quality) = fs::read(input_path)
+            .ok()
+            .and_then(|data| estimate_jpeg_quality(&data))
+    {
+        quality = quality.min(source_quality);
+    }
+    if resized_img.is_some()
+        && !args.quiet
+        && fs::read(input_path).is_ok_and(|data| is_cmyk_jpeg(&data))
+    {
+        eprintln!(
+            "Warning: {} is a CMYK JPEG; resized or converted pixels are written as RGB",
+            input_path.display()
+        );
+    }
+    let icc_profile = if args.keep_icc {
+        // Pixels converted by --to-srgb no longer match the source profile
+        read_icc_profile(input_path)?
+            .filter(|profile| !(args.to_srgb && needs_srgb_conversion(profile)))
     } else {
-        let input_data = fs::read(input_path)?;
-        let decompress = mozjpeg::Decompress::new_mem(&input_data)?;
-        let width = u32::try_from(decompress.width()).context("Width too large")?;
-        let height = u32::try_from(decompress.height()).context("Height too large")?;
-        let mut decompress_started = decompress.rgb()?;
-        let rgb_data: Vec<u8> = decompress_started.read_scanlines()?;
-        (width, height, rgb_data)
+        None
     };
 
-    let mut compress = mozjpeg::Compress::new(mozjpeg::ColorSpace::JCS_RGB);
-    compress.set_quality(f32::from(quality));
-    compress.set_size(width as usize, height as usize);
+    // mozjpeg reports libjpeg errors by panicking, so they must not unwind into the caller
+    let encoded = std::panic::catch_unwind(AssertUnwindSafe(|| -> Result<Vec<u8>> {
+        let (width, height, color_space, pixels) = if let Some(img) = resized_img {
+            let img = flatten_alpha(&img, args.background).map_or(img, DynamicImage::ImageRgb8);
+            if img.color().has_color() {
+                let rgb_img = img.to_rgb8();
+                (
+                    rgb_img.width(),
+                    rgb_img.height(),
+                    ColorSpace::JCS_RGB,
+                    rgb_img.into_raw(),
+                )
+            } else {
+                let gray_img = img.to_luma8();
+                let (width, height) = gray_img.dimensions();
+                (
+                    width,
+                    height,
+                    ColorSpace::JCS_GRAYSCALE,
+                    gray_img.into_raw(),
+                )
+            }
+        } else {
+            let input_data = fs::read(input_path)?;
+            let decompress = mozjpeg::Decompress::new_mem(&input_data)?;
+            let width = u32::try_from(decompress.width()).context("Width too large")?;
+            let height = u32::try_from(decompress.height()).context("Height too large")?;
+            let color_space = match decompress.color_space() {
+                ColorSpace::JCS_GRAYSCALE => ColorSpace::JCS_GRAYSCALE,
+                ColorSpace::JCS_CMYK | ColorSpace::JCS_YCCK => ColorSpace::JCS_CMYK,
+                _ => ColorSpace::JCS_RGB,
+            };
+            let mut decompress_started = decompress.to_colorspace(color_space)?;
+            let pixels: Vec<u8> = decompress_started.read_scanlines()?;
+            (width, height, color_space, pixels)
+        };
 
-    let mut output_data = Vec::new();
-    let mut compress_started = compress.start_compress(&mut output_data)?;
+        let mut compress = mozjpeg::Compress::new(color_space);
+        if !args.jpeg_trellis.is_on() {
+            // The wrapper can only drop trellis quantization with the whole profile: the
+            // fastest one uses libjpeg-turbo's baseline defaults and resets the tables, so
+            // it must come before the quality is set
+            compress.set_fastest_defaults();
+        }
+        compress.set_quality(f32::from(quality));
+        compress.set_size(width as usize, height as usize);
+        // Trellis quantization and progressive scans always compute optimized tables
+        compress.set_optimize_coding(args.jpeg_optimize_coding.is_on());
 
-    let row_stride = (width * 3) as usize;
-    for row in rgb_data.chunks(row_stride) {
-        compress_started.write_scanlines(row)?;
-    }
+        let mut output_data = Vec::new();
+        let mut compress_started = compress.start_compress(&mut output_data)?;
+        if let Some(profile) = icc_profile.as_deref().filter(|profile| !profile.is_empty()) {
+            write_icc_markers(&mut compress_started, profile)?;
+        }
 
-    compress_started.finish()?;
+        let row_stride = width as usize * color_space.num_components();
+        for row in pixels.chunks(row_stride) {
+            compress_started.write_scanlines(row)?;
+        }
+
+        compress_started.finish()?;
+        Ok(output_data)
+    }))
+    .map_err(|_| anyhow::anyhow!("mozjpeg failed to process {}", input_path.display()))?;
+    let output_data = encoded?;
     fs::write(output_path, output_data)?;
 
     Ok(())
 }
+
+/// Returns whether the JPEG in `data` has four color components (CMYK or YCCK).
+///
+/// The `image` crate decodes such files to RGB, while the non-resized path of
+/// [`optimize_jpeg`] re-encodes them in CMYK.
+#[must_use]
+pub fn is_cmyk_jpeg(data: &[u8]) -> bool {
+    Jpeg::from_bytes(Bytes::copy_from_slice(data)).is_ok_and(|jpeg| {
+        jpeg.segments()
+            .iter()
+            .find(|segment| is_frame_marker(segment.marker()))
+            // Precision, height and width come before the component count
+            .is_some_and(|segment| segment.contents().get(5) == Some(&4))
+    })
+}
+
+/// Returns whether `marker` starts a frame (SOF0 to SOF15, which share their range with
+/// the DHT, JPG and DAC markers).
+const fn is_frame_marker(marker: u8) -> bool {
+    matches!(marker, 0xC0..=0xCF) && !matches!(marker, 0xC4 | 0xC8 | 0xCC)
+}
+
+/// Writes an ICC profile as `ICC_PROFILE` APP2 markers.
+///
+/// mozjpeg's own `write_icc_profile` numbers the chunks from 0, which decoders reject;
+/// the ICC specification requires sequence numbers starting at 1.
+fn write_icc_markers<W>(
+    compress_started: &mut mozjpeg::compress::CompressStarted<W>,
+    profile: &[u8],
+) -> Result<()> {
+    const HEADER: &[u8] = b"ICC_PROFILE\0";
+    const MAX_CHUNK_LEN: usize = 65_533 - HEADER.len() - 2;
+
+    let chunks: Vec<&[u8]> = profile.chunks(MAX_CHUNK_LEN).collect();
+    let count = u8::try_from(chunks.len()).context("ICC profile too large")?;
+    for (sequence, chunk) in (1..=count).zip(chunks) {
+        let mut marker = Vec::with_capacity(HEADER.len() + 2 + chunk.len());
+        marker.extend_from_slice(HEADER);
+        marker.extend_from_slice(&[sequence, count]);
+        marker.extend_from_slice(chunk);
+        compress_started.write_marker(mozjpeg::Marker::APP(2), &marker);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use image::codecs::jpeg::JpegEncoder;
+    use image::{GrayImage, ImageEncoder, RgbImage};
+
+    fn write_test_jpeg_with_icc(path: &Path, profile: &[u8]) {
+        let img = RgbImage::from_fn(32, 32, |x, y| {
+            image::Rgb([
+                u8::try_from(x * 8).unwrap_or(u8::MAX),
+                u8::try_from(y * 8).unwrap_or(u8::MAX),
+                0,
+            ])
+        });
+        let mut data = Vec::new();
+        let mut encoder = JpegEncoder::new_with_quality(&mut data, 95);
+        encoder.set_icc_profile(profile.to_vec()).unwrap();
+        encoder
+            .write_image(img.as_raw(), 32, 32, image::ExtendedColorType::Rgb8)
+            .unwrap();
+        fs::write(path, data).unwrap();
+    }
+
+    #[test]
+    fn test_keep_icc_preserves_profile() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_jpeg_keep_icc");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let input = temp_dir.join("input.jpg");
+        let output = temp_dir.join("output.jpg");
+        let profile = b"fake icc profile data".to_vec();
+        write_test_jpeg_with_icc(&input, &profile);
+
+        let args = Cli::parse_from(["image-optimizer", "--keep-icc"]);
+        optimize_jpeg(&input, &output, &args, None).unwrap();
+        assert_eq!(read_icc_profile(&output).unwrap(), Some(profile));
+
+        let args = Cli::parse_from(["image-optimizer"]);
+        optimize_jpeg(&input, &output, &args, None).unwrap();
+        assert_eq!(read_icc_profile(&output).unwrap(), None);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_transparency_is_flattened_onto_background() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_jpeg_background");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let input = temp_dir.join("input.png");
+        let output = temp_dir.join("output.jpg");
+        // Left half fully transparent (storing black), right half opaque blue
+        let png = image::RgbaImage::from_fn(32, 32, |x, _| {
+            if x < 16 {
+                image::Rgba([0, 0, 0, 0])
+            } else {
+                image::Rgba([0, 0, 255, 255])
+            }
+        });
+        png.save(&input).unwrap();
+        let png = DynamicImage::ImageRgba8(png);
+
+        let args = Cli::parse_from(["image-optimizer", "--jpeg-quality", "95"]);
+        optimize_jpeg(&input, &output, &args, Some(png.clone())).unwrap();
+        let jpeg = image::open(&output).unwrap().to_rgb8();
+        let close_to = |pixel: &image::Rgb<u8>, expected: [u8; 3]| {
+            pixel
+                .0
+                .iter()
+                .zip(expected)
+                .all(|(&a, b)| a.abs_diff(b) <= 8)
+        };
+        assert!(close_to(jpeg.get_pixel(4, 16), [255, 255, 255]));
+        assert!(close_to(jpeg.get_pixel(28, 16), [0, 0, 255]));
+
+        let args = Cli::parse_from([
+            "image-optimizer",
+            "--jpeg-quality",
+            "95",
+            "--background",
+            "#ff8000",
+        ]);
+        optimize_jpeg(&input, &output, &args, Some(png)).unwrap();
+        let jpeg = image::open(&output).unwrap().to_rgb8();
+        assert!(close_to(jpeg.get_pixel(4, 16), [255, 128, 0]));
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_no_upscale_quality_keeps_low_quality_sources_low() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_jpeg_no_upscale");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let input = temp_dir.join("input.jpg");
+        let img = RgbImage::from_fn(64, 64, |x, y| {
+            image::Rgb([
+                u8::try_from(x * 4).unwrap_or(u8::MAX),
+                u8::try_from(y * 4).unwrap_or(u8::MAX),
+                u8::try_from((x * y) % 256).unwrap_or(0),
+            ])
+        });
+        let mut data = Vec::new();
+        JpegEncoder::new_with_quality(&mut data, 60)
+            .write_image(img.as_raw(), 64, 64, image::ExtendedColorType::Rgb8)
+            .unwrap();
+        fs::write(&input, &data).unwrap();
+        assert_eq!(estimate_jpeg_quality(&data), Some(60));
+        assert!(!is_cmyk_jpeg(&data));
+
+        let encode = |name: &str, args: &[&str]| {
+            let output = temp_dir.join(name);
+            let args =
+                Cli::parse_from(std::iter::once("image-optimizer").chain(args.iter().copied()));
+            optimize_jpeg(&input, &output, &args, None).unwrap();
+            fs::read(output).unwrap()
+        };
+        let capped = encode(
+            "capped.jpg",
+            &["--jpeg-quality", "85", "--no-upscale-quality"],
+        );
+        let at_source = encode("source.jpg", &["--jpeg-quality", "60"]);
+        let upscaled = encode("upscaled.jpg", &["--jpeg-quality", "85"]);
+
+        assert_eq!(capped, at_source);
+        assert!(capped.len() < upscaled.len());
+
+        // A lower requested quality is still honored
+        let lower = encode(
+            "lower.jpg",
+            &["--jpeg-quality", "40", "--no-upscale-quality"],
+        );
+        assert_eq!(lower, encode("plain.jpg", &["--jpeg-quality", "40"]));
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_turning_off_trellis_and_optimize_coding_is_not_smaller() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_jpeg_trellis");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let input = temp_dir.join("input.png");
+        let img = RgbImage::from_fn(96, 96, |x, y| {
+            image::Rgb([
+                u8::try_from(x * 2).unwrap_or(u8::MAX),
+                u8::try_from((x * y) % 256).unwrap_or(0),
+                u8::try_from(y * 2).unwrap_or(u8::MAX),
+            ])
+        });
+        img.save(&input).unwrap();
+        let img = DynamicImage::ImageRgb8(img);
+
+        let encode = |name: &str, args: &[&str]| {
+            let output = temp_dir.join(name);
+            let args =
+                Cli::parse_from(std::iter::once("image-optimizer").chain(args.iter().copied()));
+            optimize_jpeg(&input, &output, &args, Some(img.clone())).unwrap();
+            let data = fs::read(output).unwrap();
+            mozjpeg::Decompress::new_mem(&data).unwrap();
+            data.len()
+        };
+        let default = encode("default.jpg", &[]);
+        let no_trellis = encode("no_trellis.jpg", &["--jpeg-trellis", "off"]);
+        let neither = encode(
+            "neither.jpg",
+            &["--jpeg-trellis", "off", "--jpeg-optimize-coding", "off"],
+        );
+
+        assert!(no_trellis >= default);
+        assert!(neither > no_trellis);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_grayscale_stays_grayscale() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_jpeg_grayscale");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let input = temp_dir.join("input.jpg");
+        let output = temp_dir.join("output.jpg");
+        let img = GrayImage::from_fn(64, 64, |x, y| {
+            image::Luma([u8::try_from((x * y) % 256).unwrap_or(0)])
+        });
+        let mut data = Vec::new();
+        JpegEncoder::new_with_quality(&mut data, 100)
+            .write_image(img.as_raw(), 64, 64, image::ExtendedColorType::L8)
+            .unwrap();
+        fs::write(&input, &data).unwrap();
+
+        let args = Cli::parse_from(["image-optimizer"]);
+        optimize_jpeg(&input, &output, &args, None).unwrap();
+
+        let optimized = fs::read(&output).unwrap();
+        assert!(optimized.len() < data.len());
+        let decompress = mozjpeg::Decompress::new_mem(&optimized).unwrap();
+        assert_eq!(decompress.color_space(), ColorSpace::JCS_GRAYSCALE);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_cmyk_input_is_reencoded_as_cmyk() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_jpeg_cmyk");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let input = temp_dir.join("input.jpg");
+        let output = temp_dir.join("output.jpg");
+        let pixels: Vec<u8> = (0..32 * 32 * 4)
+            .map(|i| u8::try_from(i % 251).unwrap_or(0))
+            .collect();
+        let mut compress = mozjpeg::Compress::new(ColorSpace::JCS_CMYK);
+        compress.set_size(32, 32);
+        let mut started = compress.start_compress(Vec::new()).unwrap();
+        started.write_scanlines(&pixels).unwrap();
+        fs::write(&input, started.finish().unwrap()).unwrap();
+        assert!(is_cmyk_jpeg(&fs::read(&input).unwrap()));
+
+        let args = Cli::parse_from(["image-optimizer"]);
+        optimize_jpeg(&input, &output, &args, None).unwrap();
+
+        let optimized = fs::read(&output).unwrap();
+        let decompress = mozjpeg::Decompress::new_mem(&optimized).unwrap();
+        assert_eq!(decompress.color_space(), ColorSpace::JCS_CMYK);
+        assert_eq!((decompress.width(), decompress.height()), (32, 32));
+
+        // Resized pixels have no CMYK representation and are written as RGB
+        let resized = image::open(&input).unwrap().thumbnail(16, 16);
+        optimize_jpeg(&input, &output, &args, Some(resized)).unwrap();
+        let optimized = fs::read(&output).unwrap();
+        assert!(!is_cmyk_jpeg(&optimized));
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
@@ -0,0 +1,132 @@
+use img_parts::Bytes;
+use img_parts::jpeg::{Jpeg, markers};
+
+/// The IJG standard luminance quantization table (quality 50), in zigzag order as it is
+/// stored in DQT segments.
+const STANDARD_LUMINANCE: [u16; 64] = [
+    16, 11, 12, 14, 12, 10, 16, 14, 13, 14, 18, 17, 16, 19, 24, 40, 26, 24, 22, 22, 24, 49, 35, 37,
+    29, 40, 58, 51, 61, 60, 57, 51, 56, 55, 64, 72, 92, 78, 64, 68, 87, 69, 55, 56, 80, 109, 81,
+    87, 95, 98, 103, 104, 103, 62, 77, 113, 121, 112, 100, 120, 92, 101, 103, 99,
+];
+
+/// Estimates the quality a JPEG was encoded with from its luminance quantization table.
+///
+/// Encoders based on libjpeg derive their tables by scaling the IJG standard table with
+/// the quality setting. The estimate is the quality (1-100) whose scaled table is closest
+/// to the file's table 0, so it is exact for libjpeg-style encoders and a close
+/// approximation for encoders with their own tables (such as mozjpeg's defaults).
+///
+/// # Arguments
+///
+/// * `data` - Contents of a JPEG file
+///
+/// # Returns
+///
+/// Returns the estimated quality, or `None` if the data is not a JPEG or has no
+/// luminance quantization table.
+///
+/// # Examples
+///
+/// ```rust
+/// use image::codecs::jpeg::JpegEncoder;
+/// use image::{ImageEncoder, RgbImage};
+/// use image_optimizer::optimization::estimate_jpeg_quality;
+///
+/// let img = RgbImage::from_pixel(16, 16, image::Rgb([200, 120, 40]));
+/// let mut data = Vec::new();
+/// JpegEncoder::new_with_quality(&mut data, 60)
+///     .write_image(img.as_raw(), 16, 16, image::ExtendedColorType::Rgb8)
+///     .unwrap();
+/// assert_eq!(estimate_jpeg_quality(&data), Some(60));
+/// assert_eq!(estimate_jpeg_quality(b"not a jpeg"), None);
+/// ```
+#[must_use]
+pub fn estimate_jpeg_quality(data: &[u8]) -> Option<u8> {
+    let jpeg = Jpeg::from_bytes(Bytes::copy_from_slice(data)).ok()?;
+    let table = jpeg
+        .segments()
+        .iter()
+        .filter(|segment| segment.marker() == markers::DQT)
+        .find_map(|segment| luminance_table(segment.contents()))?;
+
+    (1..=100u8).rev().min_by_key(|&quality| {
+        scaled_table(quality)
+            .iter()
+            .zip(&table)
+            .map(|(&expected, &actual)| u32::from(expected.abs_diff(actual)))
+            .sum::<u32>()
+    })
+}
+
+/// Returns table 0 of a DQT segment, which may hold several tables.
+fn luminance_table(mut contents: &[u8]) -> Option<[u16; 64]> {
+    while let Some((&header, rest)) = contents.split_first() {
+        // High nibble: precision (0 = 8-bit, 1 = 16-bit values); low nibble: table id
+        let (wide, id) = (header >> 4 == 1, header & 0x0F);
+        let size = if wide { 128 } else { 64 };
+        let values = rest.get(..size)?;
+        if id == 0 {
+            let mut table = [0u16; 64];
+            for (index, value) in table.iter_mut().enumerate() {
+                *value = if wide {
+                    u16::from_be_bytes([values[index * 2], values[index * 2 + 1]])
+                } else {
+                    u16::from(values[index])
+                };
+            }
+            return Some(table);
+        }
+        contents = &rest[size..];
+    }
+    None
+}
+
+/// The standard luminance table scaled for `quality` the way libjpeg does it.
+fn scaled_table(quality: u8) -> [u16; 64] {
+    let quality = u32::from(quality.clamp(1, 100));
+    let scale = if quality < 50 {
+        5000 / quality
+    } else {
+        200 - quality * 2
+    };
+    STANDARD_LUMINANCE.map(|value| {
+        let scaled = (u32::from(value) * scale + 50) / 100;
+        u16::try_from(scaled.clamp(1, 255)).unwrap_or(255)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::codecs::jpeg::JpegEncoder;
+    use image::{ImageEncoder, RgbImage};
+
+    fn encode(quality: u8) -> Vec<u8> {
+        let img = RgbImage::from_fn(32, 32, |x, y| {
+            #[allow(clippy::cast_possible_truncation)]
+            image::Rgb([(x * 8) as u8, (y * 8) as u8, 90])
+        });
+        let mut data = Vec::new();
+        JpegEncoder::new_with_quality(&mut data, quality)
+            .write_image(img.as_raw(), 32, 32, image::ExtendedColorType::Rgb8)
+            .unwrap();
+        data
+    }
+
+    #[test]
+    fn test_estimates_libjpeg_qualities() {
+        for quality in [10, 35, 50, 60, 75, 85, 95, 100] {
+            assert_eq!(estimate_jpeg_quality(&encode(quality)), Some(quality));
+        }
+    }
+
+    #[test]
+    fn test_reads_16_bit_tables() {
+        let mut contents = vec![0x10];
+        for value in scaled_table(40) {
+            contents.extend_from_slice(&value.to_be_bytes());
+        }
+        assert_eq!(luminance_table(&contents), Some(scaled_table(40)));
+        assert_eq!(luminance_table(&contents[..100]), None);
+    }
+}
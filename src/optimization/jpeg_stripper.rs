@@ -0,0 +1,148 @@
+use anyhow::{Context, Result};
+use img_parts::jpeg::{Jpeg, JpegSegment, markers};
+use std::fs;
+use std::path::Path;
+
+use crate::cli::Cli;
+
+/// Signature at the start of the APP2 segments that carry an ICC profile.
+const ICC_SIGNATURE: &[u8] = b"ICC_PROFILE\0";
+
+/// Removes metadata segments from a JPEG without re-encoding it (`--strip-only`).
+///
+/// EXIF and XMP (APP1), comments (COM) and the other application segments are dropped;
+/// the quantization tables, Huffman tables and entropy-coded data are copied byte for
+/// byte, so the decoded pixels are identical to the input. The JFIF (APP0) and Adobe
+/// (APP14) segments are kept because they affect how colors are decoded, and the ICC
+/// profile (APP2) is kept with `--keep-icc`.
+///
+/// # Arguments
+///
+/// * `input_path` - Path to the source JPEG file
+/// * `output_path` - Path where the stripped JPEG will be written
+/// * `args` - CLI configuration; only `--keep-icc` is used
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or written, or if its segment structure
+/// cannot be parsed.
+pub fn strip_jpeg(input_path: &Path, output_path: &Path, args: &Cli) -> Result<()> {
+    let data = fs::read(input_path)?;
+    let mut jpeg = Jpeg::from_bytes(data.into())
+        .with_context(|| format!("Failed to parse JPEG segments of {}", input_path.display()))?;
+
+    jpeg.segments_mut()
+        .retain(|segment| is_kept(segment, args.keep_icc));
+
+    let mut output = Vec::with_capacity(jpeg.len());
+    jpeg.encoder().write_to(&mut output)?;
+    fs::write(output_path, output)?;
+
+    Ok(())
+}
+
+/// Whether a segment is needed to decode the image as before.
+fn is_kept(segment: &JpegSegment, keep_icc: bool) -> bool {
+    match segment.marker() {
+        markers::APP0 | markers::APP14 => true,
+        markers::APP2 => keep_icc && segment.contents().starts_with(ICC_SIGNATURE),
+        markers::COM => false,
+        marker => !(markers::APP0..=markers::APP15).contains(&marker),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use image::codecs::jpeg::JpegEncoder;
+    use image::{ImageEncoder, RgbImage};
+    use img_parts::Bytes;
+
+    /// Writes a JPEG with an EXIF segment, an XMP segment, a comment and an ICC profile.
+    fn write_jpeg_with_metadata(path: &Path) {
+        let img = RgbImage::from_fn(48, 32, |x, y| {
+            image::Rgb([
+                u8::try_from(x * 5).unwrap_or(u8::MAX),
+                u8::try_from(y * 7).unwrap_or(u8::MAX),
+                90,
+            ])
+        });
+        let mut data = Vec::new();
+        let mut encoder = JpegEncoder::new_with_quality(&mut data, 90);
+        encoder
+            .set_icc_profile(b"fake icc profile".to_vec())
+            .unwrap();
+        encoder
+            .write_image(img.as_raw(), 48, 32, image::ExtendedColorType::Rgb8)
+            .unwrap();
+
+        let mut jpeg = Jpeg::from_bytes(data.into()).unwrap();
+        let metadata = [
+            (markers::APP1, &b"Exif\0\0II*\0camera serial 1234"[..]),
+            (
+                markers::APP1,
+                &b"http://ns.adobe.com/xap/1.0/\0<x:xmpmeta/>"[..],
+            ),
+            (markers::COM, &b"edited on a laptop"[..]),
+        ];
+        for (index, (marker, contents)) in metadata.into_iter().enumerate() {
+            let segment = JpegSegment::new_with_contents(marker, Bytes::from_static(contents));
+            jpeg.segments_mut().insert(index + 1, segment);
+        }
+        let mut output = Vec::new();
+        jpeg.encoder().write_to(&mut output).unwrap();
+        fs::write(path, output).unwrap();
+    }
+
+    #[test]
+    fn test_strip_keeps_pixels_and_drops_metadata() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_strip_jpeg");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        let input = temp_dir.join("input.jpg");
+        let output = temp_dir.join("output.jpg");
+        write_jpeg_with_metadata(&input);
+
+        let args = Cli::parse_from(["image-optimizer", "--strip-only"]);
+        strip_jpeg(&input, &output, &args).unwrap();
+
+        let original = image::open(&input).unwrap().to_rgb8();
+        let stripped = image::open(&output).unwrap().to_rgb8();
+        assert_eq!(original.as_raw(), stripped.as_raw());
+
+        let stripped_data = fs::read(&output).unwrap();
+        assert!(stripped_data.len() < fs::read(&input).unwrap().len());
+        let jpeg = Jpeg::from_bytes(stripped_data.into()).unwrap();
+        for marker in [markers::APP1, markers::APP2, markers::COM] {
+            assert!(
+                jpeg.segment_by_marker(marker).is_none(),
+                "marker {marker:#x}"
+            );
+        }
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_strip_keeps_icc_profile_with_keep_icc() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_strip_jpeg_icc");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        let input = temp_dir.join("input.jpg");
+        let output = temp_dir.join("output.jpg");
+        write_jpeg_with_metadata(&input);
+
+        let args = Cli::parse_from(["image-optimizer", "--strip-only", "--keep-icc"]);
+        strip_jpeg(&input, &output, &args).unwrap();
+
+        assert_eq!(
+            crate::optimization::read_icc_profile(&output).unwrap(),
+            Some(b"fake icc profile".to_vec())
+        );
+        let jpeg = Jpeg::from_bytes(fs::read(&output).unwrap().into()).unwrap();
+        assert!(jpeg.segment_by_marker(markers::APP1).is_none());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
@@ -0,0 +1,213 @@
+use anyhow::Result;
+use mozjpeg_sys as ffi;
+use std::fs;
+use std::mem;
+use std::os::raw::{c_int, c_ulong};
+use std::panic::AssertUnwindSafe;
+use std::path::Path;
+use std::ptr;
+use std::slice;
+
+use crate::cli::Cli;
+
+/// Marker code of the APP2 segments that carry an ICC profile.
+const ICC_MARKER: c_int = 0xE2;
+
+/// Signature at the start of ICC profile segments.
+const ICC_SIGNATURE: &[u8] = b"ICC_PROFILE\0";
+
+/// Losslessly re-optimizes a JPEG on its DCT coefficients, like `jpegtran -optimize -progressive`.
+///
+/// The quantized coefficients are copied from the input and written again with optimized
+/// Huffman tables in progressive mode, so there is no generational loss: the output
+/// decodes to exactly the same pixels as the input. Metadata is dropped, except for the
+/// ICC profile with `--keep-icc`. Used for `--jpeg-lossless` when no resize is needed.
+///
+/// # Arguments
+///
+/// * `input_path` - Path to the source JPEG file
+/// * `output_path` - Path where the transcoded JPEG will be written
+/// * `args` - CLI configuration; only `--keep-icc` is used
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or written, or if libjpeg reports an
+/// error while reading or writing the coefficients.
+pub fn transcode_jpeg(input_path: &Path, output_path: &Path, args: &Cli) -> Result<()> {
+    let input_data = fs::read(input_path)?;
+    let keep_icc = args.keep_icc;
+
+    // libjpeg errors unwind out of the error handler, so they must not reach the caller
+    let output_data =
+        std::panic::catch_unwind(AssertUnwindSafe(|| transcode(&input_data, keep_icc)))
+            .map_err(|_| anyhow::anyhow!("mozjpeg failed to transcode {}", input_path.display()))?;
+    fs::write(output_path, output_data?)?;
+
+    Ok(())
+}
+
+/// Runs the coefficient copy through libjpeg and returns the new file contents.
+fn transcode(data: &[u8], keep_icc: bool) -> Result<Vec<u8>> {
+    let input_size = c_ulong::try_from(data.len())?;
+    let mut source_errors = error_manager();
+    let mut destination_errors = error_manager();
+
+    // SAFETY: the structs are zero-initialized as libjpeg expects before jpeg_Create*,
+    // point at error managers that outlive them, and are destroyed before returning.
+    // Errors unwind out of `unwind_error_exit` and are caught by the caller.
+    unsafe {
+        let mut source: Box<ffi::jpeg_decompress_struct> = Box::new(mem::zeroed());
+        source.common.err = &raw mut *source_errors;
+        ffi::jpeg_CreateDecompress(
+            &raw mut *source,
+            ffi::JPEG_LIB_VERSION,
+            mem::size_of::<ffi::jpeg_decompress_struct>(),
+        );
+        ffi::jpeg_mem_src(&mut source, data.as_ptr(), input_size);
+        if keep_icc {
+            ffi::jpeg_save_markers(&mut source, ICC_MARKER, 0xFFFF);
+        }
+        ffi::jpeg_read_header(&mut source, 1);
+        let coefficients = ffi::jpeg_read_coefficients(&mut source);
+
+        let mut destination: Box<ffi::jpeg_compress_struct> = Box::new(mem::zeroed());
+        destination.common.err = &raw mut *destination_errors;
+        ffi::jpeg_CreateCompress(
+            &raw mut *destination,
+            ffi::JPEG_LIB_VERSION,
+            mem::size_of::<ffi::jpeg_compress_struct>(),
+        );
+        ffi::jpeg_copy_critical_parameters(&source, &mut destination);
+        destination.optimize_coding = 1;
+        ffi::jpeg_simple_progression(&mut destination);
+
+        let mut buffer: *mut u8 = ptr::null_mut();
+        let mut size: c_ulong = 0;
+        ffi::jpeg_mem_dest(&mut destination, &raw mut buffer, &raw mut size);
+        ffi::jpeg_write_coefficients(&mut destination, coefficients);
+
+        let mut marker = source.marker_list;
+        while let Some(saved) = marker.as_ref() {
+            let contents = slice::from_raw_parts(saved.data, saved.data_length as usize);
+            if c_int::from(saved.marker) == ICC_MARKER && contents.starts_with(ICC_SIGNATURE) {
+                ffi::jpeg_write_marker(&mut destination, ICC_MARKER, saved.data, saved.data_length);
+            }
+            marker = saved.next;
+        }
+
+        ffi::jpeg_finish_compress(&mut destination);
+        let output = slice::from_raw_parts(buffer, usize::try_from(size)?).to_vec();
+        libc::free(buffer.cast());
+
+        ffi::jpeg_destroy_compress(&mut destination);
+        ffi::jpeg_finish_decompress(&mut source);
+        ffi::jpeg_destroy_decompress(&mut source);
+        Ok(output)
+    }
+}
+
+/// Error manager that unwinds instead of calling `exit()` on fatal libjpeg errors.
+fn error_manager() -> Box<ffi::jpeg_error_mgr> {
+    // SAFETY: the struct only holds integers and nullable function pointers, and
+    // jpeg_std_error fills in every field
+    let mut errors: Box<ffi::jpeg_error_mgr> = Box::new(unsafe { mem::zeroed() });
+    unsafe {
+        ffi::jpeg_std_error(&mut errors);
+    }
+    errors.error_exit = Some(unwind_error_exit);
+    errors.emit_message = Some(silence_message);
+    errors
+}
+
+extern "C-unwind" fn unwind_error_exit(cinfo: &mut ffi::jpeg_common_struct) {
+    // SAFETY: libjpeg only calls the handler with the struct the manager was set on
+    let code = unsafe { cinfo.err.as_ref() }.map_or(0, |errors| errors.msg_code);
+    std::panic::resume_unwind(Box::new(format!("libjpeg error code {code}")));
+}
+
+const extern "C-unwind" fn silence_message(_cinfo: &mut ffi::jpeg_common_struct, _level: c_int) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use image::codecs::jpeg::JpegEncoder;
+    use image::{ImageEncoder, RgbImage};
+
+    fn write_test_jpeg(path: &Path, profile: Option<&[u8]>) {
+        let img = RgbImage::from_fn(61, 37, |x, y| {
+            let value = if (x / 3 + y / 2) % 2 == 0 { 220 } else { 30 };
+            image::Rgb([value, u8::try_from(x * 4).unwrap_or(u8::MAX), 255 - value])
+        });
+        let mut data = Vec::new();
+        let mut encoder = JpegEncoder::new_with_quality(&mut data, 92);
+        if let Some(profile) = profile {
+            encoder.set_icc_profile(profile.to_vec()).unwrap();
+        }
+        encoder
+            .write_image(img.as_raw(), 61, 37, image::ExtendedColorType::Rgb8)
+            .unwrap();
+        fs::write(path, data).unwrap();
+    }
+
+    #[test]
+    fn test_transcode_keeps_decoded_pixels() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_jpeg_transcode");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        let input = temp_dir.join("input.jpg");
+        let output = temp_dir.join("output.jpg");
+        write_test_jpeg(&input, None);
+
+        let args = Cli::parse_from(["image-optimizer", "--jpeg-lossless"]);
+        transcode_jpeg(&input, &output, &args).unwrap();
+
+        let original = image::open(&input).unwrap().to_rgb8();
+        let transcoded = image::open(&output).unwrap().to_rgb8();
+        assert_eq!(original.dimensions(), transcoded.dimensions());
+        assert_eq!(original.as_raw(), transcoded.as_raw());
+        assert!(fs::metadata(&output).unwrap().len() < fs::metadata(&input).unwrap().len());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_transcode_copies_icc_profile_with_keep_icc() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_jpeg_transcode_icc");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        let input = temp_dir.join("input.jpg");
+        let output = temp_dir.join("output.jpg");
+        write_test_jpeg(&input, Some(b"fake icc profile"));
+
+        let args = Cli::parse_from(["image-optimizer", "--jpeg-lossless", "--keep-icc"]);
+        transcode_jpeg(&input, &output, &args).unwrap();
+        assert_eq!(
+            crate::optimization::read_icc_profile(&output).unwrap(),
+            Some(b"fake icc profile".to_vec())
+        );
+
+        let args = Cli::parse_from(["image-optimizer", "--jpeg-lossless"]);
+        transcode_jpeg(&input, &output, &args).unwrap();
+        assert_eq!(
+            crate::optimization::read_icc_profile(&output).unwrap(),
+            None
+        );
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_transcode_rejects_garbage() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_jpeg_transcode_garbage");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        let input = temp_dir.join("input.jpg");
+        fs::write(&input, b"\xFF\xD8\xFF\xE0 not really a jpeg").unwrap();
+
+        let args = Cli::parse_from(["image-optimizer", "--jpeg-lossless"]);
+        assert!(transcode_jpeg(&input, &temp_dir.join("output.jpg"), &args).is_err());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
@@ -0,0 +1,128 @@
+use std::sync::{Condvar, Mutex, PoisonError};
+
+/// Bounds the memory of the images being optimized at the same time (`--memory-limit`).
+///
+/// Each worker [acquires](Self::acquire) the estimated memory of its image before decoding
+/// it and returns it when the [`MemoryPermit`] is dropped after the result is written.
+/// Workers block while the budget is exhausted, so small images still run at full width
+/// while a cluster of huge ones is throttled. A request larger than the whole budget is
+/// clamped to it: that image then runs alone instead of waiting forever.
+///
+/// ## Examples
+///
+/// ```rust
+/// use image_optimizer::optimization::MemoryBudget;
+///
+/// let budget = MemoryBudget::new(100);
+/// let permit = budget.acquire(60);
+/// assert_eq!(budget.available(), 40);
+/// drop(permit);
+/// assert_eq!(budget.available(), 100);
+/// ```
+#[derive(Debug)]
+pub struct MemoryBudget {
+    capacity: u64,
+    available: Mutex<u64>,
+    released: Condvar,
+}
+
+/// Memory reserved from a [`MemoryBudget`], given back when dropped.
+#[derive(Debug)]
+#[must_use = "the memory is released as soon as the permit is dropped"]
+pub struct MemoryPermit<'a> {
+    budget: &'a MemoryBudget,
+    bytes: u64,
+}
+
+impl MemoryBudget {
+    /// Creates a budget of `capacity` bytes.
+    #[must_use]
+    pub const fn new(capacity: u64) -> Self {
+        Self {
+            capacity,
+            available: Mutex::new(capacity),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Reserves `bytes` of the budget, blocking until enough of it is available.
+    pub fn acquire(&self, bytes: u64) -> MemoryPermit<'_> {
+        let bytes = bytes.min(self.capacity);
+        let available = self
+            .available
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let mut available = self
+            .released
+            .wait_while(available, |available| *available < bytes)
+            .unwrap_or_else(PoisonError::into_inner);
+        *available -= bytes;
+        drop(available);
+        MemoryPermit {
+            budget: self,
+            bytes,
+        }
+    }
+
+    /// Returns the number of bytes not currently reserved.
+    #[must_use]
+    pub fn available(&self) -> u64 {
+        *self
+            .available
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Drop for MemoryPermit<'_> {
+    fn drop(&mut self) {
+        *self
+            .budget
+            .available
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) += self.bytes;
+        self.budget.released.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rayon::prelude::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[test]
+    fn test_concurrency_never_exceeds_budget() {
+        // Each image needs 40 of 100 bytes, so at most two may run at once
+        let budget = MemoryBudget::new(100);
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(8)
+            .build()
+            .unwrap();
+        pool.install(|| {
+            (0..32).into_par_iter().for_each(|_| {
+                let _permit = budget.acquire(40);
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                std::thread::sleep(Duration::from_millis(2));
+                running.fetch_sub(1, Ordering::SeqCst);
+            });
+        });
+
+        assert!(peak.load(Ordering::SeqCst) <= 2);
+        assert_eq!(budget.available(), 100);
+    }
+
+    #[test]
+    fn test_oversized_request_runs_alone() {
+        let budget = MemoryBudget::new(100);
+        let permit = budget.acquire(1_000);
+        assert_eq!(budget.available(), 0);
+        drop(permit);
+        assert_eq!(budget.available(), 100);
+    }
+}
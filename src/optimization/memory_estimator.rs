@@ -0,0 +1,60 @@
+use std::fs;
+use std::path::Path;
+
+use super::read_image_dimensions;
+
+/// Rough peak bytes per pixel while one image is optimized: the decoded buffer (up to
+/// 4 bytes for RGBA8), the converted copy handed to the encoder and the encoder's own
+/// working memory.
+const BYTES_PER_PIXEL: u64 = 12;
+
+/// Estimates the memory needed to optimize an image, for [`MemoryBudget`](super::MemoryBudget).
+///
+/// Raster images are estimated from the pixel dimensions in their header, so nothing is
+/// decoded. Files without readable dimensions, such as SVGs, are estimated by their file
+/// size, since they are processed as text.
+///
+/// # Arguments
+///
+/// * `path` - Path to the image file
+///
+/// # Returns
+///
+/// Returns the estimate in bytes, or 0 if the file cannot be read at all (optimizing it
+/// will then fail early without allocating much).
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use image_optimizer::optimization::estimate_image_memory;
+///
+/// assert_eq!(estimate_image_memory(Path::new("missing.png")), 0);
+/// ```
+#[must_use]
+pub fn estimate_image_memory(path: &Path) -> u64 {
+    read_image_dimensions(path).map_or_else(
+        || fs::metadata(path).map_or(0, |metadata| metadata.len()),
+        |(width, height)| u64::from(width) * u64::from(height) * BYTES_PER_PIXEL,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_from_header_dimensions() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_memory_estimate");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let png = temp_dir.join("image.png");
+        image::RgbImage::new(100, 50).save(&png).unwrap();
+        let svg = temp_dir.join("icon.svg");
+        fs::write(&svg, "<svg/>").unwrap();
+
+        assert_eq!(estimate_image_memory(&png), 100 * 50 * BYTES_PER_PIXEL);
+        assert_eq!(estimate_image_memory(&svg), 6);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
@@ -3,18 +3,148 @@
 //! This module provides format-specific image optimization capabilities for JPEG, PNG, WebP, and SVG
 //! formats. Each optimizer uses specialized libraries for maximum compression efficiency:
 //!
-//! - **JPEG**: Uses mozjpeg for superior compression compared to standard libjpeg
-//! - **PNG**: Uses oxipng with zopfli for advanced compression algorithms
-//! - **WebP**: Uses Google's WebP encoder with both lossy and lossless modes
-//! - **SVG**: Uses regex-based optimization to remove metadata and unused elements
+//! - **JPEG**: Uses mozjpeg for superior compression compared to standard libjpeg, or
+//!   rewrites the DCT coefficients without re-encoding with `--jpeg-lossless`
+//! - **PNG**: Uses oxipng with zopfli for advanced compression algorithms; animated PNGs
+//!   are resized frame by frame with [`write_apng`] so no frame is lost
+//! - **WebP**: Uses Google's WebP encoder with both lossy and lossless modes; animated
+//!   WebP images are re-encoded frame by frame with [`write_animated_webp`]
+//! - **SVG**: Uses a small XML tokenizer to remove comments, metadata and editor data
+//!   without touching the content of text, style and script elements, and rounds path
+//!   data to a configurable precision with [`round_path_data`]
+//!
+//! With `--strip-only`, JPEG, PNG and WebP files are not re-encoded: [`encode_image`]
+//! dispatches to the strippers instead, which only remove metadata segments and chunks and
+//! keep the pixel data byte for byte.
+//!
+//! Each optimizer is behind a Cargo feature of the same name (`jpeg`, `png`, `webp`, `svg`),
+//! all enabled by default. [`encode_image`] dispatches to the compiled-in encoders and
+//! reports [`OptimizeError::FormatDisabled`] for the others.
 //!
 //! The main entry point [`optimize_image`] automatically selects the appropriate optimizer
-//! based on file extension and coordinates the optimization process.
+//! based on file extension and coordinates the optimization process. [`optimize_stdin`]
+//! provides the same optimization for a single image piped through stdin/stdout, and
+//! [`optimize_responsive`] writes one resized copy per `--sizes` width, and
+//! [`optimize_cached`] skips images recorded as already optimized in a `--cache` manifest.
+//! [`optimize_batch`] runs the (optionally parallel) loop over many images and reports each
+//! result through a callback, which the CLI uses to drive its progress bar.
+//! [`optimize_deduplicated`] wraps it for `--dedup`, optimizing byte-identical images once
+//! and linking or copying the result to the other copies.
+//! With `--memory-limit`, a [`MemoryBudget`] bounds how many images are decoded at once
+//! based on [`estimate_image_memory`], which reads only the image header.
+//! With `--mark`, an [`SettingsMarker`] embedded in each written file lets later
+//! in-place runs skip it before decoding.
+//! [`CancelToken`] lets a batch stop early on Ctrl-C without leaving temporary files behind.
+//!
+//! With `--to-srgb`, [`convert_to_srgb`] maps images tagged with a wide-gamut ICC
+//! profile into sRGB before they are encoded.
+//!
+//! With `--verify`, [`verify_output`] decodes each result again before it is committed.
+//!
+//! With `--measure-quality`, [`QualityScore`] compares the decoded result against the
+//! original using self-contained SSIM ([`luma_ssim`]) and PSNR ([`luma_psnr`]) metrics.
+//!
+//! The optimizer functions return [`OptimizeError`], so library users can tell I/O,
+//! decode and encode failures apart; the binary converts it into an [`anyhow::Error`].
 
+pub mod alpha_flattener;
+#[cfg(feature = "png")]
+pub mod apng_encoder;
+pub mod batch_optimizer;
+pub mod cached_optimizer;
+pub mod cancel_token;
+pub mod capture_date_reader;
+pub mod date_directory;
+pub mod dedup_optimizer;
+pub mod format_encoder;
+pub mod format_group;
+pub mod header_reader;
+pub mod icc_reader;
+pub mod image_decoder;
+pub mod image_format;
 pub mod image_optimizer;
+pub mod image_outcome;
+pub mod invalid_image;
+#[cfg(feature = "jpeg")]
 pub mod jpeg_optimizer;
+#[cfg(feature = "jpeg")]
+pub mod jpeg_quality_estimator;
+#[cfg(feature = "jpeg")]
+pub mod jpeg_stripper;
+#[cfg(feature = "jpeg")]
+pub mod jpeg_transcoder;
+pub mod memory_budget;
+pub mod memory_estimator;
+pub mod optimize_error;
+pub mod output_path_resolver;
+pub mod output_verifier;
+#[cfg(feature = "png")]
 pub mod png_optimizer;
+#[cfg(feature = "png")]
+pub mod png_stripper;
+pub mod psnr_calculator;
+pub mod quality_score;
+pub mod responsive_optimizer;
+pub mod settings_marker;
+pub mod size_estimator;
+pub mod srgb_converter;
+pub mod ssim_calculator;
+pub mod stdin_optimizer;
+#[cfg(feature = "svg")]
 pub mod svg_optimizer;
+#[cfg(feature = "svg")]
+pub mod svg_path_rounder;
+pub mod svgz_codec;
+#[cfg(feature = "webp")]
+pub mod webp_animation_encoder;
+#[cfg(feature = "webp")]
 pub mod webp_optimizer;
+#[cfg(feature = "webp")]
+pub mod webp_stripper;
+#[cfg(feature = "svg")]
+pub mod xml_token;
+#[cfg(feature = "svg")]
+pub mod xml_tokenizer;
 
-pub use image_optimizer::optimize_image;
+pub use alpha_flattener::flatten_alpha;
+#[cfg(feature = "png")]
+pub use apng_encoder::{is_apng, write_apng};
+pub use batch_optimizer::optimize_batch;
+pub use cached_optimizer::optimize_cached;
+pub use cancel_token::{CancelToken, INTERRUPTED_EXIT_CODE};
+pub use capture_date_reader::read_capture_date;
+pub use date_directory::date_output_dir;
+pub use dedup_optimizer::optimize_deduplicated;
+pub use format_encoder::{encode_image, reuses_decoded_pixels};
+pub use format_group::FormatGroup;
+pub use header_reader::read_image_dimensions;
+pub use icc_reader::read_icc_profile;
+pub use image_decoder::decode_image;
+pub use image_format::ImageFormat;
+pub use image_optimizer::{optimize_image, resolved_output_path};
+pub use image_outcome::ImageOutcome;
+pub use invalid_image::InvalidImage;
+#[cfg(feature = "jpeg")]
+pub use jpeg_quality_estimator::estimate_jpeg_quality;
+pub use memory_budget::{MemoryBudget, MemoryPermit};
+pub use memory_estimator::estimate_image_memory;
+pub use optimize_error::OptimizeError;
+pub use output_path_resolver::resolve_output_path;
+pub use output_verifier::verify_output;
+pub use psnr_calculator::{MAX_PSNR, luma_psnr};
+pub use quality_score::QualityScore;
+pub use responsive_optimizer::optimize_responsive;
+pub use settings_marker::SettingsMarker;
+pub use size_estimator::estimate_optimized_size;
+pub use srgb_converter::{convert_to_srgb, needs_srgb_conversion};
+pub use ssim_calculator::luma_ssim;
+pub use stdin_optimizer::optimize_stdin;
+#[cfg(feature = "svg")]
+pub use svg_path_rounder::round_path_data;
+pub use svgz_codec::{SVGZ_EXTENSION, compress_svgz, decompress_svgz, is_svgz_path};
+#[cfg(feature = "webp")]
+pub use webp_animation_encoder::{is_animated_webp, write_animated_webp};
+#[cfg(feature = "svg")]
+pub use xml_token::{XmlAttribute, XmlToken};
+#[cfg(feature = "svg")]
+pub use xml_tokenizer::tokenize_xml;
@@ -0,0 +1,213 @@
+use std::io;
+use std::path::PathBuf;
+
+use super::{ImageFormat, InvalidImage};
+use crate::file_ops::format_bytes;
+
+/// Error returned by the optimizer functions, so library users can tell failure kinds apart.
+///
+/// The binary converts it into an [`anyhow::Error`]; library code can match on the
+/// variant instead, for example to retry after a full disk but give up on a corrupt file.
+///
+/// ## Examples
+///
+/// ```rust
+/// use clap::Parser;
+/// use std::path::Path;
+/// use image_optimizer::cli::Cli;
+/// use image_optimizer::optimization::{OptimizeError, optimize_image};
+///
+/// let args = Cli::parse_from(["image-optimizer"]);
+/// match optimize_image(Path::new("missing.png"), &args, Path::new(".")) {
+///     Err(OptimizeError::Io { source, .. }) => {
+///         assert_eq!(source.kind(), std::io::ErrorKind::NotFound);
+///     }
+///     other => panic!("unexpected result: {other:?}"),
+/// }
+/// ```
+#[derive(Debug, thiserror::Error)]
+pub enum OptimizeError {
+    /// Reading or writing a file failed, e.g. a missing file, missing permissions or a
+    /// full disk.
+    #[error("{message}")]
+    Io {
+        /// Description including the affected path where known
+        message: String,
+        /// The underlying I/O error
+        #[source]
+        source: io::Error,
+    },
+    /// The file is not a valid image of the format it claims to be. The original is
+    /// left untouched.
+    #[error(transparent)]
+    Decode(#[from] InvalidImage),
+    /// The format-specific encoder failed to write the optimized image.
+    #[error(transparent)]
+    Encode(anyhow::Error),
+    /// The file extension or detected format is not one the optimizer handles.
+    #[error("Unsupported file format: {0}")]
+    UnsupportedFormat(String),
+    /// Support for the format was not compiled into this build.
+    #[error(
+        "{} support is not compiled in (rebuild with the `{}` feature)",
+        .0.name(),
+        .0.feature()
+    )]
+    FormatDisabled(ImageFormat),
+    /// The file exceeds `--max-input-size` and was not decoded. The original is left
+    /// untouched.
+    #[error("{0} exceeds --max-input-size")]
+    InputTooLarge(String),
+    /// The file carries the `--mark` marker of the current settings and was not
+    /// optimized again.
+    #[error("already optimized with these settings (marked)")]
+    AlreadyMarked,
+    /// The output already exists and `--skip-existing`, or an output newer than the
+    /// input, kept it from being overwritten. Nothing was decoded or written.
+    #[error("{} already exists (--force overwrites it)", .0.display())]
+    OutputExists(PathBuf),
+    /// The optimized result was not smaller than the original and `--on-larger skip`
+    /// discarded it. The original is left untouched.
+    #[error(
+        "optimized size {} is not smaller than the original {} (--on-larger skip)",
+        format_bytes(*.optimized),
+        format_bytes(*.original)
+    )]
+    NotSmaller {
+        /// Size of the input file in bytes
+        original: u64,
+        /// Size of the discarded result in bytes
+        optimized: u64,
+    },
+    /// The optimized result did not pass `--verify` and was discarded. The original is
+    /// left untouched.
+    #[error("optimized result failed verification: {0}")]
+    VerificationFailed(String),
+    /// The optimized result was smaller than the original, but by less than
+    /// `--prefer-smaller-than-original-by`, so it was discarded. The original is kept.
+    #[error(
+        "optimized size {} saves less than {threshold}% of the original {}",
+        format_bytes(*.optimized),
+        format_bytes(*.original)
+    )]
+    BelowThreshold {
+        /// Size of the input file in bytes
+        original: u64,
+        /// Size of the discarded result in bytes
+        optimized: u64,
+        /// The `--prefer-smaller-than-original-by` percentage
+        threshold: u8,
+    },
+    /// The requested settings cannot be applied to this input.
+    #[error("{0}")]
+    InvalidConfig(String),
+}
+
+impl OptimizeError {
+    /// Wraps a failed file operation, keeping its context message and I/O cause.
+    ///
+    /// Errors without an underlying [`io::Error`] are kept as [`io::ErrorKind::Other`].
+    #[must_use]
+    pub fn io(error: anyhow::Error) -> Self {
+        let message = format!("{error:#}");
+        let source = error
+            .downcast::<io::Error>()
+            .unwrap_or_else(|error| io::Error::other(error.to_string()));
+        Self::Io { message, source }
+    }
+
+    /// Classifies an error from [`decode_image`](super::decode_image): an
+    /// [`InvalidImage`] becomes [`OptimizeError::Decode`], anything else is an I/O error.
+    #[must_use]
+    pub fn from_decode(error: anyhow::Error) -> Self {
+        match error.downcast::<InvalidImage>() {
+            Ok(invalid) => Self::Decode(invalid),
+            Err(error) => Self::io(error),
+        }
+    }
+
+    /// Returns whether the error may go away when the operation is repeated: an I/O
+    /// error that was interrupted (`EINTR`), would block (`EAGAIN`) or hit a busy
+    /// resource (`EBUSY`), as network filesystems report sporadically.
+    ///
+    /// Logical errors such as an unsupported format or a corrupt image are never
+    /// transient. `--retry` only repeats files that fail with a transient error.
+    #[must_use]
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Io { source, .. } => is_transient_kind(source.kind()),
+            Self::Encode(error) => error
+                .chain()
+                .filter_map(|cause| cause.downcast_ref::<io::Error>())
+                .any(|error| is_transient_kind(error.kind())),
+            _ => false,
+        }
+    }
+}
+
+const fn is_transient_kind(kind: io::ErrorKind) -> bool {
+    matches!(
+        kind,
+        io::ErrorKind::Interrupted | io::ErrorKind::WouldBlock | io::ErrorKind::ResourceBusy
+    )
+}
+
+impl From<io::Error> for OptimizeError {
+    fn from(source: io::Error) -> Self {
+        Self::Io {
+            message: source.to_string(),
+            source,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_io_keeps_context_and_kind() {
+        let error = Err::<(), _>(io::Error::from(io::ErrorKind::StorageFull))
+            .context("Failed to write out.png")
+            .unwrap_err();
+
+        let OptimizeError::Io { message, source } = OptimizeError::io(error) else {
+            panic!("expected an I/O error");
+        };
+        assert!(message.starts_with("Failed to write out.png"));
+        assert_eq!(source.kind(), io::ErrorKind::StorageFull);
+    }
+
+    #[test]
+    fn test_only_interrupted_and_busy_io_is_transient() {
+        for kind in [
+            io::ErrorKind::Interrupted,
+            io::ErrorKind::WouldBlock,
+            io::ErrorKind::ResourceBusy,
+        ] {
+            assert!(OptimizeError::from(io::Error::from(kind)).is_transient());
+        }
+        assert!(!OptimizeError::from(io::Error::from(io::ErrorKind::NotFound)).is_transient());
+
+        let encode = anyhow::Error::new(io::Error::from(io::ErrorKind::Interrupted))
+            .context("Failed to write out.jpg");
+        assert!(OptimizeError::Encode(encode).is_transient());
+        assert!(!OptimizeError::UnsupportedFormat("txt".to_string()).is_transient());
+    }
+
+    #[test]
+    fn test_from_decode_separates_invalid_images() {
+        let invalid = anyhow::Error::new(InvalidImage::new("truncated"));
+        assert!(matches!(
+            OptimizeError::from_decode(invalid),
+            OptimizeError::Decode(_)
+        ));
+
+        let missing = anyhow::Error::new(io::Error::from(io::ErrorKind::NotFound));
+        assert!(matches!(
+            OptimizeError::from_decode(missing),
+            OptimizeError::Io { .. }
+        ));
+    }
+}
@@ -0,0 +1,94 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::{date_output_dir, read_image_dimensions};
+use crate::cli::Cli;
+use crate::file_ops::{
+    apply_name_template, calculate_resize_dimensions, ensure_output_dir, normalize_extension,
+    svgz_output_path,
+};
+
+/// Returns where the optimized copy of `input_path` is written inside `output_dir`.
+///
+/// Mirrors the input structure unless `--flatten` is set, below the date subdirectory
+/// of `--organize-by-date` if given (see [`date_output_dir`]). Flattened files use the path
+/// planned for the run, or just their file name for files outside the plan (such as
+/// files picked up in watch mode). `--name-template` then renames the file,
+/// `--output-ext-normalize` rewrites the extension, and `--svgz` turns `.svg` into
+/// `.svgz`. Missing parent directories are created.
+///
+/// # Errors
+///
+/// Returns an error if the output directory cannot be created or a flattened input has
+/// no file name.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use clap::Parser;
+/// use std::path::Path;
+/// use image_optimizer::cli::Cli;
+/// use image_optimizer::optimization::resolve_output_path;
+///
+/// # fn example() -> anyhow::Result<()> {
+/// let args = Cli::parse_from(["image-optimizer", "-o", "optimized"]);
+/// let output_path = resolve_output_path(
+///     Path::new("optimized"),
+///     Path::new("photos/2024/beach.jpg"),
+///     &args,
+///     Path::new("photos"),
+/// )?;
+/// assert_eq!(output_path, Path::new("optimized/2024/beach.jpg"));
+/// # Ok(())
+/// # }
+/// ```
+pub fn resolve_output_path(
+    output_dir: &Path,
+    input_path: &Path,
+    args: &Cli,
+    input_dir: &Path,
+) -> Result<PathBuf> {
+    let dated_dir = date_output_dir(output_dir, input_path, args);
+    let output_path = if !args.flatten {
+        ensure_output_dir(&dated_dir, input_dir, input_path)?
+    } else if let Some(planned) = args.flat_outputs.get(input_path) {
+        fs::create_dir_all(&dated_dir)?;
+        planned
+            .strip_prefix(output_dir)
+            .map_or_else(|_| planned.clone(), |relative| dated_dir.join(relative))
+    } else {
+        let name = input_path
+            .file_name()
+            .with_context(|| format!("{} has no file name", input_path.display()))?;
+        fs::create_dir_all(&dated_dir)?;
+        dated_dir.join(name)
+    };
+
+    let output_path = match args.name_template {
+        Some(ref template) => {
+            let dimensions = template
+                .uses_dimensions()
+                .then(|| read_image_dimensions(input_path))
+                .flatten()
+                .map(|(width, height)| {
+                    args.max_size.map_or((width, height), |max_size| {
+                        calculate_resize_dimensions(width, height, max_size)
+                    })
+                });
+            apply_name_template(&output_path, input_path, template, dimensions)
+        }
+        None => output_path,
+    };
+
+    // Planned --flatten paths are normalized already; normalizing again is a no-op
+    let output_path = match args.output_ext_normalize {
+        Some(normalization) => normalize_extension(&output_path, normalization),
+        None => output_path,
+    };
+    Ok(if args.svgz {
+        svgz_output_path(&output_path)
+    } else {
+        output_path
+    })
+}
@@ -0,0 +1,74 @@
+use std::path::Path;
+
+use super::{ImageFormat, OptimizeError, decode_image};
+
+/// Decodes a freshly written result for `--verify` and checks its dimensions.
+///
+/// This guards against encoder bugs: a result that cannot be decoded, or that decodes to
+/// another size than the image handed to the encoder, fails before it replaces the
+/// original or is kept in the output directory.
+///
+/// # Arguments
+///
+/// * `output_path` - The written result
+/// * `format` - Format the result was encoded in
+/// * `expected` - Width and height of the encoded image, after any `--max-size` resize
+///
+/// # Errors
+///
+/// Returns [`OptimizeError::VerificationFailed`] if the result does not decode, has no
+/// pixels, or has other dimensions than `expected`.
+pub fn verify_output(
+    output_path: &Path,
+    format: ImageFormat,
+    expected: (u32, u32),
+) -> Result<(), OptimizeError> {
+    let decoded = decode_image(output_path, format)
+        .map_err(|error| OptimizeError::VerificationFailed(format!("{error:#}")))?
+        .ok_or_else(|| {
+            OptimizeError::VerificationFailed(format!(
+                "{} has no pixels to verify",
+                output_path.display()
+            ))
+        })?;
+    let actual = (decoded.width(), decoded.height());
+    if actual != expected {
+        return Err(OptimizeError::VerificationFailed(format!(
+            "{} decodes as {}x{} instead of {}x{}",
+            output_path.display(),
+            actual.0,
+            actual.1,
+            expected.0,
+            expected.1
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(all(test, feature = "png"))]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_rejects_other_dimensions_and_undecodable_results() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_output_verifier");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let path = temp_dir.join("result.png");
+        image::RgbImage::from_pixel(8, 4, image::Rgb([10, 20, 30]))
+            .save(&path)
+            .unwrap();
+
+        assert!(verify_output(&path, ImageFormat::Png, (8, 4)).is_ok());
+        let error = verify_output(&path, ImageFormat::Png, (4, 8)).unwrap_err();
+        assert!(error.to_string().contains("decodes as 8x4 instead of 4x8"));
+
+        fs::write(&path, b"\x89PNG\r\n\x1a\ntruncated").unwrap();
+        assert!(matches!(
+            verify_output(&path, ImageFormat::Png, (8, 4)),
+            Err(OptimizeError::VerificationFailed(_))
+        ));
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
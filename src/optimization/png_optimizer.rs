@@ -3,15 +3,24 @@ use image::{DynamicImage, ImageFormat};
 use std::fs;
 use std::path::Path;
 
+use super::{is_apng, write_apng};
 use crate::cli::Cli;
 
 /// Optimizes a PNG image using oxipng with configurable optimization levels.
 ///
-/// This function uses `oxipng` with configurable optimization levels (0-6 or `"max"`).
+/// This function uses `oxipng` with configurable optimization levels (0-6, with `"max"`
+/// parsed as 6).
 /// Higher levels use zopfli compression for better compression at the cost of speed.
 /// It enables alpha optimization and safe chunk stripping for the best balance
 /// between file size reduction and compatibility.
 ///
+/// A resized image keeps the color type it was decoded with, so grayscale stays
+/// grayscale and 16-bit samples are not truncated; oxipng only lowers the bit depth or
+/// color type where that is lossless.
+///
+/// An animated PNG keeps all its frames: a resized one is rebuilt frame by frame (see
+/// [`write_apng`]), and oxipng recompresses every frame without reducing the color type.
+///
 /// # Arguments
 ///
 /// * `input_path` - Path to the source PNG file
@@ -26,7 +35,6 @@ use crate::cli::Cli;
 /// # Errors
 ///
 /// Returns an error if:
-/// - Invalid optimization level is provided (not 0-6 or `"max"`)
 /// - PNG optimization fails
 /// - File I/O operations fail (copying or saving)
 /// - Image format conversion fails
@@ -37,32 +45,24 @@ pub fn optimize_png(
     resized_img: Option<DynamicImage>,
 ) -> Result<()> {
     if let Some(img) = resized_img {
-        img.save_with_format(output_path, ImageFormat::Png)?;
+        if is_apng(input_path) {
+            write_apng(input_path, output_path, img.width(), img.height(), args)?;
+        } else {
+            img.save_with_format(output_path, ImageFormat::Png)?;
+        }
     } else {
         fs::copy(input_path, output_path)?;
     }
 
-    let optimization_level = if args.png_optimization_level == "max" {
-        6
-    } else {
-        match args.png_optimization_level.parse::<u8>() {
-            Ok(level) if level <= 6 => level,
-            _ => {
-                return Err(anyhow::anyhow!(
-                    "Invalid oxipng optimization level: {}. Valid values are 0-6 or 'max'",
-                    args.png_optimization_level
-                ));
-            }
-        }
-    };
-
-    let mut options = oxipng::Options::from_preset(optimization_level);
+    let mut options = oxipng::Options::from_preset(args.png_optimization_level.get());
     options.optimize_alpha = true;
     options.fast_evaluation = true;
     options.strip = oxipng::StripChunks::Safe;
 
     if args.no_zopfli {
-        options.deflate = oxipng::Deflaters::Libdeflater { compression: 12 };
+        options.deflate = oxipng::Deflaters::Libdeflater {
+            compression: args.deflate_level,
+        };
     } else {
         options.deflate = oxipng::Deflaters::Zopfli {
             iterations: args.zopfli_iterations,
@@ -79,3 +79,160 @@ pub fn optimize_png(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use image::AnimationDecoder;
+
+    /// Optimizes `img` with `--max-size 32` and returns the color type of the result.
+    fn resized_color(name: &str, img: &DynamicImage) -> image::ColorType {
+        let temp_dir = std::env::temp_dir().join(format!("image_optimizer_png_color_{name}"));
+        let _ = fs::remove_dir_all(&temp_dir);
+        let output_dir = temp_dir.join("out");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let input = temp_dir.join("input.png");
+        img.save_with_format(&input, ImageFormat::Png).unwrap();
+
+        let args = Cli::parse_from([
+            "image-optimizer",
+            "--no-zopfli",
+            "--max-size",
+            "32",
+            "-o",
+            output_dir.to_str().unwrap(),
+        ]);
+        crate::optimization::optimize_image(&input, &args, &temp_dir).unwrap();
+
+        // The resized result is written, not a copy of the larger original
+        let result = image::open(output_dir.join("input.png")).unwrap();
+        assert_eq!((result.width(), result.height()), (32, 32));
+        fs::remove_dir_all(&temp_dir).unwrap();
+        result.color()
+    }
+
+    fn apng_frames(path: &Path) -> Vec<image::RgbaImage> {
+        let decoder = image::codecs::png::PngDecoder::new(std::io::BufReader::new(
+            fs::File::open(path).unwrap(),
+        ))
+        .unwrap();
+        assert!(decoder.is_apng().unwrap());
+        decoder
+            .apng()
+            .unwrap()
+            .into_frames()
+            .map(|frame| frame.unwrap().into_buffer())
+            .collect()
+    }
+
+    #[test]
+    fn test_resize_keeps_apng_frames() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_png_apng");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let output_dir = temp_dir.join("out");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let input = temp_dir.join("spinner.png");
+        let mut encoder = png::Encoder::new(fs::File::create(&input).unwrap(), 64, 64);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_animated(3, 0).unwrap();
+        let mut writer = encoder.write_header().unwrap();
+        for shade in [0u8, 120, 240] {
+            writer.set_frame_delay(1, 10).unwrap();
+            writer
+                .write_image_data(&[shade, 64, 255 - shade, 255].repeat(64 * 64))
+                .unwrap();
+        }
+        writer.finish().unwrap();
+        assert!(is_apng(&input));
+
+        let args = Cli::parse_from([
+            "image-optimizer",
+            "--no-zopfli",
+            "--max-size",
+            "32",
+            "-o",
+            output_dir.to_str().unwrap(),
+        ]);
+        crate::optimization::optimize_image(&input, &args, &temp_dir).unwrap();
+
+        let frames = apng_frames(&output_dir.join("spinner.png"));
+        assert_eq!(frames.len(), 3);
+        assert!(frames.iter().all(|frame| frame.dimensions() == (32, 32)));
+        assert_eq!(frames[1].get_pixel(16, 16).0, [120, 64, 135, 255]);
+
+        // Without a resize, oxipng alone must keep the animation too
+        let args = Cli::parse_from(["image-optimizer", "--no-zopfli"]);
+        optimize_png(&input, &output_dir.join("copy.png"), &args, None).unwrap();
+        assert_eq!(apng_frames(&output_dir.join("copy.png")).len(), 3);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_lower_deflate_level_is_not_smaller() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_png_deflate_level");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        let input = temp_dir.join("input.png");
+        image::RgbImage::from_fn(128, 128, |x, y| {
+            #[allow(clippy::cast_possible_truncation)]
+            image::Rgb([(x * 2) as u8, (y * 2) as u8, ((x ^ y) % 7 * 30) as u8])
+        })
+        .save(&input)
+        .unwrap();
+
+        let size = |level: &str| {
+            let output = temp_dir.join(format!("level_{level}.png"));
+            let args =
+                Cli::parse_from(["image-optimizer", "--no-zopfli", "--deflate-level", level]);
+            optimize_png(&input, &output, &args, None).unwrap();
+            fs::metadata(&output).unwrap().len()
+        };
+        let fast = size("1");
+        let best = size("12");
+        assert!(
+            best <= fast,
+            "level 12: {best} bytes, level 1: {fast} bytes"
+        );
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_resize_keeps_grayscale() {
+        let img = image::GrayImage::from_fn(64, 64, |x, y| {
+            #[allow(clippy::cast_possible_truncation)]
+            image::Luma([(x * 2 + y) as u8])
+        });
+        assert_eq!(
+            resized_color("gray", &DynamicImage::ImageLuma8(img)),
+            image::ColorType::L8
+        );
+    }
+
+    #[test]
+    fn test_resize_keeps_16_bit_depth() {
+        let img = image::ImageBuffer::from_fn(64, 64, |x, y| {
+            #[allow(clippy::cast_possible_truncation)]
+            image::Rgb([
+                (x * 1021) as u16,
+                (y * 1009 + 3) as u16,
+                ((x + y) * 511 + 1) as u16,
+            ])
+        });
+        assert_eq!(
+            resized_color("rgb16", &DynamicImage::ImageRgb16(img)),
+            image::ColorType::Rgb16
+        );
+
+        let img = image::ImageBuffer::from_fn(64, 64, |x, y| {
+            #[allow(clippy::cast_possible_truncation)]
+            image::Luma([(x * 997 + y * 13 + 1) as u16])
+        });
+        assert_eq!(
+            resized_color("gray16", &DynamicImage::ImageLuma16(img)),
+            image::ColorType::L16
+        );
+    }
+}
@@ -0,0 +1,110 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+
+use crate::cli::Cli;
+
+/// Removes metadata chunks from a PNG without touching its image data (`--strip-only`).
+///
+/// oxipng is run with every reduction and IDAT recoding disabled, so only its safe chunk
+/// stripping applies: text, time and EXIF chunks are removed, while chunks that affect
+/// rendering (such as `iCCP`, `sRGB` and `pHYs`) and the compressed pixel data are kept.
+///
+/// # Arguments
+///
+/// * `input_path` - Path to the source PNG file
+/// * `output_path` - Path where the stripped PNG will be written
+/// * `_args` - CLI configuration (currently unused for stripping)
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read, parsed or written.
+pub fn strip_png(input_path: &Path, output_path: &Path, _args: &Cli) -> Result<()> {
+    let mut options = oxipng::Options::from_preset(0);
+    options.strip = oxipng::StripChunks::Safe;
+    options.idat_recoding = false;
+    options.bit_depth_reduction = false;
+    options.color_type_reduction = false;
+    options.palette_reduction = false;
+    options.grayscale_reduction = false;
+    options.optimize_alpha = false;
+    options.interlace = None;
+    // Write the stripped file even if oxipng finds nothing to improve
+    options.force = true;
+
+    let input_file = oxipng::InFile::Path(input_path.to_path_buf());
+    let output_file = oxipng::OutFile::Path {
+        path: Some(output_path.to_path_buf()),
+        preserve_attrs: true,
+    };
+
+    oxipng::optimize(&input_file, &output_file, &options).context("Failed to strip PNG")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use std::fs;
+
+    /// Returns the chunk types of a PNG file in order.
+    fn chunk_types(data: &[u8]) -> Vec<String> {
+        let mut types = Vec::new();
+        let mut position = 8;
+        while position + 8 <= data.len() {
+            let length = u32::from_be_bytes(data[position..position + 4].try_into().unwrap());
+            types.push(String::from_utf8_lossy(&data[position + 4..position + 8]).into_owned());
+            position += 12 + length as usize;
+        }
+        types
+    }
+
+    #[test]
+    fn test_strip_removes_text_and_keeps_pixels() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_strip_png");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        let input = temp_dir.join("input.png");
+        let output = temp_dir.join("output.png");
+
+        let img = image::RgbaImage::from_fn(40, 40, |x, y| {
+            #[allow(clippy::cast_possible_truncation)]
+            image::Rgba([(x * 6) as u8, (y * 6) as u8, 77, 255])
+        });
+        let mut data = Vec::new();
+        img.write_to(
+            &mut std::io::Cursor::new(&mut data),
+            image::ImageFormat::Png,
+        )
+        .unwrap();
+        // Insert a tEXt chunk right after IHDR (8 byte signature + 25 byte IHDR chunk)
+        let text = b"Comment\0exported by a photo editor";
+        let mut chunk = Vec::new();
+        chunk.extend_from_slice(&u32::try_from(text.len()).unwrap().to_be_bytes());
+        chunk.extend_from_slice(b"tEXt");
+        chunk.extend_from_slice(text);
+        let mut crc = flate2::Crc::new();
+        crc.update(b"tEXt");
+        crc.update(text);
+        chunk.extend_from_slice(&crc.sum().to_be_bytes());
+        data.splice(33..33, chunk);
+        fs::write(&input, &data).unwrap();
+        assert!(chunk_types(&data).contains(&"tEXt".to_string()));
+
+        let args = Cli::parse_from(["image-optimizer", "--strip-only"]);
+        strip_png(&input, &output, &args).unwrap();
+
+        let stripped = fs::read(&output).unwrap();
+        assert!(!chunk_types(&stripped).contains(&"tEXt".to_string()));
+        let original = image::open(&input).unwrap().to_rgba8();
+        let result = image::open(&output).unwrap().to_rgba8();
+        assert_eq!(original.as_raw(), result.as_raw());
+        assert_eq!(
+            image::open(&output).unwrap().color(),
+            image::ColorType::Rgba8
+        );
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
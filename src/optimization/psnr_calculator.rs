@@ -0,0 +1,59 @@
+use image::GrayImage;
+
+/// PSNR reported for identical images, whose mean squared error is zero.
+pub const MAX_PSNR: f64 = 100.0;
+
+/// Computes the peak signal-to-noise ratio (PSNR) of two equally sized luma images.
+///
+/// Higher is better; values above about 40 dB are rarely distinguishable by eye.
+/// Identical images would score infinity and are reported as [`MAX_PSNR`] instead, so
+/// the value can always be written to JSON.
+///
+/// # Returns
+///
+/// Returns the PSNR in decibels, or `None` if the dimensions differ or an image is empty.
+///
+/// # Examples
+///
+/// ```rust
+/// use image::GrayImage;
+/// use image_optimizer::optimization::{MAX_PSNR, luma_psnr};
+///
+/// let black = GrayImage::new(16, 16);
+/// assert_eq!(luma_psnr(&black, &black), Some(MAX_PSNR));
+/// ```
+#[must_use]
+pub fn luma_psnr(reference: &GrayImage, distorted: &GrayImage) -> Option<f64> {
+    if reference.dimensions() != distorted.dimensions() || reference.as_raw().is_empty() {
+        return None;
+    }
+
+    let squared_error: f64 = reference
+        .as_raw()
+        .iter()
+        .zip(distorted.as_raw())
+        .map(|(&a, &b)| (f64::from(a) - f64::from(b)).powi(2))
+        .sum();
+    let pixels = f64::from(reference.width()) * f64::from(reference.height());
+    let mean_squared_error = squared_error / pixels;
+    if mean_squared_error == 0.0 {
+        return Some(MAX_PSNR);
+    }
+    Some((10.0 * (255.0 * 255.0 / mean_squared_error).log10()).min(MAX_PSNR))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_psnr_of_constant_offset() {
+        let reference = GrayImage::from_pixel(10, 10, image::Luma([100]));
+        let shifted = GrayImage::from_pixel(10, 10, image::Luma([110]));
+
+        // MSE is 100, so PSNR is 10 * log10(65025 / 100)
+        let psnr = luma_psnr(&reference, &shifted).unwrap();
+        assert!((psnr - 28.13).abs() < 0.01, "PSNR {psnr}");
+        assert_eq!(luma_psnr(&reference, &GrayImage::new(5, 5)), None);
+    }
+}
@@ -0,0 +1,52 @@
+use image::DynamicImage;
+use image::imageops::FilterType;
+use serde::Serialize;
+
+use super::{luma_psnr, luma_ssim};
+
+/// Objective visual quality of an optimized image compared to its original.
+///
+/// Both metrics are computed over the luma channel, which carries most of the detail
+/// the eye notices; alpha is ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct QualityScore {
+    /// Structural similarity, 1.0 for identical images
+    pub ssim: f64,
+    /// Peak signal-to-noise ratio in dB (capped at 100 for identical images)
+    pub psnr: f64,
+}
+
+impl QualityScore {
+    /// Measures `optimized` against `reference` for `--measure-quality`.
+    ///
+    /// The comparison happens at the optimized image's resolution: when `--max-size`
+    /// shrank the output, the reference is resized the same way first.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use image::DynamicImage;
+    /// use image_optimizer::optimization::QualityScore;
+    ///
+    /// let image = DynamicImage::new_rgb8(32, 32);
+    /// let score = QualityScore::measure(&image, &image).unwrap();
+    /// assert_eq!(score.ssim, 1.0);
+    /// ```
+    #[must_use]
+    pub fn measure(reference: &DynamicImage, optimized: &DynamicImage) -> Option<Self> {
+        let (width, height) = (optimized.width(), optimized.height());
+        let reference = if (reference.width(), reference.height()) == (width, height) {
+            reference.to_luma8()
+        } else {
+            reference
+                .resize_exact(width, height, FilterType::Lanczos3)
+                .to_luma8()
+        };
+        let optimized = optimized.to_luma8();
+
+        Some(Self {
+            ssim: luma_ssim(&reference, &optimized)?,
+            psnr: luma_psnr(&reference, &optimized)?,
+        })
+    }
+}
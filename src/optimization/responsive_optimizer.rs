@@ -0,0 +1,209 @@
+use anyhow::Context;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::Path;
+
+use super::{
+    ImageFormat, ImageOutcome, OptimizeError, convert_to_srgb, date_output_dir, decode_image,
+    encode_image, read_icc_profile,
+};
+use crate::cli::Cli;
+use crate::file_ops::{
+    apply_name_template, ensure_output_dir, normalize_extension, restore_file_attributes,
+};
+
+/// Writes one optimized copy of a raster image per `--sizes` width.
+///
+/// Each copy is resized to the width with the aspect ratio preserved and written next to
+/// the mirrored output path with the width appended to the name, e.g. `photo-320.webp`,
+/// or named by `--name-template` with the copy's `{width}` and `{height}`.
+/// The codec is `--format` if given, otherwise the input format (whose extension is then
+/// kept, subject to `--output-ext-normalize`). Widths above the source width are skipped,
+/// so images are never upscaled. Unlike a single optimized file, the copies are always
+/// written: a resized image has no original to fall back to.
+///
+/// # Arguments
+///
+/// * `input_path` - Path to the source image
+/// * `format` - Format of the source image
+/// * `original_metadata` - Metadata of the source, used for sizes and timestamps
+/// * `args` - CLI configuration with the widths, output directory and encoder settings
+/// * `input_dir` - Base input directory used to mirror paths into the output directory
+///
+/// # Returns
+///
+/// Returns the original size and, as the optimized size, the size of the largest copy
+/// written. When every width exceeds the source, nothing is written and the sizes are
+/// equal.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - `--output` is missing or `--format` is not a raster format ([`OptimizeError::InvalidConfig`])
+/// - The output format is not compiled in ([`OptimizeError::FormatDisabled`])
+/// - The source cannot be decoded or a copy cannot be encoded or written
+pub fn optimize_responsive(
+    input_path: &Path,
+    format: ImageFormat,
+    original_metadata: &fs::Metadata,
+    args: &Cli,
+    input_dir: &Path,
+) -> Result<ImageOutcome, OptimizeError> {
+    let output_dir = args
+        .output
+        .as_deref()
+        .ok_or_else(|| OptimizeError::InvalidConfig("--sizes requires --output".into()))?;
+    let output_format = args.format.unwrap_or(format);
+    if !output_format.is_raster() {
+        return Err(OptimizeError::InvalidConfig(format!(
+            "--sizes cannot write {} images",
+            output_format.name()
+        )));
+    }
+    if !output_format.is_enabled() {
+        return Err(OptimizeError::FormatDisabled(output_format));
+    }
+    if let Some(ref template) = args.name_template
+        && args.sizes.len() > 1
+        && !template.uses_dimensions()
+    {
+        return Err(OptimizeError::InvalidConfig(
+            "--name-template needs a width or height placeholder to name several --sizes apart"
+                .into(),
+        ));
+    }
+
+    let img = decode_image(input_path, format)
+        .map_err(OptimizeError::from_decode)?
+        .with_context(|| format!("{} has no pixels to resize", input_path.display()))
+        .map_err(OptimizeError::io)?;
+    let img = if args.to_srgb {
+        read_icc_profile(input_path)
+            .map_err(OptimizeError::from_decode)?
+            .and_then(|profile| convert_to_srgb(&img, &profile))
+            .unwrap_or(img)
+    } else {
+        img
+    };
+    let mirrored = ensure_output_dir(
+        &date_output_dir(output_dir, input_path, args),
+        input_dir,
+        input_path,
+    )
+    .map_err(OptimizeError::io)?;
+    // Names are built as `OsString` so stems that are not valid UTF-8 survive
+    let stem = input_path
+        .file_stem()
+        .unwrap_or_else(|| OsStr::new("image"));
+    let extension = if output_format == format {
+        input_path
+            .extension()
+            .unwrap_or_else(|| OsStr::new(output_format.extension()))
+    } else {
+        OsStr::new(output_format.extension())
+    };
+    let file_name = |suffix: &str| {
+        let mut name = stem.to_os_string();
+        name.push(suffix);
+        name.push(".");
+        name.push(extension);
+        name
+    };
+
+    let mut widths = args.sizes.clone();
+    widths.sort_unstable_by(|a, b| b.cmp(a));
+    widths.dedup();
+
+    let mut largest = None;
+    for width in widths.into_iter().filter(|&width| width <= img.width()) {
+        let height = u64::from(img.height()) * u64::from(width) / u64::from(img.width());
+        let height = u32::try_from(height).unwrap_or(u32::MAX).max(1);
+        let resized = img.resize_exact(width, height, image::imageops::FilterType::Lanczos3);
+
+        let output_path = mirrored.with_file_name(file_name(""));
+        let output_path = args.name_template.as_ref().map_or_else(
+            || output_path.with_file_name(file_name(&format!("-{width}"))),
+            |template| {
+                apply_name_template(&output_path, input_path, template, Some((width, height)))
+            },
+        );
+        let output_path = match args.output_ext_normalize {
+            Some(normalization) => normalize_extension(&output_path, normalization),
+            None => output_path,
+        };
+        encode_image(output_format, input_path, &output_path, args, Some(resized))?;
+        restore_file_attributes(
+            &output_path,
+            original_metadata,
+            !args.no_preserve_timestamps,
+        )
+        .map_err(OptimizeError::io)?;
+
+        let size = fs::metadata(&output_path)?.len();
+        largest = largest.max(Some(size));
+    }
+
+    Ok(ImageOutcome {
+        original_size: original_metadata.len(),
+        optimized_size: largest.unwrap_or(original_metadata.len()),
+        quality: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimization::read_image_dimensions;
+    use clap::Parser;
+
+    #[test]
+    fn test_writes_one_copy_per_width() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_responsive");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let input_dir = temp_dir.join("images");
+        let output_dir = temp_dir.join("out");
+        fs::create_dir_all(&input_dir).unwrap();
+        let input = input_dir.join("photo.png");
+        image::RgbImage::from_fn(400, 200, |x, y| {
+            image::Rgb([u8::try_from(x % 256).unwrap(), u8::try_from(y).unwrap(), 90])
+        })
+        .save(&input)
+        .unwrap();
+
+        let args = Cli::parse_from([
+            "image-optimizer",
+            "-o",
+            output_dir.to_str().unwrap(),
+            "--sizes",
+            "80,320,640,160",
+            "--format",
+            "webp",
+        ]);
+        let metadata = fs::metadata(&input).unwrap();
+        let outcome =
+            optimize_responsive(&input, ImageFormat::Png, &metadata, &args, &input_dir).unwrap();
+
+        let mut names: Vec<_> = fs::read_dir(&output_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["photo-160.webp", "photo-320.webp", "photo-80.webp"]);
+        let dimensions: Vec<_> = ["320", "160", "80"]
+            .iter()
+            .map(|width| read_image_dimensions(&output_dir.join(format!("photo-{width}.webp"))))
+            .collect();
+        assert_eq!(
+            dimensions,
+            [Some((320, 160)), Some((160, 80)), Some((80, 40))]
+        );
+        assert_eq!(
+            outcome.optimized_size,
+            fs::metadata(output_dir.join("photo-320.webp"))
+                .unwrap()
+                .len()
+        );
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
@@ -0,0 +1,302 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+
+use super::ImageFormat;
+use crate::cli::Cli;
+use crate::settings::output_fingerprint;
+
+/// Start of every marker, followed by a hash of the settings.
+const MARKER_PREFIX: &str = "image-optimizer:";
+
+/// Private, ancillary, safe-to-copy PNG chunk type holding the marker.
+const PNG_CHUNK_TYPE: &[u8] = b"imOp";
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// JPEG comment (`COM`) and start-of-scan (`SOS`) marker codes.
+const JPEG_COM: u8 = 0xFE;
+const JPEG_SOS: u8 = 0xDA;
+
+/// Tiny `--mark` note recording that a file was optimized with the current settings.
+///
+/// The marker is `image-optimizer:` followed by 16 hex digits of a hash of the
+/// [`output_fingerprint`], so a change of any output-affecting setting (or of the tool
+/// version) invalidates it. It is stored as a private `imOp` chunk right after the PNG
+/// header, a `COM` segment before the first JPEG scan, or a comment at the end of an
+/// SVG, adding about 40 bytes. WebP and gzipped `.svgz` files are not marked.
+///
+/// The format is sniffed from the file contents, so a file converted with `--format`
+/// is marked according to what was written.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use clap::Parser;
+/// use std::path::Path;
+/// use image_optimizer::cli::Cli;
+/// use image_optimizer::optimization::SettingsMarker;
+///
+/// # fn example() -> anyhow::Result<()> {
+/// let args = Cli::parse_from(["image-optimizer", "--mark"]);
+/// let marker = SettingsMarker::for_settings(&args);
+/// marker.embed(Path::new("logo.png"))?;
+/// assert!(marker.is_present(Path::new("logo.png")));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettingsMarker {
+    text: String,
+}
+
+impl SettingsMarker {
+    /// Creates the marker for the output-affecting settings of `args`.
+    #[must_use]
+    pub fn for_settings(args: &Cli) -> Self {
+        let hash = blake3::hash(output_fingerprint(args).as_bytes()).to_hex();
+        Self {
+            text: format!("{MARKER_PREFIX}{}", &hash[..16]),
+        }
+    }
+
+    /// Returns the marker text as embedded in files.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns `true` if the file carries exactly this marker.
+    ///
+    /// Unreadable files, unsupported formats and markers written with other settings
+    /// all count as unmarked.
+    #[must_use]
+    pub fn is_present(&self, path: &Path) -> bool {
+        let Ok(data) = fs::read(path) else {
+            return false;
+        };
+        let marker = self.text.as_bytes();
+        match ImageFormat::from_magic_bytes(&data) {
+            Some(ImageFormat::Png) => png_chunks(&data)
+                .iter()
+                .any(|(_, kind, body)| *kind == PNG_CHUNK_TYPE && *body == marker),
+            Some(ImageFormat::Jpeg) => jpeg_header(&data).is_some_and(|(segments, _)| {
+                segments
+                    .iter()
+                    .any(|(_, kind, body)| *kind == JPEG_COM && *body == marker)
+            }),
+            Some(ImageFormat::Svg) => data
+                .trim_ascii_end()
+                .ends_with(self.svg_comment().as_bytes()),
+            Some(ImageFormat::Webp) | None => false,
+        }
+    }
+
+    /// Writes the marker into the file, replacing a marker from earlier settings.
+    ///
+    /// Files in a format without a marker, or whose structure cannot be followed, are
+    /// left unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or rewritten.
+    pub fn embed(&self, path: &Path) -> Result<()> {
+        let data = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let marked = match ImageFormat::from_magic_bytes(&data) {
+            Some(ImageFormat::Png) => self.mark_png(&data),
+            Some(ImageFormat::Jpeg) => self.mark_jpeg(&data),
+            Some(ImageFormat::Svg) => Some(self.mark_svg(&data)),
+            Some(ImageFormat::Webp) | None => None,
+        };
+        if let Some(marked) = marked {
+            fs::write(path, marked)
+                .with_context(|| format!("Failed to mark {}", path.display()))?;
+        }
+        Ok(())
+    }
+
+    fn mark_png(&self, data: &[u8]) -> Option<Vec<u8>> {
+        let chunks = png_chunks(data);
+        let (header, _, _) = chunks.first().filter(|(_, kind, _)| *kind == b"IHDR")?;
+        let marker = self.text.as_bytes();
+        let length = u32::try_from(marker.len()).ok()?;
+        let mut crc = flate2::Crc::new();
+        crc.update(PNG_CHUNK_TYPE);
+        crc.update(marker);
+
+        let mut output = Vec::with_capacity(data.len() + marker.len() + 12);
+        output.extend_from_slice(&data[..header.end]);
+        output.extend_from_slice(&length.to_be_bytes());
+        output.extend_from_slice(PNG_CHUNK_TYPE);
+        output.extend_from_slice(marker);
+        output.extend_from_slice(&crc.sum().to_be_bytes());
+        for (range, kind, _) in &chunks[1..] {
+            if *kind != PNG_CHUNK_TYPE {
+                output.extend_from_slice(&data[range.clone()]);
+            }
+        }
+        let end = chunks.last().map_or(header.end, |(range, _, _)| range.end);
+        output.extend_from_slice(&data[end..]);
+        Some(output)
+    }
+
+    fn mark_jpeg(&self, data: &[u8]) -> Option<Vec<u8>> {
+        let (segments, scan_start) = jpeg_header(data)?;
+        let marker = self.text.as_bytes();
+        let length = u16::try_from(marker.len() + 2).ok()?;
+
+        let mut output = Vec::with_capacity(data.len() + marker.len() + 4);
+        output.extend_from_slice(&data[..2]);
+        for (range, kind, body) in &segments {
+            if !(*kind == JPEG_COM && body.starts_with(MARKER_PREFIX.as_bytes())) {
+                output.extend_from_slice(&data[range.clone()]);
+            }
+        }
+        output.extend_from_slice(&[0xFF, JPEG_COM]);
+        output.extend_from_slice(&length.to_be_bytes());
+        output.extend_from_slice(marker);
+        output.extend_from_slice(&data[scan_start..]);
+        Some(output)
+    }
+
+    fn mark_svg(&self, data: &[u8]) -> Vec<u8> {
+        let mut content = data.trim_ascii_end();
+        if let Some(start) = content
+            .windows(4)
+            .rposition(|window| window == b"<!--")
+            .filter(|&start| {
+                content[start..].starts_with(format!("<!--{MARKER_PREFIX}").as_bytes())
+            })
+        {
+            content = content[..start].trim_ascii_end();
+        }
+        let mut output = content.to_vec();
+        output.extend_from_slice(self.svg_comment().as_bytes());
+        output.push(b'\n');
+        output
+    }
+
+    fn svg_comment(&self) -> String {
+        format!("<!--{}-->", self.text)
+    }
+}
+
+/// Byte range, type and data of a PNG chunk.
+type PngChunk<'a> = (Range<usize>, &'a [u8], &'a [u8]);
+
+/// Byte range, marker code and payload of a JPEG header segment.
+type JpegSegment<'a> = (Range<usize>, u8, &'a [u8]);
+
+/// Splits a PNG into its chunks: byte range, type and data of each, up to `IEND`.
+///
+/// Returns no chunks for data without a PNG signature; a truncated chunk ends the list.
+fn png_chunks(data: &[u8]) -> Vec<PngChunk<'_>> {
+    let mut chunks = Vec::new();
+    if !data.starts_with(PNG_SIGNATURE) {
+        return chunks;
+    }
+    let mut pos = PNG_SIGNATURE.len();
+    while let Some(&[a, b, c, d, ref kind @ ..]) = data.get(pos..pos + 8) {
+        let length = u32::from_be_bytes([a, b, c, d]) as usize;
+        let Some(body) = data.get(pos + 8..pos + 8 + length) else {
+            break;
+        };
+        let end = pos + 12 + length;
+        if end > data.len() {
+            break;
+        }
+        chunks.push((pos..end, kind, body));
+        if kind == b"IEND" {
+            break;
+        }
+        pos = end;
+    }
+    chunks
+}
+
+/// Splits the JPEG header into its segments: byte range, marker code and payload.
+///
+/// Returns the segments between `SOI` and the first `SOS` together with the offset of
+/// that `SOS`, or `None` if the header cannot be followed up to a scan.
+fn jpeg_header(data: &[u8]) -> Option<(Vec<JpegSegment<'_>>, usize)> {
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+    let mut segments = Vec::new();
+    let mut pos = 2;
+    while let Some(&[0xFF, kind, high, low]) = data.get(pos..pos + 4) {
+        if kind == JPEG_SOS {
+            return Some((segments, pos));
+        }
+        let end = pos + 2 + usize::from(u16::from_be_bytes([high, low]));
+        let body = data.get(pos + 4..end)?;
+        segments.push((pos..end, kind, body));
+        pos = end;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    fn marker() -> SettingsMarker {
+        SettingsMarker::for_settings(&Cli::parse_from(["image-optimizer", "--mark"]))
+    }
+
+    #[test]
+    fn test_marks_each_supported_format_once() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_marker_formats");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let png = temp_dir.join("a.png");
+        let jpeg = temp_dir.join("a.jpg");
+        let svg = temp_dir.join("a.svg");
+        let img = image::RgbImage::from_pixel(8, 8, image::Rgb([200, 10, 10]));
+        img.save_with_format(&png, image::ImageFormat::Png).unwrap();
+        img.save_with_format(&jpeg, image::ImageFormat::Jpeg)
+            .unwrap();
+        fs::write(&svg, "<svg xmlns=\"http://www.w3.org/2000/svg\"/>\n").unwrap();
+
+        let marker = marker();
+        let other = SettingsMarker::for_settings(&Cli::parse_from([
+            "image-optimizer",
+            "--mark",
+            "--jpeg-quality",
+            "50",
+        ]));
+        assert_ne!(marker, other);
+        for path in [&png, &jpeg, &svg] {
+            let size = fs::metadata(path).unwrap().len();
+            assert!(!marker.is_present(path));
+            other.embed(path).unwrap();
+            marker.embed(path).unwrap();
+            marker.embed(path).unwrap();
+
+            assert!(marker.is_present(path), "{}", path.display());
+            assert!(!other.is_present(path));
+            let growth = fs::metadata(path).unwrap().len() - size;
+            assert!(growth <= 44, "{} grew by {growth} bytes", path.display());
+        }
+        image::open(&png).unwrap();
+        image::open(&jpeg).unwrap();
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_webp_is_left_unmarked() {
+        let path = std::env::temp_dir().join("image_optimizer_marker_webp.webp");
+        let img = image::RgbaImage::from_pixel(8, 8, image::Rgba([0, 0, 255, 255]));
+        img.save_with_format(&path, image::ImageFormat::WebP)
+            .unwrap();
+        let original = fs::read(&path).unwrap();
+
+        marker().embed(&path).unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), original);
+        assert!(!marker().is_present(&path));
+        fs::remove_file(&path).unwrap();
+    }
+}
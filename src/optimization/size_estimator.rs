@@ -0,0 +1,73 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+use super::{ImageFormat, SVGZ_EXTENSION, decode_image, encode_image, is_svgz_path};
+use crate::cli::Cli;
+use crate::file_ops::{TempFile, calculate_resize_dimensions};
+
+/// Estimates the size an image would have after optimization with the current settings.
+///
+/// The image is optimized into a uniquely named temporary file that is removed
+/// afterwards, so concurrent estimates never share it; the input is never modified. Resizing with `--max-size` is taken into account.
+///
+/// # Arguments
+///
+/// * `input_path` - Image to estimate
+/// * `args` - CLI configuration with quality, resize and encoder settings
+///
+/// # Returns
+///
+/// Returns the size in bytes the optimizer produced.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - The format is not supported
+/// - The image cannot be decoded
+/// - Encoding or temporary file I/O fails
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use clap::Parser;
+/// use image_optimizer::cli::Cli;
+/// use image_optimizer::optimization::estimate_optimized_size;
+///
+/// # fn example() -> anyhow::Result<()> {
+/// let args = Cli::parse_from(["image-optimizer", "--jpeg-quality", "80"]);
+/// let estimated = estimate_optimized_size(Path::new("photo.jpg"), &args)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn estimate_optimized_size(input_path: &Path, args: &Cli) -> Result<u64> {
+    let format = ImageFormat::from_path(input_path)
+        .with_context(|| format!("Unsupported image format: {}", input_path.display()))?;
+
+    let img = decode_image(input_path, format)?.map(|img| {
+        let (width, height) = (img.width(), img.height());
+        match args.max_size {
+            Some(max_size) => {
+                let (new_width, new_height) = calculate_resize_dimensions(width, height, max_size);
+                if new_width == width && new_height == height {
+                    img
+                } else {
+                    img.resize(new_width, new_height, image::imageops::FilterType::Lanczos3)
+                }
+            }
+            None => img,
+        }
+    });
+
+    // An .svgz estimate is measured compressed, like the file it replaces
+    let extension = if is_svgz_path(input_path) {
+        SVGZ_EXTENSION
+    } else {
+        format.extension()
+    };
+    let output_file = TempFile::new("image-optimizer-estimate", extension)?;
+
+    encode_image(format, input_path, output_file.path(), args, img)?;
+    Ok(fs::metadata(output_file.path())?.len())
+}
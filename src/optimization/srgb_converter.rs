@@ -0,0 +1,234 @@
+use image::DynamicImage;
+use qcms::{DataType, Intent, Profile, Transform};
+
+/// Color space signature of RGB profiles in the ICC header.
+const RGB_COLOR_SPACE: &[u8] = b"RGB ";
+
+/// Largest channel difference, in 8-bit levels, at which a profile still counts as sRGB.
+const SRGB_TOLERANCE: u8 = 1;
+
+/// Converts the pixels of an image tagged with an ICC profile to sRGB for `--to-srgb`.
+///
+/// Browsers that ignore (or never see, once metadata is stripped) a wide-gamut profile
+/// such as Display P3 or Adobe RGB show the pixels as sRGB, so saturated colors look
+/// washed out. The conversion maps them into sRGB with the perceptual intent; colors
+/// outside the sRGB gamut are clipped. The result has 8 bits per channel, keeping the
+/// alpha channel if the image has one.
+///
+/// # Arguments
+///
+/// * `img` - Decoded image
+/// * `profile` - ICC profile embedded in the source file
+///
+/// # Returns
+///
+/// Returns the converted image, or `None` when there is nothing to convert: the profile
+/// cannot be parsed, does not describe RGB (grayscale and CMYK are left alone), the image
+/// has no color channels, or the profile already matches sRGB (see
+/// [`needs_srgb_conversion`]).
+///
+/// # Examples
+///
+/// ```rust
+/// use image_optimizer::optimization::convert_to_srgb;
+///
+/// let img = image::DynamicImage::new_rgb8(4, 4);
+/// assert!(convert_to_srgb(&img, b"not an ICC profile").is_none());
+/// ```
+#[must_use]
+pub fn convert_to_srgb(img: &DynamicImage, profile: &[u8]) -> Option<DynamicImage> {
+    if !img.color().has_color() || !needs_srgb_conversion(profile) {
+        return None;
+    }
+
+    if img.color().has_alpha() {
+        let transform = srgb_transform(profile, DataType::RGBA8)?;
+        let mut rgba = img.to_rgba8();
+        transform.apply(&mut rgba);
+        Some(DynamicImage::ImageRgba8(rgba))
+    } else {
+        let transform = srgb_transform(profile, DataType::RGB8)?;
+        let mut rgb = img.to_rgb8();
+        transform.apply(&mut rgb);
+        Some(DynamicImage::ImageRgb8(rgb))
+    }
+}
+
+/// Returns whether `--to-srgb` converts images tagged with `profile`.
+///
+/// A profile that maps a grid of probe colors to sRGB within one level per channel is
+/// treated as sRGB, whatever its name or version, so re-running the conversion on its
+/// own output or on an sRGB-tagged file changes nothing. Encoders use this to drop the
+/// source profile from converted images even with `--keep-icc`.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_optimizer::optimization::needs_srgb_conversion;
+///
+/// assert!(!needs_srgb_conversion(b"not an ICC profile"));
+/// ```
+#[must_use]
+pub fn needs_srgb_conversion(profile: &[u8]) -> bool {
+    let Some(transform) = srgb_transform(profile, DataType::RGB8) else {
+        return false;
+    };
+
+    let levels = [0u8, 32, 64, 128, 192, 255];
+    let mut probe = Vec::with_capacity(levels.len().pow(3) * 3);
+    for r in levels {
+        for g in levels {
+            for b in levels {
+                probe.extend_from_slice(&[r, g, b]);
+            }
+        }
+    }
+    let mut converted = probe.clone();
+    transform.apply(&mut converted);
+
+    probe
+        .iter()
+        .zip(&converted)
+        .any(|(before, after)| before.abs_diff(*after) > SRGB_TOLERANCE)
+}
+
+/// Builds the transform from an RGB `profile` to sRGB for pixels of `data_type`.
+fn srgb_transform(profile: &[u8], data_type: DataType) -> Option<Transform> {
+    // qcms asserts on pixel layouts that do not match the profile's color space
+    if profile.get(16..20) != Some(RGB_COLOR_SPACE) {
+        return None;
+    }
+    let source = Profile::new_from_slice(profile, false)?;
+    let mut srgb = Profile::new_sRGB();
+    srgb.precache_output_transform();
+    Transform::new(&source, &srgb, data_type, Intent::Perceptual)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{RgbImage, RgbaImage};
+
+    /// Builds the bytes of a small ICC v2 matrix/TRC display profile for tests.
+    ///
+    /// The colorants are the D50-adapted XYZ values of the red, green and blue primaries;
+    /// all three channels use the sRGB transfer curve.
+    fn rgb_test_profile(colorants: [[f64; 3]; 3]) -> Vec<u8> {
+        #[allow(clippy::cast_possible_truncation)]
+        fn s15_fixed16(value: f64) -> [u8; 4] {
+            ((value * 65536.0).round() as i32).to_be_bytes()
+        }
+
+        let mut tags: Vec<(&[u8; 4], Vec<u8>)> = Vec::new();
+        for (signature, xyz) in [b"rXYZ", b"gXYZ", b"bXYZ"].into_iter().zip(colorants) {
+            let mut data = b"XYZ \0\0\0\0".to_vec();
+            for value in xyz {
+                data.extend_from_slice(&s15_fixed16(value));
+            }
+            tags.push((signature, data));
+        }
+        let mut curve = b"para\0\0\0\0\0\x03\0\0".to_vec();
+        for value in [2.4, 1.0 / 1.055, 0.055 / 1.055, 1.0 / 12.92, 0.04045] {
+            curve.extend_from_slice(&s15_fixed16(value));
+        }
+        for signature in [b"rTRC", b"gTRC", b"bTRC"] {
+            tags.push((signature, curve.clone()));
+        }
+
+        let mut table = Vec::new();
+        let mut data = Vec::new();
+        let data_start = 128 + 4 + 12 * tags.len();
+        table.extend_from_slice(&u32::try_from(tags.len()).unwrap().to_be_bytes());
+        for (signature, tag) in tags {
+            let offset = u32::try_from(data_start + data.len()).unwrap();
+            table.extend_from_slice(signature);
+            table.extend_from_slice(&offset.to_be_bytes());
+            table.extend_from_slice(&u32::try_from(tag.len()).unwrap().to_be_bytes());
+            data.extend_from_slice(&tag);
+        }
+
+        let mut header = vec![0u8; 128];
+        let size = u32::try_from(128 + table.len() + data.len()).unwrap();
+        header[0..4].copy_from_slice(&size.to_be_bytes());
+        header[8] = 2;
+        header[12..16].copy_from_slice(b"mntr");
+        header[16..20].copy_from_slice(RGB_COLOR_SPACE);
+        header[20..24].copy_from_slice(b"XYZ ");
+        header[36..40].copy_from_slice(b"acsp");
+        [header, table, data].concat()
+    }
+
+    /// D50-adapted primaries of Display P3.
+    const DISPLAY_P3_COLORANTS: [[f64; 3]; 3] = [
+        [0.5151, 0.2412, -0.0011],
+        [0.2920, 0.6922, 0.0419],
+        [0.1571, 0.0666, 0.7841],
+    ];
+
+    /// D50-adapted primaries of sRGB.
+    const SRGB_COLORANTS: [[f64; 3]; 3] = [
+        [0.4361, 0.2225, 0.0139],
+        [0.3851, 0.7169, 0.0971],
+        [0.1431, 0.0606, 0.7141],
+    ];
+
+    #[test]
+    fn test_display_p3_is_converted() {
+        let profile = rgb_test_profile(DISPLAY_P3_COLORANTS);
+        assert!(needs_srgb_conversion(&profile));
+
+        let img = DynamicImage::ImageRgb8(RgbImage::from_fn(2, 1, |x, _| {
+            if x == 0 {
+                image::Rgb([180, 60, 60])
+            } else {
+                image::Rgb([128, 128, 128])
+            }
+        }));
+        let converted = convert_to_srgb(&img, &profile).unwrap().to_rgb8();
+
+        // A P3 red is more saturated than the same values in sRGB: red rises and the
+        // other channels fall
+        let [r, g, b] = converted.get_pixel(0, 0).0;
+        assert!(r >= 190, "red {r}");
+        assert!(g < 55 && b < 58, "green {g}, blue {b}");
+        // Both spaces share the white point and transfer curve, so grays stay put
+        let gray = converted.get_pixel(1, 0).0;
+        assert!(
+            gray.iter().all(|&level| level.abs_diff(128) <= 1),
+            "{gray:?}"
+        );
+    }
+
+    #[test]
+    fn test_alpha_is_kept() {
+        let profile = rgb_test_profile(DISPLAY_P3_COLORANTS);
+        let img =
+            DynamicImage::ImageRgba8(RgbaImage::from_pixel(2, 2, image::Rgba([180, 60, 60, 77])));
+
+        let converted = convert_to_srgb(&img, &profile).unwrap();
+        let DynamicImage::ImageRgba8(rgba) = converted else {
+            panic!("expected an RGBA image");
+        };
+        assert_eq!(rgba.get_pixel(1, 1).0[3], 77);
+    }
+
+    #[test]
+    fn test_srgb_profile_is_skipped() {
+        let profile = rgb_test_profile(SRGB_COLORANTS);
+        assert!(!needs_srgb_conversion(&profile));
+
+        let img = DynamicImage::new_rgb8(4, 4);
+        assert!(convert_to_srgb(&img, &profile).is_none());
+    }
+
+    #[test]
+    fn test_non_rgb_profile_is_skipped() {
+        let mut profile = rgb_test_profile(DISPLAY_P3_COLORANTS);
+        profile[16..20].copy_from_slice(b"GRAY");
+        assert!(!needs_srgb_conversion(&profile));
+
+        let img = DynamicImage::new_luma8(4, 4);
+        let profile = rgb_test_profile(DISPLAY_P3_COLORANTS);
+        assert!(convert_to_srgb(&img, &profile).is_none());
+    }
+}
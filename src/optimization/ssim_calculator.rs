@@ -0,0 +1,127 @@
+use image::GrayImage;
+
+/// Side of the square window the local statistics are computed over.
+const WINDOW: u32 = 8;
+/// Distance between neighbouring windows (windows overlap by half).
+const STEP: u32 = 4;
+/// Stabilizing constants from the SSIM paper for 8-bit samples: (0.01 * 255)² and (0.03 * 255)².
+const C1: f64 = 6.5025;
+const C2: f64 = 58.5225;
+
+/// Computes the structural similarity (SSIM) of two equally sized luma images.
+///
+/// This is the mean SSIM over 8x8 windows with uniform weights, placed every 4 pixels.
+/// Images smaller than a window are compared as a single window. The score is 1.0 for
+/// identical images and drops towards 0 as structure is lost.
+///
+/// # Arguments
+///
+/// * `reference` - Luma channel of the original image
+/// * `distorted` - Luma channel of the optimized image, same dimensions as `reference`
+///
+/// # Returns
+///
+/// Returns the mean SSIM, or `None` if the dimensions differ or an image is empty.
+///
+/// # Examples
+///
+/// ```rust
+/// use image::GrayImage;
+/// use image_optimizer::optimization::luma_ssim;
+///
+/// let image = GrayImage::from_fn(32, 32, |x, y| image::Luma([((x * y) % 256) as u8]));
+/// assert_eq!(luma_ssim(&image, &image), Some(1.0));
+/// ```
+#[must_use]
+pub fn luma_ssim(reference: &GrayImage, distorted: &GrayImage) -> Option<f64> {
+    let (width, height) = reference.dimensions();
+    if distorted.dimensions() != (width, height) || width == 0 || height == 0 {
+        return None;
+    }
+
+    let window_width = WINDOW.min(width);
+    let window_height = WINDOW.min(height);
+    let mut total = 0.0;
+    let mut windows = 0_u32;
+    for y in (0..=height - window_height).step_by(STEP as usize) {
+        for x in (0..=width - window_width).step_by(STEP as usize) {
+            total += window_ssim(reference, distorted, x, y, window_width, window_height);
+            windows += 1;
+        }
+    }
+    Some(total / f64::from(windows))
+}
+
+/// SSIM of one window with its top-left corner at (`left`, `top`).
+fn window_ssim(
+    reference: &GrayImage,
+    distorted: &GrayImage,
+    left: u32,
+    top: u32,
+    width: u32,
+    height: u32,
+) -> f64 {
+    let (mut reference_sum, mut distorted_sum) = (0.0, 0.0);
+    let (mut reference_squares, mut distorted_squares, mut products) = (0.0, 0.0, 0.0);
+    for y in top..top + height {
+        for x in left..left + width {
+            let r = f64::from(reference.get_pixel(x, y)[0]);
+            let d = f64::from(distorted.get_pixel(x, y)[0]);
+            reference_sum += r;
+            distorted_sum += d;
+            reference_squares = r.mul_add(r, reference_squares);
+            distorted_squares = d.mul_add(d, distorted_squares);
+            products = r.mul_add(d, products);
+        }
+    }
+
+    let count = f64::from(width * height);
+    let reference_mean = reference_sum / count;
+    let distorted_mean = distorted_sum / count;
+    let reference_variance = reference_mean.mul_add(-reference_mean, reference_squares / count);
+    let distorted_variance = distorted_mean.mul_add(-distorted_mean, distorted_squares / count);
+    let covariance = reference_mean.mul_add(-distorted_mean, products / count);
+
+    let luminance = (2.0 * reference_mean).mul_add(distorted_mean, C1)
+        / reference_mean.mul_add(reference_mean, distorted_mean.mul_add(distorted_mean, C1));
+    let structure = 2.0f64.mul_add(covariance, C2) / (reference_variance + distorted_variance + C2);
+    luminance * structure
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn gradient(width: u32, height: u32) -> GrayImage {
+        GrayImage::from_fn(width, height, |x, y| {
+            image::Luma([((x * 7 + y * 3) % 256) as u8])
+        })
+    }
+
+    #[test]
+    fn test_noise_lowers_ssim() {
+        let reference = gradient(64, 64);
+        let mut noisy = reference.clone();
+        for (x, y, pixel) in noisy.enumerate_pixels_mut() {
+            if (x + y) % 2 == 0 {
+                pixel[0] = pixel[0].wrapping_add(60);
+            }
+        }
+
+        let score = luma_ssim(&reference, &noisy).unwrap();
+        assert!(score < 0.9, "SSIM {score}");
+        assert!(score > -1.0);
+    }
+
+    #[test]
+    fn test_tiny_and_mismatched_images() {
+        let tiny = gradient(3, 2);
+        assert!((luma_ssim(&tiny, &tiny).unwrap() - 1.0).abs() < 1e-12);
+        assert_eq!(luma_ssim(&tiny, &gradient(2, 3)), None);
+        assert_eq!(
+            luma_ssim(&GrayImage::new(0, 0), &GrayImage::new(0, 0)),
+            None
+        );
+    }
+}
@@ -0,0 +1,116 @@
+use anyhow::Context;
+use std::fs;
+use std::io::{Read, Write};
+
+use super::{ImageFormat, InvalidImage, OptimizeError, encode_image};
+use crate::cli::Cli;
+use crate::file_ops::{TempFile, calculate_resize_dimensions};
+
+/// Optimizes a single image read from standard input and writes it to standard output.
+///
+/// This is the `--stdin` pipeline mode. The input format is sniffed from the magic bytes
+/// (falling back to `--format` when it cannot be recognized), and the output format is
+/// `--format` if given, otherwise the input format. Raster inputs can be converted to any
+/// raster output format; SVG can only be written as SVG.
+///
+/// No scanning, backups or progress output happen in this mode. Diagnostics are written
+/// to standard error so standard output contains only the image data. As in batch mode,
+/// when the optimized result is not smaller and the format is unchanged, the original
+/// bytes are written unchanged.
+///
+/// # Arguments
+///
+/// * `args` - CLI configuration with quality, resize and format settings
+///
+/// # Returns
+///
+/// Returns `Ok(())` once the image has been written to standard output.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - Standard input cannot be read or standard output cannot be written
+///   ([`OptimizeError::Io`])
+/// - The input format cannot be determined ([`OptimizeError::UnsupportedFormat`])
+/// - An SVG is requested to be converted to a raster format or vice versa
+///   ([`OptimizeError::InvalidConfig`])
+/// - Decoding, encoding or temporary file I/O fails
+pub fn optimize_stdin(args: &Cli) -> Result<(), OptimizeError> {
+    let mut input_data = Vec::new();
+    std::io::stdin()
+        .read_to_end(&mut input_data)
+        .context("Failed to read image from stdin")
+        .map_err(OptimizeError::io)?;
+
+    let input_format = ImageFormat::from_magic_bytes(&input_data)
+        .or(args.format)
+        .ok_or_else(|| {
+            OptimizeError::UnsupportedFormat("could not detect the input image format".into())
+        })?;
+    let output_format = args.format.unwrap_or(input_format);
+
+    if !output_format.is_enabled() {
+        return Err(OptimizeError::FormatDisabled(output_format));
+    }
+    if input_format.is_raster() != output_format.is_raster() {
+        return Err(OptimizeError::InvalidConfig(format!(
+            "Cannot convert {input_format:?} to {output_format:?}"
+        )));
+    }
+
+    // Both files are removed when their guards drop, on success, error or panic
+    let input_file = TempFile::new("image-optimizer-stdin", input_format.extension())?;
+    let output_file = TempFile::new("image-optimizer-stdout", output_format.extension())?;
+    fs::write(input_file.path(), &input_data)?;
+
+    let img = if output_format.is_raster()
+        && (output_format != input_format || args.max_size.is_some())
+    {
+        let img =
+            image::load_from_memory(&input_data).map_err(|e| InvalidImage::new(e.to_string()))?;
+        let (width, height) = (img.width(), img.height());
+        match args.max_size {
+            Some(max_size) => {
+                let (new_width, new_height) = calculate_resize_dimensions(width, height, max_size);
+                if new_width != width || new_height != height {
+                    Some(img.resize(new_width, new_height, image::imageops::FilterType::Lanczos3))
+                } else {
+                    Some(img)
+                }
+            }
+            None => Some(img),
+        }
+    } else {
+        None
+    };
+
+    encode_image(
+        output_format,
+        input_file.path(),
+        output_file.path(),
+        args,
+        img,
+    )?;
+    let optimized_data = fs::read(output_file.path())?;
+
+    let output_data = if output_format == input_format && optimized_data.len() >= input_data.len() {
+        eprintln!("Optimization would increase size, writing original image");
+        &input_data
+    } else {
+        eprintln!(
+            "Optimized {} -> {} bytes",
+            input_data.len(),
+            optimized_data.len()
+        );
+        &optimized_data
+    };
+
+    let mut stdout = std::io::stdout().lock();
+    stdout
+        .write_all(output_data)
+        .and_then(|()| stdout.flush())
+        .context("Failed to write image to stdout")
+        .map_err(OptimizeError::io)?;
+
+    Ok(())
+}
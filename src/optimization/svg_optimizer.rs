@@ -1,25 +1,48 @@
 use anyhow::{Context, Result};
 use image::DynamicImage;
-use regex::Regex;
+use std::fmt::Write;
 use std::fs;
 use std::path::Path;
 
+use super::{
+    XmlAttribute, XmlToken, compress_svgz, decompress_svgz, is_svgz_path, round_path_data,
+    tokenize_xml, xml_tokenizer::local_name,
+};
 use crate::cli::Cli;
 
+/// Elements removed together with everything inside them.
+const REMOVED_ELEMENTS: &[&str] = &["metadata"];
+
+/// Namespace prefixes of editor-only elements and attributes (Inkscape, Sodipodi).
+const EDITOR_PREFIXES: &[&str] = &["inkscape:", "sodipodi:"];
+
+/// Prefix of the attributes written by Adobe Illustrator.
+const ADOBE_ATTRIBUTE_PREFIX: &str = "adobe-";
+
+/// Elements whose text is rendered or interpreted, so it is written back unchanged.
+const TEXT_ELEMENTS: &[&str] = &[
+    "text", "tspan", "textPath", "style", "script", "title", "desc",
+];
+
 /// Optimizes an SVG file by removing metadata, unused elements, and normalizing whitespace.
 ///
 /// This function provides basic SVG optimization by:
-/// - Removing XML comments and unnecessary whitespace
-/// - Stripping editor metadata and inkscape/adobe attributes
-/// - Cleaning up empty elements and unused definitions
-/// - Normalizing path data formatting
-/// - Preserving visual rendering integrity
+/// - Removing XML comments and whitespace between elements
+/// - Stripping editor metadata and inkscape/sodipodi/adobe elements and attributes
+/// - Dropping empty `<defs>` definitions
+/// - Reading gzipped `.svgz` input and compressing `.svgz` output at `--svgz-level`
+/// - Writing each tag with single spaces between its attributes
+/// - Rounding path data (`d` attributes) to `--svg-precision` decimal places, unless
+///   `--strip-only` is set
+/// - Preserving visual rendering integrity: other attribute values, CDATA sections and the
+///   content of `<text>`, `<style>` and `<script>` are copied unchanged
 ///
 /// # Arguments
 ///
 /// * `input_path` - Path to the source SVG file
-/// * `output_path` - Path where the optimized SVG will be written
-/// * `_args` - CLI configuration (currently unused for SVG optimization)
+/// * `output_path` - Path where the optimized SVG will be written, gzipped if it ends in
+///   `.svgz`
+/// * `args` - CLI configuration containing the path data precision and gzip level
 /// * `_resized_img` - Not applicable for SVG files (always None)
 ///
 /// # Returns
@@ -30,17 +53,28 @@ use crate::cli::Cli;
 ///
 /// Returns an error if:
 /// - File I/O operations fail (reading input or writing output)
-/// - Regular expression operations fail
+/// - The markup cannot be tokenized (for example an unterminated tag or comment)
 pub fn optimize_svg(
     input_path: &Path,
     output_path: &Path,
-    _args: &Cli,
+    args: &Cli,
     _resized_img: Option<DynamicImage>,
 ) -> Result<()> {
-    let input_content = fs::read_to_string(input_path)
+    let input_content = fs::read(input_path)
+        .map_err(anyhow::Error::from)
+        .and_then(|data| Ok(String::from_utf8(decompress_svgz(data)?)?))
         .with_context(|| format!("Failed to read SVG file: {}", input_path.display()))?;
 
-    let optimized_content = optimize_svg_content(&input_content)?;
+    let optimized_content = optimize_svg_content(
+        &input_content,
+        // Rounding coordinates is a visible change, which --strip-only rules out
+        (!args.strip_only).then_some(args.svg_precision),
+    )?;
+    let optimized_content = if is_svgz_path(output_path) {
+        compress_svgz(optimized_content.as_bytes(), args.svgz_level)?
+    } else {
+        optimized_content.into_bytes()
+    };
 
     fs::write(output_path, optimized_content)
         .with_context(|| format!("Failed to write optimized SVG: {}", output_path.display()))?;
@@ -48,46 +82,131 @@ pub fn optimize_svg(
     Ok(())
 }
 
-/// Performs basic SVG content optimization using regex patterns.
-fn optimize_svg_content(content: &str) -> Result<String> {
-    let mut optimized = content.to_string();
-
-    // Remove XML comments (multiline)
-    let comment_regex = Regex::new(r"(?s)<!--.*?-->").context("Failed to compile comment regex")?;
-    optimized = comment_regex.replace_all(&optimized, "").to_string();
-
-    // Remove metadata elements (multiline)
-    let metadata_regex = Regex::new(r"(?s)<metadata[^>]*>.*?</metadata>")
-        .context("Failed to compile metadata regex")?;
-    optimized = metadata_regex.replace_all(&optimized, "").to_string();
-
-    // Remove editor-specific attributes (inkscape, adobe, etc.)
-    let inkscape_regex =
-        Regex::new(r#"\s*inkscape:[^=]*="[^"]*""#).context("Failed to compile inkscape regex")?;
-    optimized = inkscape_regex.replace_all(&optimized, "").to_string();
-
-    let adobe_regex =
-        Regex::new(r#"\s*adobe-[^=]*="[^"]*""#).context("Failed to compile adobe regex")?;
-    optimized = adobe_regex.replace_all(&optimized, "").to_string();
-
-    // Remove sodipodi attributes
-    let sodipodi_regex =
-        Regex::new(r#"\s*sodipodi:[^=]*="[^"]*""#).context("Failed to compile sodipodi regex")?;
-    optimized = sodipodi_regex.replace_all(&optimized, "").to_string();
-
-    // Normalize whitespace (remove excessive whitespace, but preserve single spaces)
-    let whitespace_regex = Regex::new(r"\s+").context("Failed to compile whitespace regex")?;
-    optimized = whitespace_regex.replace_all(&optimized, " ").to_string();
-
-    // Remove leading/trailing whitespace from each line
-    optimized = optimized
-        .lines()
-        .map(str::trim)
-        .filter(|line| !line.is_empty())
-        .collect::<Vec<&str>>()
-        .join("\n");
-
-    Ok(optimized)
+/// An element whose end tag has not been reached yet.
+struct OpenElement<'a> {
+    name: &'a str,
+    /// Output length before the start tag, to drop the element if it turns out empty
+    start: usize,
+    /// Output length right after the start tag
+    content_start: usize,
+    /// Whether text inside the element must be kept byte for byte
+    keeps_text: bool,
+}
+
+/// Rewrites SVG markup token by token, rounding path data to `path_precision` decimals if set.
+fn optimize_svg_content(content: &str, path_precision: Option<u8>) -> Result<String> {
+    let tokens = tokenize_xml(content).context("Failed to parse SVG")?;
+    let mut output = String::with_capacity(content.len());
+    let mut open: Vec<OpenElement> = Vec::new();
+    // Depth inside a removed element; everything there is dropped
+    let mut removed_depth = 0_usize;
+
+    for token in tokens {
+        if removed_depth > 0 {
+            match token {
+                XmlToken::StartTag {
+                    self_closing: false,
+                    ..
+                } => removed_depth += 1,
+                XmlToken::EndTag(_) => removed_depth -= 1,
+                _ => {}
+            }
+            continue;
+        }
+
+        match token {
+            XmlToken::Comment(_) => {}
+            XmlToken::Instruction(markup) | XmlToken::Doctype(markup) | XmlToken::CData(markup) => {
+                output.push_str(markup);
+            }
+            XmlToken::Text(text) => {
+                if open.last().is_some_and(|element| element.keeps_text) {
+                    output.push_str(text);
+                } else {
+                    // Outside text elements character data is not rendered
+                    let words: Vec<&str> = text.split_whitespace().collect();
+                    output.push_str(&words.join(" "));
+                }
+            }
+            XmlToken::StartTag {
+                name,
+                attributes,
+                self_closing,
+            } => {
+                if is_removed_element(name) {
+                    removed_depth = usize::from(!self_closing);
+                    continue;
+                }
+                if self_closing && local_name(name) == "defs" {
+                    continue;
+                }
+
+                let start = output.len();
+                write_start_tag(&mut output, name, &attributes, self_closing, path_precision);
+                if !self_closing {
+                    let keeps_text = open.last().is_some_and(|parent| parent.keeps_text)
+                        || TEXT_ELEMENTS.contains(&local_name(name))
+                        || attributes.iter().any(|attribute| {
+                            attribute.name == "xml:space" && attribute.value == "preserve"
+                        });
+                    open.push(OpenElement {
+                        name,
+                        start,
+                        content_start: output.len(),
+                        keeps_text,
+                    });
+                }
+            }
+            XmlToken::EndTag(name) => {
+                if let Some(element) = open.pop()
+                    && local_name(element.name) == "defs"
+                    && output.len() == element.content_start
+                {
+                    output.truncate(element.start);
+                    continue;
+                }
+                let _ = write!(output, "</{name}>");
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+/// Whether an element is editor metadata that is dropped with its content.
+fn is_removed_element(name: &str) -> bool {
+    REMOVED_ELEMENTS.contains(&local_name(name)) || has_editor_prefix(name)
+}
+
+/// Whether an element or attribute name belongs to an editor namespace.
+fn has_editor_prefix(name: &str) -> bool {
+    EDITOR_PREFIXES
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+}
+
+/// Writes a start tag with single spaces between its kept attributes.
+fn write_start_tag(
+    output: &mut String,
+    name: &str,
+    attributes: &[XmlAttribute],
+    self_closing: bool,
+    path_precision: Option<u8>,
+) {
+    output.push('<');
+    output.push_str(name);
+    for attribute in attributes {
+        if has_editor_prefix(attribute.name) || attribute.name.starts_with(ADOBE_ATTRIBUTE_PREFIX) {
+            continue;
+        }
+        let XmlAttribute { name, value, quote } = attribute;
+        let rounded = path_precision
+            .filter(|_| *name == "d")
+            .and_then(|precision| round_path_data(value, precision));
+        let value = rounded.as_deref().unwrap_or(value);
+        let _ = write!(output, " {name}={quote}{value}{quote}");
+    }
+    output.push_str(if self_closing { "/>" } else { ">" });
 }
 
 #[cfg(test)]
@@ -107,7 +226,7 @@ mod tests {
   </g>
 </svg>"#;
 
-        let result = optimize_svg_content(input).unwrap();
+        let result = optimize_svg_content(input, None).unwrap();
 
         // Verify essential elements are preserved
         assert!(result.contains("<svg"));
@@ -146,7 +265,7 @@ mod tests {
   <circle r="10" />
 </svg>"#;
 
-        let result = optimize_svg_content(input).unwrap();
+        let result = optimize_svg_content(input, None).unwrap();
 
         // Verify comments and metadata are removed
         assert!(!result.contains("<!-- This is a comment -->"));
@@ -175,7 +294,7 @@ mod tests {
   <circle r="10" inkscape:label="Circle" adobe-blend-mode="normal" />
 </svg>"#;
 
-        let result = optimize_svg_content(input).unwrap();
+        let result = optimize_svg_content(input, None).unwrap();
 
         // Verify editor-specific attributes are removed
         assert!(!result.contains("inkscape:version"));
@@ -203,7 +322,7 @@ mod tests {
   <rect class="blue" style="opacity: 0.8" />
 </svg>"#;
 
-        let result = optimize_svg_content(input).unwrap();
+        let result = optimize_svg_content(input, None).unwrap();
 
         // Verify style-related content is preserved
         assert!(result.contains("<style>"));
@@ -233,7 +352,7 @@ mod tests {
   <use xlink:href="#someElement" />
 </svg>"##;
 
-        let result = optimize_svg_content(input).unwrap();
+        let result = optimize_svg_content(input, None).unwrap();
 
         // Verify definitions and references are preserved
         assert!(result.contains("<defs>"));
@@ -262,7 +381,7 @@ mod tests {
   </circle>
 </svg>"#;
 
-        let result = optimize_svg_content(input).unwrap();
+        let result = optimize_svg_content(input, None).unwrap();
 
         // Verify animations are preserved
         assert!(result.contains("<animate"));
@@ -287,7 +406,7 @@ mod tests {
 
 </svg>"#;
 
-        let result = optimize_svg_content(input).unwrap();
+        let result = optimize_svg_content(input, None).unwrap();
 
         // Verify structure is preserved but whitespace is normalized
         assert!(result.contains("<svg"));
@@ -318,7 +437,7 @@ mod tests {
   <circle r="10" />
 </svg>"#;
 
-        let result = optimize_svg_content(input).unwrap();
+        let result = optimize_svg_content(input, None).unwrap();
 
         // Verify multiline comment is removed
         assert!(!result.contains("This is a multiline comment"));
@@ -341,7 +460,7 @@ mod tests {
   </g>
 </svg>"#;
 
-        let result = optimize_svg_content(input).unwrap();
+        let result = optimize_svg_content(input, None).unwrap();
 
         // Verify coordinate system attributes are preserved
         assert!(result.contains("viewBox=\"0 0 200 200\""));
@@ -353,7 +472,7 @@ mod tests {
     fn test_empty_svg_handled_gracefully() {
         let input = r#"<svg xmlns="http://www.w3.org/2000/svg"></svg>"#;
 
-        let result = optimize_svg_content(input).unwrap();
+        let result = optimize_svg_content(input, None).unwrap();
 
         // Verify basic structure is preserved even for empty SVG
         assert!(result.contains("<svg"));
@@ -368,7 +487,7 @@ mod tests {
   <rect id="background" data-layer="base" />
 </svg>"#;
 
-        let result = optimize_svg_content(input).unwrap();
+        let result = optimize_svg_content(input, None).unwrap();
 
         // Verify data attributes and IDs are preserved
         assert!(result.contains("data-name=\"icon\""));
@@ -378,4 +497,96 @@ mod tests {
         assert!(result.contains("id=\"background\""));
         assert!(result.contains("data-layer=\"base\""));
     }
+
+    #[test]
+    fn test_gt_inside_attribute_values() {
+        let input = r#"<svg xmlns="http://www.w3.org/2000/svg">
+  <g aria-label="a > b" data-rule='x>1 && y<2'>
+    <rect inkscape:label="1 > 0" width="10" />
+  </g>
+</svg>"#;
+
+        let result = optimize_svg_content(input, None).unwrap();
+
+        assert!(result.contains(r#"<g aria-label="a > b" data-rule='x>1 && y<2'>"#));
+        assert!(result.contains(r#"<rect width="10"/>"#));
+        assert!(!result.contains("inkscape:label"));
+        assert!(result.ends_with("</g></svg>"));
+    }
+
+    #[test]
+    fn test_keeps_significant_whitespace_in_text() {
+        let input = "<svg xmlns=\"http://www.w3.org/2000/svg\">\n  <text x=\"0\">  two  spaces\n  <tspan> and </tspan> more </text>\n  <g xml:space=\"preserve\"> kept </g>\n</svg>";
+
+        let result = optimize_svg_content(input, None).unwrap();
+
+        assert!(
+            result.contains("<text x=\"0\">  two  spaces\n  <tspan> and </tspan> more </text>")
+        );
+        assert!(result.contains("<g xml:space=\"preserve\"> kept </g>"));
+        assert!(result.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\"><text"));
+    }
+
+    #[test]
+    fn test_keeps_style_script_and_cdata_verbatim() {
+        let input = r#"<svg xmlns="http://www.w3.org/2000/svg">
+  <style>
+    /* not an XML comment: <!-- --> */
+    a > b { fill: red; }
+  </style>
+  <script><![CDATA[
+    if (a < b && c > d) { /* x */ }
+  ]]></script>
+  <script>if (a < b) {}</script>
+</svg>"#;
+
+        let result = optimize_svg_content(input, None).unwrap();
+
+        assert!(result.contains(
+            "<style>\n    /* not an XML comment: <!-- --> */\n    a > b { fill: red; }\n  </style>"
+        ));
+        assert!(
+            result
+                .contains("<script><![CDATA[\n    if (a < b && c > d) { /* x */ }\n  ]]></script>")
+        );
+        assert!(result.contains("<script>if (a < b) {}</script>"));
+    }
+
+    #[test]
+    fn test_removes_editor_elements_and_empty_defs() {
+        let input = r#"<svg xmlns="http://www.w3.org/2000/svg">
+  <sodipodi:namedview pagecolor="white"><inkscape:grid /></sodipodi:namedview>
+  <defs>
+    <!-- nothing left -->
+    <metadata>meta</metadata>
+  </defs>
+  <defs/>
+  <defs><linearGradient id="g" /></defs>
+</svg>"#;
+
+        let result = optimize_svg_content(input, None).unwrap();
+
+        assert_eq!(
+            result,
+            r#"<svg xmlns="http://www.w3.org/2000/svg"><defs><linearGradient id="g"/></defs></svg>"#
+        );
+    }
+
+    #[test]
+    fn test_unterminated_markup_is_an_error() {
+        assert!(optimize_svg_content("<svg><rect width=\"10\"</svg>", None).is_err());
+        assert!(optimize_svg_content("<svg><!-- never closed </svg>", None).is_err());
+    }
+
+    #[test]
+    fn test_rounds_path_data_only() {
+        let input = r#"<svg><path id="p" d="M 12.345678, 0.5 L 3.14159 2.71828" stroke-width="0.123456"/><path d="not a path"/></svg>"#;
+
+        let result = optimize_svg_content(input, Some(2)).unwrap();
+
+        assert_eq!(
+            result,
+            r#"<svg><path id="p" d="M12.35.5L3.14 2.72" stroke-width="0.123456"/><path d="not a path"/></svg>"#
+        );
+    }
 }
@@ -0,0 +1,375 @@
+/// Rounds the numbers in SVG path data (`d` attributes) to `precision` decimal places.
+///
+/// Coordinates of relative commands are rounded against the position the rounded path
+/// has actually reached, so rounding errors do not add up along long relative paths:
+/// every point ends up within half a unit of the last kept decimal of its exact
+/// position. Command letters and arc flags are kept, and the output drops redundant
+/// separators and leading zeros (`0.5` becomes `.5`, `M 10,-5` becomes `M10-5`).
+///
+/// # Arguments
+///
+/// * `data` - Value of a `d` attribute
+/// * `precision` - Number of decimal places to keep
+///
+/// # Returns
+///
+/// Returns the rewritten path data, or `None` if `data` is not valid path data (it is
+/// then best left untouched).
+///
+/// # Examples
+///
+/// ```rust
+/// use image_optimizer::optimization::round_path_data;
+///
+/// assert_eq!(
+///     round_path_data("M 12.345678, 0.5 L 100.004 -0.25", 2).as_deref(),
+///     Some("M12.35.5L100-.25")
+/// );
+/// ```
+#[must_use]
+pub fn round_path_data(data: &str, precision: u8) -> Option<String> {
+    let segments = parse_path(data)?;
+    let scale = 10_f64.powi(i32::from(precision));
+    let round = |value: f64| (value * scale).round() / scale;
+
+    let mut output = String::with_capacity(data.len());
+    let mut last_number: Option<String> = None;
+    // Current point and subpath start of the exact path and of the rounded output
+    let (mut exact, mut emitted) = ((0.0, 0.0), (0.0, 0.0));
+    let (mut exact_start, mut emitted_start) = ((0.0, 0.0), (0.0, 0.0));
+
+    for segment in segments {
+        if segment.explicit {
+            output.push(segment.command);
+            last_number = None;
+        }
+        let relative = segment.command.is_ascii_lowercase();
+        let upper = segment.command.to_ascii_uppercase();
+        let (exact_origin, emitted_origin) = if relative {
+            (exact, emitted)
+        } else {
+            ((0.0, 0.0), (0.0, 0.0))
+        };
+
+        let mut end = (exact, emitted);
+        for (index, &value) in segment.values.iter().enumerate() {
+            let axis = coordinate_axis(upper, index);
+            let rounded = match axis {
+                Some(Axis::X) => {
+                    let target = exact_origin.0 + value;
+                    end.0.0 = target;
+                    let rounded = round(target - emitted_origin.0);
+                    end.1.0 = emitted_origin.0 + rounded;
+                    rounded
+                }
+                Some(Axis::Y) => {
+                    let target = exact_origin.1 + value;
+                    end.0.1 = target;
+                    let rounded = round(target - emitted_origin.1);
+                    end.1.1 = emitted_origin.1 + rounded;
+                    rounded
+                }
+                None if is_arc_flag(upper, index) => value,
+                None => round(value),
+            };
+            push_number(
+                &mut output,
+                &mut last_number,
+                &format_number(rounded, precision),
+            );
+        }
+
+        if upper == 'Z' {
+            exact = exact_start;
+            emitted = emitted_start;
+        } else {
+            (exact, emitted) = end;
+            if upper == 'M' && segment.explicit {
+                exact_start = exact;
+                emitted_start = emitted;
+            }
+        }
+    }
+
+    Some(output)
+}
+
+/// One parameter group of a path command, e.g. the six numbers of a cubic curve.
+struct Segment {
+    command: char,
+    /// Whether the command letter was written before this group (implicit repeats omit it)
+    explicit: bool,
+    values: Vec<f64>,
+}
+
+#[derive(Clone, Copy)]
+enum Axis {
+    X,
+    Y,
+}
+
+/// Number of parameters in one group of `command` (uppercase).
+const fn parameter_count(command: char) -> usize {
+    match command {
+        'M' | 'L' | 'T' => 2,
+        'H' | 'V' => 1,
+        'C' => 6,
+        'S' | 'Q' => 4,
+        'A' => 7,
+        _ => 0,
+    }
+}
+
+/// Which coordinate the parameter at `index` of `command` (uppercase) is, if any.
+const fn coordinate_axis(command: char, index: usize) -> Option<Axis> {
+    match (command, index) {
+        ('H', _) | ('A', 5) => Some(Axis::X),
+        ('V', _) | ('A', 6) => Some(Axis::Y),
+        ('A', _) => None,
+        (_, index) if index % 2 == 0 => Some(Axis::X),
+        _ => Some(Axis::Y),
+    }
+}
+
+/// Whether the parameter at `index` of `command` (uppercase) is a large-arc or sweep flag.
+const fn is_arc_flag(command: char, index: usize) -> bool {
+    matches!((command, index), ('A', 3 | 4))
+}
+
+/// Splits path data into parameter groups, or returns `None` if it is malformed.
+fn parse_path(data: &str) -> Option<Vec<Segment>> {
+    let mut segments: Vec<Segment> = Vec::new();
+    let mut command: Option<char> = None;
+    let mut values = Vec::new();
+    let mut explicit = false;
+    let mut rest = data;
+
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == ',');
+        let Some(next) = rest.chars().next() else {
+            break;
+        };
+
+        if "MmLlHhVvCcSsQqTtAaZz".contains(next) {
+            if !values.is_empty() {
+                return None;
+            }
+            rest = &rest[1..];
+            command = Some(next);
+            explicit = true;
+            if next.eq_ignore_ascii_case(&'z') {
+                segments.push(Segment {
+                    command: next,
+                    explicit,
+                    values: Vec::new(),
+                });
+                explicit = false;
+            }
+            continue;
+        }
+
+        let current = command?;
+        let upper = current.to_ascii_uppercase();
+        let count = parameter_count(upper);
+        if count == 0 {
+            return None;
+        }
+        let (value, length) = if is_arc_flag(upper, values.len()) {
+            match next {
+                '0' => (0.0, 1),
+                '1' => (1.0, 1),
+                _ => return None,
+            }
+        } else {
+            parse_number(rest)?
+        };
+        rest = &rest[length..];
+        values.push(value);
+
+        if values.len() == count {
+            segments.push(Segment {
+                command: current,
+                explicit,
+                values: std::mem::take(&mut values),
+            });
+            explicit = false;
+            // Extra coordinate pairs after a moveto are implicit linetos
+            if upper == 'M' {
+                command = Some(if current == 'M' { 'L' } else { 'l' });
+            }
+        }
+    }
+
+    values.is_empty().then_some(segments)
+}
+
+/// Parses a number at the start of `text`, returning it with its length in bytes.
+fn parse_number(text: &str) -> Option<(f64, usize)> {
+    let bytes = text.as_bytes();
+    let mut end = usize::from(matches!(bytes.first(), Some(b'+' | b'-')));
+    let digits_start = end;
+    while bytes.get(end).is_some_and(u8::is_ascii_digit) {
+        end += 1;
+    }
+    if bytes.get(end) == Some(&b'.') {
+        end += 1;
+        while bytes.get(end).is_some_and(u8::is_ascii_digit) {
+            end += 1;
+        }
+    }
+    if end == digits_start || &text[digits_start..end] == "." {
+        return None;
+    }
+    if matches!(bytes.get(end), Some(b'e' | b'E')) {
+        let mut exponent_end = end + 1;
+        if matches!(bytes.get(exponent_end), Some(b'+' | b'-')) {
+            exponent_end += 1;
+        }
+        let exponent_digits = exponent_end;
+        while bytes.get(exponent_end).is_some_and(u8::is_ascii_digit) {
+            exponent_end += 1;
+        }
+        if exponent_end > exponent_digits {
+            end = exponent_end;
+        }
+    }
+    text[..end].parse().ok().map(|value| (value, end))
+}
+
+/// Formats a rounded number without trailing zeros or a leading zero.
+fn format_number(value: f64, precision: u8) -> String {
+    let formatted = format!("{value:.*}", usize::from(precision));
+    let trimmed = if formatted.contains('.') {
+        formatted.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        &formatted
+    };
+    if trimmed == "-0" {
+        return "0".to_string();
+    }
+    trimmed.strip_prefix("0.").map_or_else(
+        || trimmed.replacen("-0.", "-.", 1),
+        |fraction| format!(".{fraction}"),
+    )
+}
+
+/// Appends a number, adding a space only where it would otherwise merge with the last one.
+fn push_number(output: &mut String, last_number: &mut Option<String>, number: &str) {
+    if let Some(previous) = last_number.as_deref() {
+        let needs_separator = if number.starts_with('-') {
+            false
+        } else if number.starts_with('.') {
+            !previous.contains('.')
+        } else {
+            true
+        };
+        if needs_separator {
+            output.push(' ');
+        }
+    }
+    output.push_str(number);
+    *last_number = Some(number.to_string());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Absolute end point of every segment of a path.
+    fn end_points(data: &str) -> Vec<(f64, f64)> {
+        let mut points = Vec::new();
+        let (mut current, mut start) = ((0.0, 0.0), (0.0, 0.0));
+        for segment in parse_path(data).unwrap() {
+            let upper = segment.command.to_ascii_uppercase();
+            let origin = if segment.command.is_ascii_lowercase() {
+                current
+            } else {
+                (0.0, 0.0)
+            };
+            let mut end = current;
+            for (index, value) in segment.values.iter().enumerate() {
+                match coordinate_axis(upper, index) {
+                    Some(Axis::X) => end.0 = origin.0 + value,
+                    Some(Axis::Y) => end.1 = origin.1 + value,
+                    None => {}
+                }
+            }
+            current = if upper == 'Z' { start } else { end };
+            if upper == 'M' && segment.explicit {
+                start = current;
+            }
+            points.push(current);
+        }
+        points
+    }
+
+    #[test]
+    fn test_rounds_to_precision() {
+        assert_eq!(
+            round_path_data("M12.345678 0", 2).as_deref(),
+            Some("M12.35 0")
+        );
+        assert_eq!(round_path_data("M12.345678 0", 0).as_deref(), Some("M12 0"));
+        assert_eq!(round_path_data("M0.004,-0.004", 2).as_deref(), Some("M0 0"));
+        assert_eq!(round_path_data("M1e-3 2.5E1", 2).as_deref(), Some("M0 25"));
+    }
+
+    #[test]
+    fn test_separators_never_merge_numbers() {
+        assert_eq!(
+            round_path_data("M 1 0.5 L 0.5 0.25", 2).as_deref(),
+            Some("M1 .5L.5.25")
+        );
+        assert_eq!(
+            round_path_data("m10-5 -3-4", 2).as_deref(),
+            Some("m10-5-3-4")
+        );
+        // Numbers written without separators are split where a new one starts
+        assert_eq!(round_path_data("M.5.5", 2).as_deref(), Some("M.5.5"));
+    }
+
+    #[test]
+    fn test_arc_flags_are_kept() {
+        assert_eq!(
+            round_path_data("a25.123 25.123 0 01 50.555 0", 1).as_deref(),
+            Some("a25.1 25.1 0 0 1 50.6 0")
+        );
+        assert_eq!(
+            round_path_data("A5 5 30 1 0 10 10", 2).as_deref(),
+            Some("A5 5 30 1 0 10 10")
+        );
+    }
+
+    #[test]
+    fn test_path_semantics_round_trip() {
+        let data = "M10.1234 20.5678 l1.333 1.333 1.333 1.333 1.333 1.333 1.333 1.333 \
+                    h2.4444 v-3.3333 c1.111 2.222 3.333 4.444 5.555 6.666 \
+                    s1.2345 1.2345 2.3456 2.3456 q.3333 .3333 .6666 .6666 t1.0101 1.0101 \
+                    a3.3333 3.3333 0 1 0 4.4444 4.4444 z m.123 .456 l1.111 1.111 Z";
+        let rounded = round_path_data(data, 2).unwrap();
+
+        let exact = end_points(data);
+        let approximate = end_points(&rounded);
+        assert_eq!(exact.len(), approximate.len());
+        for (a, b) in exact.iter().zip(&approximate) {
+            assert!(
+                (a.0 - b.0).abs() <= 0.005 + 1e-9,
+                "{a:?} vs {b:?} in {rounded}"
+            );
+            assert!(
+                (a.1 - b.1).abs() <= 0.005 + 1e-9,
+                "{a:?} vs {b:?} in {rounded}"
+            );
+        }
+        assert!(rounded.len() < data.len());
+    }
+
+    #[test]
+    fn test_malformed_data_is_rejected() {
+        assert_eq!(round_path_data("10 10", 2), None);
+        assert_eq!(round_path_data("M10", 2), None);
+        assert_eq!(round_path_data("M10 10 X", 2), None);
+        assert_eq!(round_path_data("A5 5 0 2 0 1 1", 2), None);
+        assert_eq!(round_path_data("M.", 2), None);
+    }
+}
@@ -0,0 +1,78 @@
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+/// File extension of gzip-compressed SVG files.
+pub const SVGZ_EXTENSION: &str = "svgz";
+
+/// Leading bytes of every gzip stream.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Whether a path has the `.svgz` extension (case-insensitive).
+#[must_use]
+pub fn is_svgz_path(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case(SVGZ_EXTENSION))
+}
+
+/// Returns the SVG markup of a file's contents, decompressing them if they are gzipped.
+///
+/// The gzip signature is checked instead of the extension, so mislabeled files are read
+/// correctly either way.
+///
+/// # Errors
+///
+/// Returns an error if the data starts like a gzip stream but cannot be decompressed.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_optimizer::optimization::{compress_svgz, decompress_svgz};
+///
+/// # fn example() -> std::io::Result<()> {
+/// let markup = b"<svg></svg>".to_vec();
+/// assert_eq!(decompress_svgz(compress_svgz(&markup, 9)?)?, markup);
+/// assert_eq!(decompress_svgz(markup.clone())?, markup);
+/// # Ok(())
+/// # }
+/// ```
+pub fn decompress_svgz(data: Vec<u8>) -> io::Result<Vec<u8>> {
+    if !data.starts_with(&GZIP_MAGIC) {
+        return Ok(data);
+    }
+    let mut markup = Vec::with_capacity(data.len() * 4);
+    GzDecoder::new(data.as_slice()).read_to_end(&mut markup)?;
+    Ok(markup)
+}
+
+/// Gzips SVG markup for a `.svgz` file at `level` (0-9, higher is smaller and slower).
+///
+/// # Errors
+///
+/// Returns an error if compression fails.
+pub fn compress_svgz(markup: &[u8], level: u32) -> io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::new(level));
+    encoder.write_all(markup)?;
+    encoder.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_svgz_path() {
+        assert!(is_svgz_path(Path::new("icons/logo.svgz")));
+        assert!(is_svgz_path(Path::new("LOGO.SVGZ")));
+        assert!(!is_svgz_path(Path::new("logo.svg")));
+        assert!(!is_svgz_path(Path::new("svgz")));
+    }
+
+    #[test]
+    fn test_truncated_gzip_is_an_error() {
+        let compressed = compress_svgz(b"<svg><rect/></svg>", 6).unwrap();
+        assert!(decompress_svgz(compressed[..compressed.len() / 2].to_vec()).is_err());
+    }
+}
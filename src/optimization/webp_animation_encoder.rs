@@ -0,0 +1,102 @@
+use anyhow::{Context, Result, anyhow};
+use image::DynamicImage;
+use image::imageops::FilterType;
+use std::fs;
+use std::path::Path;
+
+use super::{convert_to_srgb, read_icc_profile};
+use crate::cli::Cli;
+
+/// Re-encodes every frame of an animated WebP, optionally at a new size.
+///
+/// Decoding a WebP with the `image` crate keeps only its first frame, so encoding the
+/// decoded pixels would silently flatten the animation. Instead each frame is decoded
+/// onto the full canvas with libwebp's animation decoder, resized to `size` if given,
+/// converted to sRGB with `--to-srgb`, and encoded again with `config` as an animation
+/// with the original frame timing and loop count. libwebp's encoder wrapper does not
+/// take the end time of the last frame, so that frame is shown for the average delay of
+/// the others.
+///
+/// # Arguments
+///
+/// * `input_path` - Path to the source animated WebP
+/// * `output_path` - Path where the re-encoded animation is written
+/// * `size` - Width and height of the output frames, or `None` to keep the canvas size
+/// * `config` - Encoder settings applied to every frame
+/// * `args` - CLI configuration; only `--to-srgb` is used
+///
+/// # Errors
+///
+/// Returns an error if the frames cannot be decoded, or the output cannot be encoded or
+/// written.
+pub fn write_animated_webp(
+    input_path: &Path,
+    output_path: &Path,
+    size: Option<(u32, u32)>,
+    config: &webp::WebPConfig,
+    args: &Cli,
+) -> Result<()> {
+    let data = fs::read(input_path)?;
+    let animation = webp::AnimDecoder::new(&data).decode().map_err(|error| {
+        anyhow!(
+            "Failed to decode the frames of {}: {error}",
+            input_path.display()
+        )
+    })?;
+    let first = animation
+        .get_frame(0)
+        .with_context(|| format!("{} has no frames", input_path.display()))?;
+    let (width, height) = size.unwrap_or_else(|| (first.width(), first.height()));
+    let profile = if args.to_srgb {
+        read_icc_profile(input_path)?
+    } else {
+        None
+    };
+
+    // The decoder reports when each frame ends; the encoder wants when it starts
+    let mut frames = Vec::with_capacity(animation.len());
+    let mut start = 0;
+    for frame in &animation {
+        let mut canvas = DynamicImage::from(&frame);
+        if (canvas.width(), canvas.height()) != (width, height) {
+            canvas = canvas.resize_exact(width, height, FilterType::Lanczos3);
+        }
+        if let Some(converted) = profile
+            .as_deref()
+            .and_then(|profile| convert_to_srgb(&canvas, profile))
+        {
+            canvas = converted;
+        }
+        frames.push((canvas.to_rgba8(), start));
+        start = frame.get_time_ms();
+    }
+
+    let mut encoder = webp::AnimEncoder::new(width, height, config);
+    encoder.set_loop_count(i32::try_from(animation.loop_count).unwrap_or(0));
+    for (canvas, start) in &frames {
+        encoder.add_frame(webp::AnimFrame::from_rgba(canvas, width, height, *start));
+    }
+    let animated = encoder
+        .try_encode()
+        .map_err(|error| anyhow!("WebP animation encoding failed: {error:?}"))?;
+    fs::write(output_path, &*animated)?;
+
+    Ok(())
+}
+
+/// Returns whether the file is an animated WebP (its `VP8X` header has the animation
+/// flag set).
+///
+/// Files that are not WebP images at all, or that cannot be read, are not animated.
+#[must_use]
+pub fn is_animated_webp(path: &Path) -> bool {
+    const ANIMATION_FLAG: u8 = 0x02;
+
+    fs::read(path).is_ok_and(|data| {
+        data.len() > 20
+            && &data[..4] == b"RIFF"
+            && &data[8..12] == b"WEBP"
+            && &data[12..16] == b"VP8X"
+            && data[20] & ANIMATION_FLAG != 0
+    })
+}
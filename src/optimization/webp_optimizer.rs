@@ -1,21 +1,26 @@
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use image::DynamicImage;
 use std::fs;
 use std::path::Path;
 
+use super::{ImageFormat, is_animated_webp, write_animated_webp};
 use crate::cli::Cli;
 
 /// Optimizes a WebP image with configurable quality and lossless options.
 ///
 /// This function uses Google's WebP encoder to create optimized WebP images.
-/// It supports both lossy compression with quality control and lossless compression
-/// mode for maximum quality preservation.
+/// It supports lossy compression with quality control, lossless compression for
+/// maximum quality preservation, and near-lossless compression (`--webp-near-lossless`),
+/// which lossless-encodes after adjusting pixel values in flat regions while keeping edges.
+/// `--webp-method` trades encoding time for size: higher methods are slower and smaller.
+/// Animated WebP images are re-encoded frame by frame with [`write_animated_webp`], so the
+/// animation is never flattened to its first frame.
 ///
 /// # Arguments
 ///
 /// * `input_path` - Path to the source WebP file
 /// * `output_path` - Path where the optimized WebP will be written
-/// * `args` - CLI configuration containing quality settings and lossless flag
+/// * `args` - CLI configuration containing quality, lossless and encoder effort settings
 /// * `resized_img` - Optional pre-resized image data; if None, reads from `input_path`
 ///
 /// # Returns
@@ -34,20 +39,131 @@ pub fn optimize_webp(
     args: &Cli,
     resized_img: Option<DynamicImage>,
 ) -> Result<()> {
-    let rgb_img = if let Some(img) = resized_img {
-        img.to_rgb8()
+    let lossless = args.webp_lossless || args.webp_near_lossless.is_some();
+    let mut config =
+        webp::WebPConfig::new().map_err(|()| anyhow!("Failed to initialize WebP encoder"))?;
+    config.lossless = i32::from(lossless);
+    config.alpha_compression = i32::from(!lossless);
+    // For lossless encoding libwebp reads quality as compression effort, as `encode_lossless` does
+    config.quality = if lossless {
+        75.0
     } else {
-        image::open(input_path)?.to_rgb8()
+        f32::from(args.quality_for(ImageFormat::Webp))
     };
+    config.method = i32::from(args.webp_method);
+    config.near_lossless = i32::from(args.webp_near_lossless.unwrap_or(100));
 
-    let encoder = if args.webp_lossless {
-        webp::Encoder::from_rgb(&rgb_img, rgb_img.width(), rgb_img.height()).encode_lossless()
+    if is_animated_webp(input_path) {
+        let size = resized_img.map(|img| (img.width(), img.height()));
+        return write_animated_webp(input_path, output_path, size, &config, args);
+    }
+
+    let rgb_img = if let Some(img) = resized_img {
+        img.to_rgb8()
     } else {
-        webp::Encoder::from_rgb(&rgb_img, rgb_img.width(), rgb_img.height())
-            .encode(f32::from(args.jpeg_quality))
+        image::open(input_path)?.to_rgb8()
     };
+    let encoded = webp::Encoder::from_rgb(&rgb_img, rgb_img.width(), rgb_img.height())
+        .encode_advanced(&config)
+        .map_err(|error| anyhow!("WebP encoding failed: {error:?}"))?;
 
-    fs::write(output_path, &*encoder)?;
+    fs::write(output_path, &*encoded)?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use image::RgbImage;
+
+    fn encode(args: &[&str]) -> Vec<u8> {
+        let temp_dir = std::env::temp_dir().join(format!(
+            "image_optimizer_webp_{}",
+            args[1..].join("_").replace('-', "")
+        ));
+        fs::create_dir_all(&temp_dir).unwrap();
+        let input = temp_dir.join("input.webp");
+        let output = temp_dir.join("output.webp");
+        let img = RgbImage::from_fn(96, 96, |x, y| {
+            let noise = u8::try_from((x * 7 + y * 13) % 23).unwrap();
+            image::Rgb([
+                u8::try_from(x * 2).unwrap() + noise,
+                u8::try_from(y * 2).unwrap(),
+                if (x / 16 + y / 16) % 2 == 0 { 40 } else { 200 },
+            ])
+        });
+        img.save(&input).unwrap();
+
+        optimize_webp(&input, &output, &Cli::parse_from(args), None).unwrap();
+        let encoded = fs::read(&output).unwrap();
+        fs::remove_dir_all(&temp_dir).unwrap();
+        encoded
+    }
+
+    #[test]
+    fn test_higher_method_is_not_larger() {
+        let fast = encode(&["image-optimizer", "--webp-method", "0"]).len();
+        let thorough = encode(&["image-optimizer", "--webp-method", "6"]).len();
+        assert!(
+            thorough <= fast,
+            "method 6: {thorough} bytes, method 0: {fast} bytes"
+        );
+    }
+
+    #[test]
+    fn test_near_lossless_implies_lossless_bitstream() {
+        let lossy = encode(&["image-optimizer"]);
+        assert_eq!(&lossy[12..16], b"VP8 ");
+
+        let near_lossless = encode(&["image-optimizer", "--webp-near-lossless", "40"]);
+        assert_eq!(&near_lossless[12..16], b"VP8L");
+    }
+
+    #[test]
+    fn test_animated_webp_keeps_every_frame() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_webp_animated");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let input = temp_dir.join("input.webp");
+        let output = temp_dir.join("output.webp");
+
+        let frames: Vec<_> = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255]]
+            .into_iter()
+            .map(|color| image::RgbaImage::from_pixel(32, 16, image::Rgba(color)))
+            .collect();
+        let config = webp::WebPConfig::new().unwrap();
+        let mut encoder = webp::AnimEncoder::new(32, 16, &config);
+        encoder.set_loop_count(3);
+        for (index, frame) in (0..).zip(&frames) {
+            encoder.add_frame(webp::AnimFrame::from_rgba(frame, 32, 16, index * 100));
+        }
+        fs::write(&input, &*encoder.encode()).unwrap();
+        assert!(is_animated_webp(&input));
+
+        let args = Cli::parse_from(["image-optimizer"]);
+        optimize_webp(&input, &output, &args, None).unwrap();
+        let data = fs::read(&output).unwrap();
+        let animation = webp::AnimDecoder::new(&data).decode().unwrap();
+        assert_eq!(animation.len(), 3);
+        assert_eq!(animation.loop_count, 3);
+        let ends: Vec<_> = (&animation)
+            .into_iter()
+            .map(|frame| frame.get_time_ms())
+            .collect();
+        assert_eq!(ends, [100, 200, 300]);
+        let second = animation.get_frame(1).unwrap();
+        assert!(second.get_image()[1] > 200, "green frame kept its color");
+
+        // A --max-size resize resizes every frame
+        let resized = DynamicImage::new_rgb8(16, 8);
+        optimize_webp(&input, &output, &args, Some(resized)).unwrap();
+        let data = fs::read(&output).unwrap();
+        let animation = webp::AnimDecoder::new(&data).decode().unwrap();
+        assert_eq!(animation.len(), 3);
+        let first = animation.get_frame(0).unwrap();
+        assert_eq!((first.width(), first.height()), (16, 8));
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
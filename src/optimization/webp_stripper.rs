@@ -0,0 +1,114 @@
+use anyhow::{Context, Result};
+use img_parts::riff::{RiffChunk, RiffContent};
+use img_parts::webp::{CHUNK_EXIF, CHUNK_ICCP, CHUNK_VP8X, CHUNK_XMP, WebP};
+use std::fs;
+use std::path::Path;
+
+use crate::cli::Cli;
+
+/// `VP8X` feature flags announcing an ICC profile, EXIF and XMP chunk.
+const ICC_FLAG: u8 = 0x20;
+const EXIF_FLAG: u8 = 0x08;
+const XMP_FLAG: u8 = 0x04;
+
+/// Removes metadata chunks from a WebP without re-encoding it (`--strip-only`).
+///
+/// The `EXIF` and `XMP ` chunks are removed, and the `ICCP` chunk unless `--keep-icc`
+/// is set; the bitstream, alpha and animation chunks are copied byte for byte. The
+/// feature flags of the extended (`VP8X`) header are cleared for the removed chunks.
+///
+/// # Arguments
+///
+/// * `input_path` - Path to the source WebP file
+/// * `output_path` - Path where the stripped WebP will be written
+/// * `args` - CLI configuration; only `--keep-icc` is used
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or written, or if its RIFF structure
+/// cannot be parsed.
+pub fn strip_webp(input_path: &Path, output_path: &Path, args: &Cli) -> Result<()> {
+    let data = fs::read(input_path)?;
+    let mut webp = WebP::from_bytes(data.into())
+        .with_context(|| format!("Failed to parse WebP chunks of {}", input_path.display()))?;
+
+    let mut cleared_flags = EXIF_FLAG | XMP_FLAG;
+    webp.remove_chunks_by_id(CHUNK_EXIF);
+    webp.remove_chunks_by_id(CHUNK_XMP);
+    if !args.keep_icc {
+        cleared_flags |= ICC_FLAG;
+        webp.remove_chunks_by_id(CHUNK_ICCP);
+    }
+
+    for chunk in webp.chunks_mut() {
+        if chunk.id() != CHUNK_VP8X {
+            continue;
+        }
+        let Some(header) = chunk.content().data().filter(|header| !header.is_empty()) else {
+            continue;
+        };
+        let mut header = header.to_vec();
+        header[0] &= !cleared_flags;
+        *chunk = RiffChunk::new(CHUNK_VP8X, RiffContent::Data(header.into()));
+    }
+
+    let mut output = Vec::new();
+    webp.encoder().write_to(&mut output)?;
+    fs::write(output_path, output)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use img_parts::Bytes;
+
+    #[test]
+    fn test_strip_removes_exif_and_clears_flag() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_strip_webp");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        let input = temp_dir.join("input.webp");
+        let output = temp_dir.join("output.webp");
+
+        let img = image::RgbImage::from_fn(20, 10, |x, y| {
+            #[allow(clippy::cast_possible_truncation)]
+            image::Rgb([(x * 12) as u8, (y * 25) as u8, 200])
+        });
+        let encoded = webp::Encoder::from_rgb(&img, 20, 10).encode_lossless();
+        let mut webp = WebP::from_bytes(Bytes::copy_from_slice(&encoded)).unwrap();
+        // Extended header: EXIF flag, then the canvas size minus one as 24-bit values
+        let header = vec![EXIF_FLAG, 0, 0, 0, 19, 0, 0, 9, 0, 0];
+        webp.chunks_mut().insert(
+            0,
+            RiffChunk::new(CHUNK_VP8X, RiffContent::Data(header.into())),
+        );
+        webp.chunks_mut().push(RiffChunk::new(
+            CHUNK_EXIF,
+            RiffContent::Data(Bytes::from_static(b"II*\0camera serial 1234")),
+        ));
+        let mut data = Vec::new();
+        webp.encoder().write_to(&mut data).unwrap();
+        fs::write(&input, data).unwrap();
+
+        let args = Cli::parse_from(["image-optimizer", "--strip-only"]);
+        strip_webp(&input, &output, &args).unwrap();
+
+        let stripped = WebP::from_bytes(fs::read(&output).unwrap().into()).unwrap();
+        assert!(!stripped.has_chunk(CHUNK_EXIF));
+        let header = stripped
+            .chunk_by_id(CHUNK_VP8X)
+            .unwrap()
+            .content()
+            .data()
+            .unwrap();
+        assert_eq!(header[0] & EXIF_FLAG, 0);
+        let original = image::open(&input).unwrap().to_rgba8();
+        let result = image::open(&output).unwrap().to_rgba8();
+        assert_eq!(original.as_raw(), result.as_raw());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
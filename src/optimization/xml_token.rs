@@ -0,0 +1,39 @@
+/// One piece of an XML document as produced by [`tokenize_xml`](super::tokenize_xml).
+///
+/// Tokens borrow from the source text, so anything not rewritten by the SVG optimizer
+/// (declarations, text, CDATA, attribute values) is written back byte for byte.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum XmlToken<'a> {
+    /// `<?xml ...?>` declaration or other processing instruction, including delimiters
+    Instruction(&'a str),
+    /// `<!DOCTYPE ...>`, including delimiters and any internal subset
+    Doctype(&'a str),
+    /// Text between `<!--` and `-->`
+    Comment(&'a str),
+    /// `<![CDATA[...]]>` section, including delimiters
+    CData(&'a str),
+    /// Start tag or empty-element tag
+    StartTag {
+        /// Qualified element name, e.g. `svg` or `sodipodi:namedview`
+        name: &'a str,
+        /// Attributes in source order
+        attributes: Vec<XmlAttribute<'a>>,
+        /// Whether the tag ends with `/>`
+        self_closing: bool,
+    },
+    /// End tag `</name>`
+    EndTag(&'a str),
+    /// Character data between markup, with entities left unexpanded
+    Text(&'a str),
+}
+
+/// An attribute of a start tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct XmlAttribute<'a> {
+    /// Qualified attribute name, e.g. `fill` or `inkscape:label`
+    pub name: &'a str,
+    /// Value between the quotes, with entities left unexpanded
+    pub value: &'a str,
+    /// Quote character the value was written with (`"` or `'`)
+    pub quote: char,
+}
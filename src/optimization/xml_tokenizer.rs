@@ -0,0 +1,248 @@
+use anyhow::{Result, anyhow};
+
+use super::{XmlAttribute, XmlToken};
+
+/// Elements whose content is read as raw text up to the matching end tag.
+///
+/// Stylesheets and scripts may contain `<` and `&` without escaping them in a CDATA
+/// section, which browsers accept even though it is not well-formed XML.
+const RAW_TEXT_ELEMENTS: &[&str] = &["style", "script"];
+
+/// Splits an XML document into tokens.
+///
+/// This is a small non-validating tokenizer, sufficient for rewriting SVG markup: it
+/// understands comments, CDATA sections, processing instructions, doctypes and tags
+/// with single- or double-quoted attributes (a `>` inside a quoted value does not end
+/// the tag). It does not expand entities or check that tags are balanced.
+///
+/// # Errors
+///
+/// Returns an error if markup is not terminated or a tag is malformed, with the byte
+/// offset where the problem starts
+///
+/// # Examples
+///
+/// ```rust
+/// use image_optimizer::optimization::{XmlToken, tokenize_xml};
+///
+/// let tokens = tokenize_xml(r#"<g id="a>b"/>"#).unwrap();
+/// assert!(matches!(&tokens[0], XmlToken::StartTag { name: "g", self_closing: true, .. }));
+/// ```
+pub fn tokenize_xml(source: &str) -> Result<Vec<XmlToken<'_>>> {
+    let mut tokens = Vec::new();
+    let mut position = 0;
+
+    while position < source.len() {
+        let rest = &source[position..];
+        if !rest.starts_with('<') {
+            let end = rest
+                .find('<')
+                .map_or(source.len(), |offset| position + offset);
+            tokens.push(XmlToken::Text(&source[position..end]));
+            position = end;
+            continue;
+        }
+
+        let (token, end) = if let Some(body) = rest.strip_prefix("<!--") {
+            let close = find_from(body, "-->", position)?;
+            (XmlToken::Comment(&body[..close]), position + 4 + close + 3)
+        } else if rest.starts_with("<![CDATA[") {
+            let end = position + find_from(rest, "]]>", position)? + 3;
+            (XmlToken::CData(&source[position..end]), end)
+        } else if rest.starts_with("<?") {
+            let end = position + find_from(rest, "?>", position)? + 2;
+            (XmlToken::Instruction(&source[position..end]), end)
+        } else if rest.starts_with("<!") {
+            let end = position + doctype_length(rest, position)?;
+            (XmlToken::Doctype(&source[position..end]), end)
+        } else if let Some(body) = rest.strip_prefix("</") {
+            let close = find_from(body, ">", position)?;
+            let name = body[..close].trim();
+            (XmlToken::EndTag(name), position + 2 + close + 1)
+        } else {
+            read_start_tag(source, position)?
+        };
+        position = end;
+
+        if let XmlToken::StartTag {
+            name,
+            self_closing: false,
+            ..
+        } = token
+            && RAW_TEXT_ELEMENTS.contains(&local_name(name))
+        {
+            tokens.push(token);
+            let close = source[position..]
+                .find(&format!("</{name}"))
+                .ok_or_else(|| anyhow!("<{name}> at byte {position} is never closed"))?;
+            if close > 0 {
+                tokens.push(XmlToken::Text(&source[position..position + close]));
+            }
+            position += close;
+            continue;
+        }
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+/// Returns the part of a qualified name after its namespace prefix.
+pub(super) fn local_name(name: &str) -> &str {
+    name.rsplit_once(':').map_or(name, |(_, local)| local)
+}
+
+/// Finds `needle` in `haystack`, reporting unterminated markup at `start` otherwise.
+fn find_from(haystack: &str, needle: &str, start: usize) -> Result<usize> {
+    haystack
+        .find(needle)
+        .ok_or_else(|| anyhow!("markup at byte {start} is not terminated by {needle}"))
+}
+
+/// Length of a `<!DOCTYPE ...>` declaration, skipping over an internal `[...]` subset.
+fn doctype_length(rest: &str, start: usize) -> Result<usize> {
+    let mut in_subset = false;
+    for (offset, character) in rest.char_indices() {
+        match character {
+            '[' => in_subset = true,
+            ']' => in_subset = false,
+            '>' if !in_subset => return Ok(offset + 1),
+            _ => {}
+        }
+    }
+    Err(anyhow!(
+        "declaration at byte {start} is not terminated by >"
+    ))
+}
+
+/// Reads the start tag beginning at `start` and returns it with the offset after it.
+fn read_start_tag(source: &str, start: usize) -> Result<(XmlToken<'_>, usize)> {
+    let malformed = |at: usize| anyhow!("malformed tag at byte {start} (near byte {at})");
+    let bytes = source.as_bytes();
+    let name_end = source[start + 1..]
+        .find(|c: char| c.is_whitespace() || c == '/' || c == '>')
+        .map_or(source.len(), |offset| start + 1 + offset);
+    let name = &source[start + 1..name_end];
+    if name.is_empty() {
+        return Err(malformed(start + 1));
+    }
+
+    let mut attributes = Vec::new();
+    let mut position = name_end;
+    loop {
+        position += source[position..]
+            .find(|c: char| !c.is_whitespace())
+            .ok_or_else(|| malformed(position))?;
+        match bytes[position] {
+            b'>' => {
+                let token = XmlToken::StartTag {
+                    name,
+                    attributes,
+                    self_closing: false,
+                };
+                return Ok((token, position + 1));
+            }
+            b'/' if bytes.get(position + 1) == Some(&b'>') => {
+                let token = XmlToken::StartTag {
+                    name,
+                    attributes,
+                    self_closing: true,
+                };
+                return Ok((token, position + 2));
+            }
+            _ => {}
+        }
+
+        let attribute_end = source[position..]
+            .find(|c: char| c.is_whitespace() || c == '=' || c == '/' || c == '>')
+            .map_or(source.len(), |offset| position + offset);
+        let attribute_name = &source[position..attribute_end];
+        if attribute_name.is_empty() {
+            return Err(malformed(position));
+        }
+        position = attribute_end;
+        position += source[position..]
+            .find(|c: char| !c.is_whitespace())
+            .ok_or_else(|| malformed(position))?;
+        if bytes[position] != b'=' {
+            return Err(malformed(position));
+        }
+        position += 1;
+        position += source[position..]
+            .find(|c: char| !c.is_whitespace())
+            .ok_or_else(|| malformed(position))?;
+
+        let quote = char::from(bytes[position]);
+        if quote != '"' && quote != '\'' {
+            return Err(malformed(position));
+        }
+        let value_start = position + 1;
+        let value_end = source[value_start..]
+            .find(quote)
+            .map(|offset| value_start + offset)
+            .ok_or_else(|| malformed(value_start))?;
+        attributes.push(XmlAttribute {
+            name: attribute_name,
+            value: &source[value_start..value_end],
+            quote,
+        });
+        position = value_end + 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quoted_gt_does_not_end_tag() {
+        let tokens = tokenize_xml(r#"<rect title='a > b' data-x="1>2"/><g>"#).unwrap();
+        let XmlToken::StartTag {
+            name,
+            ref attributes,
+            self_closing,
+        } = tokens[0]
+        else {
+            panic!("expected a start tag, got {:?}", tokens[0]);
+        };
+        assert_eq!(name, "rect");
+        assert!(self_closing);
+        assert_eq!(attributes[0].value, "a > b");
+        assert_eq!(attributes[0].quote, '\'');
+        assert_eq!(attributes[1].value, "1>2");
+        assert!(matches!(tokens[1], XmlToken::StartTag { name: "g", .. }));
+    }
+
+    #[test]
+    fn test_markup_kinds() {
+        let source = "<?xml version=\"1.0\"?><!DOCTYPE svg [<!ENTITY a \"b\">]><!-- c -->\
+                      <![CDATA[<x>]]>text</svg >";
+        let tokens = tokenize_xml(source).unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                XmlToken::Instruction("<?xml version=\"1.0\"?>"),
+                XmlToken::Doctype("<!DOCTYPE svg [<!ENTITY a \"b\">]>"),
+                XmlToken::Comment(" c "),
+                XmlToken::CData("<![CDATA[<x>]]>"),
+                XmlToken::Text("text"),
+                XmlToken::EndTag("svg"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_script_content_is_raw_text() {
+        let tokens = tokenize_xml("<script>if (a < b && c) {}</script>").unwrap();
+        assert_eq!(tokens[1], XmlToken::Text("if (a < b && c) {}"));
+        assert_eq!(tokens[2], XmlToken::EndTag("script"));
+    }
+
+    #[test]
+    fn test_unterminated_markup_is_an_error() {
+        assert!(tokenize_xml("<svg><!-- open").is_err());
+        assert!(tokenize_xml("<svg width=\"10").is_err());
+        assert!(tokenize_xml("<svg width=10>").is_err());
+        assert!(tokenize_xml("<style>a {}").is_err());
+    }
+}
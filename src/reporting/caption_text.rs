@@ -0,0 +1,117 @@
+use image::{Rgba, RgbaImage};
+
+/// Width of a glyph in font pixels.
+const GLYPH_WIDTH: u32 = 3;
+/// Height of a glyph in font pixels.
+pub const GLYPH_HEIGHT: u32 = 5;
+
+/// Draws a one-line caption into an image with a built-in 3x5 pixel font.
+///
+/// The font only covers what size captions need: digits, `.`, `+`, `-`, `>`, `%`,
+/// parentheses and the unit letters `K`, `M`, `G` and `B`. Other characters are drawn
+/// as spaces. Pixels outside the image are clipped.
+///
+/// # Arguments
+///
+/// * `canvas` - Image to draw into
+/// * `text` - Caption to draw
+/// * `x`, `y` - Top-left corner of the caption
+/// * `scale` - Size of one font pixel in image pixels
+/// * `color` - Text color
+///
+/// # Examples
+///
+/// ```rust
+/// use image::{Rgba, RgbaImage};
+/// use image_optimizer::reporting::{caption_width, draw_caption};
+///
+/// let mut canvas = RgbaImage::new(caption_width("-8.0%", 2), 10);
+/// draw_caption(&mut canvas, "-8.0%", 0, 0, 2, Rgba([255, 255, 255, 255]));
+/// ```
+pub fn draw_caption(
+    canvas: &mut RgbaImage,
+    text: &str,
+    x: u32,
+    y: u32,
+    scale: u32,
+    color: Rgba<u8>,
+) {
+    let advance = (GLYPH_WIDTH + 1) * scale;
+    for (column, character) in (0..).zip(text.chars()) {
+        let glyph_x = x + column * advance;
+        for (row, bits) in (0..).zip(glyph(character)) {
+            for bit in 0..GLYPH_WIDTH {
+                if bits & (0b100 >> bit) == 0 {
+                    continue;
+                }
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        let (px, py) = (glyph_x + bit * scale + dx, y + row * scale + dy);
+                        if px < canvas.width() && py < canvas.height() {
+                            canvas.put_pixel(px, py, color);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Returns the width in pixels of `text` drawn by [`draw_caption`] at `scale`.
+#[must_use]
+pub fn caption_width(text: &str, scale: u32) -> u32 {
+    let characters = u32::try_from(text.chars().count()).unwrap_or(u32::MAX);
+    characters.saturating_mul((GLYPH_WIDTH + 1) * scale)
+}
+
+/// Rows of a glyph, three bits each with the leftmost pixel in the highest bit.
+const fn glyph(character: char) -> [u8; 5] {
+    match character {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+        '>' => [0b100, 0b010, 0b001, 0b010, 0b100],
+        '%' => [0b101, 0b001, 0b010, 0b100, 0b101],
+        '(' => [0b001, 0b010, 0b010, 0b010, 0b001],
+        ')' => [0b100, 0b010, 0b010, 0b010, 0b100],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'G' => [0b111, 0b100, 0b101, 0b101, 0b111],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        _ => [0; 5],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_draws_only_inside_caption_area() {
+        let white = Rgba([255, 255, 255, 255]);
+        let mut canvas = RgbaImage::new(40, 20);
+        draw_caption(&mut canvas, "1 KB", 0, 0, 2, white);
+
+        let lit: Vec<_> = canvas
+            .enumerate_pixels()
+            .filter(|(_, _, pixel)| **pixel == white)
+            .collect();
+        assert!(!lit.is_empty());
+        assert!(
+            lit.iter()
+                .all(|(x, y, _)| *x < caption_width("1 KB", 2) && *y < 10)
+        );
+        // The space between "1" and "KB" stays empty
+        assert!(lit.iter().all(|(x, _, _)| !(8..16).contains(x)));
+    }
+}
@@ -0,0 +1,136 @@
+use anyhow::{Context, Result};
+use image::{DynamicImage, Rgba, RgbaImage, imageops};
+use std::path::Path;
+
+use super::caption_text::{GLYPH_HEIGHT, caption_width, draw_caption};
+use crate::file_ops::{calculate_resize_dimensions, format_bytes, format_delta, percent_change};
+use crate::optimization::ImageOutcome;
+
+/// Longest edge of each thumbnail in a comparison image.
+pub const THUMBNAIL_SIZE: u32 = 256;
+
+/// Space between and around the thumbnails, in pixels.
+const PADDING: u32 = 8;
+/// Size of one caption font pixel.
+const CAPTION_SCALE: u32 = 2;
+const BACKGROUND: Rgba<u8> = Rgba([40, 40, 40, 255]);
+const TEXT_COLOR: Rgba<u8> = Rgba([240, 240, 240, 255]);
+
+/// Writes a side-by-side PNG of an image before and after optimization for `--compare-out`.
+///
+/// The original is shown on the left and the optimized result on the right, each scaled
+/// down to fit [`THUMBNAIL_SIZE`]. A caption below shows the original size, optimized
+/// size and the change, e.g. `97.7 KB -> 89.8 KB -7.8 KB (-8.0%)`. Transparent areas are
+/// drawn over the dark background.
+///
+/// # Arguments
+///
+/// * `original` - Decoded original image (any size; it is scaled down here, but callers
+///   holding on to it may pass [`shrink_to_thumbnail`] output to save memory)
+/// * `optimized` - Decoded optimized image
+/// * `outcome` - Sizes shown in the caption
+/// * `comparison_path` - Where to write the PNG
+///
+/// # Errors
+///
+/// Returns an error if the PNG cannot be written
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::path::Path;
+/// use image_optimizer::optimization::ImageOutcome;
+/// use image_optimizer::reporting::write_comparison;
+///
+/// # fn example() -> anyhow::Result<()> {
+/// let original = image::open("photo.jpg")?;
+/// let optimized = image::open("optimized/photo.jpg")?;
+/// let outcome = ImageOutcome { original_size: 100_000, optimized_size: 92_000, quality: None };
+/// write_comparison(&original, &optimized, outcome, Path::new("compare/photo.jpg.png"))?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn write_comparison(
+    original: &DynamicImage,
+    optimized: &DynamicImage,
+    outcome: ImageOutcome,
+    comparison_path: &Path,
+) -> Result<()> {
+    let before = shrink_to_thumbnail(original).to_rgba8();
+    let after = shrink_to_thumbnail(optimized).to_rgba8();
+    let caption = format!(
+        "{} -> {} {} ({:+.1}%)",
+        format_bytes(outcome.original_size),
+        format_bytes(outcome.optimized_size),
+        format_delta(outcome.size_delta()),
+        percent_change(outcome.original_size, outcome.optimized_size)
+    );
+
+    let column_width = before.width().max(after.width());
+    let thumbnails_height = before.height().max(after.height());
+    let width =
+        (2 * column_width + 3 * PADDING).max(caption_width(&caption, CAPTION_SCALE) + 2 * PADDING);
+    let caption_y = thumbnails_height + 2 * PADDING;
+    let height = caption_y + GLYPH_HEIGHT * CAPTION_SCALE + PADDING;
+
+    let mut canvas = RgbaImage::from_pixel(width, height, BACKGROUND);
+    imageops::overlay(&mut canvas, &before, i64::from(PADDING), i64::from(PADDING));
+    imageops::overlay(
+        &mut canvas,
+        &after,
+        i64::from(column_width + 2 * PADDING),
+        i64::from(PADDING),
+    );
+    draw_caption(
+        &mut canvas,
+        &caption,
+        PADDING,
+        caption_y,
+        CAPTION_SCALE,
+        TEXT_COLOR,
+    );
+
+    if let Some(parent) = comparison_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    canvas
+        .save_with_format(comparison_path, image::ImageFormat::Png)
+        .with_context(|| format!("Failed to write {}", comparison_path.display()))
+}
+
+/// Scales an image down to fit [`THUMBNAIL_SIZE`]; smaller images are not enlarged.
+#[must_use]
+pub fn shrink_to_thumbnail(img: &DynamicImage) -> DynamicImage {
+    let (width, height) = calculate_resize_dimensions(img.width(), img.height(), THUMBNAIL_SIZE);
+    if (width, height) == (img.width(), img.height()) {
+        img.clone()
+    } else {
+        img.thumbnail_exact(width, height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_comparison_is_twice_as_wide_as_a_thumbnail() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_comparison_image");
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        let path = temp_dir.join("nested/photo.jpg.png");
+        let image = DynamicImage::new_rgb8(1024, 512);
+        let outcome = ImageOutcome {
+            original_size: 100_000,
+            optimized_size: 92_000,
+            quality: None,
+        };
+
+        write_comparison(&image, &image, outcome, &path).unwrap();
+
+        let comparison = image::open(&path).unwrap();
+        assert!(comparison.width() >= 2 * THUMBNAIL_SIZE);
+        assert!(comparison.width() <= 2 * THUMBNAIL_SIZE + 4 * PADDING);
+        assert!(comparison.height() > THUMBNAIL_SIZE / 2);
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+}
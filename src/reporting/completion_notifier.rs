@@ -0,0 +1,33 @@
+use std::io::{self, Write};
+
+use super::RunSummary;
+
+/// Signals the end of a run for `--notify`.
+///
+/// Rings the terminal bell on stderr, so redirected stdout stays clean. When built with
+/// the `desktop-notify` feature, a desktop notification with
+/// [`RunSummary::notification_text`] is shown as well. Both are best effort: a missing
+/// terminal or notification service (for example over SSH) is silently ignored.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_optimizer::reporting::{RunSummary, notify_completion};
+///
+/// notify_completion(&RunSummary::default());
+/// ```
+pub fn notify_completion(summary: &RunSummary) {
+    let mut stderr = io::stderr();
+    let _ = stderr.write_all(b"\x07").and_then(|()| stderr.flush());
+
+    #[cfg(feature = "desktop-notify")]
+    {
+        let _ = notify_rust::Notification::new()
+            .summary("Image optimization finished")
+            .body(&summary.notification_text())
+            .appname("image-optimizer")
+            .show();
+    }
+    #[cfg(not(feature = "desktop-notify"))]
+    let _ = summary;
+}
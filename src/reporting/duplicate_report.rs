@@ -0,0 +1,80 @@
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::file_ops::format_bytes;
+
+/// Formats the groups of identical files listed by `--dedup report`.
+///
+/// Each group shows the file that `--dedup link` or `copy` would optimize, with its size,
+/// followed by its indented copies. The header totals the bytes taken up by the copies.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::PathBuf;
+/// use image_optimizer::reporting::format_duplicate_groups;
+///
+/// assert_eq!(format_duplicate_groups(&[]), "No identical files found\n");
+/// let groups = vec![vec![PathBuf::from("missing-a.png"), PathBuf::from("missing-b.png")]];
+/// assert!(format_duplicate_groups(&groups).starts_with("1 group of identical files"));
+/// ```
+#[must_use]
+pub fn format_duplicate_groups(groups: &[Vec<PathBuf>]) -> String {
+    if groups.is_empty() {
+        return "No identical files found\n".to_string();
+    }
+
+    let mut redundant = 0;
+    let mut lines = String::new();
+    for group in groups {
+        let Some((first, copies)) = group.split_first() else {
+            continue;
+        };
+        let size = fs::metadata(first).map_or(0, |metadata| metadata.len());
+        redundant += size * copies.len() as u64;
+        let _ = writeln!(lines, "  {} ({})", first.display(), format_bytes(size));
+        for copy in copies {
+            let _ = writeln!(lines, "    {}", copy.display());
+        }
+    }
+
+    let noun = if groups.len() == 1 { "group" } else { "groups" };
+    format!(
+        "{} {noun} of identical files ({} in duplicate copies):\n{lines}",
+        groups.len(),
+        format_bytes(redundant)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lists_copies_under_first_file() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_duplicate_report");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let paths: Vec<PathBuf> = ["a.png", "b.png", "c.png"]
+            .iter()
+            .map(|name| temp_dir.join(name))
+            .collect();
+        for path in &paths {
+            fs::write(path, [0u8; 100]).unwrap();
+        }
+
+        let report = format_duplicate_groups(std::slice::from_ref(&paths));
+
+        assert_eq!(
+            report,
+            format!(
+                "1 group of identical files (200 B in duplicate copies):\n  {} (100 B)\n    {}\n    {}\n",
+                paths[0].display(),
+                paths[1].display(),
+                paths[2].display()
+            )
+        );
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
@@ -0,0 +1,337 @@
+use serde::{Deserialize, Serialize, Serializer};
+use std::path::{Path, PathBuf};
+
+use crate::optimization::{ImageOutcome, InvalidImage, OptimizeError, QualityScore};
+
+/// How a single file was handled during a run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileStatus {
+    /// The file was replaced by a smaller optimized version
+    Optimized,
+    /// The original was kept because optimization would not reduce its size
+    SkippedLarger,
+    /// The original was kept because the result saved less than
+    /// `--prefer-smaller-than-original-by`
+    SkippedThreshold,
+    /// The file is smaller than `--min-size` and was not optimized
+    SkippedSmall,
+    /// The file exceeds `--max-input-size` and was not decoded
+    SkippedTooLarge,
+    /// The output already exists and was kept (`--skip-existing`, or newer than the
+    /// input without `--force`)
+    SkippedExisting,
+    /// The cache lists the file as already optimized
+    Cached,
+    /// The file carries the `--mark` marker of the current settings
+    Marked,
+    /// The file could not be decoded
+    Invalid,
+    /// Optimizing the file failed
+    Failed,
+}
+
+impl FileStatus {
+    /// Returns the status as written in the JSON report and `--porcelain` lines, e.g.
+    /// `skipped_larger`.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Optimized => "optimized",
+            Self::SkippedLarger => "skipped_larger",
+            Self::SkippedThreshold => "skipped_threshold",
+            Self::SkippedSmall => "skipped_small",
+            Self::SkippedTooLarge => "skipped_too_large",
+            Self::SkippedExisting => "skipped_existing",
+            Self::Cached => "cached",
+            Self::Marked => "marked",
+            Self::Invalid => "invalid",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// The result of processing one file, as listed in the JSON report.
+///
+/// Sizes are present for files that were optimized (or would have grown); the error
+/// message is present for files that failed or exceeded `--max-input-size`. Duplicates
+/// handled by `--dedup` name the file whose result they share.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use image_optimizer::optimization::ImageOutcome;
+/// use image_optimizer::reporting::{FileResult, FileStatus};
+///
+/// let outcome = ImageOutcome { original_size: 1000, optimized_size: 800, quality: None };
+/// let result = FileResult::from_outcome(Path::new("photo.jpg"), outcome);
+/// assert_eq!(result.status, FileStatus::Optimized);
+/// ```
+#[derive(Debug, Clone, Serialize)]
+pub struct FileResult {
+    /// Path of the input file
+    #[serde(serialize_with = "serialize_path_lossy")]
+    pub path: PathBuf,
+    /// How the file was handled
+    pub status: FileStatus,
+    /// Size of the input file in bytes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original_size: Option<u64>,
+    /// Size of the optimized result in bytes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub optimized_size: Option<u64>,
+    /// SSIM and PSNR of the result, measured with `--measure-quality`
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub quality: Option<QualityScore>,
+    /// Why the file failed or was skipped as too large
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The identical file that was optimized in this file's place with `--dedup`
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_optional_path_lossy"
+    )]
+    pub duplicate_of: Option<PathBuf>,
+}
+
+/// Writes a path as a string, replacing bytes that are not valid UTF-8, so reports of
+/// files with such names can still be written.
+fn serialize_path_lossy<S: Serializer>(path: &Path, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&path.to_string_lossy())
+}
+
+#[allow(clippy::ref_option)] // Signature required by serde's `serialize_with`
+fn serialize_optional_path_lossy<S: Serializer>(
+    path: &Option<PathBuf>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match path {
+        Some(path) => serialize_path_lossy(path, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+impl FileResult {
+    /// Creates the result for a file that went through the optimizer.
+    #[must_use]
+    pub fn from_outcome(path: &Path, outcome: ImageOutcome) -> Self {
+        let status = if outcome.saved_bytes() > 0 {
+            FileStatus::Optimized
+        } else {
+            FileStatus::SkippedLarger
+        };
+        Self {
+            path: path.to_path_buf(),
+            status,
+            original_size: Some(outcome.original_size),
+            optimized_size: Some(outcome.optimized_size),
+            quality: outcome.quality,
+            error: None,
+            duplicate_of: None,
+        }
+    }
+
+    /// Creates the result for a file skipped because of a cache hit.
+    #[must_use]
+    pub fn cached(path: &Path) -> Self {
+        Self::skipped(path, FileStatus::Cached)
+    }
+
+    /// Creates the result for a file whose existing output was kept.
+    #[must_use]
+    pub fn existing(path: &Path) -> Self {
+        Self::skipped(path, FileStatus::SkippedExisting)
+    }
+
+    /// Creates the result for a file below the `--min-size` threshold.
+    #[must_use]
+    pub fn too_small(path: &Path) -> Self {
+        Self::skipped(path, FileStatus::SkippedSmall)
+    }
+
+    fn skipped(path: &Path, status: FileStatus) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            status,
+            original_size: None,
+            optimized_size: None,
+            quality: None,
+            error: None,
+            duplicate_of: None,
+        }
+    }
+
+    /// Creates the result for a file that failed, telling decode errors and files above
+    /// `--max-input-size` apart. Files skipped because of their `--mark` marker get a
+    /// result without an error message, as do files whose existing output was kept.
+    #[must_use]
+    pub fn from_error(path: &Path, error: &anyhow::Error) -> Self {
+        let status = match error.downcast_ref::<OptimizeError>() {
+            Some(OptimizeError::AlreadyMarked) => return Self::skipped(path, FileStatus::Marked),
+            Some(OptimizeError::OutputExists(_)) => return Self::existing(path),
+            Some(&OptimizeError::BelowThreshold {
+                original,
+                optimized,
+                ..
+            }) => {
+                return Self {
+                    original_size: Some(original),
+                    optimized_size: Some(optimized),
+                    ..Self::skipped(path, FileStatus::SkippedThreshold)
+                };
+            }
+            Some(OptimizeError::Decode(_)) => FileStatus::Invalid,
+            Some(OptimizeError::InputTooLarge(_)) => FileStatus::SkippedTooLarge,
+            _ if error.downcast_ref::<InvalidImage>().is_some() => FileStatus::Invalid,
+            _ => FileStatus::Failed,
+        };
+        Self {
+            path: path.to_path_buf(),
+            status,
+            original_size: None,
+            optimized_size: None,
+            quality: None,
+            error: Some(error.to_string()),
+            duplicate_of: None,
+        }
+    }
+
+    /// Creates the result of a `--dedup` duplicate that received the result of `original`.
+    ///
+    /// The duplicate shares the status and sizes of the file it is identical to.
+    #[must_use]
+    pub fn duplicate(path: &Path, original: &Self) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            duplicate_of: Some(original.path.clone()),
+            ..original.clone()
+        }
+    }
+
+    /// Returns `true` if optimizing the file failed, including files that could not be
+    /// decoded.
+    #[must_use]
+    pub const fn is_failure(&self) -> bool {
+        matches!(self.status, FileStatus::Invalid | FileStatus::Failed)
+    }
+
+    /// Returns the sizes of a file that went through the optimizer.
+    #[must_use]
+    pub fn outcome(&self) -> Option<ImageOutcome> {
+        Some(ImageOutcome {
+            original_size: self.original_size?,
+            optimized_size: self.optimized_size?,
+            quality: self.quality,
+        })
+    }
+
+    /// Returns the bytes saved for this file, or 0 if it was not optimized.
+    #[must_use]
+    pub const fn saved_bytes(&self) -> u64 {
+        match (self.status, self.original_size, self.optimized_size) {
+            (FileStatus::Optimized, Some(original), Some(optimized)) => {
+                original.saturating_sub(optimized)
+            }
+            _ => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_from_outcome() {
+        let grown = ImageOutcome {
+            original_size: 100,
+            optimized_size: 120,
+            quality: None,
+        };
+        let result = FileResult::from_outcome(Path::new("a.png"), grown);
+        assert_eq!(result.status, FileStatus::SkippedLarger);
+        assert_eq!(result.saved_bytes(), 0);
+    }
+
+    #[test]
+    fn test_status_from_error() {
+        let invalid = anyhow::Error::new(InvalidImage::new("truncated"));
+        let result = FileResult::from_error(Path::new("a.png"), &invalid);
+        assert_eq!(result.status, FileStatus::Invalid);
+
+        let decode = anyhow::Error::new(OptimizeError::Decode(InvalidImage::new("truncated")));
+        let result = FileResult::from_error(Path::new("a.png"), &decode);
+        assert_eq!(result.status, FileStatus::Invalid);
+
+        let too_large = anyhow::Error::new(OptimizeError::InputTooLarge("a.png".to_string()));
+        let result = FileResult::from_error(Path::new("a.png"), &too_large);
+        assert_eq!(result.status, FileStatus::SkippedTooLarge);
+        assert!(!result.is_failure());
+
+        let marked = anyhow::Error::new(OptimizeError::AlreadyMarked);
+        let result = FileResult::from_error(Path::new("a.png"), &marked);
+        assert_eq!(result.status, FileStatus::Marked);
+        assert_eq!(result.error, None);
+
+        let existing = anyhow::Error::new(OptimizeError::OutputExists("out/a.png".into()));
+        let result = FileResult::from_error(Path::new("a.png"), &existing);
+        assert_eq!(result.status, FileStatus::SkippedExisting);
+        assert_eq!(result.error, None);
+        assert!(!result.is_failure());
+
+        let below = anyhow::Error::new(OptimizeError::BelowThreshold {
+            original: 1000,
+            optimized: 990,
+            threshold: 5,
+        });
+        let result = FileResult::from_error(Path::new("a.png"), &below);
+        assert_eq!(result.status, FileStatus::SkippedThreshold);
+        assert_eq!(result.error, None);
+        assert_eq!(result.optimized_size, Some(990));
+        assert_eq!(result.saved_bytes(), 0);
+
+        let failed = anyhow::anyhow!("disk full");
+        let result = FileResult::from_error(Path::new("a.png"), &failed);
+        assert_eq!(result.status, FileStatus::Failed);
+        assert_eq!(result.error.as_deref(), Some("disk full"));
+    }
+
+    #[test]
+    fn test_serializes_quality_next_to_sizes() {
+        let outcome = ImageOutcome {
+            original_size: 100,
+            optimized_size: 80,
+            quality: Some(QualityScore {
+                ssim: 0.5,
+                psnr: 30.0,
+            }),
+        };
+        let result = FileResult::from_outcome(Path::new("a.png"), outcome);
+        let json = serde_json::to_string(&result).unwrap();
+        assert!(json.contains(r#""optimized_size":80,"ssim":0.5,"psnr":30.0"#));
+        assert_eq!(result.outcome(), Some(outcome));
+    }
+
+    #[test]
+    fn test_duplicate_shares_status_and_sizes() {
+        let outcome = ImageOutcome {
+            original_size: 100,
+            optimized_size: 80,
+            quality: None,
+        };
+        let original = FileResult::from_outcome(Path::new("a.png"), outcome);
+        let duplicate = FileResult::duplicate(Path::new("b.png"), &original);
+        assert_eq!(duplicate.status, FileStatus::Optimized);
+        assert_eq!(duplicate.saved_bytes(), 20);
+        let json = serde_json::to_string(&duplicate).unwrap();
+        assert!(json.ends_with(r#""duplicate_of":"a.png"}"#));
+    }
+
+    #[test]
+    fn test_serializes_snake_case_status() {
+        let result = FileResult::cached(Path::new("a.png"));
+        let json = serde_json::to_string(&result).unwrap();
+        assert_eq!(json, r#"{"path":"a.png","status":"cached"}"#);
+    }
+}
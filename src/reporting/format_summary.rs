@@ -0,0 +1,131 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use super::{FileResult, FileStatus};
+use crate::file_ops::format_bytes;
+use crate::optimization::ImageFormat;
+
+/// Totals of a finished run for the files of one input format, printed below the
+/// summary and included in the JSON report as `by_format`.
+///
+/// Files are grouped by the format of their input extension, so with `--format` the
+/// savings of converted files count towards the format they were converted from.
+/// Files without a recognized image extension are left out.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use image_optimizer::optimization::{ImageFormat, ImageOutcome};
+/// use image_optimizer::reporting::{FileResult, FormatSummary};
+///
+/// let outcome = ImageOutcome { original_size: 4096, optimized_size: 3072, quality: None };
+/// let results = [
+///     FileResult::from_outcome(Path::new("a.jpg"), outcome),
+///     FileResult::cached(Path::new("b.png")),
+/// ];
+///
+/// let totals = FormatSummary::by_format(&results);
+/// assert_eq!(totals[&ImageFormat::Jpeg].total_saved, 1024);
+/// assert_eq!(totals[&ImageFormat::Png].files, 1);
+/// assert_eq!(
+///     totals[&ImageFormat::Jpeg].format_line(ImageFormat::Jpeg),
+///     "JPEG: 1 files, saved 1.0 KB (avg 25%)"
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct FormatSummary {
+    /// Files of this format in the run, whatever their status
+    pub files: usize,
+    /// Files replaced by a smaller optimized version
+    pub processed: usize,
+    /// Total bytes saved across the optimized files
+    pub total_saved: u64,
+    /// Total size in bytes of the files that went through the optimizer
+    pub total_input: u64,
+}
+
+impl FormatSummary {
+    /// Tallies per-file results into totals per input format.
+    #[must_use]
+    pub fn by_format(results: &[FileResult]) -> BTreeMap<ImageFormat, Self> {
+        let mut totals: BTreeMap<ImageFormat, Self> = BTreeMap::new();
+        for result in results {
+            let Some(format) = ImageFormat::from_path(&result.path) else {
+                continue;
+            };
+            let format_summary = totals.entry(format).or_default();
+            format_summary.files += 1;
+            if result.status == FileStatus::Optimized {
+                format_summary.processed += 1;
+            }
+            format_summary.total_saved += result.saved_bytes();
+            format_summary.total_input += result.original_size.unwrap_or(0);
+        }
+        totals
+    }
+
+    /// Formats the totals as printed below the summary, e.g.
+    /// `JPEG: 120 files, saved 45.0 MB (avg 12%)`.
+    ///
+    /// The average is the share of the format's input size that was saved; it is left
+    /// out when nothing was saved.
+    #[must_use]
+    pub fn format_line(&self, format: ImageFormat) -> String {
+        let line = format!(
+            "{}: {} files, saved {}",
+            format.name(),
+            self.files,
+            format_bytes(self.total_saved)
+        );
+        if self.total_saved == 0 || self.total_input == 0 {
+            return line;
+        }
+        #[allow(clippy::cast_precision_loss)]
+        let average = self.total_saved as f64 / self.total_input as f64 * 100.0;
+        format!("{line} (avg {average:.0}%)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimization::ImageOutcome;
+    use std::path::Path;
+
+    #[test]
+    fn test_groups_results_by_input_extension() {
+        let outcome = |original_size, optimized_size| ImageOutcome {
+            original_size,
+            optimized_size,
+            quality: None,
+        };
+        let mut larger = FileResult::from_outcome(Path::new("c.png"), outcome(100, 120));
+        larger.status = FileStatus::SkippedLarger;
+        let results = [
+            FileResult::from_outcome(Path::new("a.JPG"), outcome(1000, 800)),
+            FileResult::from_outcome(Path::new("b.jpeg"), outcome(1000, 900)),
+            larger,
+            FileResult::from_error(Path::new("d.svgz"), &anyhow::anyhow!("disk full")),
+            FileResult::from_error(Path::new("notes.txt"), &anyhow::anyhow!("unsupported")),
+        ];
+
+        let totals = FormatSummary::by_format(&results);
+
+        assert_eq!(
+            totals.keys().copied().collect::<Vec<_>>(),
+            [ImageFormat::Jpeg, ImageFormat::Png, ImageFormat::Svg]
+        );
+        let jpeg = totals[&ImageFormat::Jpeg];
+        assert_eq!((jpeg.files, jpeg.processed), (2, 2));
+        assert_eq!((jpeg.total_saved, jpeg.total_input), (300, 2000));
+        assert_eq!(
+            jpeg.format_line(ImageFormat::Jpeg),
+            "JPEG: 2 files, saved 300 B (avg 15%)"
+        );
+        let png = totals[&ImageFormat::Png];
+        assert_eq!((png.files, png.processed, png.total_saved), (1, 0, 0));
+        assert_eq!(png.format_line(ImageFormat::Png), "PNG: 1 files, saved 0 B");
+        assert_eq!(totals[&ImageFormat::Svg].files, 1);
+    }
+}
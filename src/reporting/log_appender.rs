@@ -0,0 +1,196 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::{FileResult, RunStats, RunSummary};
+
+#[derive(Serialize)]
+struct RunEntry<'a> {
+    timestamp: &'a str,
+    kind: &'static str,
+    version: &'static str,
+    input: &'a Path,
+    elapsed_secs: f64,
+    #[serde(flatten)]
+    summary: RunSummary,
+}
+
+#[derive(Serialize)]
+struct FileEntry<'a> {
+    timestamp: &'a str,
+    kind: &'static str,
+    #[serde(flatten)]
+    result: &'a FileResult,
+}
+
+/// Appends the entries of a finished run to the `--log-file` log.
+///
+/// The log is in JSON lines format: one object per line with a local RFC 3339
+/// `timestamp` and a `kind`. Every run adds a `run` entry with the tool version, the
+/// input, the elapsed time and the run totals; with `per_file` it is preceded by one
+/// `file` entry per file, shaped like the files of the `--report-json` report. Nothing
+/// is printed, so the log can be kept next to the normal output of scheduled runs.
+///
+/// With `max_bytes`, a log that would grow past the cap is first renamed to the same
+/// name with `.1` appended (replacing an older rotated log), and the run starts a new
+/// file. The entries of one run are never split across the two files.
+///
+/// # Arguments
+///
+/// * `path` - Log file to append to; created if missing
+/// * `input` - Input file or directory of the run
+/// * `stats` - Results collected during the run
+/// * `elapsed` - Wall-clock time the batch took
+/// * `per_file` - Whether to log one entry per file as well (`--log-per-file`)
+/// * `max_bytes` - Size at which the log is rotated (`--log-max-bytes`)
+///
+/// # Errors
+///
+/// Returns an error if the entries cannot be serialized, or the log cannot be rotated
+/// or written.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use std::time::Duration;
+/// use image_optimizer::reporting::{RunStats, append_run_log};
+///
+/// # fn example() -> anyhow::Result<()> {
+/// let stats = RunStats::default();
+/// append_run_log(Path::new("runs.log"), Path::new("images"), &stats, Duration::from_secs(1), false, None)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn append_run_log(
+    path: &Path,
+    input: &Path,
+    stats: &RunStats,
+    elapsed: Duration,
+    per_file: bool,
+    max_bytes: Option<u64>,
+) -> Result<()> {
+    let timestamp = chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, false);
+    let mut entries = String::new();
+    if per_file {
+        for result in stats.results() {
+            let entry = FileEntry {
+                timestamp: &timestamp,
+                kind: "file",
+                result: &result,
+            };
+            entries.push_str(&serde_json::to_string(&entry)?);
+            entries.push('\n');
+        }
+    }
+    let entry = RunEntry {
+        timestamp: &timestamp,
+        kind: "run",
+        version: env!("CARGO_PKG_VERSION"),
+        input,
+        elapsed_secs: elapsed.as_secs_f64(),
+        summary: stats.summary(),
+    };
+    entries.push_str(&serde_json::to_string(&entry)?);
+    entries.push('\n');
+
+    if let Some(max_bytes) = max_bytes {
+        let current = fs::metadata(path).map_or(0, |metadata| metadata.len());
+        if current > 0 && current + entries.len() as u64 > max_bytes {
+            fs::rename(path, rotated_path(path))
+                .with_context(|| format!("Failed to rotate log {}", path.display()))?;
+        }
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open log {}", path.display()))?;
+    file.write_all(entries.as_bytes())
+        .with_context(|| format!("Failed to write log {}", path.display()))
+}
+
+/// Returns where a rotated log is kept: the log's name with `.1` appended.
+fn rotated_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".1");
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimization::ImageOutcome;
+
+    fn stats() -> RunStats {
+        let stats = RunStats::default();
+        let outcome = ImageOutcome {
+            original_size: 300,
+            optimized_size: 200,
+            quality: None,
+        };
+        stats.record(FileResult::from_outcome(Path::new("a.png"), outcome));
+        stats
+    }
+
+    #[test]
+    fn test_runs_are_appended() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_log_appender_append");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        let log = temp_dir.join("runs.log");
+        let input = Path::new("images");
+
+        append_run_log(&log, input, &stats(), Duration::from_secs(2), false, None).unwrap();
+        let first_size = fs::metadata(&log).unwrap().len();
+        append_run_log(&log, input, &stats(), Duration::from_secs(1), false, None).unwrap();
+        assert!(fs::metadata(&log).unwrap().len() > first_size);
+
+        let contents = fs::read_to_string(&log).unwrap();
+        let entries: Vec<serde_json::Value> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["kind"], "run");
+        assert_eq!(entries[0]["total_saved"], 100);
+        assert_eq!(entries[0]["elapsed_secs"], 2.0);
+        assert_eq!(entries[1]["input"], "images");
+        assert!(entries[1]["timestamp"].as_str().unwrap().contains('T'));
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_per_file_entries_and_rotation() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_log_appender_rotate");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+        let log = temp_dir.join("runs.log");
+        let input = Path::new("images");
+
+        append_run_log(&log, input, &stats(), Duration::ZERO, true, Some(300)).unwrap();
+        let contents = fs::read_to_string(&log).unwrap();
+        let kinds: Vec<String> = contents
+            .lines()
+            .map(|line| {
+                serde_json::from_str::<serde_json::Value>(line).unwrap()["kind"].to_string()
+            })
+            .collect();
+        assert_eq!(kinds, ["\"file\"", "\"run\""]);
+
+        // The second run does not fit under the cap, so the first moves aside
+        append_run_log(&log, input, &stats(), Duration::ZERO, true, Some(300)).unwrap();
+        assert_eq!(
+            fs::read_to_string(temp_dir.join("runs.log.1")).unwrap(),
+            contents
+        );
+        assert_eq!(fs::read_to_string(&log).unwrap().lines().count(), 2);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
@@ -0,0 +1,43 @@
+//! Run statistics and summary output.
+//!
+//! This module collects per-file results while a batch is processed, formats the
+//! per-file `--verbose` and `--porcelain` lines, prints `--progress-interval` progress
+//! lines and the end-of-run summary with per-format totals and the run's duration and
+//! throughput, and writes the `--report-json` report. It compares a run with an earlier
+//! report for `--diff-report`, writes the `--compare-out` before/after images, and
+//! appends runs to the `--log-file` log. With `--dedup report` it lists the groups of
+//! identical inputs, and with `--notify` it signals the end of a run.
+
+pub mod caption_text;
+pub mod comparison_image;
+pub mod completion_notifier;
+pub mod duplicate_report;
+pub mod file_result;
+pub mod format_summary;
+pub mod log_appender;
+pub mod porcelain_output;
+pub mod progress_display;
+pub mod progress_log;
+pub mod report_diff;
+pub mod report_writer;
+pub mod run_stats;
+pub mod run_summary;
+pub mod run_timing;
+pub mod verbose_line;
+
+pub use caption_text::{caption_width, draw_caption};
+pub use comparison_image::{THUMBNAIL_SIZE, shrink_to_thumbnail, write_comparison};
+pub use completion_notifier::notify_completion;
+pub use duplicate_report::format_duplicate_groups;
+pub use file_result::{FileResult, FileStatus};
+pub use format_summary::FormatSummary;
+pub use log_appender::append_run_log;
+pub use porcelain_output::{format_porcelain_line, format_porcelain_summary};
+pub use progress_display::{create_progress_bar, create_scan_spinner};
+pub use progress_log::ProgressLog;
+pub use report_diff::{FileChange, ReportDiff};
+pub use report_writer::write_json_report;
+pub use run_stats::RunStats;
+pub use run_summary::RunSummary;
+pub use run_timing::RunTiming;
+pub use verbose_line::format_file_line;
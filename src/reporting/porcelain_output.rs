@@ -0,0 +1,143 @@
+use std::fmt::Write;
+use std::path::Path;
+
+use super::{FileResult, RunSummary};
+
+/// Formats the `--porcelain` line for one file.
+///
+/// Lines have five tab-separated fields in a fixed order: status (as in the JSON report,
+/// e.g. `optimized` or `skipped_larger`), path, original size, optimized size and bytes
+/// saved. Sizes are plain byte counts; a size that is not known (for a cached or failed
+/// file) is written as `-`. Tabs, newlines and backslashes in the path are escaped as
+/// `\t`, `\n` and `\\`, so every line splits into exactly five fields.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use image_optimizer::optimization::ImageOutcome;
+/// use image_optimizer::reporting::{FileResult, format_porcelain_line};
+///
+/// let outcome = ImageOutcome { original_size: 1000, optimized_size: 800, quality: None };
+/// let result = FileResult::from_outcome(Path::new("photo.jpg"), outcome);
+/// assert_eq!(format_porcelain_line(&result), "optimized\tphoto.jpg\t1000\t800\t200");
+/// ```
+#[must_use]
+pub fn format_porcelain_line(result: &FileResult) -> String {
+    fields(
+        result.status.as_str(),
+        &result.path,
+        result.original_size,
+        result.optimized_size,
+        result.saved_bytes(),
+    )
+}
+
+/// Formats the `--porcelain` summary line that ends a run.
+///
+/// It has the same five fields as [`format_porcelain_line`], with the status `total`, the
+/// input as the path, the total size of the processed files, their size after
+/// optimization and the bytes saved.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use image_optimizer::reporting::{RunSummary, format_porcelain_summary};
+///
+/// let summary = RunSummary { total_input: 3000, total_saved: 500, ..RunSummary::default() };
+/// assert_eq!(
+///     format_porcelain_summary(Path::new("images"), &summary),
+///     "total\timages\t3000\t2500\t500"
+/// );
+/// ```
+#[must_use]
+pub fn format_porcelain_summary(input: &Path, summary: &RunSummary) -> String {
+    fields(
+        "total",
+        input,
+        Some(summary.total_input),
+        Some(summary.total_input.saturating_sub(summary.total_saved)),
+        summary.total_saved,
+    )
+}
+
+fn fields(
+    status: &str,
+    path: &Path,
+    original: Option<u64>,
+    optimized: Option<u64>,
+    saved: u64,
+) -> String {
+    let mut line = String::from(status);
+    line.push('\t');
+    for c in path.to_string_lossy().chars() {
+        match c {
+            '\t' => line.push_str("\\t"),
+            '\n' => line.push_str("\\n"),
+            '\\' => line.push_str("\\\\"),
+            c => line.push(c),
+        }
+    }
+    for size in [original, optimized] {
+        match size {
+            Some(size) => {
+                let _ = write!(line, "\t{size}");
+            }
+            None => line.push_str("\t-"),
+        }
+    }
+    let _ = write!(line, "\t{saved}");
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimization::ImageOutcome;
+
+    #[test]
+    fn test_field_layout() {
+        let outcome = ImageOutcome {
+            original_size: 4096,
+            optimized_size: 5000,
+            quality: None,
+        };
+        let grown = FileResult::from_outcome(Path::new("dir/a b.png"), outcome);
+        let fields: Vec<_> = format_porcelain_line(&grown)
+            .split('\t')
+            .map(str::to_string)
+            .collect();
+        assert_eq!(
+            fields,
+            ["skipped_larger", "dir/a b.png", "4096", "5000", "0"]
+        );
+
+        let cached = FileResult::cached(Path::new("c.png"));
+        assert_eq!(format_porcelain_line(&cached), "cached\tc.png\t-\t-\t0");
+    }
+
+    #[test]
+    fn test_path_is_escaped() {
+        let cached = FileResult::cached(Path::new("tab\there\nnew\\line.png"));
+        let line = format_porcelain_line(&cached);
+        assert_eq!(line, "cached\ttab\\there\\nnew\\\\line.png\t-\t-\t0");
+        assert_eq!(line.split('\t').count(), 5);
+    }
+
+    #[test]
+    fn test_summary_layout() {
+        let summary = RunSummary::from_results(&[FileResult::from_outcome(
+            Path::new("a.png"),
+            ImageOutcome {
+                original_size: 1000,
+                optimized_size: 600,
+                quality: None,
+            },
+        )]);
+        assert_eq!(
+            format_porcelain_summary(Path::new("images"), &summary),
+            "total\timages\t1000\t600\t400"
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use anyhow::Result;
+use indicatif::{ProgressBar, ProgressStyle};
+use std::io::IsTerminal;
+
+/// Creates the progress bar shown while a batch is processed.
+///
+/// The bar is hidden with `--quiet` and whenever stdout is not a terminal, so logs
+/// and CI output do not fill up with cursor-control sequences. A hidden bar still
+/// tracks progress, and output routed through [`ProgressBar::suspend`] is printed
+/// normally.
+///
+/// # Arguments
+///
+/// * `len` - Number of files in the batch
+/// * `quiet` - Whether `--quiet` was given
+///
+/// # Errors
+///
+/// Returns an error if the progress bar template is invalid.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_optimizer::reporting::create_progress_bar;
+///
+/// # fn example() -> anyhow::Result<()> {
+/// let pb = create_progress_bar(10, true)?;
+/// assert!(pb.is_hidden());
+/// # Ok(())
+/// # }
+/// ```
+pub fn create_progress_bar(len: u64, quiet: bool) -> Result<ProgressBar> {
+    if quiet || !std::io::stdout().is_terminal() {
+        return Ok(ProgressBar::hidden());
+    }
+
+    let pb = ProgressBar::new(len);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template(
+                "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} {msg}",
+            )?
+            .progress_chars("█▉▊▋▌▍▎▏  "),
+    );
+    Ok(pb)
+}
+
+/// Creates the spinner shown while the input directory is scanned.
+///
+/// It counts the files visited so far and is meant to be cleared with
+/// [`ProgressBar::finish_and_clear`] before the batch progress bar starts. Like the
+/// progress bar, it is hidden with `--quiet` and whenever stdout is not a terminal.
+///
+/// # Arguments
+///
+/// * `hidden` - Whether the spinner should not be drawn at all
+///
+/// # Errors
+///
+/// Returns an error if the spinner template is invalid.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_optimizer::reporting::create_scan_spinner;
+///
+/// # fn example() -> anyhow::Result<()> {
+/// let spinner = create_scan_spinner(true)?;
+/// spinner.inc(1);
+/// spinner.finish_and_clear();
+/// # Ok(())
+/// # }
+/// ```
+pub fn create_scan_spinner(hidden: bool) -> Result<ProgressBar> {
+    if hidden || !std::io::stdout().is_terminal() {
+        return Ok(ProgressBar::hidden());
+    }
+
+    let spinner = ProgressBar::new_spinner();
+    spinner.set_style(
+        ProgressStyle::default_spinner().template("{spinner:.green} Scanning: {pos} files")?,
+    );
+    spinner.enable_steady_tick(std::time::Duration::from_millis(100));
+    Ok(spinner)
+}
@@ -0,0 +1,96 @@
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use crate::cli::ProgressInterval;
+use crate::file_ops::format_bytes;
+
+/// Plain-text progress lines printed at a `--progress-interval` during a batch.
+///
+/// Results arrive from rayon worker threads, so finished files are counted with an
+/// atomic counter and every count is seen exactly once: with a file interval a line is
+/// due exactly when the count reaches a multiple of it. With a time interval the time of
+/// the last line is kept behind a mutex that is only tried, never waited for; a file
+/// that finds it taken leaves the line to the next one.
+///
+/// ## Examples
+///
+/// ```rust
+/// use std::num::NonZeroUsize;
+/// use image_optimizer::cli::ProgressInterval;
+/// use image_optimizer::reporting::ProgressLog;
+///
+/// let log = ProgressLog::new(ProgressInterval::Files(NonZeroUsize::new(2).unwrap()), 3);
+/// assert_eq!(log.record(0), None);
+/// assert_eq!(log.record(2048).as_deref(), Some("2/3 processed, 2.0 KB saved"));
+/// ```
+#[derive(Debug)]
+pub struct ProgressLog {
+    interval: ProgressInterval,
+    total: usize,
+    processed: AtomicUsize,
+    last_line: Mutex<Instant>,
+}
+
+impl ProgressLog {
+    /// Creates the log for a batch of `total` files, starting the clock now.
+    #[must_use]
+    pub fn new(interval: ProgressInterval, total: usize) -> Self {
+        Self {
+            interval,
+            total,
+            processed: AtomicUsize::new(0),
+            last_line: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Counts one finished file and returns the progress line if one is due.
+    ///
+    /// `saved` is the number of bytes saved by the batch so far.
+    pub fn record(&self, saved: u64) -> Option<String> {
+        let processed = self.processed.fetch_add(1, Ordering::Relaxed) + 1;
+        let due = match self.interval {
+            ProgressInterval::Files(files) => processed.is_multiple_of(files.get()),
+            ProgressInterval::Time(interval) => self.last_line.try_lock().is_ok_and(|mut last| {
+                let due = last.elapsed() >= interval;
+                if due {
+                    *last = Instant::now();
+                }
+                due
+            }),
+        };
+        due.then(|| {
+            format!(
+                "{processed}/{} processed, {} saved",
+                self.total,
+                format_bytes(saved)
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::NonZeroUsize;
+    use std::time::Duration;
+
+    #[test]
+    fn test_line_at_every_file_interval() {
+        let log = ProgressLog::new(ProgressInterval::Files(NonZeroUsize::new(3).unwrap()), 7);
+        let lines: Vec<_> = (1..=7).filter_map(|file| log.record(file * 1024)).collect();
+        assert_eq!(
+            lines,
+            ["3/7 processed, 3.0 KB saved", "6/7 processed, 6.0 KB saved"]
+        );
+    }
+
+    #[test]
+    fn test_time_interval_waits_for_the_interval() {
+        let log = ProgressLog::new(ProgressInterval::Time(Duration::from_hours(1)), 2);
+        assert_eq!(log.record(0), None);
+
+        let log = ProgressLog::new(ProgressInterval::Time(Duration::ZERO), 2);
+        assert_eq!(log.record(0).as_deref(), Some("1/2 processed, 0 B saved"));
+    }
+}
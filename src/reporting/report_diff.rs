@@ -0,0 +1,308 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use super::{FileResult, FileStatus};
+use crate::file_ops::{format_bytes, format_delta};
+
+#[derive(Deserialize)]
+struct BaselineReport {
+    files: Vec<BaselineFile>,
+}
+
+#[derive(Deserialize)]
+struct BaselineFile {
+    path: String,
+    status: FileStatus,
+    original_size: Option<u64>,
+    optimized_size: Option<u64>,
+}
+
+/// One file whose result differs between the baseline report and the current run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    /// Path of the input file, as written in the reports
+    pub path: String,
+    /// Status of the file in the baseline report
+    pub before_status: FileStatus,
+    /// Status of the file in the current run
+    pub after_status: FileStatus,
+    /// Size the file was left at by the baseline run, if known
+    pub before_size: Option<u64>,
+    /// Size the file was left at by the current run, if known
+    pub after_size: Option<u64>,
+}
+
+impl FileChange {
+    /// Returns the signed size change from the baseline to the current run, or `None`
+    /// if either size is unknown.
+    #[must_use]
+    pub fn delta(&self) -> Option<i64> {
+        let before = i64::try_from(self.before_size?).unwrap_or(i64::MAX);
+        let after = i64::try_from(self.after_size?).unwrap_or(i64::MAX);
+        Some(after.saturating_sub(before))
+    }
+}
+
+/// The differences between a `--report-json` report of an earlier run and the results of
+/// the current run, printed by `--diff-report`.
+///
+/// Files are matched by the path written in the reports. A file counts as bigger or
+/// smaller when both runs optimized it, or kept it because optimization would have grown
+/// it, and the size it was left at changed; files whose status changed (e.g. from
+/// `optimized` to `skipped_larger`) are listed separately with both sizes. Files present
+/// in only one of the runs are listed as new or missing.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use image_optimizer::optimization::ImageOutcome;
+/// use image_optimizer::reporting::{FileResult, ReportDiff};
+///
+/// let baseline = r#"{"files": [{"path": "a.png", "status": "optimized",
+///     "original_size": 1000, "optimized_size": 800}]}"#;
+/// let outcome = ImageOutcome { original_size: 1000, optimized_size: 700, quality: None };
+/// let current = [FileResult::from_outcome(Path::new("a.png"), outcome)];
+///
+/// let diff = ReportDiff::compare(baseline, &current).unwrap();
+/// assert_eq!(diff.smaller.len(), 1);
+/// assert_eq!(diff.smaller[0].delta(), Some(-100));
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ReportDiff {
+    /// Files left larger than in the baseline run
+    pub bigger: Vec<FileChange>,
+    /// Files left smaller than in the baseline run
+    pub smaller: Vec<FileChange>,
+    /// Files whose status differs from the baseline run
+    pub status_changed: Vec<FileChange>,
+    /// Files of the current run that the baseline report does not list
+    pub new: Vec<String>,
+    /// Files of the baseline report that the current run did not process
+    pub missing: Vec<String>,
+    /// Files with the same status and size in both runs
+    pub unchanged: usize,
+}
+
+impl ReportDiff {
+    /// Reads the baseline report at `path` and compares the current results against it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the baseline cannot be read or is not a `--report-json` report.
+    pub fn load(path: &Path, current: &[FileResult]) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read baseline report {}", path.display()))?;
+        Self::compare(&contents, current)
+            .with_context(|| format!("Invalid baseline report {}", path.display()))
+    }
+
+    /// Compares the current results against the contents of a baseline report.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `baseline` is not a `--report-json` report.
+    pub fn compare(baseline: &str, current: &[FileResult]) -> Result<Self> {
+        let report: BaselineReport = serde_json::from_str(baseline)?;
+        let mut before: HashMap<String, BaselineFile> = report
+            .files
+            .into_iter()
+            .map(|file| (file.path.clone(), file))
+            .collect();
+
+        let mut diff = Self::default();
+        for result in current {
+            let path = result.path.to_string_lossy().into_owned();
+            let Some(baseline) = before.remove(&path) else {
+                diff.new.push(path);
+                continue;
+            };
+            let change = FileChange {
+                path,
+                before_status: baseline.status,
+                after_status: result.status,
+                before_size: final_size(
+                    baseline.status,
+                    baseline.original_size,
+                    baseline.optimized_size,
+                ),
+                after_size: final_size(result.status, result.original_size, result.optimized_size),
+            };
+            match change.delta() {
+                _ if change.before_status != change.after_status => {
+                    diff.status_changed.push(change);
+                }
+                Some(delta) if delta > 0 => diff.bigger.push(change),
+                Some(delta) if delta < 0 => diff.smaller.push(change),
+                _ => diff.unchanged += 1,
+            }
+        }
+        diff.missing = before.into_keys().collect();
+        diff.missing.sort();
+        diff.new.sort();
+        for changes in [
+            &mut diff.bigger,
+            &mut diff.smaller,
+            &mut diff.status_changed,
+        ] {
+            changes.sort_by(|a, b| a.path.cmp(&b.path));
+        }
+        Ok(diff)
+    }
+
+    /// Formats the differences as printed after the summary.
+    ///
+    /// The header totals the size change over the files listed as bigger or smaller;
+    /// each following section lists its files with their sizes and deltas.
+    #[must_use]
+    pub fn format(&self) -> String {
+        let net: i64 = self
+            .bigger
+            .iter()
+            .chain(&self.smaller)
+            .filter_map(FileChange::delta)
+            .sum();
+        let mut text = format!(
+            "Compared with baseline: {} bigger, {} smaller, {} changed status, {} new, {} missing, {} unchanged ({})\n",
+            self.bigger.len(),
+            self.smaller.len(),
+            self.status_changed.len(),
+            self.new.len(),
+            self.missing.len(),
+            self.unchanged,
+            format_delta(net)
+        );
+        for (title, changes) in [
+            ("Bigger", &self.bigger),
+            ("Smaller", &self.smaller),
+            ("Changed status", &self.status_changed),
+        ] {
+            if changes.is_empty() {
+                continue;
+            }
+            let _ = writeln!(text, "{title}:");
+            for change in changes {
+                let _ = writeln!(text, "  {}", format_change(change));
+            }
+        }
+        for (title, paths) in [("New", &self.new), ("Missing", &self.missing)] {
+            if paths.is_empty() {
+                continue;
+            }
+            let _ = writeln!(text, "{title}:");
+            for path in paths {
+                let _ = writeln!(text, "  {path}");
+            }
+        }
+        text
+    }
+}
+
+/// Returns the size a run left the file at: the optimized size if it was replaced, the
+/// original size if the result was discarded for being larger or saving too little.
+const fn final_size(
+    status: FileStatus,
+    original_size: Option<u64>,
+    optimized_size: Option<u64>,
+) -> Option<u64> {
+    match status {
+        FileStatus::Optimized => optimized_size,
+        FileStatus::SkippedLarger | FileStatus::SkippedThreshold => original_size,
+        _ => None,
+    }
+}
+
+fn format_change(change: &FileChange) -> String {
+    let size = |size: Option<u64>| size.map_or_else(|| "-".to_string(), format_bytes);
+    let mut line = format!(
+        "{}  {} -> {}",
+        change.path,
+        size(change.before_size),
+        size(change.after_size)
+    );
+    if change.before_status != change.after_status {
+        let _ = write!(
+            line,
+            "  {} -> {}",
+            change.before_status.as_str(),
+            change.after_status.as_str()
+        );
+    }
+    if let Some(delta) = change.delta() {
+        let _ = write!(line, "  {}", format_delta(delta));
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimization::ImageOutcome;
+
+    fn optimized(path: &str, original_size: u64, optimized_size: u64) -> FileResult {
+        let outcome = ImageOutcome {
+            original_size,
+            optimized_size,
+            quality: None,
+        };
+        FileResult::from_outcome(Path::new(path), outcome)
+    }
+
+    #[test]
+    fn test_categorizes_changes_between_runs() {
+        let baseline = r#"{
+            "version": "1.0.0",
+            "files": [
+                {"path": "bigger.jpg", "status": "optimized", "original_size": 1000, "optimized_size": 600},
+                {"path": "smaller.jpg", "status": "optimized", "original_size": 1000, "optimized_size": 800},
+                {"path": "same.png", "status": "optimized", "original_size": 500, "optimized_size": 400},
+                {"path": "flipped.webp", "status": "optimized", "original_size": 900, "optimized_size": 850},
+                {"path": "gone.png", "status": "cached"}
+            ]
+        }"#;
+        let mut kept = optimized("flipped.webp", 900, 950);
+        kept.status = FileStatus::SkippedLarger;
+        let current = [
+            optimized("bigger.jpg", 1000, 700),
+            optimized("smaller.jpg", 1000, 500),
+            optimized("same.png", 500, 400),
+            kept,
+            optimized("added.png", 300, 200),
+        ];
+
+        let diff = ReportDiff::compare(baseline, &current).unwrap();
+
+        assert_eq!(diff.bigger.len(), 1);
+        assert_eq!(diff.bigger[0].path, "bigger.jpg");
+        assert_eq!(diff.bigger[0].delta(), Some(100));
+        assert_eq!(diff.smaller.len(), 1);
+        assert_eq!(diff.smaller[0].path, "smaller.jpg");
+        assert_eq!(diff.smaller[0].delta(), Some(-300));
+        assert_eq!(diff.status_changed.len(), 1);
+        assert_eq!(
+            diff.status_changed[0].after_status,
+            FileStatus::SkippedLarger
+        );
+        assert_eq!(diff.status_changed[0].after_size, Some(900));
+        assert_eq!(diff.status_changed[0].delta(), Some(50));
+        assert_eq!(diff.new, ["added.png"]);
+        assert_eq!(diff.missing, ["gone.png"]);
+        assert_eq!(diff.unchanged, 1);
+
+        let text = diff.format();
+        assert!(text.starts_with(
+            "Compared with baseline: 1 bigger, 1 smaller, 1 changed status, 1 new, 1 missing, 1 unchanged"
+        ));
+        assert!(text.contains("flipped.webp  850 B -> 900 B  optimized -> skipped_larger  +50 B"));
+    }
+
+    #[test]
+    fn test_rejects_a_file_that_is_not_a_report() {
+        assert!(ReportDiff::compare("{\"summary\": {}}", &[]).is_err());
+        assert!(ReportDiff::compare("not json", &[]).is_err());
+    }
+}
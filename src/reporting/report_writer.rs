@@ -0,0 +1,94 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use super::{FileResult, FormatSummary, RunStats, RunSummary, RunTiming};
+use crate::optimization::ImageFormat;
+
+#[derive(Serialize)]
+struct Report {
+    version: &'static str,
+    summary: RunSummary,
+    by_format: BTreeMap<ImageFormat, FormatSummary>,
+    timing: RunTiming,
+    files: Vec<FileResult>,
+}
+
+/// Writes the machine-readable report for `--report-json`.
+///
+/// The report holds the tool version, the run totals and the totals per input format
+/// (`by_format`, keyed by `jpeg`, `png`, `webp` and `svg`), the duration and throughput of
+/// the run and one entry per file (sorted by path) with its status, sizes and, for
+/// failures, the error message.
+///
+/// # Arguments
+///
+/// * `path` - Where to write the report
+/// * `stats` - Results collected during the run
+/// * `elapsed` - Wall-clock time the batch took
+///
+/// # Errors
+///
+/// Returns an error if the report cannot be serialized or written.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use std::time::Duration;
+/// use image_optimizer::reporting::{RunStats, write_json_report};
+///
+/// # fn example() -> anyhow::Result<()> {
+/// let stats = RunStats::default();
+/// write_json_report(Path::new("report.json"), &stats, Duration::from_secs(1))?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn write_json_report(path: &Path, stats: &RunStats, elapsed: Duration) -> Result<()> {
+    let summary = stats.summary();
+    let report = Report {
+        version: env!("CARGO_PKG_VERSION"),
+        summary,
+        by_format: stats.by_format(),
+        timing: RunTiming::new(elapsed, &summary),
+        files: stats.results(),
+    };
+    let contents = serde_json::to_string_pretty(&report)?;
+    fs::write(path, contents).with_context(|| format!("Failed to write report {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimization::ImageOutcome;
+
+    #[test]
+    fn test_report_contains_summary_and_files() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_json_report");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let report_path = temp_dir.join("report.json");
+
+        let stats = RunStats::default();
+        let outcome = ImageOutcome {
+            original_size: 300,
+            optimized_size: 200,
+            quality: None,
+        };
+        stats.record(FileResult::from_outcome(Path::new("a.png"), outcome));
+        write_json_report(&report_path, &stats, Duration::from_secs(2)).unwrap();
+
+        let report: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&report_path).unwrap()).unwrap();
+        assert_eq!(report["summary"]["total_saved"], 100);
+        assert_eq!(report["summary"]["total_input"], 300);
+        assert_eq!(report["timing"]["elapsed_secs"], 2.0);
+        assert_eq!(report["timing"]["images_per_sec"], 0.5);
+        assert_eq!(report["files"][0]["status"], "optimized");
+        assert_eq!(report["files"][0]["optimized_size"], 200);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
@@ -0,0 +1,250 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use super::{FileResult, FileStatus, FormatSummary, RunSummary, RunTiming, format_file_line};
+use crate::optimization::ImageFormat;
+
+/// Per-file results collected while optimizing a batch of images.
+///
+/// Results are pushed from rayon worker threads through a shared reference, so they
+/// are kept behind a mutex. They are returned sorted by path, which keeps reports
+/// identical across runs regardless of the order the workers finished in. The bytes saved
+/// so far are also kept in an atomic counter, so the progress bar can show a running
+/// total after every file without taking the lock.
+///
+/// ## Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use image_optimizer::optimization::ImageOutcome;
+/// use image_optimizer::reporting::{FileResult, RunStats};
+///
+/// let stats = RunStats::default();
+/// let outcome = ImageOutcome { original_size: 4096, optimized_size: 2048, quality: None };
+/// stats.record(FileResult::from_outcome(Path::new("a.png"), outcome));
+/// stats.record(FileResult::cached(Path::new("b.png")));
+/// assert_eq!(stats.summary().total_saved, 2048);
+/// ```
+#[derive(Debug, Default)]
+pub struct RunStats {
+    results: Mutex<Vec<FileResult>>,
+    saved: AtomicU64,
+}
+
+impl RunStats {
+    /// Records the result of one file.
+    pub fn record(&self, result: FileResult) {
+        self.saved
+            .fetch_add(result.saved_bytes(), Ordering::Relaxed);
+        if let Ok(mut results) = self.results.lock() {
+            results.push(result);
+        }
+    }
+
+    /// Returns the bytes saved by the files recorded so far.
+    ///
+    /// Once every result is recorded this equals the `total_saved` of [`Self::summary`].
+    #[must_use]
+    pub fn saved_so_far(&self) -> u64 {
+        self.saved.load(Ordering::Relaxed)
+    }
+
+    /// Returns all recorded results, sorted by path.
+    #[must_use]
+    pub fn results(&self) -> Vec<FileResult> {
+        let mut results = self
+            .results
+            .lock()
+            .map(|results| results.clone())
+            .unwrap_or_default();
+        results.sort_by(|a, b| a.path.cmp(&b.path));
+        results
+    }
+
+    /// Returns the run totals computed from the recorded results.
+    #[must_use]
+    pub fn summary(&self) -> RunSummary {
+        self.results
+            .lock()
+            .map(|results| RunSummary::from_results(&results))
+            .unwrap_or_default()
+    }
+
+    /// Returns the run totals per input format, computed from the recorded results.
+    #[must_use]
+    pub fn by_format(&self) -> BTreeMap<ImageFormat, FormatSummary> {
+        self.results
+            .lock()
+            .map(|results| FormatSummary::by_format(&results))
+            .unwrap_or_default()
+    }
+
+    /// Returns the number of images that could not be optimized, including images that
+    /// could not be decoded.
+    #[must_use]
+    pub fn failed(&self) -> usize {
+        let summary = self.summary();
+        summary.failed + summary.invalid
+    }
+
+    /// Returns the files kept because optimization would increase their size, sorted
+    /// by path.
+    #[must_use]
+    pub fn skipped_larger(&self) -> Vec<FileResult> {
+        self.results()
+            .into_iter()
+            .filter(|result| result.status == FileStatus::SkippedLarger)
+            .collect()
+    }
+
+    /// Returns the `count` optimized files that saved the most bytes, largest first.
+    ///
+    /// Ties are ordered by path so the list is stable across runs.
+    #[must_use]
+    pub fn top_savings(&self, count: usize) -> Vec<FileResult> {
+        let mut optimized: Vec<FileResult> = self
+            .results()
+            .into_iter()
+            .filter(|result| result.status == FileStatus::Optimized)
+            .collect();
+        let by_savings = |a: &FileResult, b: &FileResult| {
+            b.saved_bytes()
+                .cmp(&a.saved_bytes())
+                .then_with(|| a.path.cmp(&b.path))
+        };
+        if count < optimized.len() {
+            optimized.select_nth_unstable_by(count, by_savings);
+            optimized.truncate(count);
+        }
+        optimized.sort_by(by_savings);
+        optimized
+    }
+
+    /// Prints the end-of-run summary to stdout, followed by one line per input format
+    /// and the duration and throughput of a batch that took `elapsed`.
+    pub fn print_summary(&self, elapsed: Duration) {
+        let summary = self.summary();
+        summary.print();
+        for (format, totals) in self.by_format() {
+            println!("{}", totals.format_line(format));
+        }
+        RunTiming::new(elapsed, &summary).print();
+    }
+
+    /// Prints the `--list-skipped` list of files that would have grown to stdout, one
+    /// `--verbose` style line per file.
+    pub fn print_skipped_larger(&self) {
+        let skipped = self.skipped_larger();
+        if skipped.is_empty() {
+            return;
+        }
+        println!("\nFiles kept because optimization would increase their size:");
+        for result in skipped {
+            if let Some(outcome) = result.outcome() {
+                println!("  {}", format_file_line(&result.path, outcome));
+            }
+        }
+    }
+
+    /// Prints the `--top` list of the files with the largest savings to stdout, one
+    /// `--verbose` style line per file showing the bytes and percentage saved.
+    pub fn print_top_savings(&self, count: usize) {
+        let top = self.top_savings(count);
+        if top.is_empty() {
+            return;
+        }
+        println!("\nTop {} savings:", top.len());
+        for result in top {
+            if let Some(outcome) = result.outcome() {
+                println!("  {}", format_file_line(&result.path, outcome));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_results_are_sorted_by_path() {
+        let stats = RunStats::default();
+        stats.record(FileResult::cached(Path::new("b.png")));
+        stats.record(FileResult::cached(Path::new("a.png")));
+
+        let paths: Vec<_> = stats.results().into_iter().map(|r| r.path).collect();
+        assert_eq!(paths, [Path::new("a.png"), Path::new("b.png")]);
+    }
+
+    #[test]
+    fn test_skipped_larger_lists_only_grown_files() {
+        let stats = RunStats::default();
+        let outcome = |optimized_size| crate::optimization::ImageOutcome {
+            original_size: 100,
+            optimized_size,
+            quality: None,
+        };
+        stats.record(FileResult::from_outcome(Path::new("b.svg"), outcome(100)));
+        stats.record(FileResult::from_outcome(Path::new("c.png"), outcome(80)));
+        stats.record(FileResult::from_outcome(Path::new("a.png"), outcome(120)));
+        stats.record(FileResult::cached(Path::new("d.png")));
+
+        let paths: Vec<_> = stats.skipped_larger().into_iter().map(|r| r.path).collect();
+        assert_eq!(paths, [Path::new("a.png"), Path::new("b.svg")]);
+    }
+
+    #[test]
+    fn test_saved_so_far_matches_summary_total() {
+        let stats = RunStats::default();
+        let outcome = |optimized_size| crate::optimization::ImageOutcome {
+            original_size: 1000,
+            optimized_size,
+            quality: None,
+        };
+        stats.record(FileResult::from_outcome(Path::new("a.png"), outcome(400)));
+        assert_eq!(stats.saved_so_far(), 600);
+        stats.record(FileResult::from_outcome(Path::new("b.png"), outcome(1200)));
+        stats.record(FileResult::from_outcome(Path::new("c.png"), outcome(900)));
+        stats.record(FileResult::cached(Path::new("d.png")));
+
+        assert_eq!(stats.saved_so_far(), 700);
+        assert_eq!(stats.saved_so_far(), stats.summary().total_saved);
+    }
+
+    #[test]
+    fn test_top_savings_are_largest_first() {
+        let stats = RunStats::default();
+        let outcome = |optimized_size| crate::optimization::ImageOutcome {
+            original_size: 1000,
+            optimized_size,
+            quality: None,
+        };
+        stats.record(FileResult::from_outcome(Path::new("a.png"), outcome(900)));
+        stats.record(FileResult::from_outcome(Path::new("b.png"), outcome(200)));
+        stats.record(FileResult::from_outcome(Path::new("c.png"), outcome(1200)));
+        stats.record(FileResult::from_outcome(Path::new("d.png"), outcome(500)));
+        stats.record(FileResult::from_outcome(Path::new("e.png"), outcome(500)));
+
+        let top: Vec<_> = stats.top_savings(3).into_iter().map(|r| r.path).collect();
+        assert_eq!(
+            top,
+            [Path::new("b.png"), Path::new("d.png"), Path::new("e.png")]
+        );
+        assert_eq!(stats.top_savings(10).len(), 4);
+    }
+
+    #[test]
+    fn test_failed_counts_invalid_images() {
+        let stats = RunStats::default();
+        let invalid = anyhow::Error::new(crate::optimization::InvalidImage::new("bad"));
+        stats.record(FileResult::from_error(Path::new("a.png"), &invalid));
+        stats.record(FileResult::from_error(
+            Path::new("b.png"),
+            &anyhow::anyhow!("io"),
+        ));
+        assert_eq!(stats.failed(), 2);
+    }
+}
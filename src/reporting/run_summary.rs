@@ -0,0 +1,175 @@
+use serde::Serialize;
+
+use super::{FileResult, FileStatus};
+use crate::file_ops::format_bytes;
+
+/// Totals for a finished run, printed at the end and included in the JSON report.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use image_optimizer::optimization::ImageOutcome;
+/// use image_optimizer::reporting::{FileResult, RunSummary};
+///
+/// let outcome = ImageOutcome { original_size: 4096, optimized_size: 2048, quality: None };
+/// let summary = RunSummary::from_results(&[FileResult::from_outcome(Path::new("a.png"), outcome)]);
+/// assert_eq!(summary.processed, 1);
+/// assert_eq!(summary.total_saved, 2048);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct RunSummary {
+    /// Files replaced by a smaller optimized version
+    pub processed: usize,
+    /// Files kept because optimization would increase their size
+    pub skipped_larger: usize,
+    /// Files kept because the result saved less than `--prefer-smaller-than-original-by`
+    pub skipped_threshold: usize,
+    /// Files not optimized because they are smaller than `--min-size`
+    pub skipped_small: usize,
+    /// Files not decoded because they exceed `--max-input-size`
+    pub skipped_too_large: usize,
+    /// Files not optimized because their existing output was kept
+    pub skipped_existing: usize,
+    /// Files skipped because the cache lists them as already optimized
+    pub cached: usize,
+    /// Files skipped because a `--mark` marker shows they were optimized with these settings
+    pub marked: usize,
+    /// Files that could not be decoded
+    pub invalid: usize,
+    /// Files that failed to optimize for other reasons
+    pub failed: usize,
+    /// Total bytes saved across all optimized files
+    pub total_saved: u64,
+    /// Total size in bytes of the files that went through the optimizer
+    pub total_input: u64,
+}
+
+impl RunSummary {
+    /// Tallies per-file results into run totals.
+    #[must_use]
+    pub fn from_results(results: &[FileResult]) -> Self {
+        let mut summary = Self::default();
+        for result in results {
+            match result.status {
+                FileStatus::Optimized => summary.processed += 1,
+                FileStatus::SkippedLarger => summary.skipped_larger += 1,
+                FileStatus::SkippedThreshold => summary.skipped_threshold += 1,
+                FileStatus::SkippedSmall => summary.skipped_small += 1,
+                FileStatus::SkippedTooLarge => summary.skipped_too_large += 1,
+                FileStatus::SkippedExisting => summary.skipped_existing += 1,
+                FileStatus::Cached => summary.cached += 1,
+                FileStatus::Marked => summary.marked += 1,
+                FileStatus::Invalid => summary.invalid += 1,
+                FileStatus::Failed => summary.failed += 1,
+            }
+            summary.total_saved += result.saved_bytes();
+            summary.total_input += result.original_size.unwrap_or(0);
+        }
+        summary
+    }
+
+    /// Returns the one-line totals shown in the `--notify` desktop notification, e.g.
+    /// `Processed 12 files, saved 3.4 MB`, followed by the failures if there were any.
+    #[must_use]
+    pub fn notification_text(&self) -> String {
+        let text = format!(
+            "Processed {} files, saved {}",
+            self.processed,
+            format_bytes(self.total_saved)
+        );
+        match self.invalid + self.failed {
+            0 => text,
+            failures => format!("{text}, {failures} failed"),
+        }
+    }
+
+    /// Prints the end-of-run summary to stdout.
+    pub fn print(&self) {
+        println!("\nProcessed {} files", self.processed);
+        if self.skipped_larger > 0 {
+            println!(
+                "Skipped {} files (optimization would increase size)",
+                self.skipped_larger
+            );
+        }
+        if self.skipped_threshold > 0 {
+            println!(
+                "Skipped {} files (saving below --prefer-smaller-than-original-by)",
+                self.skipped_threshold
+            );
+        }
+        if self.skipped_small > 0 {
+            println!(
+                "Skipped {} files (smaller than --min-size)",
+                self.skipped_small
+            );
+        }
+        if self.skipped_too_large > 0 {
+            println!(
+                "Skipped {} files (larger than --max-input-size)",
+                self.skipped_too_large
+            );
+        }
+        if self.skipped_existing > 0 {
+            println!(
+                "Skipped {} files (output already exists; --force overwrites)",
+                self.skipped_existing
+            );
+        }
+        if self.cached > 0 {
+            println!(
+                "Skipped {} files (already optimized according to the cache)",
+                self.cached
+            );
+        }
+        if self.marked > 0 {
+            println!(
+                "Skipped {} files (already optimized according to their marker)",
+                self.marked
+            );
+        }
+        if self.total_saved > 0 {
+            println!("Total space saved: {}", format_bytes(self.total_saved));
+        }
+        if self.invalid > 0 {
+            println!("{} files skipped due to decode errors", self.invalid);
+        }
+        if self.failed > 0 {
+            println!("{} files failed", self.failed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimization::ImageOutcome;
+    use std::path::Path;
+
+    #[test]
+    fn test_notification_text_matches_run_totals() {
+        let outcome = |original_size, optimized_size| ImageOutcome {
+            original_size,
+            optimized_size,
+            quality: None,
+        };
+        let results = [
+            FileResult::from_outcome(Path::new("a.png"), outcome(4096, 1024)),
+            FileResult::from_outcome(Path::new("b.png"), outcome(2048, 1024)),
+            FileResult::from_outcome(Path::new("c.svg"), outcome(100, 120)),
+            FileResult::from_error(Path::new("d.jpg"), &anyhow::anyhow!("disk full")),
+        ];
+
+        let summary = RunSummary::from_results(&results);
+
+        assert_eq!(
+            summary.notification_text(),
+            "Processed 2 files, saved 4.0 KB, 1 failed"
+        );
+        assert_eq!(
+            RunSummary::default().notification_text(),
+            "Processed 0 files, saved 0 B"
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use serde::Serialize;
+use std::time::Duration;
+
+use super::RunSummary;
+
+/// Wall-clock duration and throughput of a run, printed after the summary and included
+/// in the JSON report.
+///
+/// The elapsed time is measured around the whole batch, so the throughput reflects what
+/// the user waited for with or without `--no-parallel`. Throughput counts the files that
+/// went through the optimizer; cached files and files below `--min-size` are left out.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::time::Duration;
+/// use image_optimizer::reporting::{RunSummary, RunTiming};
+///
+/// let summary = RunSummary { processed: 4, total_input: 8 * 1024 * 1024, ..RunSummary::default() };
+/// let timing = RunTiming::new(Duration::from_secs(2), &summary);
+/// assert_eq!(timing.megabytes_per_sec, 4.0);
+/// assert_eq!(timing.images_per_sec, 2.0);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct RunTiming {
+    /// Wall-clock time of the batch in seconds
+    pub elapsed_secs: f64,
+    /// Input bytes optimized per second, in MB (1024 × 1024 bytes)
+    pub megabytes_per_sec: f64,
+    /// Images optimized per second
+    pub images_per_sec: f64,
+}
+
+impl RunTiming {
+    /// Computes the throughput of a run that took `elapsed`.
+    ///
+    /// A zero duration has no meaningful rate, so both rates are 0.0 then.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn new(elapsed: Duration, summary: &RunSummary) -> Self {
+        let elapsed_secs = elapsed.as_secs_f64();
+        let images = summary.processed
+            + summary.skipped_larger
+            + summary.skipped_threshold
+            + summary.invalid
+            + summary.failed;
+        let rate = |amount: f64| {
+            if elapsed_secs > 0.0 {
+                amount / elapsed_secs
+            } else {
+                0.0
+            }
+        };
+        Self {
+            elapsed_secs,
+            megabytes_per_sec: rate(summary.total_input as f64 / (1024.0 * 1024.0)),
+            images_per_sec: rate(images as f64),
+        }
+    }
+
+    /// Prints the elapsed time and throughput to stdout.
+    pub fn print(&self) {
+        println!(
+            "Elapsed: {:.2} s ({:.1} MB/s, {:.1} images/s)",
+            self.elapsed_secs, self.megabytes_per_sec, self.images_per_sec
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zero_duration_has_no_rate() {
+        let summary = RunSummary {
+            processed: 3,
+            total_input: 1024,
+            ..RunSummary::default()
+        };
+        let timing = RunTiming::new(Duration::ZERO, &summary);
+        assert_eq!(timing, RunTiming::default());
+    }
+
+    #[test]
+    fn test_failed_files_count_as_images() {
+        let summary = RunSummary {
+            processed: 1,
+            skipped_larger: 1,
+            failed: 2,
+            cached: 10,
+            ..RunSummary::default()
+        };
+        let timing = RunTiming::new(Duration::from_millis(500), &summary);
+        assert!((timing.images_per_sec - 8.0).abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,92 @@
+use std::path::Path;
+
+use crate::file_ops::{format_bytes, format_delta, percent_change};
+use crate::optimization::ImageOutcome;
+
+/// Formats the `--verbose` line for one processed image.
+///
+/// The line shows the path, the original and optimized sizes, the signed size change
+/// and the percentage change, e.g. `photo.jpg  97.7 KB -> 89.8 KB  -7.8 KB (-8.0%)`.
+/// With `--measure-quality` the SSIM and PSNR of the result follow. Files whose optimized
+/// result was not smaller are marked as kept, since the original was left in place.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use image_optimizer::optimization::ImageOutcome;
+/// use image_optimizer::reporting::format_file_line;
+///
+/// let outcome = ImageOutcome { original_size: 100_000, optimized_size: 92_000, quality: None };
+/// assert_eq!(
+///     format_file_line(Path::new("photo.jpg"), outcome),
+///     "photo.jpg  97.7 KB -> 89.8 KB  -7.8 KB (-8.0%)"
+/// );
+/// ```
+#[must_use]
+pub fn format_file_line(path: &Path, outcome: ImageOutcome) -> String {
+    let quality = outcome.quality.map_or_else(String::new, |quality| {
+        format!("  SSIM {:.4}, PSNR {:.1} dB", quality.ssim, quality.psnr)
+    });
+    let line = format!(
+        "{}  {} -> {}  {} ({:+.1}%){quality}",
+        path.display(),
+        format_bytes(outcome.original_size),
+        format_bytes(outcome.optimized_size),
+        format_delta(outcome.size_delta()),
+        percent_change(outcome.original_size, outcome.optimized_size)
+    );
+    if outcome.saved_bytes() > 0 {
+        line
+    } else {
+        format!("{line}, kept original")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimization::QualityScore;
+
+    #[test]
+    fn test_grown_file_is_marked_kept() {
+        let outcome = ImageOutcome {
+            original_size: 1000,
+            optimized_size: 1100,
+            quality: None,
+        };
+        assert_eq!(
+            format_file_line(Path::new("logo.png"), outcome),
+            "logo.png  1000 B -> 1.1 KB  +100 B (+10.0%), kept original"
+        );
+    }
+
+    #[test]
+    fn test_quality_follows_sizes() {
+        let outcome = ImageOutcome {
+            original_size: 2000,
+            optimized_size: 1000,
+            quality: Some(QualityScore {
+                ssim: 0.987_65,
+                psnr: 41.26,
+            }),
+        };
+        assert_eq!(
+            format_file_line(Path::new("photo.jpg"), outcome),
+            "photo.jpg  2.0 KB -> 1000 B  -1000 B (-50.0%)  SSIM 0.9877, PSNR 41.3 dB"
+        );
+    }
+
+    #[test]
+    fn test_unchanged_file() {
+        let outcome = ImageOutcome {
+            original_size: 0,
+            optimized_size: 0,
+            quality: None,
+        };
+        assert_eq!(
+            format_file_line(Path::new("empty.svg"), outcome),
+            "empty.svg  0 B -> 0 B  0 B (+0.0%), kept original"
+        );
+    }
+}
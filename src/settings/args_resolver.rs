@@ -0,0 +1,159 @@
+use anyhow::Result;
+use clap::{ArgMatches, FromArgMatches};
+use std::path::Path;
+use std::sync::Arc;
+
+use super::{SidecarConfigs, apply_config, apply_preset, find_config_file, load_config};
+use crate::cli::Cli;
+
+/// Builds the effective arguments for a run from command-line matches.
+///
+/// The configuration file is taken from `--config` or, unless `--no-config` is given,
+/// discovered by walking up from the input (or the current directory when no input
+/// is given). Settings are merged with CLI > preset > config file > built-in defaults
+/// precedence.
+///
+/// Unless `--no-config` is given, an input directory also gets a [`SidecarConfigs`]
+/// lookup, so `.image-optimizer.toml` files in its subdirectories override the settings
+/// for the files under them.
+///
+/// # Arguments
+///
+/// * `matches` - Matches produced by parsing the command line with [`Cli`]'s command
+///
+/// # Returns
+///
+/// Returns the merged `Cli` used for the rest of the run.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - The matches cannot be converted into a `Cli`
+/// - The config file cannot be read or contains invalid settings
+///
+/// # Examples
+///
+/// ```rust
+/// use clap::CommandFactory;
+/// use image_optimizer::cli::Cli;
+/// use image_optimizer::settings::resolve_args;
+///
+/// # fn example() -> anyhow::Result<()> {
+/// let matches = Cli::command().get_matches();
+/// let args = resolve_args(&matches)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn resolve_args(matches: &ArgMatches) -> Result<Cli> {
+    let mut args = Cli::from_arg_matches(matches)?;
+
+    let config_path = if args.no_config {
+        None
+    } else {
+        args.config
+            .clone()
+            .or_else(|| find_config_file(args.input.as_deref().unwrap_or_else(|| Path::new("."))))
+    };
+    if let Some(config_path) = config_path {
+        let config = load_config(&config_path)?;
+        apply_config(&mut args, config, matches);
+    }
+    if let Some(preset) = args.preset {
+        apply_preset(&mut args, preset, matches);
+    }
+    if !args.no_config
+        && let Some(input) = args.input.as_deref().filter(|input| input.is_dir())
+    {
+        let sidecars = SidecarConfigs::new(input, matches.clone());
+        args.sidecar_configs = Some(Arc::new(sidecars));
+    }
+
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+    use std::fs;
+
+    #[test]
+    fn test_discovers_config_from_input() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_resolve_discover");
+        fs::create_dir_all(temp_dir.join("images")).unwrap();
+        fs::write(
+            temp_dir.join(".image-optimizer.toml"),
+            "jpeg-quality = 70\n",
+        )
+        .unwrap();
+        let input = temp_dir.join("images");
+
+        let matches = Cli::command()
+            .try_get_matches_from(["image-optimizer", "-i", input.to_str().unwrap()])
+            .unwrap();
+        let args = resolve_args(&matches).unwrap();
+        assert_eq!(args.jpeg_quality, 70);
+
+        let matches = Cli::command()
+            .try_get_matches_from([
+                "image-optimizer",
+                "-i",
+                input.to_str().unwrap(),
+                "--no-config",
+            ])
+            .unwrap();
+        let args = resolve_args(&matches).unwrap();
+        assert_eq!(args.jpeg_quality, 85);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_preset_overrides_config() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_resolve_preset");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let config_path = temp_dir.join("team.toml");
+        fs::write(&config_path, "jpeg-quality = 60\nrecursive = true\n").unwrap();
+
+        let matches = Cli::command()
+            .try_get_matches_from([
+                "image-optimizer",
+                "--config",
+                config_path.to_str().unwrap(),
+                "--preset",
+                "thumbnail",
+            ])
+            .unwrap();
+        let args = resolve_args(&matches).unwrap();
+
+        assert_eq!(args.jpeg_quality, 70);
+        assert_eq!(args.max_size, Some(400));
+        assert!(args.recursive);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_explicit_config_path() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_resolve_explicit");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let config_path = temp_dir.join("team.toml");
+        fs::write(&config_path, "recursive = true\njpeg-quality = 60\n").unwrap();
+
+        let matches = Cli::command()
+            .try_get_matches_from([
+                "image-optimizer",
+                "--config",
+                config_path.to_str().unwrap(),
+                "--jpeg-quality",
+                "95",
+            ])
+            .unwrap();
+        let args = resolve_args(&matches).unwrap();
+
+        assert!(args.recursive);
+        assert_eq!(args.jpeg_quality, 95);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
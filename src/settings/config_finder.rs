@@ -0,0 +1,88 @@
+use std::path::{Path, PathBuf};
+
+/// File name looked up by [`find_config_file`].
+pub const CONFIG_FILE_NAME: &str = ".image-optimizer.toml";
+
+/// Searches for an `.image-optimizer.toml` file starting at the input and walking up.
+///
+/// The search starts in the input directory (or the directory containing the input
+/// file) and continues through each parent directory up to the filesystem root, so a
+/// config committed at the repository root applies to every subdirectory.
+///
+/// # Arguments
+///
+/// * `start` - Input file or directory the search starts from
+///
+/// # Returns
+///
+/// Returns the path of the nearest configuration file, or `None` if no directory up
+/// to the root contains one.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use image_optimizer::settings::find_config_file;
+///
+/// if let Some(config_path) = find_config_file(Path::new("./images")) {
+///     println!("Using config {}", config_path.display());
+/// }
+/// ```
+#[must_use]
+pub fn find_config_file(start: &Path) -> Option<PathBuf> {
+    let start = std::path::absolute(start).ok()?;
+    let first_dir = if start.is_file() {
+        start.parent()?
+    } else {
+        start.as_path()
+    };
+
+    first_dir
+        .ancestors()
+        .map(|dir| dir.join(CONFIG_FILE_NAME))
+        .find(|candidate| candidate.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_finds_config_in_parent_directory() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_config_find_parent");
+        let nested = temp_dir.join("assets").join("images");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(temp_dir.join(CONFIG_FILE_NAME), "").unwrap();
+
+        assert_eq!(
+            find_config_file(&nested),
+            Some(temp_dir.join(CONFIG_FILE_NAME))
+        );
+
+        let image = nested.join("photo.jpg");
+        fs::write(&image, "fake").unwrap();
+        assert_eq!(
+            find_config_file(&image),
+            Some(temp_dir.join(CONFIG_FILE_NAME))
+        );
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_prefers_nearest_config() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_config_find_nearest");
+        let nested = temp_dir.join("images");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(temp_dir.join(CONFIG_FILE_NAME), "").unwrap();
+        fs::write(nested.join(CONFIG_FILE_NAME), "").unwrap();
+
+        assert_eq!(
+            find_config_file(&nested),
+            Some(nested.join(CONFIG_FILE_NAME))
+        );
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
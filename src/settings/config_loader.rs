@@ -0,0 +1,111 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+
+use super::FileConfig;
+
+/// Reads and parses a TOML configuration file.
+///
+/// Relative `backup-dir` values are resolved against the directory containing the
+/// configuration file, so a committed config behaves the same regardless of the
+/// directory the optimizer is started from.
+///
+/// # Arguments
+///
+/// * `path` - Path to the TOML configuration file
+///
+/// # Returns
+///
+/// Returns the parsed [`FileConfig`].
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - The file cannot be read
+/// - The file is not valid TOML or contains unknown keys or invalid values
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use image_optimizer::settings::load_config;
+///
+/// # fn example() -> anyhow::Result<()> {
+/// let config = load_config(Path::new(".image-optimizer.toml"))?;
+/// println!("JPEG quality: {:?}", config.jpeg_quality);
+/// # Ok(())
+/// # }
+/// ```
+pub fn load_config(path: &Path) -> Result<FileConfig> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file {}", path.display()))?;
+    let mut config: FileConfig = toml::from_str(&contents)
+        .with_context(|| format!("Invalid config file {}", path.display()))?;
+
+    if let (Some(backup_dir), Some(config_dir)) = (&config.backup_dir, path.parent())
+        && backup_dir.is_relative()
+    {
+        config.backup_dir = Some(config_dir.join(backup_dir));
+    }
+
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::SortOrder;
+    use std::fs;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_loads_kebab_case_keys() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_config_load");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let path = temp_dir.join(".image-optimizer.toml");
+        fs::write(
+            &path,
+            "jpeg-quality = 80\nrecursive = true\nsort = \"size\"\nexclude = [\"node_modules\"]\n",
+        )
+        .unwrap();
+
+        let config = load_config(&path).unwrap();
+
+        assert_eq!(config.jpeg_quality, Some(80));
+        assert_eq!(config.recursive, Some(true));
+        assert_eq!(config.sort, Some(SortOrder::Size));
+        assert_eq!(config.exclude, Some(vec!["node_modules".to_string()]));
+        assert_eq!(config.max_size, None);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_unknown_keys() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_config_unknown");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let path = temp_dir.join(".image-optimizer.toml");
+        fs::write(&path, "jpeg-qualty = 80\n").unwrap();
+
+        assert!(load_config(&path).is_err());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_resolves_backup_dir_relative_to_config() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_config_backup_dir");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let path = temp_dir.join(".image-optimizer.toml");
+        fs::write(&path, "backup-dir = \"backups\"\n").unwrap();
+
+        let config = load_config(&path).unwrap();
+
+        assert_eq!(config.backup_dir, Some(temp_dir.join("backups")));
+
+        fs::write(&path, "backup-dir = \"/var/backups\"\n").unwrap();
+        let config = load_config(&path).unwrap();
+        assert_eq!(config.backup_dir, Some(PathBuf::from("/var/backups")));
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
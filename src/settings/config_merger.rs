@@ -0,0 +1,220 @@
+use clap::ArgMatches;
+use clap::parser::ValueSource;
+
+use super::FileConfig;
+use crate::cli::Cli;
+
+/// Applies configuration file (or preset) settings to parsed command-line arguments.
+///
+/// Precedence is CLI > config file > built-in defaults: a config value only replaces
+/// a field whose value did not come from the command line (or an environment
+/// variable). The resulting `Cli` is the merged configuration used for the rest of
+/// the run, so validation must happen after this call.
+///
+/// # Arguments
+///
+/// * `args` - Arguments parsed from `matches`, updated in place
+/// * `config` - Settings loaded from the configuration file
+/// * `matches` - Raw clap matches used to tell explicit flags apart from defaults
+///
+/// # Examples
+///
+/// ```rust
+/// use clap::{CommandFactory, FromArgMatches};
+/// use image_optimizer::cli::Cli;
+/// use image_optimizer::settings::{FileConfig, apply_config};
+///
+/// # fn example() -> anyhow::Result<()> {
+/// let matches = Cli::command().try_get_matches_from(["image-optimizer", "-i", "images"])?;
+/// let mut args = Cli::from_arg_matches(&matches)?;
+/// let config = FileConfig { jpeg_quality: Some(80), ..FileConfig::default() };
+/// apply_config(&mut args, config, &matches);
+/// assert_eq!(args.jpeg_quality, 80);
+/// # Ok(())
+/// # }
+/// ```
+pub fn apply_config(args: &mut Cli, config: FileConfig, matches: &ArgMatches) {
+    let from_cli = |id: &str| {
+        matches!(
+            matches.value_source(id),
+            Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+        )
+    };
+
+    macro_rules! merge {
+        ($($field:ident),+ $(,)?) => {
+            $(
+                if let Some(value) = config.$field
+                    && !from_cli(stringify!($field))
+                {
+                    args.$field = value;
+                }
+            )+
+        };
+    }
+
+    macro_rules! merge_optional {
+        ($($field:ident),+ $(,)?) => {
+            $(
+                if let Some(value) = config.$field
+                    && !from_cli(stringify!($field))
+                {
+                    args.$field = Some(value);
+                }
+            )+
+        };
+    }
+
+    merge!(
+        skip_existing,
+        force,
+        backup,
+        backup_mode,
+        on_larger,
+        prefer_smaller_than_original_by,
+        webp_lossless,
+        webp_method,
+        strip_only,
+        jpeg_lossless,
+        jpeg_quality,
+        no_upscale_quality,
+        jpeg_trellis,
+        jpeg_optimize_coding,
+        keep_icc,
+        to_srgb,
+        recursive,
+        follow_symlinks,
+        include,
+        exclude,
+        exclude_dir,
+        respect_gitignore,
+        strict_scan,
+        sort,
+        png_optimization_level,
+        zopfli_iterations,
+        no_zopfli,
+        deflate_level,
+        svg_precision,
+        svgz_level,
+        no_parallel,
+        no_preserve_timestamps,
+        mark,
+        verify,
+        retry,
+    );
+    merge_optional!(
+        format,
+        backup_dir,
+        keep_larger_as,
+        max_size,
+        webp_near_lossless,
+        max_depth,
+        max_files,
+        memory_limit,
+        dedup
+    );
+
+    if let Some(quality) = config.quality
+        && !from_cli("quality")
+    {
+        args.quality = quality.into_iter().collect();
+    }
+    if let Some(concurrency) = config.concurrency_per_format
+        && !from_cli("concurrency_per_format")
+    {
+        args.concurrency_per_format = concurrency.into_iter().collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::SortOrder;
+    use crate::optimization::ImageFormat;
+    use clap::{CommandFactory, FromArgMatches};
+    use std::path::PathBuf;
+
+    fn merged(cli_args: &[&str], config: FileConfig) -> Cli {
+        let matches = Cli::command().try_get_matches_from(cli_args).unwrap();
+        let mut args = Cli::from_arg_matches(&matches).unwrap();
+        apply_config(&mut args, config, &matches);
+        args
+    }
+
+    #[test]
+    fn test_config_overrides_defaults() {
+        let config = FileConfig {
+            jpeg_quality: Some(70),
+            recursive: Some(true),
+            sort: Some(SortOrder::Mtime),
+            max_size: Some(2048),
+            exclude: Some(vec!["node_modules".to_string()]),
+            backup_dir: Some(PathBuf::from("/backups")),
+            ..FileConfig::default()
+        };
+
+        let args = merged(&["image-optimizer", "-i", "images"], config);
+
+        assert_eq!(args.jpeg_quality, 70);
+        assert!(args.recursive);
+        assert_eq!(args.sort, SortOrder::Mtime);
+        assert_eq!(args.max_size, Some(2048));
+        assert_eq!(args.exclude, vec!["node_modules"]);
+        assert_eq!(args.backup_dir, Some(PathBuf::from("/backups")));
+    }
+
+    #[test]
+    fn test_cli_flags_override_config() {
+        let config = FileConfig {
+            jpeg_quality: Some(70),
+            max_size: Some(2048),
+            exclude: Some(vec!["node_modules".to_string()]),
+            no_preserve_timestamps: Some(false),
+            ..FileConfig::default()
+        };
+
+        let args = merged(
+            &[
+                "image-optimizer",
+                "--jpeg-quality",
+                "90",
+                "--max-size",
+                "512",
+                "--exclude",
+                "vendor",
+                "--no-preserve-timestamps",
+            ],
+            config,
+        );
+
+        assert_eq!(args.jpeg_quality, 90);
+        assert_eq!(args.max_size, Some(512));
+        assert_eq!(args.exclude, vec!["vendor"]);
+        assert!(args.no_preserve_timestamps);
+    }
+
+    #[test]
+    fn test_per_format_quality_from_config() {
+        let config = FileConfig {
+            quality: Some(std::collections::BTreeMap::from([(ImageFormat::Webp, 70)])),
+            ..FileConfig::default()
+        };
+        let args = merged(&["image-optimizer"], config.clone());
+        assert_eq!(args.quality_for(ImageFormat::Webp), 70);
+        assert_eq!(args.quality_for(ImageFormat::Jpeg), 85);
+
+        let args = merged(&["image-optimizer", "--quality", "jpeg=90"], config);
+        assert_eq!(args.quality_for(ImageFormat::Webp), 85);
+        assert_eq!(args.quality_for(ImageFormat::Jpeg), 90);
+    }
+
+    #[test]
+    fn test_empty_config_keeps_defaults() {
+        let args = merged(&["image-optimizer"], FileConfig::default());
+
+        assert_eq!(args.jpeg_quality, 85);
+        assert_eq!(args.png_optimization_level.get(), 2);
+        assert!(!args.recursive);
+        assert_eq!(args.max_size, None);
+    }
+}
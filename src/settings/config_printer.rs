@@ -0,0 +1,138 @@
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+
+use super::FileConfig;
+use crate::cli::Cli;
+
+/// Formats the effective settings of a run as TOML for `--print-config`.
+///
+/// The settings are taken after the command line, preset and configuration file have
+/// been merged, so the output shows which value won. Every setting a configuration file
+/// can hold is listed, using the same kebab-case keys; settings without a value (such
+/// as an unset `max-size`) are left out. The output can be saved as an
+/// `.image-optimizer.toml` file to reproduce the run.
+///
+/// # Arguments
+///
+/// * `args` - Effective CLI configuration for the run
+///
+/// # Errors
+///
+/// Returns an error if the settings cannot be serialized.
+///
+/// # Examples
+///
+/// ```rust
+/// use clap::Parser;
+/// use image_optimizer::cli::Cli;
+/// use image_optimizer::settings::format_effective_config;
+///
+/// # fn example() -> anyhow::Result<()> {
+/// let args = Cli::parse_from(["image-optimizer", "--jpeg-quality", "80"]);
+/// assert!(format_effective_config(&args)?.contains("jpeg-quality = 80"));
+/// # Ok(())
+/// # }
+/// ```
+pub fn format_effective_config(args: &Cli) -> Result<String> {
+    // Later --quality entries for a format win, as in Cli::quality_for
+    let quality: BTreeMap<_, _> = args.quality.iter().copied().collect();
+    let concurrency: BTreeMap<_, _> = args.concurrency_per_format.iter().copied().collect();
+    let config = FileConfig {
+        format: args.format,
+        skip_existing: Some(args.skip_existing),
+        force: Some(args.force),
+        backup: Some(args.backup),
+        backup_dir: args.backup_dir.clone(),
+        backup_mode: Some(args.backup_mode),
+        on_larger: Some(args.on_larger),
+        keep_larger_as: args.keep_larger_as.clone(),
+        prefer_smaller_than_original_by: Some(args.prefer_smaller_than_original_by),
+        webp_lossless: Some(args.webp_lossless),
+        webp_near_lossless: args.webp_near_lossless,
+        webp_method: Some(args.webp_method),
+        strip_only: Some(args.strip_only),
+        jpeg_lossless: Some(args.jpeg_lossless),
+        jpeg_quality: Some(args.jpeg_quality),
+        quality: (!quality.is_empty()).then_some(quality),
+        no_upscale_quality: Some(args.no_upscale_quality),
+        jpeg_trellis: Some(args.jpeg_trellis),
+        jpeg_optimize_coding: Some(args.jpeg_optimize_coding),
+        keep_icc: Some(args.keep_icc),
+        to_srgb: Some(args.to_srgb),
+        recursive: Some(args.recursive),
+        max_depth: args.max_depth,
+        max_files: args.max_files,
+        follow_symlinks: Some(args.follow_symlinks),
+        include: Some(args.include.clone()),
+        exclude: Some(args.exclude.clone()),
+        exclude_dir: Some(args.exclude_dir.clone()),
+        respect_gitignore: Some(args.respect_gitignore),
+        strict_scan: Some(args.strict_scan),
+        sort: Some(args.sort),
+        dedup: args.dedup,
+        mark: Some(args.mark),
+        max_size: args.max_size,
+        png_optimization_level: Some(args.png_optimization_level),
+        zopfli_iterations: Some(args.zopfli_iterations),
+        no_zopfli: Some(args.no_zopfli),
+        deflate_level: Some(args.deflate_level),
+        svg_precision: Some(args.svg_precision),
+        svgz_level: Some(args.svgz_level),
+        no_parallel: Some(args.no_parallel),
+        concurrency_per_format: (!concurrency.is_empty()).then_some(concurrency),
+        memory_limit: args.memory_limit,
+        no_preserve_timestamps: Some(args.no_preserve_timestamps),
+        verify: Some(args.verify),
+        retry: Some(args.retry),
+    };
+    toml::to_string(&config).context("Failed to format the effective settings")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::resolve_args;
+    use clap::CommandFactory;
+    use std::fs;
+
+    #[test]
+    fn test_cli_override_is_printed() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_print_config");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let config_path = temp_dir.join("team.toml");
+        fs::write(&config_path, "jpeg-quality = 60\nrecursive = true\n").unwrap();
+
+        let matches = Cli::command()
+            .try_get_matches_from([
+                "image-optimizer",
+                "--config",
+                config_path.to_str().unwrap(),
+                "--jpeg-quality",
+                "95",
+                "--quality",
+                "webp=70",
+                "--exclude",
+                "**/thumbs",
+            ])
+            .unwrap();
+        let printed = format_effective_config(&resolve_args(&matches).unwrap()).unwrap();
+
+        assert!(printed.contains("jpeg-quality = 95"), "{printed}");
+        assert!(printed.contains("recursive = true"), "{printed}");
+        assert!(printed.contains(r#"exclude = ["**/thumbs"]"#), "{printed}");
+        assert!(!printed.contains("max-size"), "{printed}");
+
+        // The dump is a valid configuration file with the same settings
+        let reloaded: FileConfig = toml::from_str(&printed).unwrap();
+        assert_eq!(reloaded.jpeg_quality, Some(95));
+        assert_eq!(
+            reloaded.quality,
+            Some(BTreeMap::from([(
+                crate::optimization::ImageFormat::Webp,
+                70
+            )]))
+        );
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::num::{NonZeroU8, NonZeroU64, NonZeroUsize};
+use std::path::PathBuf;
+
+use crate::cli::{BackupMode, DedupMode, LargerPolicy, PngLevel, SortOrder, Toggle};
+use crate::optimization::ImageFormat;
+
+/// Settings read from an `.image-optimizer.toml` configuration file.
+///
+/// Keys use the same kebab-case names as the corresponding command-line flags, so
+/// `--jpeg-quality 80` becomes `jpeg-quality = 80`. Every key is optional; keys that are
+/// absent leave the built-in default in place. Unknown keys are rejected so that typos
+/// do not silently fall back to defaults.
+///
+/// [`format_effective_config`](super::format_effective_config) writes the settings of a
+/// run in the same format for `--print-config`.
+///
+/// Only settings that make sense to share across invocations are supported. Per-run
+/// options such as `--input`, `--output` or `--stdin` must be given on the command line.
+///
+/// ## Example
+///
+/// ```toml
+/// jpeg-quality = 80
+/// max-size = 2048
+/// recursive = true
+/// exclude = ["node_modules", "**/thumbnails"]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct FileConfig {
+    pub format: Option<ImageFormat>,
+    pub skip_existing: Option<bool>,
+    pub force: Option<bool>,
+    pub backup: Option<bool>,
+    /// Relative paths are resolved against the directory containing the config file
+    pub backup_dir: Option<PathBuf>,
+    pub backup_mode: Option<BackupMode>,
+    pub on_larger: Option<LargerPolicy>,
+    pub keep_larger_as: Option<String>,
+    pub prefer_smaller_than_original_by: Option<u8>,
+    pub webp_lossless: Option<bool>,
+    pub webp_near_lossless: Option<u8>,
+    pub webp_method: Option<u8>,
+    pub strip_only: Option<bool>,
+    pub jpeg_lossless: Option<bool>,
+    pub jpeg_quality: Option<u8>,
+    /// Per-format quality, e.g. `quality = { webp = 75 }`
+    pub quality: Option<BTreeMap<ImageFormat, u8>>,
+    pub no_upscale_quality: Option<bool>,
+    pub jpeg_trellis: Option<Toggle>,
+    pub jpeg_optimize_coding: Option<Toggle>,
+    pub keep_icc: Option<bool>,
+    pub to_srgb: Option<bool>,
+    pub recursive: Option<bool>,
+    pub max_depth: Option<NonZeroUsize>,
+    pub max_files: Option<NonZeroUsize>,
+    pub follow_symlinks: Option<bool>,
+    pub include: Option<Vec<String>>,
+    pub exclude: Option<Vec<String>>,
+    pub exclude_dir: Option<Vec<String>>,
+    pub respect_gitignore: Option<bool>,
+    pub strict_scan: Option<bool>,
+    pub sort: Option<SortOrder>,
+    pub dedup: Option<DedupMode>,
+    pub mark: Option<bool>,
+    pub max_size: Option<u32>,
+    pub png_optimization_level: Option<PngLevel>,
+    pub zopfli_iterations: Option<NonZeroU8>,
+    pub no_zopfli: Option<bool>,
+    pub deflate_level: Option<u8>,
+    pub svg_precision: Option<u8>,
+    pub svgz_level: Option<u32>,
+    pub no_parallel: Option<bool>,
+    /// Per-format parallelism, e.g. `concurrency-per-format = { png = 2 }`
+    pub concurrency_per_format: Option<BTreeMap<ImageFormat, NonZeroUsize>>,
+    pub memory_limit: Option<NonZeroU64>,
+    pub no_preserve_timestamps: Option<bool>,
+    pub verify: Option<bool>,
+    pub retry: Option<u8>,
+}
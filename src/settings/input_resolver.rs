@@ -0,0 +1,345 @@
+use clap::CommandFactory;
+use clap::error::ErrorKind;
+use std::fs;
+use std::path::Path;
+
+use crate::cli::{Cli, parse_name_suffix};
+use crate::file_ops::{is_same_file, output_within_input};
+use crate::optimization::ImageFormat;
+
+/// Validates the batch-mode arguments and returns the input base directory.
+///
+/// The base is `--input` when given. With only `--files-from`, listed paths are mirrored
+/// into `--output` relative to the current directory, so the base is the empty path.
+///
+/// # Arguments
+///
+/// * `args` - Effective CLI configuration after config and preset merging
+///
+/// # Returns
+///
+/// Returns the path that discovered or listed images are relative to.
+///
+/// # Errors
+///
+/// Returns a clap usage error (exit code 2 when passed to [`clap::Error::exit`]) if:
+/// - Neither `--input` nor `--files-from` is given
+/// - The JPEG quality or a per-format quality is out of range (1-100)
+/// - A per-format quality is set for a format without a quality setting
+/// - A WebP quality is set together with lossless WebP encoding, which ignores it
+/// - A `deflate-level` from a config file is outside 1-12
+/// - A `keep_larger_as` suffix from a config file is empty or contains a path separator
+/// - A `prefer-smaller-than-original-by` percentage from a config file is above 99
+/// - The input file or directory does not exist
+/// - `--output` is the input directory itself, or the directory of the input file
+/// - `--output-file` is given with an input directory, or names the input file itself
+/// - `--watch` is given without an input directory
+///
+/// # Examples
+///
+/// ```rust
+/// use clap::Parser;
+/// use image_optimizer::cli::Cli;
+/// use image_optimizer::settings::resolve_input;
+///
+/// let args = Cli::parse_from(["image-optimizer", "--files-from", "list.txt"]);
+/// assert_eq!(resolve_input(&args).unwrap().as_os_str(), "");
+/// ```
+pub fn resolve_input(args: &Cli) -> Result<&Path, clap::Error> {
+    let input = match (&args.input, &args.files_from) {
+        (Some(input), _) => input.as_path(),
+        (None, Some(_)) => Path::new(""),
+        (None, None) => {
+            return Err(Cli::command().error(
+                ErrorKind::MissingRequiredArgument,
+                "Input file or directory is required",
+            ));
+        }
+    };
+
+    if args.jpeg_quality == 0 || args.jpeg_quality > 100 {
+        return Err(Cli::command().error(
+            ErrorKind::ValueValidation,
+            "Quality must be between 1 and 100",
+        ));
+    }
+    // Entries from --quality are checked while parsing; these came from a config file
+    for &(format, quality) in &args.quality {
+        if !format.supports_quality() || quality == 0 || quality > 100 {
+            return Err(Cli::command().error(
+                ErrorKind::ValueValidation,
+                format!("Invalid quality {quality} for {}", format.extension()),
+            ));
+        }
+    }
+
+    check_config_values(args)?;
+
+    // Flag-level contradictions are rejected by clap; this one depends on the format
+    if (args.webp_lossless || args.webp_near_lossless.is_some())
+        && let Some(&(_, quality)) = args
+            .quality
+            .iter()
+            .find(|(format, _)| *format == ImageFormat::Webp)
+    {
+        return Err(Cli::command().error(
+            ErrorKind::ArgumentConflict,
+            format!(
+                "--quality webp={quality} has no effect with lossless WebP encoding; drop it \
+                 or --webp-lossless/--webp-near-lossless"
+            ),
+        ));
+    }
+
+    if args.input.is_some() && !input.exists() {
+        return Err(Cli::command().error(
+            ErrorKind::InvalidValue,
+            "Input file or directory does not exist",
+        ));
+    }
+
+    if args.watch && !input.is_dir() {
+        return Err(Cli::command().error(
+            ErrorKind::InvalidValue,
+            "--watch requires --input to be a directory",
+        ));
+    }
+
+    if let Some(ref output_file) = args.output_file {
+        if input.is_dir() {
+            return Err(Cli::command().error(
+                ErrorKind::ArgumentConflict,
+                "--output-file needs a single input file; use -o/--output for a directory",
+            ));
+        }
+        if fs::canonicalize(input).ok() == fs::canonicalize(output_file).ok() {
+            return Err(Cli::command().error(
+                ErrorKind::ArgumentConflict,
+                "--output-file is the input file itself; omit it to optimize in place",
+            ));
+        }
+    }
+
+    if let Some(ref output) = args.output
+        && input.is_dir()
+        && output_within_input(input, output)
+            .is_some_and(|relative| relative.as_os_str().is_empty())
+    {
+        return Err(Cli::command().error(
+            ErrorKind::ArgumentConflict,
+            "--output is the same directory as --input; omit -o/--output to optimize in place",
+        ));
+    }
+
+    // Options that rename the output are left to the per-file check in optimize_image
+    if let Some(ref output) = args.output
+        && input.is_file()
+        && args.name_template.is_none()
+        && args.organize_by_date.is_none()
+        && args.format.is_none()
+        && args.output_ext_normalize.is_none()
+        && args.sizes.is_empty()
+        && !args.svgz
+        && input
+            .file_name()
+            .is_some_and(|name| is_same_file(&output.join(name), input))
+    {
+        return Err(Cli::command().error(
+            ErrorKind::ArgumentConflict,
+            "--output is the directory of the --input file; omit -o/--output to optimize in place",
+        ));
+    }
+
+    Ok(input)
+}
+
+/// Checks the settings that clap validates while parsing, for values that came from a
+/// config file instead.
+fn check_config_values(args: &Cli) -> Result<(), clap::Error> {
+    if !(1..=12).contains(&args.deflate_level) {
+        return Err(Cli::command().error(
+            ErrorKind::ValueValidation,
+            format!(
+                "Invalid deflate-level {}: expected 1-12",
+                args.deflate_level
+            ),
+        ));
+    }
+
+    if let Some(ref suffix) = args.keep_larger_as
+        && let Err(message) = parse_name_suffix(suffix)
+    {
+        return Err(Cli::command().error(
+            ErrorKind::ValueValidation,
+            format!("Invalid keep_larger_as: {message}"),
+        ));
+    }
+
+    if args.retry > 10 {
+        return Err(Cli::command().error(
+            ErrorKind::ValueValidation,
+            format!("Invalid retry {}: expected 0-10", args.retry),
+        ));
+    }
+
+    if args.prefer_smaller_than_original_by > 99 {
+        return Err(Cli::command().error(
+            ErrorKind::ValueValidation,
+            format!(
+                "Invalid prefer-smaller-than-original-by {}: expected 0-99",
+                args.prefer_smaller_than_original_by
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn test_missing_input_is_usage_error() {
+        let args = Cli::parse_from(["image-optimizer"]);
+        let error = resolve_input(&args).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::MissingRequiredArgument);
+        assert_eq!(error.exit_code(), 2);
+    }
+
+    #[test]
+    fn test_output_same_as_input_is_rejected() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_resolve_same_output");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let input = temp_dir.to_str().unwrap();
+        let output = temp_dir.join(".").to_str().unwrap().to_string();
+
+        let args = Cli::parse_from(["image-optimizer", "-i", input, "-o", &output]);
+        let error = resolve_input(&args).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::ArgumentConflict);
+        assert!(error.to_string().contains("in place"));
+
+        let nested = temp_dir.join("out").to_str().unwrap().to_string();
+        let args = Cli::parse_from(["image-optimizer", "-i", input, "-o", &nested]);
+        assert!(resolve_input(&args).is_ok());
+
+        // A single input file written to its own directory
+        let file = temp_dir.join("a.png");
+        std::fs::write(&file, b"png").unwrap();
+        let file = file.to_str().unwrap();
+        let args = Cli::parse_from(["image-optimizer", "-i", file, "-o", &output, "--force"]);
+        let error = resolve_input(&args).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::ArgumentConflict);
+        let args = Cli::parse_from(["image-optimizer", "-i", file, "-o", &nested]);
+        assert!(resolve_input(&args).is_ok());
+
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_quality_out_of_range() {
+        let args = Cli::parse_from(["image-optimizer", "-i", ".", "--jpeg-quality", "101"]);
+        assert_eq!(
+            resolve_input(&args).unwrap_err().kind(),
+            ErrorKind::ValueValidation
+        );
+    }
+
+    #[test]
+    fn test_config_quality_out_of_range() {
+        let mut args = Cli::parse_from(["image-optimizer", "-i", "."]);
+        args.quality = vec![(ImageFormat::Webp, 0)];
+        assert_eq!(
+            resolve_input(&args).unwrap_err().kind(),
+            ErrorKind::ValueValidation
+        );
+
+        args.quality = vec![(ImageFormat::Png, 80)];
+        assert!(resolve_input(&args).is_err());
+    }
+
+    #[test]
+    fn test_config_values_checked_while_parsing_are_validated() {
+        let mut args = Cli::parse_from(["image-optimizer", "-i", "."]);
+        args.keep_larger_as = Some("../larger".to_string());
+        assert_eq!(
+            resolve_input(&args).unwrap_err().kind(),
+            ErrorKind::ValueValidation
+        );
+
+        args.keep_larger_as = None;
+        args.deflate_level = 13;
+        assert_eq!(
+            resolve_input(&args).unwrap_err().kind(),
+            ErrorKind::ValueValidation
+        );
+
+        args.deflate_level = 12;
+        args.prefer_smaller_than_original_by = 100;
+        assert_eq!(
+            resolve_input(&args).unwrap_err().kind(),
+            ErrorKind::ValueValidation
+        );
+    }
+
+    #[test]
+    fn test_webp_quality_conflicts_with_lossless() {
+        for lossless in [&["--webp-lossless"][..], &["--webp-near-lossless", "60"]] {
+            let args = Cli::parse_from(
+                ["image-optimizer", "-i", ".", "--quality", "webp=75"]
+                    .into_iter()
+                    .chain(lossless.iter().copied()),
+            );
+            let error = resolve_input(&args).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::ArgumentConflict);
+            assert!(error.to_string().contains("webp=75"), "{error}");
+        }
+
+        // Lossless WebP still leaves the JPEG quality in effect
+        let args = Cli::parse_from([
+            "image-optimizer",
+            "-i",
+            ".",
+            "--webp-lossless",
+            "--quality",
+            "jpeg=75",
+        ]);
+        assert!(resolve_input(&args).is_ok());
+    }
+
+    #[test]
+    fn test_output_file_needs_a_single_input_file() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_output_file_input");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let input = temp_dir.join("a.png");
+        fs::write(&input, b"png").unwrap();
+        let dir = temp_dir.to_str().unwrap();
+        let file = input.to_str().unwrap();
+
+        for (input, output_file) in [(dir, "b.png"), (file, file)] {
+            let args =
+                Cli::parse_from(["image-optimizer", "-i", input, "--output-file", output_file]);
+            assert_eq!(
+                resolve_input(&args).unwrap_err().kind(),
+                ErrorKind::ArgumentConflict
+            );
+        }
+        let args = Cli::parse_from(["image-optimizer", "-i", file, "--output-file", "b.png"]);
+        assert!(resolve_input(&args).is_ok());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_nonexistent_input() {
+        let args = Cli::parse_from(["image-optimizer", "-i", "/nonexistent/path"]);
+        assert_eq!(
+            resolve_input(&args).unwrap_err().kind(),
+            ErrorKind::InvalidValue
+        );
+
+        let args = Cli::parse_from(["image-optimizer", "-i", "."]);
+        assert_eq!(resolve_input(&args).unwrap(), Path::new("."));
+    }
+}
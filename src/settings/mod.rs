@@ -0,0 +1,36 @@
+//! Configuration file settings.
+//!
+//! This module loads shared settings from an `.image-optimizer.toml` file and merges
+//! them into the parsed command-line arguments:
+//!
+//! - **Discovery**: Finding the nearest config file by walking up from the input
+//! - **Loading**: Parsing the TOML file into a typed configuration
+//! - **Merging**: Applying file values with CLI > config file > built-in defaults precedence
+//! - **Sidecars**: Overriding the settings for the files of a subdirectory with its own
+//!   `.image-optimizer.toml`
+//! - **Presets**: Expanding `--preset` into a bundle of default settings
+//! - **Printing**: Writing the merged settings as TOML for `--print-config`
+//! - **Fingerprinting**: Describing the settings that affect output, for cache invalidation
+//! - **Resolution**: Producing the effective arguments for a run and validating the input
+
+pub mod args_resolver;
+pub mod config_finder;
+pub mod config_loader;
+pub mod config_merger;
+pub mod config_printer;
+pub mod file_config;
+pub mod input_resolver;
+pub mod output_fingerprint;
+pub mod preset_expander;
+pub mod sidecar_configs;
+
+pub use args_resolver::resolve_args;
+pub use config_finder::find_config_file;
+pub use config_loader::load_config;
+pub use config_merger::apply_config;
+pub use config_printer::format_effective_config;
+pub use file_config::FileConfig;
+pub use input_resolver::resolve_input;
+pub use output_fingerprint::output_fingerprint;
+pub use preset_expander::apply_preset;
+pub use sidecar_configs::SidecarConfigs;
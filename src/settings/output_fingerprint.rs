@@ -0,0 +1,113 @@
+use crate::cli::Cli;
+use crate::optimization::ImageFormat;
+
+/// Describes the settings that affect optimized output as a stable string.
+///
+/// Two runs with the same fingerprint produce the same bytes for the same input, so an
+/// [`OptimizationCache`](crate::file_ops::OptimizationCache) entry stays valid only while
+/// the fingerprint is unchanged. Settings that only influence which files are processed
+/// or how they are reported (scanning, sorting, backups) are deliberately left out.
+///
+/// # Arguments
+///
+/// * `args` - Effective CLI configuration for the run
+///
+/// # Returns
+///
+/// Returns a human-readable `key=value` list, including the tool version since encoder
+/// upgrades can change the output as well.
+///
+/// # Examples
+///
+/// ```rust
+/// use clap::Parser;
+/// use image_optimizer::cli::Cli;
+/// use image_optimizer::settings::output_fingerprint;
+///
+/// let args = Cli::parse_from(["image-optimizer", "--jpeg-quality", "80"]);
+/// assert!(output_fingerprint(&args).contains("jpeg-quality=80"));
+/// ```
+#[must_use]
+pub fn output_fingerprint(args: &Cli) -> String {
+    format!(
+        "version={};output={};flatten={};output-ext-normalize={:?};format={:?};name-template={};organize-by-date={:?};\
+         jpeg-quality={};no-upscale-quality={};jpeg-trellis={:?};jpeg-optimize-coding={:?};webp-quality={};keep-icc={};to-srgb={};background={};webp-lossless={};webp-near-lossless={:?};webp-method={};strip-only={};jpeg-lossless={};max-size={:?};sizes={:?};\
+         png-optimization-level={};zopfli-iterations={};no-zopfli={};deflate-level={};svg-precision={};svgz={};svgz-level={};mark={};on-larger={:?};prefer-smaller-than-original-by={}",
+        env!("CARGO_PKG_VERSION"),
+        args.output
+            .as_ref()
+            .map_or_else(String::new, |output| output.display().to_string()),
+        args.flatten,
+        args.output_ext_normalize,
+        args.format,
+        args.name_template
+            .as_ref()
+            .map_or("", crate::file_ops::NameTemplate::as_str),
+        args.organize_by_date,
+        args.quality_for(ImageFormat::Jpeg),
+        args.no_upscale_quality,
+        args.jpeg_trellis,
+        args.jpeg_optimize_coding,
+        args.quality_for(ImageFormat::Webp),
+        args.keep_icc,
+        args.to_srgb,
+        args.background,
+        args.webp_lossless,
+        args.webp_near_lossless,
+        args.webp_method,
+        args.strip_only,
+        args.jpeg_lossless,
+        args.max_size,
+        args.sizes,
+        args.png_optimization_level,
+        args.zopfli_iterations,
+        args.no_zopfli,
+        args.deflate_level,
+        args.svg_precision,
+        args.svgz,
+        args.svgz_level,
+        args.mark,
+        args.on_larger,
+        args.prefer_smaller_than_original_by,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn test_fingerprint_tracks_output_settings() {
+        let base = output_fingerprint(&Cli::parse_from(["image-optimizer"]));
+
+        let quality = output_fingerprint(&Cli::parse_from([
+            "image-optimizer",
+            "--jpeg-quality",
+            "70",
+        ]));
+        assert_ne!(base, quality);
+
+        let resized =
+            output_fingerprint(&Cli::parse_from(["image-optimizer", "--max-size", "512"]));
+        assert_ne!(base, resized);
+
+        let renamed = output_fingerprint(&Cli::parse_from([
+            "image-optimizer",
+            "-o",
+            "out",
+            "--output-ext-normalize",
+            "canonical",
+        ]));
+        let mirrored = output_fingerprint(&Cli::parse_from(["image-optimizer", "-o", "out"]));
+        assert_ne!(mirrored, renamed);
+
+        let sorted = output_fingerprint(&Cli::parse_from([
+            "image-optimizer",
+            "--sort",
+            "size",
+            "-r",
+        ]));
+        assert_eq!(base, sorted);
+    }
+}
@@ -0,0 +1,148 @@
+use clap::ArgMatches;
+use clap::parser::ValueSource;
+
+use super::{FileConfig, apply_config};
+use crate::cli::{Cli, Preset};
+use crate::optimization::ImageFormat;
+
+/// Expands a preset into its bundle of settings and applies it to the arguments.
+///
+/// Preset values override the config file and built-in defaults but never a flag that
+/// was given explicitly on the command line, so `--preset web --jpeg-quality 90` uses
+/// quality 90 with the rest of the web settings. The `print` preset also clears any
+/// `max-size` inherited from a config file, since print output must not be resized.
+///
+/// # Arguments
+///
+/// * `args` - Arguments parsed from `matches`, updated in place
+/// * `preset` - Preset selected with `--preset`
+/// * `matches` - Raw clap matches used to tell explicit flags apart from defaults
+///
+/// # Examples
+///
+/// ```rust
+/// use clap::{CommandFactory, FromArgMatches};
+/// use image_optimizer::cli::{Cli, Preset};
+/// use image_optimizer::settings::apply_preset;
+///
+/// # fn example() -> anyhow::Result<()> {
+/// let matches = Cli::command().try_get_matches_from(["image-optimizer", "--preset", "thumbnail"])?;
+/// let mut args = Cli::from_arg_matches(&matches)?;
+/// apply_preset(&mut args, Preset::Thumbnail, &matches);
+/// assert_eq!(args.max_size, Some(400));
+/// # Ok(())
+/// # }
+/// ```
+pub fn apply_preset(args: &mut Cli, preset: Preset, matches: &ArgMatches) {
+    apply_config(args, preset_settings(preset), matches);
+
+    if preset == Preset::Print && matches.value_source("max_size") != Some(ValueSource::CommandLine)
+    {
+        args.max_size = None;
+    }
+}
+
+fn preset_settings(preset: Preset) -> FileConfig {
+    match preset {
+        // Re-encoding already drops EXIF/XMP, so stripping metadata means not keeping ICC
+        Preset::Web => FileConfig {
+            jpeg_quality: Some(80),
+            max_size: Some(2048),
+            keep_icc: Some(false),
+            format: Some(ImageFormat::Webp),
+            ..FileConfig::default()
+        },
+        Preset::Thumbnail => FileConfig {
+            jpeg_quality: Some(70),
+            max_size: Some(400),
+            ..FileConfig::default()
+        },
+        Preset::Print => FileConfig {
+            jpeg_quality: Some(95),
+            keep_icc: Some(true),
+            ..FileConfig::default()
+        },
+        Preset::Archive => FileConfig {
+            jpeg_quality: Some(100),
+            jpeg_lossless: Some(true),
+            webp_lossless: Some(true),
+            keep_icc: Some(true),
+            ..FileConfig::default()
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{CommandFactory, FromArgMatches};
+
+    fn expanded(cli_args: &[&str], preset: Preset) -> Cli {
+        let matches = Cli::command().try_get_matches_from(cli_args).unwrap();
+        let mut args = Cli::from_arg_matches(&matches).unwrap();
+        apply_preset(&mut args, preset, &matches);
+        args
+    }
+
+    #[test]
+    fn test_web_preset() {
+        let args = expanded(&["image-optimizer"], Preset::Web);
+        assert_eq!(args.jpeg_quality, 80);
+        assert_eq!(args.max_size, Some(2048));
+        assert!(!args.keep_icc);
+        assert_eq!(args.format, Some(ImageFormat::Webp));
+    }
+
+    #[test]
+    fn test_thumbnail_preset() {
+        let args = expanded(&["image-optimizer"], Preset::Thumbnail);
+        assert_eq!(args.jpeg_quality, 70);
+        assert_eq!(args.max_size, Some(400));
+    }
+
+    #[test]
+    fn test_print_preset() {
+        let args = expanded(&["image-optimizer"], Preset::Print);
+        assert_eq!(args.jpeg_quality, 95);
+        assert!(args.keep_icc);
+        assert_eq!(args.max_size, None);
+    }
+
+    #[test]
+    fn test_print_preset_clears_configured_max_size() {
+        let matches = Cli::command()
+            .try_get_matches_from(["image-optimizer"])
+            .unwrap();
+        let mut args = Cli::from_arg_matches(&matches).unwrap();
+        let config = FileConfig {
+            max_size: Some(1024),
+            ..FileConfig::default()
+        };
+        apply_config(&mut args, config, &matches);
+        apply_preset(&mut args, Preset::Print, &matches);
+        assert_eq!(args.max_size, None);
+
+        let args = expanded(&["image-optimizer", "--max-size", "3000"], Preset::Print);
+        assert_eq!(args.max_size, Some(3000));
+    }
+
+    #[test]
+    fn test_archive_preset() {
+        let args = expanded(&["image-optimizer"], Preset::Archive);
+        assert!(args.webp_lossless);
+        assert!(args.jpeg_lossless);
+        assert_eq!(args.jpeg_quality, 100);
+        assert!(args.keep_icc);
+    }
+
+    #[test]
+    fn test_explicit_flag_overrides_preset() {
+        let args = expanded(
+            &["image-optimizer", "--jpeg-quality", "90", "--format", "png"],
+            Preset::Web,
+        );
+        assert_eq!(args.jpeg_quality, 90);
+        assert_eq!(args.format, Some(ImageFormat::Png));
+        assert_eq!(args.max_size, Some(2048));
+    }
+}
@@ -0,0 +1,170 @@
+use anyhow::{Result, anyhow};
+use clap::ArgMatches;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use super::config_finder::CONFIG_FILE_NAME;
+use super::{apply_config, apply_preset, load_config, resolve_input};
+use crate::cli::Cli;
+
+/// Per-directory `.image-optimizer.toml` sidecars below the input directory.
+///
+/// The config found by [`resolve_args`](super::resolve_args) applies to the whole run. A
+/// subdirectory of the input can hold its own `.image-optimizer.toml` that overrides it for
+/// the files under that subdirectory, e.g. a lower quality for `icons/`. Sidecars are
+/// looked up lazily for each directory that holds an image, and the result is cached.
+///
+/// Precedence is CLI > preset > nearest sidecar > sidecars further up > root config >
+/// built-in defaults. Sidecars only affect how each file is optimized: settings that
+/// control the scan or the whole run (`recursive`, `include`, `max-files`, `no-parallel`,
+/// `dedup`, ...) are taken from the run's own settings.
+pub struct SidecarConfigs {
+    root: PathBuf,
+    matches: ArgMatches,
+    resolved: Mutex<HashMap<PathBuf, Option<Arc<Cli>>>>,
+}
+
+impl SidecarConfigs {
+    /// Creates the lookup for sidecars in the subdirectories of `root`.
+    ///
+    /// `matches` are the command-line matches of the run, so command-line flags keep
+    /// precedence over every sidecar.
+    #[must_use]
+    pub fn new(root: &Path, matches: ArgMatches) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            matches,
+            resolved: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the settings for `path` if a sidecar between the input directory and the
+    /// file's directory changes them, or `None` if the run's settings apply unchanged.
+    ///
+    /// The sidecars are applied over `args`, the settings of the run, from the top
+    /// directory down, so the nearest one wins.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a sidecar cannot be read, is not a valid config file, or sets
+    /// an invalid value.
+    pub fn args_for(&self, args: &Cli, path: &Path) -> Result<Option<Arc<Cli>>> {
+        let Some(dir) = path.parent() else {
+            return Ok(None);
+        };
+        if let Some(resolved) = self
+            .resolved
+            .lock()
+            .ok()
+            .and_then(|resolved| resolved.get(dir).cloned())
+        {
+            return Ok(resolved);
+        }
+
+        let resolved = self.resolve(args, dir)?.map(Arc::new);
+        if let Ok(mut cache) = self.resolved.lock() {
+            cache.insert(dir.to_path_buf(), resolved.clone());
+        }
+        Ok(resolved)
+    }
+
+    fn resolve(&self, args: &Cli, dir: &Path) -> Result<Option<Cli>> {
+        let Ok(relative) = dir.strip_prefix(&self.root) else {
+            return Ok(None);
+        };
+
+        let mut dir_args: Option<Cli> = None;
+        let mut current = self.root.clone();
+        for component in relative.components() {
+            current.push(component);
+            let sidecar = current.join(CONFIG_FILE_NAME);
+            if !sidecar.is_file() {
+                continue;
+            }
+            let merged = dir_args.get_or_insert_with(|| args.clone());
+            apply_config(merged, load_config(&sidecar)?, &self.matches);
+            resolve_input(merged).map_err(|error| {
+                // Only the message line; the usage text of the clap error does not apply
+                let error = error.to_string();
+                let message = error.lines().next().unwrap_or_default();
+                anyhow!(
+                    "Invalid config file {}: {}",
+                    sidecar.display(),
+                    message.trim_start_matches("error: ")
+                )
+            })?;
+        }
+
+        if let Some(ref mut merged) = dir_args
+            && let Some(preset) = merged.preset
+        {
+            apply_preset(merged, preset, &self.matches);
+        }
+        Ok(dir_args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+    use clap::FromArgMatches;
+    use std::fs;
+
+    #[test]
+    fn test_nearest_sidecar_wins_and_cli_keeps_precedence() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_sidecar_configs");
+        let _ = fs::remove_dir_all(&temp_dir);
+        let icons = temp_dir.join("icons");
+        let small = icons.join("small");
+        fs::create_dir_all(&small).unwrap();
+        fs::write(
+            icons.join(CONFIG_FILE_NAME),
+            "jpeg-quality = 60\nmax-size = 128\n",
+        )
+        .unwrap();
+        fs::write(small.join(CONFIG_FILE_NAME), "jpeg-quality = 40\n").unwrap();
+
+        let matches = Cli::command()
+            .try_get_matches_from([
+                "image-optimizer",
+                "-i",
+                temp_dir.to_str().unwrap(),
+                "--max-size",
+                "512",
+            ])
+            .unwrap();
+        let args = Cli::from_arg_matches(&matches).unwrap();
+        let sidecars = SidecarConfigs::new(&temp_dir, matches);
+
+        assert!(
+            sidecars
+                .args_for(&args, &temp_dir.join("hero.jpg"))
+                .unwrap()
+                .is_none()
+        );
+        let icon_args = sidecars
+            .args_for(&args, &icons.join("logo.jpg"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(icon_args.jpeg_quality, 60);
+        assert_eq!(icon_args.max_size, Some(512));
+        let small_args = sidecars
+            .args_for(&args, &small.join("dot.jpg"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(small_args.jpeg_quality, 40);
+
+        fs::write(small.join(CONFIG_FILE_NAME), "jpeg-quality = 0\n").unwrap();
+        let sidecars = SidecarConfigs::new(
+            &temp_dir,
+            Cli::command()
+                .try_get_matches_from(["image-optimizer", "-i", temp_dir.to_str().unwrap()])
+                .unwrap(),
+        );
+        assert!(sidecars.args_for(&args, &small.join("dot.jpg")).is_err());
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
@@ -0,0 +1,132 @@
+use super::Repository;
+use super::github_release::{GitHubAsset, GitHubRelease};
+use super::update_failure::UpdateFailure;
+
+/// Finds the binary for the platform `target` among the assets of a release.
+///
+/// Assets are named `image-optimizer-<target>`, e.g.
+/// `image-optimizer-x86_64-unknown-linux-gnu`.
+///
+/// # Arguments
+///
+/// * `release` - Release to install
+/// * `target` - Rust target triple of the current platform
+/// * `repository` - Repository the release comes from, for the download link
+///
+/// # Errors
+///
+/// Returns [`UpdateFailure::MissingAsset`] listing the published asset names if the
+/// release has no binary for `target`.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_optimizer::updater::Repository;
+/// use image_optimizer::updater::asset_selector::select_asset;
+/// use image_optimizer::updater::github_release::GitHubRelease;
+///
+/// let release = GitHubRelease {
+///     tag_name: "v1.6.0".to_string(),
+///     assets: Vec::new(),
+///     prerelease: false,
+///     draft: false,
+/// };
+/// assert!(select_asset(&release, "x86_64-unknown-linux-gnu", &Repository::default()).is_err());
+/// ```
+pub fn select_asset<'a>(
+    release: &'a GitHubRelease,
+    target: &str,
+    repository: &Repository,
+) -> Result<&'a GitHubAsset, UpdateFailure> {
+    let expected = format!("image-optimizer-{target}");
+    release
+        .assets
+        .iter()
+        .find(|asset| asset.name == expected)
+        .ok_or_else(|| UpdateFailure::MissingAsset {
+            tag: release.tag_name.clone(),
+            expected,
+            available: release
+                .assets
+                .iter()
+                .map(|asset| asset.name.clone())
+                .collect(),
+            releases_url: repository.releases_url(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mock_release(assets: &[&str]) -> GitHubRelease {
+        let assets: Vec<String> = assets
+            .iter()
+            .map(|name| {
+                format!(
+                    r#"{{"name": "{name}", "browser_download_url": "https://example.com/{name}"}}"#
+                )
+            })
+            .collect();
+        serde_json::from_str(&format!(
+            r#"{{"tag_name": "v1.6.0", "assets": [{}]}}"#,
+            assets.join(",")
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_matching_asset_is_selected() {
+        let release = mock_release(&[
+            "image-optimizer-x86_64-apple-darwin",
+            "image-optimizer-x86_64-unknown-linux-gnu",
+        ]);
+        let asset =
+            select_asset(&release, "x86_64-unknown-linux-gnu", &Repository::default()).unwrap();
+        assert_eq!(
+            asset.browser_download_url,
+            "https://example.com/image-optimizer-x86_64-unknown-linux-gnu"
+        );
+    }
+
+    #[test]
+    fn test_missing_asset_lists_published_assets() {
+        let release = mock_release(&[
+            "image-optimizer-x86_64-apple-darwin",
+            "image-optimizer-x86_64-pc-windows-msvc",
+        ]);
+        let repository: Repository = "acme/image-optimizer".parse().unwrap();
+        let error = select_asset(&release, "aarch64-unknown-linux-gnu", &repository)
+            .unwrap_err()
+            .to_string();
+
+        assert!(error.contains("Release v1.6.0"), "{error}");
+        assert!(
+            error.contains("image-optimizer-aarch64-unknown-linux-gnu"),
+            "{error}"
+        );
+        assert!(
+            error.contains(
+                "Published assets: image-optimizer-x86_64-apple-darwin, \
+                 image-optimizer-x86_64-pc-windows-msvc"
+            ),
+            "{error}"
+        );
+        assert!(
+            error.contains("https://github.com/acme/image-optimizer/releases"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn test_release_without_assets_says_none() {
+        let error = select_asset(
+            &mock_release(&[]),
+            "x86_64-unknown-linux-gnu",
+            &Repository::default(),
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(error.contains("Published assets: none"), "{error}");
+    }
+}
@@ -0,0 +1,133 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+/// Replaces an executable with a backup copy of a previous version.
+///
+/// The backup is first copied next to the target as `.tmp` and then renamed over it,
+/// the same flow `update_self` uses, so the target is never left half-written. The
+/// backup's permissions are carried over; on Unix the backup must be executable. The
+/// backup itself is kept.
+///
+/// # Arguments
+///
+/// * `backup_path` - Backup of the previous executable (usually `<exe>.bak`)
+/// * `target_path` - Executable to replace
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - The backup does not exist or is not a file
+/// - The backup is not executable (Unix)
+/// - Copying, renaming or setting permissions fails
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use image_optimizer::updater::backup_restorer::restore_backup;
+///
+/// # fn example() -> anyhow::Result<()> {
+/// restore_backup(Path::new("image-optimizer.bak"), Path::new("image-optimizer"))?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn restore_backup(backup_path: &Path, target_path: &Path) -> Result<()> {
+    let backup_metadata = fs::metadata(backup_path)
+        .with_context(|| format!("Backup not found: {}", backup_path.display()))?;
+    if !backup_metadata.is_file() {
+        return Err(anyhow::anyhow!(
+            "Backup is not a file: {}",
+            backup_path.display()
+        ));
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if backup_metadata.permissions().mode() & 0o111 == 0 {
+            return Err(anyhow::anyhow!(
+                "Backup is not executable: {}",
+                backup_path.display()
+            ));
+        }
+    }
+
+    let temp_path = target_path.with_extension("tmp");
+    fs::copy(backup_path, &temp_path)
+        .map_err(|e| anyhow::anyhow!("Failed to copy backup: {}", e))?;
+    fs::set_permissions(&temp_path, backup_metadata.permissions())?;
+
+    if let Err(e) = fs::rename(&temp_path, target_path) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(anyhow::anyhow!("Failed to restore backup: {}", e));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_swaps_backup_into_place() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_restore_backup");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let target = temp_dir.join("image-optimizer");
+        let backup = temp_dir.join("image-optimizer.bak");
+        fs::write(&target, "new version").unwrap();
+        fs::write(&backup, "old version").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&backup, fs::Permissions::from_mode(0o750)).unwrap();
+        }
+
+        restore_backup(&backup, &target).unwrap();
+
+        assert_eq!(fs::read_to_string(&target).unwrap(), "old version");
+        assert!(backup.exists());
+        assert!(!temp_dir.join("image-optimizer.tmp").exists());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&target).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o750);
+        }
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_missing_backup_leaves_target_untouched() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_restore_missing");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let target = temp_dir.join("image-optimizer");
+        fs::write(&target, "current").unwrap();
+
+        assert!(restore_backup(&temp_dir.join("image-optimizer.bak"), &target).is_err());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "current");
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_rejects_non_executable_backup() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = std::env::temp_dir().join("image_optimizer_restore_noexec");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let target = temp_dir.join("image-optimizer");
+        let backup = temp_dir.join("image-optimizer.bak");
+        fs::write(&target, "current").unwrap();
+        fs::write(&backup, "old").unwrap();
+        fs::set_permissions(&backup, fs::Permissions::from_mode(0o644)).unwrap();
+
+        assert!(restore_backup(&backup, &target).is_err());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "current");
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
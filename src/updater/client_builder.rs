@@ -0,0 +1,98 @@
+use anyhow::{Context, Result};
+use reqwest::blocking::Client;
+use reqwest::{NoProxy, Proxy};
+
+use super::UpdateSource;
+
+/// Builds the HTTP client used for update requests.
+///
+/// Connecting and each request are limited to `source.timeout`, so an unreachable
+/// GitHub or a stalled proxy produces an error instead of a hang. With `--update-proxy`
+/// all requests go through that proxy; otherwise `HTTPS_PROXY`/`HTTP_PROXY` (or their
+/// lowercase forms) are applied explicitly. `NO_PROXY` is honored in both cases.
+///
+/// # Arguments
+///
+/// * `source` - Update settings with the proxy and timeout
+///
+/// # Errors
+///
+/// Returns an error if a proxy URL is invalid or the client cannot be created.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_optimizer::updater::UpdateSource;
+/// use image_optimizer::updater::client_builder::build_http_client;
+///
+/// # fn example() -> anyhow::Result<()> {
+/// let client = build_http_client(&UpdateSource::default())?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn build_http_client(source: &UpdateSource) -> Result<Client> {
+    let mut builder = Client::builder()
+        .connect_timeout(source.timeout)
+        .timeout(source.timeout);
+
+    let env_proxy = |names: [&str; 2]| {
+        names
+            .iter()
+            .find_map(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+    };
+
+    if let Some(ref proxy_url) = source.proxy {
+        let proxy = Proxy::all(proxy_url)
+            .with_context(|| format!("Invalid proxy URL: {proxy_url}"))?
+            .no_proxy(NoProxy::from_env());
+        builder = builder.proxy(proxy);
+    } else {
+        if let Some(proxy_url) = env_proxy(["HTTPS_PROXY", "https_proxy"]) {
+            let proxy = Proxy::https(&proxy_url)
+                .with_context(|| format!("Invalid HTTPS_PROXY URL: {proxy_url}"))?
+                .no_proxy(NoProxy::from_env());
+            builder = builder.proxy(proxy);
+        }
+        if let Some(proxy_url) = env_proxy(["HTTP_PROXY", "http_proxy"]) {
+            let proxy = Proxy::http(&proxy_url)
+                .with_context(|| format!("Invalid HTTP_PROXY URL: {proxy_url}"))?
+                .no_proxy(NoProxy::from_env());
+            builder = builder.proxy(proxy);
+        }
+    }
+
+    builder
+        .build()
+        .context("Failed to create the HTTP client for updates")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_unreachable_host_fails_within_timeout() {
+        let source = UpdateSource {
+            timeout: Duration::from_secs(1),
+            ..UpdateSource::default()
+        };
+        let client = build_http_client(&source).unwrap();
+
+        // TEST-NET-1 (RFC 5737) is reserved and never routed
+        let started = Instant::now();
+        let result = client.get("http://192.0.2.1/").send();
+
+        assert!(result.is_err());
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_invalid_proxy_is_rejected() {
+        let source = UpdateSource {
+            proxy: Some("not a url".to_string()),
+            ..UpdateSource::default()
+        };
+        assert!(build_http_client(&source).is_err());
+    }
+}
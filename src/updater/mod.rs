@@ -6,17 +6,47 @@
 //!
 //! ## Components
 //!
-//! - **Self updater**: Main update orchestration and GitHub API interaction
+//! - **Self updater**: Main update orchestration
+//! - **Update checker**: Reports whether an update is available without installing it
+//! - **Self rollback**: Restores the executable backed up by the last update
+//! - **Backup restorer**: Atomic swap of a backup executable into place
+//! - **Release fetcher**: GitHub API request for the latest release
+//! - **Client builder**: HTTP client with timeouts and proxy settings
+//! - **Request failure**: Actionable messages for failed or timed out requests
+//! - **Release selector**: Newest release from a list, including prereleases
+//! - **Update source**: Repository (`owner/name`) and channel to update from
 //! - **GitHub release**: Data structures for GitHub API responses
 //! - **Platform detector**: Determines the correct binary target for the current platform
+//! - **Asset selector**: Picks the release binary for that target
+//! - **Update failure**: Actionable errors when no binary fits this platform
 //! - **Version comparator**: Semantic version comparison logic
 //! - **Executable manager**: Current executable path management
 
+pub mod asset_selector;
+pub mod backup_restorer;
+pub mod client_builder;
 pub mod executable_manager;
 pub mod github_release;
 pub mod platform_detector;
+pub mod release_fetcher;
+pub mod release_selector;
+pub mod repository;
+pub mod request_failure;
+pub mod self_rollback;
 #[allow(clippy::module_name_repetitions)]
 pub mod self_updater;
+pub mod update_checker;
+#[allow(clippy::module_name_repetitions)]
+pub mod update_failure;
+pub mod update_source;
 pub mod version_comparator;
 
+pub use repository::Repository;
+pub use self_rollback::rollback_self;
 pub use self_updater::update_self;
+pub use update_checker::check_update;
+pub use update_failure::UpdateFailure;
+pub use update_source::UpdateSource;
+
+/// Current version of the application from Cargo.toml.
+pub(super) const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -0,0 +1,85 @@
+use anyhow::Result;
+
+use super::github_release::GitHubRelease;
+use super::release_selector::select_newest_release;
+use super::request_failure::describe_request_error;
+use super::{CURRENT_VERSION, UpdateSource};
+
+/// Fetches the latest release from the GitHub releases API.
+///
+/// Without prereleases this asks for `/releases/latest`, which GitHub resolves to the
+/// newest final release. With `include_prereleases` the release list is fetched and
+/// the newest version among all published releases is picked.
+///
+/// # Arguments
+///
+/// * `client` - HTTP client used for the request
+/// * `source` - Repository and channel to fetch from
+///
+/// # Returns
+///
+/// Returns the latest published release with its tag and downloadable assets.
+///
+/// # Errors
+///
+/// Returns an error if the request fails, the response is not a valid release, or
+/// the repository has no releases.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_optimizer::updater::UpdateSource;
+/// use image_optimizer::updater::client_builder::build_http_client;
+/// use image_optimizer::updater::release_fetcher::fetch_latest_release;
+///
+/// # fn example() -> anyhow::Result<()> {
+/// let source = UpdateSource::default();
+/// let client = build_http_client(&source)?;
+/// let release = fetch_latest_release(&client, &source)?;
+/// println!("Latest version: {}", release.tag_name);
+/// # Ok(())
+/// # }
+/// ```
+pub fn fetch_latest_release(
+    client: &reqwest::blocking::Client,
+    source: &UpdateSource,
+) -> Result<GitHubRelease> {
+    let repository = &source.repository;
+    let endpoint = if source.include_prereleases {
+        "releases"
+    } else {
+        "releases/latest"
+    };
+    let url = format!(
+        "https://api.github.com/repos/{}/{}/{endpoint}",
+        repository.owner, repository.name
+    );
+
+    let response = client
+        .get(&url)
+        .header(
+            "User-Agent",
+            format!("{}/{CURRENT_VERSION}", env!("CARGO_PKG_NAME")),
+        )
+        .send()
+        .and_then(reqwest::blocking::Response::error_for_status)
+        .map_err(|e| {
+            describe_request_error(
+                &format!("Failed to check for updates from {repository}"),
+                &e,
+                source.timeout,
+            )
+        })?;
+
+    if !source.include_prereleases {
+        return response
+            .json()
+            .map_err(|e| anyhow::anyhow!("Failed to parse release information: {}", e));
+    }
+
+    let releases: Vec<GitHubRelease> = response
+        .json()
+        .map_err(|e| anyhow::anyhow!("Failed to parse release information: {}", e))?;
+    select_newest_release(releases)
+        .ok_or_else(|| anyhow::anyhow!("No releases found in {repository}"))
+}
@@ -0,0 +1,75 @@
+use super::github_release::GitHubRelease;
+use super::version_comparator::compare_versions;
+
+/// Picks the newest release from a list returned by the GitHub releases API.
+///
+/// Drafts are ignored, as are tags that are not valid versions. Prereleases are
+/// included, since this is used for `--update-prerelease`; a final release wins over
+/// prereleases of the same version.
+///
+/// # Arguments
+///
+/// * `releases` - Releases in any order
+///
+/// # Returns
+///
+/// Returns the release with the highest version, or `None` if there is none.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_optimizer::updater::release_selector::select_newest_release;
+///
+/// assert!(select_newest_release(Vec::new()).is_none());
+/// ```
+#[must_use]
+pub fn select_newest_release(releases: Vec<GitHubRelease>) -> Option<GitHubRelease> {
+    releases
+        .into_iter()
+        .filter(|release| !release.draft && compare_versions("0", &release.tag_name).is_ok())
+        .reduce(|newest, candidate| {
+            if compare_versions(&newest.tag_name, &candidate.tag_name).unwrap_or(false) {
+                candidate
+            } else {
+                newest
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mock_releases(json: &str) -> Vec<GitHubRelease> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_selects_newest_prerelease() {
+        let releases = mock_releases(
+            r#"[
+                {"tag_name": "v1.5.0", "prerelease": false, "assets": []},
+                {"tag_name": "v1.6.0-beta.2", "prerelease": true, "assets": []},
+                {"tag_name": "v1.6.0-beta.10", "prerelease": true, "assets": []},
+                {"tag_name": "v1.7.0-alpha.1", "draft": true, "assets": []},
+                {"tag_name": "nightly", "prerelease": true, "assets": []}
+            ]"#,
+        );
+
+        let newest = select_newest_release(releases).unwrap();
+        assert_eq!(newest.tag_name, "v1.6.0-beta.10");
+        assert!(newest.prerelease);
+    }
+
+    #[test]
+    fn test_final_release_beats_its_prereleases() {
+        let releases = mock_releases(
+            r#"[
+                {"tag_name": "v1.6.0-rc.1", "prerelease": true, "assets": []},
+                {"tag_name": "v1.6.0", "assets": []}
+            ]"#,
+        );
+
+        assert_eq!(select_newest_release(releases).unwrap().tag_name, "v1.6.0");
+    }
+}
@@ -0,0 +1,92 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// A GitHub repository that releases are fetched from, written as `owner/name`.
+///
+/// Defaults to the official `nixuuu/image-optimizer` repository; forks and internal
+/// builds can point `--update-repo` at their own.
+///
+/// # Examples
+///
+/// ```rust
+/// use image_optimizer::updater::Repository;
+///
+/// let repository: Repository = "my-org/image-optimizer".parse().unwrap();
+/// assert_eq!(repository.owner, "my-org");
+/// assert_eq!(repository.to_string(), "my-org/image-optimizer");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repository {
+    /// User or organization that owns the repository
+    pub owner: String,
+    /// Repository name
+    pub name: String,
+}
+
+impl Repository {
+    /// Returns the repository's releases page, where binaries can be downloaded manually.
+    #[must_use]
+    pub fn releases_url(&self) -> String {
+        format!("https://github.com/{}/{}/releases", self.owner, self.name)
+    }
+}
+
+impl Default for Repository {
+    fn default() -> Self {
+        Self {
+            owner: "nixuuu".to_string(),
+            name: "image-optimizer".to_string(),
+        }
+    }
+}
+
+impl FromStr for Repository {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let is_valid_part = |part: &str| {
+            !part.is_empty()
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        };
+
+        match value.trim().split_once('/') {
+            Some((owner, name)) if is_valid_part(owner) && is_valid_part(name) => Ok(Self {
+                owner: owner.to_string(),
+                name: name.to_string(),
+            }),
+            _ => Err(format!("expected OWNER/NAME, got '{value}'")),
+        }
+    }
+}
+
+impl fmt::Display for Repository {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.owner, self.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_owner_and_name() {
+        let repository: Repository = "acme/image-optimizer.fork".parse().unwrap();
+        assert_eq!(repository.owner, "acme");
+        assert_eq!(repository.name, "image-optimizer.fork");
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_slugs() {
+        for slug in ["", "acme", "acme/", "/tool", "a/b/c", "acme/to ol"] {
+            assert!(slug.parse::<Repository>().is_err(), "{slug}");
+        }
+    }
+
+    #[test]
+    fn test_default_is_upstream() {
+        assert_eq!(Repository::default().to_string(), "nixuuu/image-optimizer");
+    }
+}
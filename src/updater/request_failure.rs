@@ -0,0 +1,39 @@
+use std::time::Duration;
+
+/// Turns a failed update request into an error message that names the likely fix.
+///
+/// Timeouts point at `--update-timeout` and `--update-proxy`, since a hang usually
+/// means GitHub is unreachable without a proxy; other errors are passed through.
+///
+/// # Arguments
+///
+/// * `action` - What was being done, e.g. `Failed to download update`
+/// * `error` - The request error
+/// * `timeout` - Timeout the client was built with
+///
+/// # Examples
+///
+/// ```rust
+/// use std::time::Duration;
+/// use image_optimizer::updater::request_failure::describe_request_error;
+///
+/// # fn example(error: reqwest::Error) {
+/// let error = describe_request_error("Failed to check for updates", &error, Duration::from_secs(30));
+/// eprintln!("{error}");
+/// # }
+/// ```
+#[must_use]
+pub fn describe_request_error(
+    action: &str,
+    error: &reqwest::Error,
+    timeout: Duration,
+) -> anyhow::Error {
+    if error.is_timeout() {
+        anyhow::anyhow!(
+            "{action}: no response within {}s (increase --update-timeout or set --update-proxy)",
+            timeout.as_secs()
+        )
+    } else {
+        anyhow::anyhow!("{action}: {error}")
+    }
+}
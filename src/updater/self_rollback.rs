@@ -0,0 +1,51 @@
+use anyhow::Result;
+
+use super::backup_restorer::restore_backup;
+use super::executable_manager::get_current_executable;
+
+/// Restores the executable that was replaced by the last `--update`.
+///
+/// `update_self` keeps the previous binary as `<exe>.bak` next to the current
+/// executable. This swaps that backup back into place. When no backup exists a warning
+/// is printed and nothing is changed.
+///
+/// # Returns
+///
+/// Returns `true` if the backup was restored, or `false` if no backup was found.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - The current executable path cannot be determined
+/// - The backup is not executable or cannot be swapped into place
+///
+/// # Examples
+///
+/// ```rust
+/// use image_optimizer::updater::rollback_self;
+///
+/// # fn example() -> anyhow::Result<()> {
+/// if !rollback_self()? {
+///     eprintln!("Nothing to roll back");
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn rollback_self() -> Result<bool> {
+    let current_exe = get_current_executable()?;
+    let backup_path = current_exe.with_extension("bak");
+
+    if !backup_path.exists() {
+        eprintln!(
+            "Warning: no backup found at {}, nothing to roll back",
+            backup_path.display()
+        );
+        return Ok(false);
+    }
+
+    println!("⏪ Restoring {}...", backup_path.display());
+    restore_backup(&backup_path, &current_exe)?;
+    println!("✅ Rolled back to the previous version");
+
+    Ok(true)
+}
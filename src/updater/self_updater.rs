@@ -1,18 +1,13 @@
 use anyhow::Result;
 
+use super::asset_selector::select_asset;
+use super::client_builder::build_http_client;
 use super::executable_manager::get_current_executable;
-use super::github_release::GitHubRelease;
 use super::platform_detector::get_platform_target;
+use super::release_fetcher::fetch_latest_release;
+use super::request_failure::describe_request_error;
 use super::version_comparator::compare_versions;
-
-/// Current version of the application from Cargo.toml.
-const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
-
-/// GitHub repository owner for fetching releases.
-const REPO_OWNER: &str = "nixuuu";
-
-/// GitHub repository name for fetching releases.
-const REPO_NAME: &str = "image-optimizer";
+use super::{CURRENT_VERSION, UpdateFailure, UpdateSource};
 
 /// Downloads and installs the latest version from GitHub releases.
 ///
@@ -26,6 +21,10 @@ const REPO_NAME: &str = "image-optimizer";
 /// The update process is atomic - if any step fails, the original binary remains unchanged.
 /// A backup is always created before replacement for safety.
 ///
+/// # Arguments
+///
+/// * `source` - Repository and channel to update from (`--update-repo`, `--update-prerelease`)
+///
 /// # Returns
 ///
 /// Returns `Ok(())` on successful update or if already up-to-date.
@@ -33,8 +32,9 @@ const REPO_NAME: &str = "image-optimizer";
 /// # Errors
 ///
 /// Returns an error if:
-/// - Network requests to GitHub API fail
-/// - Platform is unsupported for automatic updates
+/// - Network requests to GitHub API fail or time out
+/// - Platform is unsupported for automatic updates, or the release has no binary for
+///   it ([`UpdateFailure`], with a link to download a build manually)
 /// - File operations fail (backup creation, binary replacement)
 /// - Downloaded binary is corrupted or invalid
 /// - Permission changes fail on Unix systems
@@ -45,22 +45,12 @@ const REPO_NAME: &str = "image-optimizer";
 /// - Linux (`x86_64`, aarch64)
 /// - macOS (`x86_64`, Apple Silicon)
 /// - Windows (`x86_64`)
-pub fn update_self() -> Result<()> {
+pub fn update_self(source: &UpdateSource) -> Result<()> {
     println!("🔍 Checking for updates...");
     println!("Current version: v{CURRENT_VERSION}");
 
-    let client = reqwest::blocking::Client::new();
-    let url = format!("https://api.github.com/repos/{REPO_OWNER}/{REPO_NAME}/releases/latest");
-
-    let response = client
-        .get(&url)
-        .header("User-Agent", format!("{REPO_NAME}/{CURRENT_VERSION}"))
-        .send()
-        .map_err(|e| anyhow::anyhow!("Failed to check for updates: {}", e))?;
-
-    let release: GitHubRelease = response
-        .json()
-        .map_err(|e| anyhow::anyhow!("Failed to parse release information: {}", e))?;
+    let client = build_http_client(source)?;
+    let release = fetch_latest_release(&client, source)?;
 
     println!("Latest version: {}", release.tag_name);
 
@@ -71,23 +61,20 @@ pub fn update_self() -> Result<()> {
 
     println!("📦 New version available: {}", release.tag_name);
 
-    let target = get_platform_target()?;
-    let binary_name = format!("image-optimizer-{target}");
-
-    let asset = release
-        .assets
-        .iter()
-        .find(|asset| asset.name == binary_name)
-        .ok_or_else(|| anyhow::anyhow!("No binary found for platform: {}", target))?;
+    let target = get_platform_target().map_err(|_| UpdateFailure::UnsupportedPlatform {
+        platform: format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH),
+        releases_url: source.repository.releases_url(),
+    })?;
+    let asset = select_asset(&release, &target, &source.repository)?;
 
     println!("⬇️  Downloading update...");
 
     let binary_data = client
         .get(&asset.browser_download_url)
         .send()
-        .map_err(|e| anyhow::anyhow!("Failed to download update: {}", e))?
+        .map_err(|e| describe_request_error("Failed to download update", &e, source.timeout))?
         .bytes()
-        .map_err(|e| anyhow::anyhow!("Failed to read update data: {}", e))?;
+        .map_err(|e| describe_request_error("Failed to read update data", &e, source.timeout))?;
 
     let current_exe = get_current_executable()?;
     let backup_path = current_exe.with_extension("bak");
@@ -0,0 +1,90 @@
+use anyhow::Result;
+
+use super::client_builder::build_http_client;
+use super::github_release::GitHubRelease;
+use super::release_fetcher::fetch_latest_release;
+use super::version_comparator::compare_versions;
+use super::{CURRENT_VERSION, UpdateSource};
+
+/// Checks whether a newer release is available without installing it.
+///
+/// Prints the current and latest versions and whether an update is available. This is
+/// the `--check-update` mode; `main` turns an available update into exit code 10 so
+/// scripts can tell the cases apart.
+///
+/// # Arguments
+///
+/// * `source` - Repository and channel to check
+///
+/// # Returns
+///
+/// Returns `true` if a newer version than the running one has been released.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - The request to the GitHub API fails or returns an invalid response
+/// - The current or latest version cannot be parsed
+///
+/// # Examples
+///
+/// ```rust
+/// use image_optimizer::updater::{UpdateSource, check_update};
+///
+/// # fn example() -> anyhow::Result<()> {
+/// if check_update(&UpdateSource::default())? {
+///     println!("Run `image-optimizer --update` to install it");
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn check_update(source: &UpdateSource) -> Result<bool> {
+    let client = build_http_client(source)?;
+    let release = fetch_latest_release(&client, source)?;
+    report_update(CURRENT_VERSION, &release)
+}
+
+/// Prints the version comparison for `release` and returns whether it is newer.
+fn report_update(current_version: &str, release: &GitHubRelease) -> Result<bool> {
+    println!("Current version: v{current_version}");
+    println!("Latest version: {}", release.tag_name);
+
+    let update_available = compare_versions(current_version, &release.tag_name)?;
+    if update_available {
+        println!("Update available: {}", release.tag_name);
+    } else {
+        println!("You're already running the latest version");
+    }
+    Ok(update_available)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mock_release(tag_name: &str) -> GitHubRelease {
+        serde_json::from_str(&format!(
+            r#"{{"tag_name": "{tag_name}", "assets": [{{
+                "name": "image-optimizer-x86_64-unknown-linux-gnu",
+                "browser_download_url": "https://example.com/image-optimizer"
+            }}]}}"#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_newer_release_is_reported() {
+        assert!(report_update("1.5.0", &mock_release("v1.6.0")).unwrap());
+    }
+
+    #[test]
+    fn test_same_or_older_release_is_not_an_update() {
+        assert!(!report_update("1.5.0", &mock_release("v1.5.0")).unwrap());
+        assert!(!report_update("1.5.0", &mock_release("v1.4.2")).unwrap());
+    }
+
+    #[test]
+    fn test_invalid_tag_is_an_error() {
+        assert!(report_update("1.5.0", &mock_release("nightly")).is_err());
+    }
+}
@@ -0,0 +1,42 @@
+/// Why `--update` cannot install a prebuilt binary for this machine.
+///
+/// Both cases leave the installed executable untouched and tell the user where to get
+/// a build instead. The binary exits with [`UpdateFailure::EXIT_CODE`] for them, so
+/// scripts can tell "no binary for this platform" apart from network or I/O failures.
+#[derive(Debug, thiserror::Error)]
+pub enum UpdateFailure {
+    /// No binaries are published for this operating system and architecture.
+    #[error(
+        "Automatic updates are not available for this platform ({platform}).\n\
+         Download a release manually from {releases_url} or build one with \
+         `cargo install image-optimizer`"
+    )]
+    UnsupportedPlatform {
+        /// Operating system and architecture, e.g. `freebsd-x86_64`
+        platform: String,
+        /// Releases page of the repository updates come from
+        releases_url: String,
+    },
+    /// The release has no binary for this platform.
+    #[error(
+        "Release {tag} has no binary for this platform (expected an asset named {expected}).\n\
+         Published assets: {}\n\
+         Download a release manually from {releases_url}",
+        if .available.is_empty() { "none".to_string() } else { .available.join(", ") }
+    )]
+    MissingAsset {
+        /// Tag of the release that was searched
+        tag: String,
+        /// Asset name the current platform needs
+        expected: String,
+        /// Names of the assets the release does publish
+        available: Vec<String>,
+        /// Releases page of the repository updates come from
+        releases_url: String,
+    },
+}
+
+impl UpdateFailure {
+    /// Exit code of `--update` when no binary can be installed for this platform.
+    pub const EXIT_CODE: u8 = 11;
+}
@@ -0,0 +1,42 @@
+use std::time::Duration;
+
+use super::Repository;
+use crate::cli::Cli;
+
+/// Where updates come from and how they are fetched: the repository, whether
+/// prereleases count, and the network settings for the GitHub requests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdateSource {
+    /// Repository whose releases are used
+    pub repository: Repository,
+    /// Consider prereleases when looking for the newest release
+    pub include_prereleases: bool,
+    /// Proxy URL for all update requests; `None` uses the proxy environment variables
+    pub proxy: Option<String>,
+    /// Limit for connecting and for each request
+    pub timeout: Duration,
+}
+
+impl Default for UpdateSource {
+    fn default() -> Self {
+        Self {
+            repository: Repository::default(),
+            include_prereleases: false,
+            proxy: None,
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+impl UpdateSource {
+    /// Builds the update source from the `--update-*` options.
+    #[must_use]
+    pub fn from_cli(args: &Cli) -> Self {
+        Self {
+            repository: args.update_repo.clone().unwrap_or_default(),
+            include_prereleases: args.update_prerelease,
+            proxy: args.update_proxy.clone(),
+            timeout: Duration::from_secs(args.update_timeout),
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use std::cmp::Ordering;
 
 /// Compares two semantic version strings to determine if an update is available.
 ///
@@ -6,6 +7,10 @@ use anyhow::Result;
 /// It automatically strips 'v' prefixes and compares major.minor.patch version numbers.
 /// The comparison determines whether the latest version is newer than the current version.
 ///
+/// A prerelease suffix (`1.6.0-beta.2`) ranks below the release with the same numbers;
+/// two prereleases of the same version are ordered by their suffixes, comparing numeric
+/// identifiers numerically.
+///
 /// # Arguments
 ///
 /// * `current` - The current version string (e.g., "1.2.1" or "v1.2.1")
@@ -34,12 +39,16 @@ use anyhow::Result;
 /// // No update needed
 /// assert!(!compare_versions("1.0.1", "1.0.0")?);
 /// assert!(!compare_versions("1.0.0", "1.0.0")?);
+///
+/// // Prereleases
+/// assert!(compare_versions("1.0.0", "1.1.0-beta.1")?);
+/// assert!(compare_versions("1.1.0-beta.1", "1.1.0")?);
 /// # Ok(())
 /// # }
 /// ```
 pub fn compare_versions(current: &str, latest: &str) -> Result<bool> {
-    let current_clean = current.trim_start_matches('v');
-    let latest_clean = latest.trim_start_matches('v');
+    let (current_clean, current_pre) = split_prerelease(current.trim_start_matches('v'));
+    let (latest_clean, latest_pre) = split_prerelease(latest.trim_start_matches('v'));
 
     let parse_version = |v: &str| -> Result<Vec<u32>> {
         v.split('.')
@@ -63,7 +72,53 @@ pub fn compare_versions(current: &str, latest: &str) -> Result<bool> {
     }
 
     // If all compared parts are equal, check if latest has more parts
-    Ok(latest_parts.len() > current_parts.len())
+    if latest_parts.len() != current_parts.len() {
+        return Ok(latest_parts.len() > current_parts.len());
+    }
+
+    // Same numbers: a release is newer than its prereleases
+    Ok(match (current_pre, latest_pre) {
+        (Some(_), None) => true,
+        (Some(current_pre), Some(latest_pre)) => {
+            compare_prerelease(current_pre, latest_pre) == Ordering::Less
+        }
+        (None, _) => false,
+    })
+}
+
+/// Splits `1.2.0-beta.1` into `("1.2.0", Some("beta.1"))`; build metadata is dropped.
+fn split_prerelease(version: &str) -> (&str, Option<&str>) {
+    let version = version
+        .split_once('+')
+        .map_or(version, |(version, _)| version);
+    match version.split_once('-') {
+        Some((numbers, prerelease)) => (numbers, Some(prerelease)),
+        None => (version, None),
+    }
+}
+
+/// Orders prerelease suffixes identifier by identifier, numeric identifiers numerically.
+fn compare_prerelease(current: &str, latest: &str) -> Ordering {
+    let mut current_ids = current.split('.');
+    let mut latest_ids = latest.split('.');
+    loop {
+        match (current_ids.next(), latest_ids.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(current_id), Some(latest_id)) => {
+                let ordering = match (current_id.parse::<u64>(), latest_id.parse::<u64>()) {
+                    (Ok(current_num), Ok(latest_num)) => current_num.cmp(&latest_num),
+                    (Ok(_), Err(_)) => Ordering::Less,
+                    (Err(_), Ok(_)) => Ordering::Greater,
+                    (Err(_), Err(_)) => current_id.cmp(latest_id),
+                };
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -102,6 +157,17 @@ mod tests {
         assert!(compare_versions("1.x.0", "1.0.0").is_err());
     }
 
+    #[test]
+    fn test_prerelease_versions() {
+        assert!(compare_versions("1.5.0", "v1.6.0-beta.1").unwrap());
+        assert!(!compare_versions("1.6.0", "v1.6.0-beta.1").unwrap());
+        assert!(compare_versions("1.6.0-beta.1", "1.6.0").unwrap());
+        assert!(compare_versions("1.6.0-beta.2", "1.6.0-beta.10").unwrap());
+        assert!(compare_versions("1.6.0-alpha", "1.6.0-beta").unwrap());
+        assert!(!compare_versions("1.6.0-rc.1", "1.6.0-rc.1").unwrap());
+        assert!(compare_versions("1.6.0-rc", "1.6.0-rc.1").unwrap());
+    }
+
     #[test]
     fn test_real_world_versions() {
         assert!(compare_versions("1.2.1", "1.2.2").unwrap());
@@ -0,0 +1,141 @@
+use anyhow::{Context, Result};
+use notify_debouncer_mini::notify::RecursiveMode;
+use notify_debouncer_mini::{DebounceEventResult, new_debouncer};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+
+use super::{WatchFilter, WrittenFiles};
+use crate::cli::Cli;
+use crate::file_ops::ScanOptions;
+use crate::optimization::{CancelToken, ImageOutcome, OptimizeError, optimize_image};
+use crate::reporting::format_file_line;
+
+/// Quiet period after the last event for a file before it is optimized, so an editor
+/// or exporter writing a file in several steps triggers a single optimization.
+const DEBOUNCE_DELAY: Duration = Duration::from_millis(500);
+
+/// How often the loop checks for Ctrl-C while no events arrive.
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Optimizes images in `input` as they are created or modified, until cancelled.
+///
+/// Events are debounced per file and filtered with the scan settings of the run (see
+/// [`WatchFilter`]). Each optimization is printed as it happens, errors go to stderr
+/// and do not stop watching. Files the watcher wrote itself are recognized by their
+/// content hash and skipped, so in-place optimization does not trigger itself again.
+///
+/// # Arguments
+///
+/// * `args` - Effective CLI configuration used for every optimization
+/// * `input` - Directory to watch
+/// * `cancel` - Token that ends watching, set by Ctrl-C
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - The input directory cannot be resolved or watched
+/// - `--include` or `--exclude` patterns are invalid
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use clap::Parser;
+/// use std::path::Path;
+/// use image_optimizer::cli::Cli;
+/// use image_optimizer::optimization::CancelToken;
+/// use image_optimizer::watcher::watch_directory;
+///
+/// # fn example() -> anyhow::Result<()> {
+/// let args = Cli::parse_from(["image-optimizer", "-i", "assets", "--watch"]);
+/// let cancel = CancelToken::default();
+/// cancel.cancel_on_interrupt()?;
+/// watch_directory(&args, Path::new("assets"), &cancel)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn watch_directory(args: &Cli, input: &Path, cancel: &CancelToken) -> Result<()> {
+    let root = input
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve {}", input.display()))?;
+    let scan_options = ScanOptions::from_cli(args)?;
+    let mode = if scan_options.recursive {
+        RecursiveMode::Recursive
+    } else {
+        RecursiveMode::NonRecursive
+    };
+    let filter = WatchFilter::new(root, scan_options);
+    let mut written = WrittenFiles::default();
+
+    let (sender, receiver) = mpsc::channel::<DebounceEventResult>();
+    let mut debouncer =
+        new_debouncer(DEBOUNCE_DELAY, sender).context("Failed to start the file watcher")?;
+    debouncer
+        .watcher()
+        .watch(filter.root(), mode)
+        .with_context(|| format!("Failed to watch {}", input.display()))?;
+
+    if !args.quiet {
+        println!(
+            "Watching {} for changes (press Ctrl-C to stop)",
+            input.display()
+        );
+    }
+
+    while !cancel.is_cancelled() {
+        match receiver.recv_timeout(CANCEL_POLL_INTERVAL) {
+            Ok(Ok(events)) => {
+                let changed: BTreeSet<PathBuf> =
+                    events.into_iter().map(|event| event.path).collect();
+                for path in changed {
+                    if path.is_file() && filter.accepts(&path) && !written.is_unchanged(&path) {
+                        // Show paths as given on the command line, not canonicalized
+                        let shown = path
+                            .strip_prefix(filter.root())
+                            .map_or_else(|_| path.clone(), |relative| input.join(relative));
+                        optimize_changed_file(&path, &shown, args, filter.root());
+                        written.record(&path);
+                    }
+                }
+            }
+            Ok(Err(e)) => eprintln!("Warning: file watcher error: {e}"),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+
+    Ok(())
+}
+
+/// Optimizes one changed file and prints the result line or the error.
+fn optimize_changed_file(path: &Path, shown: &Path, args: &Cli, root: &Path) {
+    match optimize_image(path, args, root) {
+        Ok(outcome) => {
+            if !args.quiet {
+                println!("{}", format_file_line(shown, outcome));
+            }
+        }
+        // The file is one the watcher (or an earlier run) already optimized, or its
+        // output from an earlier run is kept
+        Err(OptimizeError::AlreadyMarked | OptimizeError::OutputExists(_)) => {}
+        Err(OptimizeError::BelowThreshold {
+            original,
+            optimized,
+            ..
+        }) => {
+            if !args.quiet {
+                let outcome = ImageOutcome {
+                    original_size: original,
+                    optimized_size: optimized,
+                    quality: None,
+                };
+                println!(
+                    "{}, kept original (below threshold)",
+                    format_file_line(shown, outcome)
+                );
+            }
+        }
+        Err(e) => eprintln!("Error processing {}: {}", shown.display(), e),
+    }
+}
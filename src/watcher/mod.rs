@@ -0,0 +1,16 @@
+//! Watch mode.
+//!
+//! After the initial pass, `--watch` keeps optimizing images in the input directory as
+//! they are created or modified:
+//!
+//! - **Event loop**: Debounced filesystem events and the optimization loop
+//! - **Watch filter**: Applies the scan settings (recursion, include/exclude, size) to event paths
+//! - **Written files**: Recognizes files the watcher itself just wrote to avoid loops
+
+pub mod event_loop;
+pub mod watch_filter;
+pub mod written_files;
+
+pub use event_loop::watch_directory;
+pub use watch_filter::WatchFilter;
+pub use written_files::WrittenFiles;
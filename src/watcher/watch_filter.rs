@@ -0,0 +1,134 @@
+use std::path::{Path, PathBuf};
+
+use crate::file_ops::{ScanOptions, has_supported_extension};
+
+/// Decides which changed files watch mode optimizes.
+///
+/// Event paths are checked against the same settings as the initial scan: only
+/// top-level files unless recursive (and no deeper than `--max-depth`), supported
+/// extensions, `--include`/`--exclude` globs relative to the watched directory (an
+/// excluded directory excludes everything below it) and `--min-size`. Temporary files
+/// written during in-place optimization are always ignored.
+///
+/// ## Examples
+///
+/// ```rust
+/// use std::path::PathBuf;
+/// use image_optimizer::file_ops::ScanOptions;
+/// use image_optimizer::watcher::WatchFilter;
+///
+/// let filter = WatchFilter::new(PathBuf::from("/assets"), ScanOptions::default());
+/// assert!(!filter.accepts(&PathBuf::from("/assets/nested/logo.png")));
+/// ```
+#[derive(Debug, Clone)]
+pub struct WatchFilter {
+    root: PathBuf,
+    options: ScanOptions,
+}
+
+impl WatchFilter {
+    /// Creates a filter for events below `root` using the given scan settings.
+    #[must_use]
+    pub const fn new(root: PathBuf, options: ScanOptions) -> Self {
+        Self { root, options }
+    }
+
+    /// Returns the watched directory.
+    #[must_use]
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns `true` if the file at `path` should be optimized.
+    ///
+    /// Paths are matched as given, so files that do not exist (anymore) are only
+    /// rejected by the size check when `--min-size` is set; callers skip those anyway.
+    #[must_use]
+    pub fn accepts(&self, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+        if self
+            .options
+            .depth_limit()
+            .is_some_and(|depth| relative.components().count() > depth)
+        {
+            return false;
+        }
+        let is_temp_file = path
+            .file_stem()
+            .and_then(|stem| Path::new(stem).extension())
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("tmp"));
+        if is_temp_file || !has_supported_extension(path) {
+            return false;
+        }
+
+        let is_excluded = self.options.exclude.as_ref().is_some_and(|exclude| {
+            relative
+                .ancestors()
+                .filter(|ancestor| !ancestor.as_os_str().is_empty())
+                .any(|ancestor| exclude.is_match(ancestor))
+        }) || relative
+            .parent()
+            .is_some_and(|parent| parent.iter().any(|name| self.options.is_excluded_dir(name)));
+        let is_included = self
+            .options
+            .include
+            .as_ref()
+            .is_none_or(|include| include.is_match(relative));
+        let is_too_small = self.options.min_size.is_some_and(|min_size| {
+            std::fs::metadata(path).is_ok_and(|metadata| metadata.len() < min_size)
+        });
+
+        !is_excluded && is_included && !is_too_small
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_ops::build_glob_set;
+
+    #[test]
+    fn test_top_level_only_unless_recursive() {
+        let root = PathBuf::from("/assets");
+        let filter = WatchFilter::new(root.clone(), ScanOptions::default());
+        assert!(filter.accepts(&root.join("logo.png")));
+        assert!(!filter.accepts(&root.join("icons/logo.png")));
+        assert!(!filter.accepts(&PathBuf::from("/elsewhere/logo.png")));
+
+        let recursive = WatchFilter::new(
+            root.clone(),
+            ScanOptions {
+                recursive: true,
+                ..ScanOptions::default()
+            },
+        );
+        assert!(recursive.accepts(&root.join("icons/logo.png")));
+    }
+
+    #[test]
+    fn test_skips_temp_files_and_unsupported_extensions() {
+        let root = PathBuf::from("/assets");
+        let filter = WatchFilter::new(root.clone(), ScanOptions::default());
+        assert!(!filter.accepts(&root.join("logo.tmp.png")));
+        assert!(!filter.accepts(&root.join("notes.txt")));
+    }
+
+    #[test]
+    fn test_excluded_directory_covers_nested_files() {
+        let root = PathBuf::from("/assets");
+        let filter = WatchFilter::new(
+            root.clone(),
+            ScanOptions {
+                recursive: true,
+                include: build_glob_set(&["**/*.png".to_string()]).unwrap(),
+                exclude: build_glob_set(&["optimized".to_string()]).unwrap(),
+                ..ScanOptions::default()
+            },
+        );
+        assert!(filter.accepts(&root.join("icons/logo.png")));
+        assert!(!filter.accepts(&root.join("icons/photo.jpg")));
+        assert!(!filter.accepts(&root.join("optimized/icons/logo.png")));
+    }
+}
@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::file_ops::hash_file;
+
+/// Content hashes of the files watch mode has just handled.
+///
+/// Optimizing a file in place (or only restoring its timestamps) produces filesystem
+/// events of its own. Recording the content after each optimization lets the watcher
+/// recognize those events and skip the file, while a real change by someone else
+/// produces a different hash and is optimized again.
+#[derive(Debug, Default)]
+pub struct WrittenFiles {
+    hashes: HashMap<PathBuf, String>,
+}
+
+impl WrittenFiles {
+    /// Records the current content of `path` as handled by the watcher.
+    pub fn record(&mut self, path: &Path) {
+        if let Ok(hash) = hash_file(path) {
+            self.hashes.insert(path.to_path_buf(), hash);
+        }
+    }
+
+    /// Returns `true` if `path` still has the content recorded after it was handled.
+    #[must_use]
+    pub fn is_unchanged(&self, path: &Path) -> bool {
+        self.hashes
+            .get(path)
+            .is_some_and(|recorded| hash_file(path).is_ok_and(|hash| &hash == recorded))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_detects_changes_after_recording() {
+        let temp_dir = std::env::temp_dir().join("image_optimizer_written_files");
+        fs::create_dir_all(&temp_dir).unwrap();
+        let path = temp_dir.join("image.png");
+        fs::write(&path, "optimized").unwrap();
+
+        let mut written = WrittenFiles::default();
+        assert!(!written.is_unchanged(&path));
+        written.record(&path);
+        assert!(written.is_unchanged(&path));
+
+        fs::write(&path, "exported again").unwrap();
+        assert!(!written.is_unchanged(&path));
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
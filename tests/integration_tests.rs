@@ -1,10 +1,18 @@
 use std::fs;
-use std::process::Command;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+#[allow(clippy::cast_possible_truncation)]
+fn gradient_image(width: u32, height: u32) -> image::RgbImage {
+    image::RgbImage::from_fn(width, height, |x, y| {
+        image::Rgb([(x * 255 / width) as u8, (y * 255 / height) as u8, 64])
+    })
+}
 
 #[test]
 fn test_cli_help() {
     let output = Command::new("cargo")
-        .args(&["run", "--", "--help"])
+        .args(["run", "--", "--help"])
         .output()
         .expect("Failed to execute command");
 
@@ -19,7 +27,7 @@ fn test_cli_help() {
 #[test]
 fn test_cli_version() {
     let output = Command::new("cargo")
-        .args(&["run", "--", "--version"])
+        .args(["run", "--", "--version"])
         .output()
         .expect("Failed to execute command");
 
@@ -31,7 +39,7 @@ fn test_cli_version() {
 #[test]
 fn test_invalid_input_path() {
     let output = Command::new("cargo")
-        .args(&["run", "--", "-i", "/nonexistent/path"])
+        .args(["run", "--", "-i", "/nonexistent/path"])
         .output()
         .expect("Failed to execute command");
 
@@ -42,17 +50,1156 @@ fn test_invalid_input_path() {
     assert!(stderr.contains("does not exist") || stderr.contains("No such file"));
 }
 
+#[test]
+fn test_missing_input_exits_with_usage_error() {
+    let output = Command::new("cargo")
+        .args(["run", "--"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn test_corrupt_input_sets_failure_exit_code() {
+    let temp_dir = std::env::temp_dir().join("test_corrupt_exit_code");
+    fs::create_dir_all(&temp_dir).unwrap();
+    fs::write(temp_dir.join("a_corrupt.png"), b"not really a png").unwrap();
+    gradient_image(32, 32)
+        .save(temp_dir.join("b_valid.png"))
+        .unwrap();
+
+    let run = |extra: &[&str]| {
+        Command::new("cargo")
+            .args(["run", "--", "-i", temp_dir.to_str().unwrap(), "--no-zopfli"])
+            .args(["--no-parallel"])
+            .args(extra)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    let output = run(&[]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout.contains("1 files skipped due to decode errors"));
+    assert!(!stdout.contains("Processed 0 files"));
+
+    gradient_image(32, 32)
+        .save(temp_dir.join("b_valid.png"))
+        .unwrap();
+    let output = run(&["--fail-fast"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout.contains("Processed 0 files"));
+    assert!(stdout.contains("1 files skipped due to decode errors"));
+
+    fs::remove_dir_all(&temp_dir).unwrap();
+}
+
+#[test]
+fn test_second_run_fails_while_lock_is_held() {
+    let temp_dir = std::env::temp_dir().join("test_run_lock_held");
+    fs::create_dir_all(&temp_dir).unwrap();
+    let image_path = temp_dir.join("a.png");
+    gradient_image(32, 32).save(&image_path).unwrap();
+    let original = fs::read(&image_path).unwrap();
+
+    // Stands in for a first run that is still optimizing the directory
+    let lock = image_optimizer::file_ops::RunLock::acquire_for_input(&temp_dir, false).unwrap();
+    let output = Command::new("cargo")
+        .args(["run", "--", "-i", temp_dir.to_str().unwrap(), "--no-zopfli"])
+        .output()
+        .expect("Failed to execute command");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr.contains(".image-optimizer.lock"));
+    assert!(stderr.contains("--wait"));
+    assert_eq!(fs::read(&image_path).unwrap(), original);
+
+    drop(lock);
+    let output = Command::new("cargo")
+        .args(["run", "--", "-i", temp_dir.to_str().unwrap(), "--no-zopfli"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+    assert!(!temp_dir.join(".image-optimizer.lock").exists());
+
+    fs::remove_dir_all(&temp_dir).unwrap();
+}
+
 #[test]
 fn test_empty_directory() {
     let temp_dir = std::env::temp_dir().join("test_empty_dir");
     fs::create_dir_all(&temp_dir).unwrap();
 
     let output = Command::new("cargo")
-        .args(&["run", "--", "-i", temp_dir.to_str().unwrap()])
+        .args(["run", "--", "-i", temp_dir.to_str().unwrap()])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+
+    fs::remove_dir_all(&temp_dir).unwrap();
+}
+
+#[test]
+fn test_files_from_processes_only_listed_files() {
+    let temp_dir = std::env::temp_dir().join("test_files_from");
+    let input_dir = temp_dir.join("input");
+    let output_dir = temp_dir.join("output");
+    fs::create_dir_all(&input_dir).unwrap();
+    for name in ["listed.png", "also_listed.png", "unlisted.png"] {
+        image::RgbImage::from_pixel(16, 16, image::Rgb([200, 100, 50]))
+            .save(input_dir.join(name))
+            .unwrap();
+    }
+    let list = temp_dir.join("list.txt");
+    fs::write(
+        &list,
+        format!(
+            "{}\n{}\n",
+            input_dir.join("listed.png").display(),
+            input_dir.join("also_listed.png").display()
+        ),
+    )
+    .unwrap();
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "--files-from",
+            list.to_str().unwrap(),
+            "-i",
+            input_dir.to_str().unwrap(),
+            "-o",
+            output_dir.to_str().unwrap(),
+            "--no-zopfli",
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    assert!(output_dir.join("listed.png").exists());
+    assert!(output_dir.join("also_listed.png").exists());
+    assert!(!output_dir.join("unlisted.png").exists());
+
+    fs::remove_dir_all(&temp_dir).unwrap();
+}
+
+#[test]
+fn test_files_from_rejects_absolute_paths_outside_the_base_with_output() {
+    let temp_dir = std::env::temp_dir().join("test_files_from_absolute");
+    let _ = fs::remove_dir_all(&temp_dir);
+    let output_dir = temp_dir.join("out");
+    fs::create_dir_all(&temp_dir).unwrap();
+    let png = temp_dir.join("a.png");
+    gradient_image(32, 32).save(&png).unwrap();
+    let svg = temp_dir.join("b.svg");
+    fs::write(
+        &svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\"><!-- c --><rect width=\"1\" height=\"1\"/></svg>",
+    )
+    .unwrap();
+    let originals = [fs::read(&png).unwrap(), fs::read(&svg).unwrap()];
+    let list = temp_dir.join("list.txt");
+    fs::write(&list, format!("{}\n{}\n", png.display(), svg.display())).unwrap();
+
+    // Without --input the base is the current directory, which does not contain them
+    let output = Command::new("cargo")
+        .args(["run", "--", "--files-from", list.to_str().unwrap()])
+        .args(["-o", output_dir.to_str().unwrap(), "--no-zopfli", "--force"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("is not inside the current directory")
+    );
+    assert_eq!(fs::read(&png).unwrap(), originals[0]);
+    assert_eq!(fs::read(&svg).unwrap(), originals[1]);
+    assert!(!output_dir.join("a.png").exists());
+
+    fs::remove_dir_all(&temp_dir).unwrap();
+}
+
+#[test]
+fn test_cache_skips_unchanged_files_on_second_run() {
+    let temp_dir = std::env::temp_dir().join("test_cache_second_run");
+    let input_dir = temp_dir.join("images");
+    fs::create_dir_all(&input_dir).unwrap();
+    gradient_image(64, 64)
+        .save(input_dir.join("a.png"))
+        .unwrap();
+    gradient_image(48, 48)
+        .save(input_dir.join("b.png"))
+        .unwrap();
+    let cache = temp_dir.join("cache.json");
+
+    let run = || {
+        Command::new("cargo")
+            .args([
+                "run",
+                "--",
+                "-i",
+                input_dir.to_str().unwrap(),
+                "--cache",
+                cache.to_str().unwrap(),
+                "--no-zopfli",
+            ])
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    let first = run();
+    assert!(first.status.success());
+    assert!(cache.exists());
+    let optimized = fs::read(input_dir.join("a.png")).unwrap();
+
+    let second = run();
+    assert!(second.status.success());
+    let stdout = String::from_utf8_lossy(&second.stdout);
+    assert!(stdout.contains("Processed 0 files"));
+    assert!(stdout.contains("Skipped 2 files (already optimized according to the cache)"));
+    assert_eq!(fs::read(input_dir.join("a.png")).unwrap(), optimized);
+
+    fs::remove_dir_all(&temp_dir).unwrap();
+}
+
+fn parse_size(value: &str, unit: &str) -> Option<f64> {
+    let multiplier = match unit {
+        "B" => 1.0,
+        "KB" => 1024.0,
+        "MB" => 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some(value.trim_start_matches('+').parse::<f64>().ok()? * multiplier)
+}
+
+#[test]
+fn test_verbose_lines_are_consistent() {
+    let temp_dir = std::env::temp_dir().join("test_verbose_lines");
+    fs::create_dir_all(&temp_dir).unwrap();
+    gradient_image(256, 256)
+        .save(temp_dir.join("a.png"))
+        .unwrap();
+    gradient_image(128, 128)
+        .save(temp_dir.join("b.png"))
+        .unwrap();
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "-i",
+            temp_dir.to_str().unwrap(),
+            "--verbose",
+            "--no-zopfli",
+        ])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+
+    // "<path>  <before> <unit> -> <after> <unit>  <delta> <unit> (<percent>%)"
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout
+        .lines()
+        .filter(|line| line.contains(" -> "))
+        .collect();
+    assert_eq!(lines.len(), 2);
+    for line in lines {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let before = parse_size(fields[1], fields[2]).unwrap();
+        let after = parse_size(fields[4], fields[5]).unwrap();
+        let delta = parse_size(fields[6], fields[7]).unwrap();
+        let percent: f64 = fields[8]
+            .trim_matches(|c| c == '(' || c == ')' || c == '%' || c == ',')
+            .parse()
+            .unwrap();
+
+        // Sizes are rounded to 0.1 of their unit when printed
+        let tolerance = 0.05 * 1024.0;
+        assert!(
+            ((after - before) - delta).abs() <= 3.0 * tolerance,
+            "{line}"
+        );
+        assert!(
+            (percent / 100.0).mul_add(before, -delta).abs()
+                <= tolerance * (1.0 + percent.abs() / 100.0) + 1.0,
+            "{line}"
+        );
+        assert!(delta < 0.0 || line.ends_with("kept original"), "{line}");
+    }
+
+    fs::remove_dir_all(&temp_dir).unwrap();
+}
+
+#[test]
+fn test_min_size_skips_tiny_files() {
+    let temp_dir = std::env::temp_dir().join("test_min_size");
+    fs::create_dir_all(&temp_dir).unwrap();
+    let tiny = temp_dir.join("tiny.png");
+    fs::write(&tiny, [0u8; 100]).unwrap();
+    gradient_image(256, 256)
+        .save(temp_dir.join("large.png"))
+        .unwrap();
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "-i", temp_dir.to_str().unwrap(), "--no-zopfli"])
+        .args(["--min-size", "1KB"])
+        .output()
+        .expect("Failed to execute command");
+
+    // The tiny file is not a valid PNG, so it would fail if it were optimized
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Found 1 image files"));
+    assert!(stdout.contains("Skipped 1 files (smaller than --min-size)"));
+    assert_eq!(fs::read(&tiny).unwrap(), [0u8; 100]);
+
+    fs::remove_dir_all(&temp_dir).unwrap();
+}
+
+#[test]
+fn test_max_files_aborts_before_touching_files() {
+    let temp_dir = std::env::temp_dir().join("image_optimizer_max_files");
+    let _ = fs::remove_dir_all(&temp_dir);
+    fs::create_dir_all(&temp_dir).unwrap();
+    for index in 0..3 {
+        gradient_image(64, 64)
+            .save(temp_dir.join(format!("image_{index}.png")))
+            .unwrap();
+    }
+    let original = fs::read(temp_dir.join("image_0.png")).unwrap();
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "-i", temp_dir.to_str().unwrap(), "--no-zopfli"])
+        .args(["--max-files", "2"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Found 3 image files, more than --max-files 2"),
+        "{stderr}"
+    );
+    assert_eq!(fs::read(temp_dir.join("image_0.png")).unwrap(), original);
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "-i", temp_dir.to_str().unwrap(), "--no-zopfli"])
+        .args(["--max-files", "2", "--yes"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+
+    fs::remove_dir_all(&temp_dir).unwrap();
+}
+
+#[test]
+fn test_output_file_writes_the_exact_path() {
+    let temp_dir = std::env::temp_dir().join("image_optimizer_output_file");
+    let _ = fs::remove_dir_all(&temp_dir);
+    fs::create_dir_all(&temp_dir).unwrap();
+    let input = temp_dir.join("a.png");
+    let output_file = temp_dir.join("nested").join("b.png");
+    gradient_image(64, 64).save(&input).unwrap();
+    let original = fs::read(&input).unwrap();
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "-i", input.to_str().unwrap(), "--no-zopfli"])
+        .args(["--output-file", output_file.to_str().unwrap()])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(fs::read(&input).unwrap(), original);
+    let written = image::open(&output_file).unwrap();
+    assert_eq!((written.width(), written.height()), (64, 64));
+    assert_eq!(fs::read_dir(temp_dir.join("nested")).unwrap().count(), 1);
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "-i", temp_dir.to_str().unwrap()])
+        .args(["--output-file", output_file.to_str().unwrap()])
+        .output()
+        .expect("Failed to execute command");
+    assert_eq!(output.status.code(), Some(2));
+
+    fs::remove_dir_all(&temp_dir).unwrap();
+}
+
+#[test]
+fn test_progress_interval_prints_lines_for_logs() {
+    let temp_dir = std::env::temp_dir().join("image_optimizer_progress_interval");
+    let _ = fs::remove_dir_all(&temp_dir);
+    fs::create_dir_all(&temp_dir).unwrap();
+    for index in 0..4 {
+        gradient_image(32, 32)
+            .save(temp_dir.join(format!("image_{index}.png")))
+            .unwrap();
+    }
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "-i", temp_dir.to_str().unwrap(), "--no-zopfli"])
+        .args(["--progress-interval", "2", "--quiet"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let lines: Vec<_> = stderr
+        .lines()
+        .filter(|line| line.contains(" processed, "))
+        .collect();
+    assert_eq!(lines.len(), 2, "{stderr}");
+    assert!(lines[0].starts_with("2/4 processed, "), "{stderr}");
+    assert!(lines[1].starts_with("4/4 processed, "), "{stderr}");
+
+    fs::remove_dir_all(&temp_dir).unwrap();
+}
+
+#[test]
+fn test_large_in_place_run_without_terminal_does_not_prompt() {
+    let temp_dir = std::env::temp_dir().join("image_optimizer_confirm_in_place");
+    let _ = fs::remove_dir_all(&temp_dir);
+    fs::create_dir_all(&temp_dir).unwrap();
+    // One more than the confirmation threshold of 100 files
+    for index in 0..101 {
+        gradient_image(8, 8)
+            .save(temp_dir.join(format!("image_{index:03}.png")))
+            .unwrap();
+    }
+
+    for extra in [&[][..], &["--yes"]] {
+        let output = Command::new(env!("CARGO_BIN_EXE_image-optimizer"))
+            .args(["-i", temp_dir.to_str().unwrap(), "--no-zopfli"])
+            .args(extra)
+            .stdin(Stdio::null())
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(!stderr.contains("Continue? [y/N]"), "{stderr}");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("Found 101 image files"));
+    }
+
+    fs::remove_dir_all(&temp_dir).unwrap();
+}
+
+#[test]
+fn test_output_inside_input_is_not_rescanned() {
+    let temp_dir = std::env::temp_dir().join("test_nested_output");
+    let _ = fs::remove_dir_all(&temp_dir);
+    fs::create_dir_all(&temp_dir).unwrap();
+    gradient_image(64, 64)
+        .save(temp_dir.join("gradient.png"))
+        .unwrap();
+    let input = temp_dir.to_str().unwrap();
+    let nested = temp_dir.join("optimized");
+
+    for _ in 0..2 {
+        let output = Command::new("cargo")
+            .args(["run", "--", "-i", input, "-r", "--no-zopfli"])
+            .args(["-o", nested.to_str().unwrap()])
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("Found 1 image files"));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("will not be scanned"));
+    }
+    assert!(!nested.join("optimized").exists());
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "-i", input, "-o", input])
+        .output()
+        .expect("Failed to execute command");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("omit -o/--output"));
+
+    fs::remove_dir_all(&temp_dir).unwrap();
+}
+
+#[test]
+fn test_backup_dir_inside_input_is_not_rescanned() {
+    let temp_dir = std::env::temp_dir().join("test_nested_backup_dir");
+    let _ = fs::remove_dir_all(&temp_dir);
+    fs::create_dir_all(&temp_dir).unwrap();
+    gradient_image(64, 64)
+        .save(temp_dir.join("gradient.png"))
+        .unwrap();
+    let input = temp_dir.to_str().unwrap();
+    let backups = temp_dir.join("backups");
+
+    for _ in 0..2 {
+        let output = Command::new("cargo")
+            .args(["run", "--", "-i", input, "-r", "--no-zopfli", "--force"])
+            .args(["--backup-dir", backups.to_str().unwrap()])
+            .output()
+            .expect("Failed to execute command");
+
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("Found 1 image files"), "{stdout}");
+    }
+    assert!(backups.join("gradient.png").exists());
+    assert!(!backups.join("backups").exists());
+
+    fs::remove_dir_all(&temp_dir).unwrap();
+}
+
+#[test]
+fn test_single_file_input_with_output_dir() {
+    let temp_dir = std::env::temp_dir().join("test_single_file_output");
+    let _ = fs::remove_dir_all(&temp_dir);
+    fs::create_dir_all(&temp_dir).unwrap();
+    let photo = temp_dir.join("photo.jpg");
+    gradient_image(64, 64).save(&photo).unwrap();
+    let output_dir = temp_dir.join("out");
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "-i", photo.to_str().unwrap()])
+        .args(["-o", &format!("{}/", output_dir.display())])
         .output()
         .expect("Failed to execute command");
 
     assert!(output.status.success());
+    assert!(image::open(output_dir.join("photo.jpg")).is_ok());
+    fs::remove_dir_all(&temp_dir).unwrap();
+}
+
+#[test]
+fn test_subdirectory_config_sets_its_own_quality() {
+    let temp_dir = std::env::temp_dir().join("test_subdirectory_config");
+    let _ = fs::remove_dir_all(&temp_dir);
+    fs::create_dir_all(temp_dir.join("icons")).unwrap();
+    gradient_image(128, 128)
+        .save(temp_dir.join("photo.jpg"))
+        .unwrap();
+    gradient_image(128, 128)
+        .save(temp_dir.join("icons/photo.jpg"))
+        .unwrap();
+    fs::write(
+        temp_dir.join("icons/.image-optimizer.toml"),
+        "jpeg-quality = 20\n",
+    )
+    .unwrap();
+    let output_dir = temp_dir.join("out");
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "-i", temp_dir.to_str().unwrap(), "-r"])
+        .args(["-o", output_dir.to_str().unwrap()])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+
+    // Both files are identical, so only the sidecar's quality tells them apart
+    let root_size = fs::metadata(output_dir.join("photo.jpg")).unwrap().len();
+    let icon_size = fs::metadata(output_dir.join("icons/photo.jpg"))
+        .unwrap()
+        .len();
+    assert!(
+        icon_size < root_size,
+        "icons/photo.jpg has {icon_size} bytes, photo.jpg {root_size}"
+    );
+    fs::remove_dir_all(&temp_dir).unwrap();
+}
+
+#[test]
+fn test_flatten_writes_colliding_names_side_by_side() {
+    let temp_dir = std::env::temp_dir().join("test_flatten_output");
+    let _ = fs::remove_dir_all(&temp_dir);
+    let input = temp_dir.join("in");
+    let output_dir = temp_dir.join("out");
+    for dir in ["a", "b"] {
+        fs::create_dir_all(input.join(dir)).unwrap();
+        gradient_image(32, 32)
+            .save(input.join(dir).join("logo.png"))
+            .unwrap();
+    }
+    let run = |collisions: &str| {
+        Command::new("cargo")
+            .args(["run", "--", "-r", "--no-zopfli", "--flatten"])
+            .args(["--flatten-collisions", collisions])
+            .args([
+                "-i",
+                input.to_str().unwrap(),
+                "-o",
+                output_dir.to_str().unwrap(),
+            ])
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    let output = run("error");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--flatten would write both"));
+    assert!(!output_dir.exists());
+
+    let output = run("suffix");
+    assert!(output.status.success());
+    let mut names: Vec<_> = fs::read_dir(&output_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    names.sort();
+    assert_eq!(names, ["logo-1.png", "logo.png"]);
+
+    fs::remove_dir_all(&temp_dir).unwrap();
+}
+
+#[test]
+fn test_watch_optimizes_new_files() {
+    use std::io::{BufRead, BufReader};
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    let temp_dir = std::env::temp_dir().join("test_watch_mode");
+    let _ = fs::remove_dir_all(&temp_dir);
+    fs::create_dir_all(&temp_dir).unwrap();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_image-optimizer"))
+        .args(["-i", temp_dir.to_str().unwrap(), "--watch", "--no-zopfli"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("Failed to execute command");
+
+    let (sender, receiver) = mpsc::channel();
+    let stdout = BufReader::new(child.stdout.take().unwrap());
+    std::thread::spawn(move || {
+        for line in stdout.lines().map_while(Result::ok) {
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    let wait_for = |needle: &str| {
+        while let Ok(line) = receiver.recv_timeout(Duration::from_secs(20)) {
+            if line.contains(needle) {
+                return true;
+            }
+        }
+        false
+    };
+
+    assert!(wait_for("Watching"));
+    // Write under another extension first so the watcher only sees the finished image
+    let staged = temp_dir.join("exported.part");
+    gradient_image(64, 64)
+        .save_with_format(&staged, image::ImageFormat::Png)
+        .unwrap();
+    fs::rename(&staged, temp_dir.join("exported.png")).unwrap();
+    let optimized = wait_for("exported.png");
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+    assert!(optimized);
+    assert!(image::open(temp_dir.join("exported.png")).is_ok());
+
+    fs::remove_dir_all(&temp_dir).unwrap();
+}
+
+#[test]
+fn test_stats_subcommand_reports_known_directory() {
+    let temp_dir = std::env::temp_dir().join("test_stats_subcommand");
+    fs::create_dir_all(temp_dir.join("nested")).unwrap();
+    fs::write(temp_dir.join("a.jpg"), vec![0u8; 2048]).unwrap();
+    fs::write(temp_dir.join("b.jpg"), vec![0u8; 1024]).unwrap();
+    fs::write(temp_dir.join("c.png"), vec![0u8; 512]).unwrap();
+    fs::write(temp_dir.join("nested/d.svg"), vec![0u8; 100]).unwrap();
+    fs::write(temp_dir.join("notes.txt"), "not an image").unwrap();
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "stats", temp_dir.to_str().unwrap(), "-r"])
+        .args(["--top", "2"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("4 image files, 3.6 KB"));
+    assert!(stdout.contains("JPEG         2       3.0 KB"));
+    assert!(stdout.contains("PNG          1        512 B"));
+    assert!(stdout.contains("SVG          1        100 B"));
+    assert!(stdout.contains("Average size: 921 B"));
+    assert!(stdout.contains("Median size: 768 B"));
+    assert!(stdout.contains(&format!("2.0 KB  {}", temp_dir.join("a.jpg").display())));
+    assert!(!stdout.contains("c.png\n"));
+
+    // Nothing is modified
+    assert_eq!(fs::read(temp_dir.join("a.jpg")).unwrap().len(), 2048);
+
+    fs::remove_dir_all(&temp_dir).unwrap();
+}
+
+#[test]
+fn test_info_subcommand_reports_dimensions_and_color_type() {
+    let temp_dir = std::env::temp_dir().join("test_info_subcommand");
+    fs::create_dir_all(&temp_dir).unwrap();
+    let image_path = temp_dir.join("photo.png");
+    gradient_image(120, 80).save(&image_path).unwrap();
+    let original = fs::read(&image_path).unwrap();
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "info", image_path.to_str().unwrap()])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Format: PNG"));
+    assert!(stdout.contains("Dimensions: 120x80"));
+    assert!(stdout.contains("Color type: Rgb8"));
+    assert!(stdout.contains("Alpha: no"));
+    assert!(stdout.contains("Estimated size: "));
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "--no-zopfli",
+            "info",
+            image_path.to_str().unwrap(),
+        ])
+        .arg("--json")
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+    let info: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(info["format"], "png");
+    assert_eq!(info["width"], 120);
+    assert_eq!(info["height"], 80);
+    assert_eq!(info["color_type"], "Rgb8");
+    assert!(info["estimated_size"].as_u64().unwrap() > 0);
+
+    assert_eq!(fs::read(&image_path).unwrap(), original);
+
+    fs::remove_dir_all(&temp_dir).unwrap();
+}
+
+#[test]
+fn test_bash_completions_without_input() {
+    let output = Command::new("cargo")
+        .args(["run", "--", "completions", "bash"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("_image-optimizer()"));
+    assert!(stdout.contains("--jpeg-quality"));
+}
+
+#[test]
+fn test_piped_output_has_no_escape_sequences() {
+    let temp_dir = std::env::temp_dir().join("test_piped_output");
+    fs::create_dir_all(&temp_dir).unwrap();
+    gradient_image(64, 64).save(temp_dir.join("a.png")).unwrap();
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "-i", temp_dir.to_str().unwrap(), "--no-zopfli"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Found 1 image files"));
+    assert!(!stdout.contains('\x1b'));
+
+    fs::remove_dir_all(&temp_dir).unwrap();
+}
+
+#[test]
+fn test_list_skipped_names_files_that_would_grow() {
+    let temp_dir = std::env::temp_dir().join("test_list_skipped");
+    fs::create_dir_all(temp_dir.join("images")).unwrap();
+    gradient_image(64, 64)
+        .save(temp_dir.join("images/a.png"))
+        .unwrap();
+    let minimal = r#"<svg xmlns="http://www.w3.org/2000/svg"/>"#;
+    fs::write(temp_dir.join("images/minimal.svg"), minimal).unwrap();
+    let report_path = temp_dir.join("report.json");
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "-i", temp_dir.join("images").to_str().unwrap()])
+        .args(["--no-zopfli", "--list-skipped", "--report-json"])
+        .arg(&report_path)
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (_, listed) = stdout
+        .split_once("Files kept because optimization would increase their size:")
+        .unwrap();
+    assert!(listed.contains("minimal.svg"));
+    assert!(listed.contains("kept original"));
+    assert!(!listed.contains("a.png"));
+    assert_eq!(
+        fs::read_to_string(temp_dir.join("images/minimal.svg")).unwrap(),
+        minimal
+    );
+
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&report_path).unwrap()).unwrap();
+    assert_eq!(report["files"][1]["status"], "skipped_larger");
+
+    fs::remove_dir_all(&temp_dir).unwrap();
+}
+
+#[test]
+fn test_kept_outputs_are_counted_as_existing() {
+    let temp_dir = std::env::temp_dir().join("test_skip_existing_summary");
+    let _ = fs::remove_dir_all(&temp_dir);
+    fs::create_dir_all(temp_dir.join("images")).unwrap();
+    fs::create_dir_all(temp_dir.join("out")).unwrap();
+    gradient_image(64, 64)
+        .save(temp_dir.join("images/a.png"))
+        .unwrap();
+    fs::write(temp_dir.join("out/a.png"), "existing").unwrap();
+    let report_path = temp_dir.join("report.json");
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "-i", temp_dir.join("images").to_str().unwrap()])
+        .args(["-o", temp_dir.join("out").to_str().unwrap()])
+        .args(["--no-zopfli", "--skip-existing", "--report-json"])
+        .arg(&report_path)
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Skipped 1 files (output already exists; --force overwrites)"));
+    assert!(!stdout.contains("optimization would increase size"));
+    assert_eq!(
+        fs::read_to_string(temp_dir.join("out/a.png")).unwrap(),
+        "existing"
+    );
+
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&report_path).unwrap()).unwrap();
+    assert_eq!(report["files"][0]["status"], "skipped_existing");
+    assert_eq!(report["summary"]["skipped_existing"], 1);
+    assert_eq!(report["summary"]["skipped_larger"], 0);
+
+    fs::remove_dir_all(&temp_dir).unwrap();
+}
+
+#[test]
+fn test_dedup_links_identical_files_in_place() {
+    let temp_dir = std::env::temp_dir().join("test_dedup_in_place");
+    fs::create_dir_all(temp_dir.join("images/copies")).unwrap();
+    gradient_image(64, 64)
+        .save(temp_dir.join("images/logo.png"))
+        .unwrap();
+    fs::copy(
+        temp_dir.join("images/logo.png"),
+        temp_dir.join("images/copies/logo.png"),
+    )
+    .unwrap();
+    let report_path = temp_dir.join("report.json");
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "-i", temp_dir.join("images").to_str().unwrap()])
+        .args(["-r", "--no-zopfli", "--dedup", "link", "--report-json"])
+        .arg(&report_path)
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+
+    assert_eq!(
+        fs::read(temp_dir.join("images/logo.png")).unwrap(),
+        fs::read(temp_dir.join("images/copies/logo.png")).unwrap()
+    );
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&report_path).unwrap()).unwrap();
+    let files = report["files"].as_array().unwrap();
+    let duplicates: Vec<_> = files
+        .iter()
+        .filter(|file| file.get("duplicate_of").is_some())
+        .collect();
+    assert_eq!(files.len(), 2);
+    assert_eq!(duplicates.len(), 1);
+    assert_eq!(duplicates[0]["status"], "optimized");
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "-i", temp_dir.join("images").to_str().unwrap()])
+        .args(["-r", "--no-zopfli", "--dedup", "report"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("1 group of identical files"));
+
+    fs::remove_dir_all(&temp_dir).unwrap();
+}
+
+#[test]
+fn test_summary_reports_elapsed_time_and_throughput() {
+    let temp_dir = std::env::temp_dir().join("test_run_timing");
+    fs::create_dir_all(temp_dir.join("images")).unwrap();
+    gradient_image(64, 64)
+        .save(temp_dir.join("images/a.png"))
+        .unwrap();
+    let report_path = temp_dir.join("report.json");
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "-i", temp_dir.join("images").to_str().unwrap()])
+        .args(["--no-zopfli", "--no-parallel", "--report-json"])
+        .arg(&report_path)
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Elapsed: "));
+
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&report_path).unwrap()).unwrap();
+    assert!(report["timing"]["elapsed_secs"].as_f64().unwrap() > 0.0);
+    assert!(report["timing"]["megabytes_per_sec"].as_f64().unwrap() > 0.0);
+    assert!(report["timing"]["images_per_sec"].as_f64().unwrap() > 0.0);
+
+    fs::remove_dir_all(&temp_dir).unwrap();
+}
+
+#[test]
+fn test_summary_breaks_savings_down_by_format() {
+    let temp_dir = std::env::temp_dir().join("test_format_totals");
+    let _ = fs::remove_dir_all(&temp_dir);
+    let input = temp_dir.join("images");
+    fs::create_dir_all(&input).unwrap();
+    gradient_image(64, 64).save(input.join("a.png")).unwrap();
+    gradient_image(96, 64).save(input.join("b.png")).unwrap();
+    gradient_image(64, 64).save(input.join("c.jpg")).unwrap();
+    fs::write(
+        input.join("d.svg"),
+        "<svg xmlns=\"http://www.w3.org/2000/svg\"><!-- editor comment --><rect width=\"1\" height=\"1\"/></svg>",
+    )
+    .unwrap();
+    let report_path = temp_dir.join("report.json");
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "-i", input.to_str().unwrap()])
+        .args(["--no-zopfli", "--jpeg-quality", "60", "--report-json"])
+        .arg(&report_path)
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("JPEG: 1 files, saved "));
+    assert!(stdout.contains("PNG: 2 files, saved "));
+    assert!(stdout.contains("SVG: 1 files, saved "));
+
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&report_path).unwrap()).unwrap();
+    let by_format = report["by_format"].as_object().unwrap();
+    assert_eq!(by_format.keys().collect::<Vec<_>>(), ["jpeg", "png", "svg"]);
+    let sum = |field: &str| -> u64 {
+        by_format
+            .values()
+            .map(|totals| totals[field].as_u64().unwrap())
+            .sum()
+    };
+    assert_eq!(sum("files"), 4);
+    assert_eq!(sum("total_saved"), report["summary"]["total_saved"]);
+    assert_eq!(sum("total_input"), report["summary"]["total_input"]);
+    assert_eq!(sum("processed"), report["summary"]["processed"]);
+
+    fs::remove_dir_all(&temp_dir).unwrap();
+}
+
+#[test]
+fn test_quiet_still_writes_json_report() {
+    let temp_dir = std::env::temp_dir().join("test_quiet_report");
+    fs::create_dir_all(temp_dir.join("images")).unwrap();
+    gradient_image(64, 64)
+        .save(temp_dir.join("images/a.png"))
+        .unwrap();
+    fs::write(temp_dir.join("images/b.png"), b"not really a png").unwrap();
+    let report_path = temp_dir.join("report.json");
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "-i", temp_dir.join("images").to_str().unwrap()])
+        .args(["--no-zopfli", "--quiet", "--report-json"])
+        .arg(&report_path)
+        .output()
+        .expect("Failed to execute command");
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Error processing"));
+
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&report_path).unwrap()).unwrap();
+    assert_eq!(report["summary"]["invalid"], 1);
+    assert_eq!(report["files"].as_array().unwrap().len(), 2);
+    assert_eq!(report["files"][1]["status"], "invalid");
+
+    fs::remove_dir_all(&temp_dir).unwrap();
+}
+
+#[test]
+fn test_porcelain_prints_only_tab_separated_lines() {
+    let temp_dir = std::env::temp_dir().join("test_porcelain_output");
+    let _ = fs::remove_dir_all(&temp_dir);
+    fs::create_dir_all(temp_dir.join("images")).unwrap();
+    gradient_image(64, 64)
+        .save(temp_dir.join("images/a.png"))
+        .unwrap();
+    let input = temp_dir.join("images");
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "-i", input.to_str().unwrap()])
+        .args(["--no-zopfli", "--porcelain", "--verbose"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<Vec<&str>> = stdout
+        .lines()
+        .map(|line| line.split('\t').collect())
+        .collect();
+    assert_eq!(lines.len(), 2, "{stdout}");
+    let [file, total] = [&lines[0], &lines[1]];
+    assert!(
+        ["optimized", "skipped_larger"].contains(&file[0]),
+        "{stdout}"
+    );
+    assert_eq!(file[1], input.join("a.png").to_str().unwrap());
+    let original: u64 = file[2].parse().unwrap();
+    let optimized: u64 = file[3].parse().unwrap();
+    assert_eq!(
+        file[4].parse::<u64>().unwrap(),
+        original.saturating_sub(optimized)
+    );
+    assert_eq!(total[0], "total");
+    assert_eq!(total[1], input.to_str().unwrap());
+    assert_eq!(total[2], file[2]);
+    assert_eq!(total[4], file[4]);
+
+    fs::remove_dir_all(&temp_dir).unwrap();
+}
+
+#[test]
+fn test_stdin_to_stdout_pipeline() {
+    let mut png_data = Vec::new();
+    gradient_image(32, 32)
+        .write_to(
+            &mut std::io::Cursor::new(&mut png_data),
+            image::ImageFormat::Png,
+        )
+        .unwrap();
+
+    for (format, expected) in [
+        ("png", image::ImageFormat::Png),
+        ("webp", image::ImageFormat::WebP),
+    ] {
+        let mut child = Command::new("cargo")
+            .args(["run", "--", "--stdin", "--no-zopfli", "--format", format])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("Failed to execute command");
+        child.stdin.take().unwrap().write_all(&png_data).unwrap();
+        let output = child.wait_with_output().unwrap();
+
+        assert!(output.status.success());
+        assert_eq!(image::guess_format(&output.stdout).unwrap(), expected);
+        let decoded = image::load_from_memory(&output.stdout).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (32, 32));
+    }
+}
+
+// macOS filesystems reject names that are not valid UTF-8
+#[cfg(target_os = "linux")]
+#[test]
+fn test_non_utf8_file_name_is_optimized() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let temp_dir = std::env::temp_dir().join("image_optimizer_non_utf8");
+    let _ = fs::remove_dir_all(&temp_dir);
+    let output_dir = temp_dir.join("out");
+    fs::create_dir_all(&temp_dir).unwrap();
+    // "café.png" in Latin-1
+    let name = OsStr::from_bytes(b"caf\xe9.png");
+    gradient_image(256, 256).save(temp_dir.join(name)).unwrap();
+    let report = temp_dir.join("report.json");
+
+    let output = Command::new("cargo")
+        .args(["run", "--", "-i", temp_dir.to_str().unwrap(), "--no-zopfli"])
+        .args(["-o", output_dir.to_str().unwrap()])
+        .args(["--report-json", report.to_str().unwrap()])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Found 1 image files"));
+    assert!(image::open(output_dir.join(name)).is_ok());
+    let report = fs::read_to_string(&report).unwrap();
+    assert!(report.contains("caf\u{fffd}.png"));
+
+    fs::remove_dir_all(&temp_dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_interrupt_leaves_no_temp_files() {
+    use std::io::{BufRead, BufReader};
+
+    let temp_dir = std::env::temp_dir().join("image_optimizer_interrupt");
+    let _ = fs::remove_dir_all(&temp_dir);
+    fs::create_dir_all(&temp_dir).unwrap();
+    for index in 0..12 {
+        gradient_image(256, 256)
+            .save(temp_dir.join(format!("image_{index:02}.png")))
+            .unwrap();
+    }
+
+    // Run the binary directly so the signal reaches it rather than cargo
+    let mut child = Command::new(env!("CARGO_BIN_EXE_image-optimizer"))
+        .args([
+            "-i",
+            temp_dir.to_str().unwrap(),
+            "--no-parallel",
+            "--no-zopfli",
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to execute command");
+
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+    let mut first_line = String::new();
+    stdout.read_line(&mut first_line).unwrap();
+    assert!(first_line.contains("Found 12 image files"));
+
+    let status = Command::new("kill")
+        .args(["-INT", &child.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let output = child.wait_with_output().unwrap();
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(130));
+    assert!(stderr.contains("Interrupted after"));
+
+    for entry in fs::read_dir(&temp_dir).unwrap() {
+        let path = entry.unwrap().path();
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        assert!(!name.contains(".tmp."), "leftover temp file {name}");
+        assert!(image::open(&path).is_ok(), "{name} is not a valid image");
+    }
 
     fs::remove_dir_all(&temp_dir).unwrap();
 }